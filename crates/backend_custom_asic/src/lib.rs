@@ -1,4 +1,5 @@
 use anyhow::Result;
+#[cfg(feature = "telemetry")]
 use std::collections::BTreeMap;
 #[cfg(feature = "telemetry")]
 use nc_telemetry as telemetry;
@@ -6,6 +7,7 @@ use nc_telemetry as telemetry;
 pub fn compile(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Result<String> {
     graph.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
     nc_hal::validate_manifest(manifest)?;
+    nc_hal::check_model_support(manifest, graph.populations.iter().map(|p| p.model.as_str()))?;
 
     #[cfg(feature = "telemetry")]
     let app = std::env::var("NC_PROFILE_JSONL")
@@ -4,6 +4,7 @@ pub fn compile(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Resu
     // Basic validation
     graph.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
     nc_hal::validate_manifest(manifest)?;
+    nc_hal::check_model_support(manifest, graph.populations.iter().map(|p| p.model.as_str()))?;
 
     // Optional telemetry profiling
     #[cfg(feature = "telemetry")]
@@ -53,4 +54,30 @@ mod tests {
         "#).unwrap();
         let _ = compile(&g, &m).unwrap();
     }
+
+    #[test]
+    fn unsupported_model_fails_before_compile() {
+        let mut g = nc_nir::Graph::new("izh");
+        g.populations.push(nc_nir::Population {
+            name: "a".into(),
+            size: 4,
+            model: "Izhikevich".into(),
+            params: Default::default(),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
+        });
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "lif_only"
+            vendor = "Intel"
+            family = "Loihi"
+            version = "2"
+            [capabilities]
+            neuron_models = ["LIF"]
+        "#).unwrap();
+        let err = compile(&g, &m).unwrap_err();
+        let hal = err.downcast_ref::<nc_hal::HalError>().expect("structured HalError");
+        assert!(matches!(hal, nc_hal::HalError::UnsupportedModels { models, .. } if models == &["Izhikevich".to_string()]));
+        assert_eq!(err.to_string(), "target 'lif_only' does not support neuron model(s): Izhikevich");
+    }
 }
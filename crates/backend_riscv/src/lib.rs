@@ -20,14 +20,13 @@ impl Pass for RvLayoutPass {
         let align = caps.and_then(|v| v.get("align_bytes")).and_then(|x| x.as_u64()).unwrap_or(16);
         let vec_ok = caps.and_then(|v| v.get("vector_available")).and_then(|x| x.as_bool()).unwrap_or(false);
         let qbits = caps.and_then(|v| v.get("quant_bits_default")).and_then(|x| x.as_u64()).unwrap_or(8);
-        let vec_bytes = if vec_ok { 64 } else { 16 };
-        let meta = json!({
-            "align_bytes": align,
-            "vector_available": vec_ok,
-            "vector_bytes": vec_bytes,
-            "quant_bits_default": qbits
+        g.set_attr(&nc_nir::RvLayoutMeta {
+            align_bytes: align,
+            vector_available: vec_ok,
+            vector_bytes: if vec_ok { 64 } else { 16 },
+            quant_bits_default: qbits,
+            ..Default::default()
         });
-        g.attributes.insert("rv_layout".to_string(), meta);
         Ok(g)
     }
 }
@@ -35,17 +34,9 @@ struct RvSchedulePass;
 impl Pass for RvSchedulePass {
     fn name(&self) -> &str { "rv-schedule" }
     fn run(&self, mut g: nc_nir::Graph) -> Result<nc_nir::Graph> {
-        let vec_ok = g.attributes
-            .get("rv_layout")
-            .and_then(|v| v.get("vector_available"))
-            .and_then(|x| x.as_bool())
-            .unwrap_or(false);
-        let fused = if vec_ok { vec!["op_fuse_vadd_vmul"] } else { vec!["op_fuse_scalar"] };
-        let meta = json!({
-            "threads": 1,
-            "fused_stages": fused
-        });
-        g.attributes.insert("rv_schedule".to_string(), meta);
+        let vec_ok = g.attrs::<nc_nir::RvLayoutMeta>().is_some_and(|l| l.vector_available);
+        let fused = if vec_ok { "op_fuse_vadd_vmul" } else { "op_fuse_scalar" };
+        g.set_attr(&nc_nir::RvScheduleMeta { threads: 1, fused_stages: vec![fused.to_string()], ..Default::default() });
         Ok(g)
     }
 }
@@ -54,18 +45,12 @@ struct RvVectorizePass;
 impl Pass for RvVectorizePass {
     fn name(&self) -> &str { "rv-vectorize" }
     fn run(&self, mut g: nc_nir::Graph) -> Result<nc_nir::Graph> {
-        let vec_ok = g.attributes
-            .get("rv_layout")
-            .and_then(|v| v.get("vector_available"))
-            .and_then(|x| x.as_bool())
-            .unwrap_or(false);
-        let vlen = g.attributes
-            .get("rv_layout")
-            .and_then(|v| v.get("vector_bytes"))
-            .and_then(|x| x.as_u64())
-            .unwrap_or(0);
-        let meta = json!({ "enabled": vec_ok, "vlen_bytes": vlen });
-        g.attributes.insert("rv_vectorize".to_string(), meta);
+        let layout = g.attrs::<nc_nir::RvLayoutMeta>().unwrap_or_default();
+        g.set_attr(&nc_nir::RvVectorizeMeta {
+            enabled: layout.vector_available,
+            vlen_bytes: layout.vector_bytes,
+            ..Default::default()
+        });
         Ok(g)
     }
 }
@@ -76,11 +61,7 @@ impl Pass for RvBaremetalTuningPass {
     fn run(&self, mut g: nc_nir::Graph) -> Result<nc_nir::Graph> {
         let caps = g.attributes.get("caps_riscv");
         let has_c = caps.and_then(|v| v.get("has_c")).and_then(|x| x.as_bool()).unwrap_or(false);
-        let meta = json!({
-            "size_optimized": true,
-            "use_compressed": has_c
-        });
-        g.attributes.insert("rv_bare_tuning".to_string(), meta);
+        g.set_attr(&nc_nir::RvBareTuningMeta { size_optimized: true, use_compressed: has_c, ..Default::default() });
         Ok(g)
     }
 }
@@ -94,13 +75,13 @@ impl Pass for RvControlPlanePass {
         let mmio_w = caps.and_then(|v| v.get("mmio_width_bits")).and_then(|x| x.as_u64());
         let dma = caps.and_then(|v| v.get("dma_supported")).and_then(|x| x.as_bool()).unwrap_or(false);
         let dma_alignment = caps.and_then(|v| v.get("dma_alignment")).and_then(|x| x.as_u64());
-        let meta = json!({
-            "mmio_supported": mmio,
-            "mmio_width_bits": mmio_w,
-            "dma_supported": dma,
-            "dma_alignment": dma_alignment
+        g.set_attr(&nc_nir::RvCtrlPlaneMeta {
+            mmio_supported: mmio,
+            mmio_width_bits: mmio_w,
+            dma_supported: dma,
+            dma_alignment,
+            ..Default::default()
         });
-        g.attributes.insert("rv_ctrl_plane".to_string(), meta);
         Ok(g)
     }
 }
@@ -110,9 +91,54 @@ impl Pass for RvControlPlanePass {
 /// are present, it will run under qemu-user and capture JSONL to NC_PROFILE_JSONL or
 /// to {out_dir}/profile.jsonl.
 pub fn compile(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Result<String> {
+    compile_with_diagnostics(graph, manifest).map(|(artifact, _)| artifact)
+}
+
+/// Like `compile`, but also returns the warnings recorded during the compile (the same lines
+/// written to `WARN.txt`), so callers can surface them without reading the out_dir.
+pub fn compile_with_diagnostics(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Result<(String, Vec<String>)> {
+    let out_dir = default_out_dir(&graph.name, &manifest.name);
+    compile_in(graph, manifest, &out_dir)
+}
+
+/// Compile several graphs, each into its own out_dir. Jobs run in parallel with the `rayon`
+/// feature and sequentially otherwise. Jobs that would share a `default_out_dir` (same graph
+/// and target name) get an index suffix so their artifacts do not collide. Results are
+/// returned per job, in input order.
+pub fn compile_many(jobs: &[(&nc_nir::Graph, &nc_hal::TargetManifest)]) -> Vec<Result<String>> {
+    let mut seen = std::collections::HashSet::new();
+    let planned: Vec<(&nc_nir::Graph, &nc_hal::TargetManifest, PathBuf)> = jobs
+        .iter()
+        .enumerate()
+        .map(|(i, (g, m))| {
+            let base = default_out_dir(&g.name, &m.name);
+            let dir = if seen.insert(base.clone()) {
+                base
+            } else {
+                PathBuf::from(format!("{}-{i}", base.to_string_lossy()))
+            };
+            (*g, *m, dir)
+        })
+        .collect();
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        planned.par_iter().map(|(g, m, dir)| compile_in(g, m, dir).map(|(a, _)| a)).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        planned.iter().map(|(g, m, dir)| compile_in(g, m, dir).map(|(a, _)| a)).collect()
+    }
+}
+
+/// Validate and compile into an explicit `out_dir` (shared by `compile` and `compile_many`).
+/// Returns the artifact string and the compile warnings.
+fn compile_in(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest, out_dir: &Path) -> Result<(String, Vec<String>)> {
     // Validate input IR and target manifest
     graph.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
     nc_hal::validate_manifest(manifest)?;
+    nc_hal::check_model_support(manifest, graph.populations.iter().map(|p| p.model.as_str()))?;
 
     // Optional telemetry profiling
     #[cfg(feature = "telemetry")]
@@ -130,9 +156,8 @@ pub fn compile(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Resu
         }
     };
 
-    let out_dir = default_out_dir(&graph.name, &manifest.name);
     if !out_dir.exists() {
-        let _ = fs::create_dir_all(&out_dir);
+        let _ = fs::create_dir_all(out_dir);
     }
 
     // Determine profile with backward-compatible fallback
@@ -146,11 +171,11 @@ pub fn compile(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Resu
         });
 
     // Dispatch by profile
-    let artifact = match profile {
-        "linux_user" => compile_linux_user(graph, manifest, &out_dir)?,
-        "bare_metal" => compile_bare_metal(graph, manifest, &out_dir)?,
-        "control_plane" => compile_control_plane(graph, manifest, &out_dir)?,
-        _ => compile_linux_user(graph, manifest, &out_dir)?,
+    let compiled = match profile {
+        "linux_user" => compile_linux_user(graph, manifest, out_dir)?,
+        "bare_metal" => compile_bare_metal(graph, manifest, out_dir)?,
+        "control_plane" => compile_control_plane(graph, manifest, out_dir)?,
+        _ => compile_linux_user(graph, manifest, out_dir)?,
     };
 
     // Telemetry counters (unchanged)
@@ -162,7 +187,7 @@ pub fn compile(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Resu
         let _ = a.counter("graph.probes", graph.probes.len() as f64, l);
     }
 
-    Ok(artifact)
+    Ok(compiled)
 }
 
 /// Run the RISC-V pass pipeline appropriate to `profile`, dump JSON, and collect README metadata.
@@ -172,6 +197,7 @@ fn run_pipeline_and_collect_meta(
     manifest: &nc_hal::TargetManifest,
     out_dir: &Path,
     profile: &str,
+    passes_run: &mut Vec<String>,
     warnings: &mut Vec<String>,
 ) -> Vec<String> {
     let mut meta_lines: Vec<String> = Vec::new();
@@ -231,43 +257,25 @@ fn run_pipeline_and_collect_meta(
                 };
                 match pm.run_with_config(g_owned, &cfg) {
                     Ok(g_after) => {
-                        if let Some(v) = g_after.attributes.get("rv_layout") {
-                            if let Some(al) = v.get("align_bytes").and_then(|x| x.as_u64()) {
-                                meta_lines.push(format!("align_bytes={al}"));
-                            }
-                            if let Some(q) = v.get("quant_bits_default").and_then(|x| x.as_u64()) {
-                                meta_lines.push(format!("quant_bits_default={q}"));
-                            }
-                            if let Some(vec_ok) = v.get("vector_available").and_then(|x| x.as_bool()) {
-                                meta_lines.push(format!("vector_available={vec_ok}"));
-                            }
+                        passes_run.extend(pm.pass_names());
+                        if let Some(v) = g_after.attrs::<nc_nir::RvLayoutMeta>() {
+                            meta_lines.push(format!("align_bytes={}", v.align_bytes));
+                            meta_lines.push(format!("quant_bits_default={}", v.quant_bits_default));
+                            meta_lines.push(format!("vector_available={}", v.vector_available));
                         }
-                        if let Some(v) = g_after.attributes.get("rv_schedule") {
-                            if let Some(fs) = v.get("fused_stages").and_then(|x| x.as_array()) {
-                                let names: Vec<String> = fs.iter().filter_map(|e| e.as_str().map(|s| s.to_string())).collect();
-                                if !names.is_empty() {
-                                    meta_lines.push(format!("fused_stages={}", names.join("+")));
-                                }
-                            }
-                            if let Some(t) = v.get("threads").and_then(|x| x.as_u64()) {
-                                meta_lines.push(format!("threads={t}"));
+                        if let Some(v) = g_after.attrs::<nc_nir::RvScheduleMeta>() {
+                            if !v.fused_stages.is_empty() {
+                                meta_lines.push(format!("fused_stages={}", v.fused_stages.join("+")));
                             }
+                            meta_lines.push(format!("threads={}", v.threads));
                         }
-                        if let Some(v) = g_after.attributes.get("rv_bare_tuning") {
-                            if let Some(sz) = v.get("size_optimized").and_then(|x| x.as_bool()) {
-                                meta_lines.push(format!("size_optimized={sz}"));
-                            }
-                            if let Some(uc) = v.get("use_compressed").and_then(|x| x.as_bool()) {
-                                meta_lines.push(format!("use_compressed={uc}"));
-                            }
+                        if let Some(v) = g_after.attrs::<nc_nir::RvBareTuningMeta>() {
+                            meta_lines.push(format!("size_optimized={}", v.size_optimized));
+                            meta_lines.push(format!("use_compressed={}", v.use_compressed));
                         }
-                        if let Some(v) = g_after.attributes.get("rv_ctrl_plane") {
-                            if let Some(mmio) = v.get("mmio_supported").and_then(|x| x.as_bool()) {
-                                meta_lines.push(format!("mmio_supported={mmio}"));
-                            }
-                            if let Some(d) = v.get("dma_supported").and_then(|x| x.as_bool()) {
-                                meta_lines.push(format!("dma_supported={d}"));
-                            }
+                        if let Some(v) = g_after.attrs::<nc_nir::RvCtrlPlaneMeta>() {
+                            meta_lines.push(format!("mmio_supported={}", v.mmio_supported));
+                            meta_lines.push(format!("dma_supported={}", v.dma_supported));
                         }
                     }
                     Err(e) => {
@@ -284,26 +292,135 @@ fn run_pipeline_and_collect_meta(
     meta_lines
 }
 
+/// True when the graph carries a boolean attribute `key` set to `true` (e.g. `rv_emit_asm`).
+fn graph_flag(graph: &nc_nir::Graph, key: &str) -> bool {
+    graph.attributes.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// Write `pass_meta.csv`: the `key=value` pass metadata lines as `key,value` rows, so tools
+/// don't have to scrape README.txt.
+fn write_pass_meta_csv(out_dir: &Path, meta_lines: &[String]) -> Result<()> {
+    let mut csv = String::from("key,value\n");
+    for l in meta_lines {
+        let (k, v) = l.split_once('=').unwrap_or((l.as_str(), ""));
+        csv.push_str(&format!("{},{}\n", csv_field(k), csv_field(v)));
+    }
+    fs::write(out_dir.join("pass_meta.csv"), csv).context("write pass_meta.csv")
+}
+
+/// Quote a CSV field when it contains a separator, quote or newline.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Write `run.sh`: a one-command reproduction of the run step for `profile` (qemu-user or
+/// spike for linux_user, qemu-system for bare_metal, renode for control_plane). JSONL goes
+/// to `NC_PROFILE_JSONL` when set, else `profile.jsonl` next to the script.
+fn write_run_script(out_dir: &Path, graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest, profile: &str) -> Result<()> {
+    let runner = match profile {
+        "bare_metal" => format!(
+            "MACHINE=\"${{NC_RISCV_QEMU_MACHINE:-virt}}\"\n\
+             {} -nographic -machine \"$MACHINE\" -bios none -no-reboot -kernel \"$DIR/firmware.elf\" > \"$OUT\"\n",
+            BareMetalArch::from_manifest(manifest).qemu_system()
+        ),
+        "control_plane" => "cd \"$DIR\"\n\
+             renode --disable-xwt --console --script run.resc --execute quit > renode.log\n\
+             grep '\"metric\"' renode.log > \"$OUT\" || true\n"
+            .to_string(),
+        _ => "case \"${NC_RISCV_SIM:-qemu}\" in\n\
+             \x20 spike) spike ${NC_RISCV_SPIKE_ISA:+--isa=$NC_RISCV_SPIKE_ISA} \"${NC_RISCV_PK:-pk}\" \"$DIR/prog-rv64\" > \"$OUT\" ;;\n\
+             \x20 *) qemu-riscv64 \"$DIR/prog-rv64\" > \"$OUT\" ;;\n\
+             esac\n"
+            .to_string(),
+    };
+    let script = format!(
+        "#!/bin/sh\n\
+         # Reproduce the {profile} run of graph '{}' on target '{}'.\n\
+         set -e\n\
+         PROFILE={profile}\n\
+         DIR=\"$(cd \"$(dirname \"$0\")\" && pwd)\"\n\
+         OUT=\"${{NC_PROFILE_JSONL:-$DIR/profile.jsonl}}\"\n\
+         echo \"run.sh: profile=$PROFILE output=$OUT\" >&2\n\
+         {runner}",
+        graph.name, manifest.name
+    );
+    let path = out_dir.join("run.sh");
+    fs::write(&path, script).context("write run.sh")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).context("chmod run.sh")?;
+    }
+    Ok(())
+}
+
+/// Write `provenance.json`: a structured copy of what README.txt records about this compile.
+fn write_provenance(
+    out_dir: &Path,
+    graph: &nc_nir::Graph,
+    manifest: &nc_hal::TargetManifest,
+    profile: &str,
+    passes_run: &[String],
+    warnings: &[String],
+) -> Result<()> {
+    let nir_version = graph
+        .attributes
+        .get("nir_version")
+        .and_then(|v| v.as_str())
+        .unwrap_or(nc_nir::VERSION);
+    let prov = json!({
+        "graph": graph.name,
+        "target": manifest.name,
+        "manifest_digest": manifest.digest(),
+        "profile": profile,
+        "nir_version": nir_version,
+        "content_hash": graph.content_hash(),
+        "passes": passes_run,
+        "warnings": warnings,
+    });
+    fs::write(out_dir.join("provenance.json"), serde_json::to_string_pretty(&prov)?)
+        .context("write provenance.json")?;
+    Ok(())
+}
+
 /// Compile the linux_user profile (existing logic preserved).
-fn compile_linux_user(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest, out_dir: &Path) -> Result<String> {
+fn compile_linux_user(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest, out_dir: &Path) -> Result<(String, Vec<String>)> {
     let mut warnings: Vec<String> = Vec::new();
+    let mut passes_run: Vec<String> = Vec::new();
 
-    let meta_lines = run_pipeline_and_collect_meta(graph, manifest, out_dir, "linux_user", &mut warnings);
+    let meta_lines = run_pipeline_and_collect_meta(graph, manifest, out_dir, "linux_user", &mut passes_run, &mut warnings);
 
     if let Err(e) = emit_linux_rv64_runtime(out_dir, graph, manifest) {
         warnings.push(format!("emit failed: {e}"));
     }
 
-    match build_rv64_linux_binary(out_dir) {
+    let build_opts = Rv64BuildOptions::resolve(graph, manifest);
+    match build_rv64_linux_binary(out_dir, &build_opts, &mut warnings) {
         Ok(exe) => {
             if std::env::var("NC_RISCV_QEMU_RUN").ok().as_deref() == Some("1") {
-                if let Err(e) = run_qemu_and_capture(&exe, out_dir) {
-                    warnings.push(format!("qemu run failed: {e}"));
+                match RiscvSim::from_env() {
+                    RiscvSim::Qemu => {
+                        if let Err(e) = run_qemu_and_capture(&exe, out_dir) {
+                            warnings.push(format!("qemu run failed: {e}"));
+                        }
+                    }
+                    RiscvSim::Spike => {
+                        if let Err(e) = run_spike_and_capture(&exe, out_dir) {
+                            warnings.push(format!("spike run failed: {e}"));
+                        }
+                    }
                 }
             }
         }
         Err(e) => {
             warnings.push(format!("{e}"));
+            if build_opts.emit_asm {
+                warnings.push("rv_emit_asm: no assembly listing without a successful build".into());
+            }
         }
     }
 
@@ -318,28 +435,35 @@ fn compile_linux_user(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest,
         for l in &meta_lines {
             writeln!(f, "- {l}")?;
         }
+        write_pass_meta_csv(out_dir, &meta_lines)?;
     }
 
+    write_run_script(out_dir, graph, manifest, "linux_user")?;
+    write_provenance(out_dir, graph, manifest, "linux_user", &passes_run, &warnings)?;
+
     if !warnings.is_empty() {
         let _ = fs::write(out_dir.join("WARN.txt"), warnings.join("\n"));
     }
 
-    Ok(format!("artifact:{}", out_dir.to_string_lossy()))
+    Ok((format!("artifact:{}", out_dir.to_string_lossy()), warnings))
 }
 
 /// Compile the bare_metal profile: emit crt0.S/linker.ld/main.c, best-effort build, and optional QEMU-system run.
-fn compile_bare_metal(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest, out_dir: &Path) -> Result<String> {
+fn compile_bare_metal(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest, out_dir: &Path) -> Result<(String, Vec<String>)> {
     let mut warnings: Vec<String> = Vec::new();
+    let mut passes_run: Vec<String> = Vec::new();
 
-    let meta_lines = run_pipeline_and_collect_meta(graph, manifest, out_dir, "bare_metal", &mut warnings);
+    let meta_lines = run_pipeline_and_collect_meta(graph, manifest, out_dir, "bare_metal", &mut passes_run, &mut warnings);
 
     if let Err(e) = emit_bare_metal_runtime(out_dir, graph, manifest) {
         warnings.push(format!("emit (bare-metal) failed: {e}"));
     } else {
-        match build_rv32_bare_metal_binary(out_dir) {
+        let arch = BareMetalArch::from_manifest(manifest);
+        match build_bare_metal_binary(out_dir, arch, manifest_big_endian(manifest), &mut warnings) {
             Ok(elf) => {
                 if std::env::var("NC_RISCV_QEMU_RUN").ok().as_deref() == Some("1") {
-                    if let Err(e) = run_qemu_system_and_capture(&elf, out_dir) {
+                    let machine = qemu_machine(std::env::var("NC_RISCV_QEMU_MACHINE").ok().as_deref(), &mut warnings);
+                    if let Err(e) = run_qemu_system_and_capture(&elf, out_dir, arch, &machine) {
                         warnings.push(format!("qemu-system run failed: {e}"));
                     }
                 }
@@ -359,27 +483,32 @@ fn compile_bare_metal(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest,
         for l in &meta_lines {
             writeln!(f, "- {l}")?;
         }
+        write_pass_meta_csv(out_dir, &meta_lines)?;
     }
 
+    write_run_script(out_dir, graph, manifest, "bare_metal")?;
+    write_provenance(out_dir, graph, manifest, "bare_metal", &passes_run, &warnings)?;
+
     if !warnings.is_empty() {
         let _ = fs::write(out_dir.join("WARN.txt"), warnings.join("\n"));
     }
 
-    Ok(format!("artifact:{}", out_dir.to_string_lossy()))
+    Ok((format!("artifact:{}", out_dir.to_string_lossy()), warnings))
 }
 
 /// Compile the control_plane profile: emit Renode artifacts and run simulation.
-fn compile_control_plane(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest, out_dir: &Path) -> Result<String> {
+fn compile_control_plane(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest, out_dir: &Path) -> Result<(String, Vec<String>)> {
     let mut warnings: Vec<String> = Vec::new();
+    let mut passes_run: Vec<String> = Vec::new();
 
-    let meta_lines = run_pipeline_and_collect_meta(graph, manifest, out_dir, "control_plane", &mut warnings);
+    let meta_lines = run_pipeline_and_collect_meta(graph, manifest, out_dir, "control_plane", &mut passes_run, &mut warnings);
 
     // Emit control-plane artifacts (main.c, .repl, .py, .resc)
     if let Err(e) = emit_control_plane_runtime(out_dir, graph, manifest) {
         warnings.push(format!("emit_control_plane_runtime failed: {e}"));
     } else {
         // Build the Linux binary (control-plane is also a linux_user binary)
-        match build_rv64_linux_binary(out_dir) {
+        match build_rv64_linux_binary(out_dir, &Rv64BuildOptions::resolve(graph, manifest), &mut warnings) {
             Ok(exe) => {
                 // If NC_RISCV_QEMU_RUN=1, run Renode simulation
                 if std::env::var("NC_RISCV_QEMU_RUN").ok().as_deref() == Some("1") {
@@ -413,16 +542,20 @@ fn compile_control_plane(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifes
         for l in &meta_lines {
             writeln!(f, "- {l}")?;
         }
+        write_pass_meta_csv(out_dir, &meta_lines)?;
     }
 
+    write_run_script(out_dir, graph, manifest, "control_plane")?;
+    write_provenance(out_dir, graph, manifest, "control_plane", &passes_run, &warnings)?;
+
     if !warnings.is_empty() {
         let _ = fs::write(out_dir.join("WARN.txt"), warnings.join("\n"));
     }
 
-    Ok(format!("artifact:{}", out_dir.to_string_lossy()))
+    Ok((format!("artifact:{}", out_dir.to_string_lossy()), warnings))
 }
 
-/// Emit bare-metal templates: linker.ld, crt0.S, and main.c (polled UART at 0x10000000).
+/// Emit bare-metal templates: linker.ld, crt0.S, and main.c (polled UART, default 0x10000000) for rv32 or rv64.
 fn emit_bare_metal_runtime(out_dir: &Path, graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Result<()> {
     if !out_dir.exists() {
         fs::create_dir_all(out_dir)?;
@@ -430,44 +563,56 @@ fn emit_bare_metal_runtime(out_dir: &Path, graph: &nc_nir::Graph, manifest: &nc_
 
     let graph_name = &graph.name;
     let _target_name = &manifest.name;
+    let arch = BareMetalArch::from_manifest(manifest);
+    let isa_label = arch.march();
+    let big_endian = manifest_big_endian(manifest);
+    // Big-endian records carry an extra "endianness" label; little-endian output is unchanged
+    let endian_label = if big_endian { r#"uart_puts("\",\"endianness\":\"big"); "# } else { "" };
+    let uart_base = manifest
+        .capabilities
+        .as_ref()
+        .and_then(|c| c.uart_base_addr)
+        .unwrap_or(0x1000_0000);
 
     // linker.ld
-    let ld = r#"
+    let ld = format!(r#"
 OUTPUT_ARCH(riscv)
 ENTRY(_start)
 
 MEMORY
-{
+{{
   RAM (rwx) : ORIGIN = 0x80000000, LENGTH = 4M
-}
+}}
 
 SECTIONS
-{
-  .text : {
+{{
+  .text : {{
     KEEP(*(.init))
     *(.text*)
     *(.rodata*)
-  } > RAM
+  }} > RAM
 
-  .data : {
+  .data : {{
     *(.data*)
-  } > RAM
+  }} > RAM
 
-  .bss (NOLOAD) : {
+  .bss (NOLOAD) : {{
+    . = ALIGN({align});
     __bss_start__ = .;
     *(.bss*)
     *(COMMON)
+    . = ALIGN({align});
     __bss_end__ = .;
-  } > RAM
+  }} > RAM
 
   . = ORIGIN(RAM) + LENGTH(RAM);
   __stack_top = .;
-}
-"#;
+}}
+"#, align = arch.word_bytes());
     fs::write(out_dir.join("linker.ld"), ld)?;
 
-    // crt0.S
-    let crt0 = r#"
+    // crt0.S (word-sized .bss clearing: sw/4 on rv32, sd/8 on rv64)
+    let crt0 = format!(r#"
     .section .init
     .globl _start
 _start:
@@ -478,8 +623,8 @@ _start:
     la  a1, __bss_end__
 1:
     beq a0, a1, 2f
-    sw  zero, 0(a0)
-    addi a0, a0, 4
+    {store}  zero, 0(a0)
+    addi a0, a0, {word}
     blt a0, a1, 1b
 2:
     /* Jump to C main */
@@ -488,15 +633,17 @@ _start:
 3:
     wfi
     j 3b
-"#;
+"#, store = arch.store_insn(), word = arch.word_bytes());
     fs::write(out_dir.join("crt0.S"), crt0)?;
+    emit_graph_header(out_dir, graph)?;
 
-    // main.c (bare-metal, polled UART at 0x10000000, QEMU exit via 0x100000)
+    // main.c (bare-metal, polled UART at uart_base_addr (default 0x10000000), QEMU exit via 0x100000)
     let c_src = format!(r#"
 #include <stdint.h>
 #include <stddef.h>
+#include "graph.h"
 
-#define UART0_BASE      0x10000000UL
+#define UART0_BASE      0x{uart_base:08x}UL
 #define UART_THR        (UART0_BASE + 0x00)
 #define UART_LSR        (UART0_BASE + 0x05)
 #define LSR_THRE        0x20
@@ -548,7 +695,7 @@ static inline void qemu_exit(uint32_t code) {{
 int main(void) {{
     const char* graph = "{graph_name}";
     const char* backend = "riscv";
-    const char* isa = "rv32imac";
+    const char* isa = "{isa_label}";
     const char* simulator = "qemu";
 
     volatile uint32_t acc = 0;
@@ -565,19 +712,19 @@ int main(void) {{
     /* JSONL lines */
     uart_puts("{{\"metric\":\"kernel.step_ns\",\"value\":"); print_u32(dc); uart_puts(",\"labels\":{{\"graph\":\"");
     uart_puts(graph); uart_puts("\",\"backend\":\""); uart_puts(backend); uart_puts("\",\"isa\":\""); uart_puts(isa);
-    uart_puts("\",\"simulator\":\""); uart_puts(simulator); uart_puts("\"}}}}\\n");
+    uart_puts("\",\"simulator\":\""); uart_puts(simulator); {endian_label}uart_puts("\"}}}}\\n");
 
     uart_puts("{{\"metric\":\"events.processed\",\"value\":"); print_u32(100000u); uart_puts(",\"labels\":{{\"graph\":\"");
     uart_puts(graph); uart_puts("\",\"backend\":\""); uart_puts(backend); uart_puts("\",\"isa\":\""); uart_puts(isa);
-    uart_puts("\",\"simulator\":\""); uart_puts(simulator); uart_puts("\"}}}}\\n");
+    uart_puts("\",\"simulator\":\""); uart_puts(simulator); {endian_label}uart_puts("\"}}}}\\n");
 
     uart_puts("{{\"metric\":\"cpu.cycle\",\"value\":"); print_u32(dc); uart_puts(",\"labels\":{{\"graph\":\"");
     uart_puts(graph); uart_puts("\",\"backend\":\""); uart_puts(backend); uart_puts("\",\"isa\":\""); uart_puts(isa);
-    uart_puts("\",\"simulator\":\""); uart_puts(simulator); uart_puts("\"}}}}\\n");
+    uart_puts("\",\"simulator\":\""); uart_puts(simulator); {endian_label}uart_puts("\"}}}}\\n");
 
     uart_puts("{{\"metric\":\"cpu.instret\",\"value\":"); print_u32(di); uart_puts(",\"labels\":{{\"graph\":\"");
     uart_puts(graph); uart_puts("\",\"backend\":\""); uart_puts(backend); uart_puts("\",\"isa\":\""); uart_puts(isa);
-    uart_puts("\",\"simulator\":\""); uart_puts(simulator); uart_puts("\"}}}}\\n");
+    uart_puts("\",\"simulator\":\""); uart_puts(simulator); {endian_label}uart_puts("\"}}}}\\n");
 
     (void)acc;
     qemu_exit(0);
@@ -591,20 +738,98 @@ int main(void) {{
     let mut readme = fs::File::create(out_dir.join("README.txt"))?;
     writeln!(
         readme,
-        "Generated RV32IMAC bare-metal runtime for graph '{graph_name}' (UART @ 0x10000000, QEMU finisher @ 0x00100000)."
+        "Generated {} bare-metal runtime for graph '{graph_name}' (UART @ 0x{uart_base:08x}, QEMU finisher @ 0x00100000{}).",
+        arch.march().to_uppercase(),
+        if big_endian { ", big-endian" } else { "" }
     )?;
 
     Ok(())
 }
 
-/// Best-effort build of the bare-metal firmware. Returns path to ELF on success.
-fn build_rv32_bare_metal_binary(out_dir: &Path) -> Result<PathBuf> {
+/// Simulator used for linux_user runs, selected by `NC_RISCV_SIM` (`spike`; anything else or unset means qemu-user).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RiscvSim {
+    Qemu,
+    Spike,
+}
+
+impl RiscvSim {
+    fn parse(v: Option<&str>) -> Self {
+        match v.map(|s| s.trim().to_ascii_lowercase()).as_deref() {
+            Some("spike") => Self::Spike,
+            _ => Self::Qemu,
+        }
+    }
+    fn from_env() -> Self {
+        Self::parse(std::env::var("NC_RISCV_SIM").ok().as_deref())
+    }
+    /// Value of the `simulator` telemetry label.
+    fn label(self) -> &'static str {
+        match self {
+            Self::Qemu => "qemu",
+            Self::Spike => "spike",
+        }
+    }
+}
+
+/// Bare-metal flavour selected from the manifest ISA: `rv64*` builds rv64imac/lp64, everything else rv32imac/ilp32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BareMetalArch {
+    Rv32,
+    Rv64,
+}
+
+impl BareMetalArch {
+    fn from_manifest(manifest: &nc_hal::TargetManifest) -> Self {
+        let isa = manifest.capabilities.as_ref().and_then(|c| c.isa.as_deref()).unwrap_or("");
+        if isa.to_ascii_lowercase().starts_with("rv64") { Self::Rv64 } else { Self::Rv32 }
+    }
+    fn march(self) -> &'static str {
+        match self { Self::Rv32 => "rv32imac", Self::Rv64 => "rv64imac" }
+    }
+    fn mabi(self) -> &'static str {
+        match self { Self::Rv32 => "ilp32", Self::Rv64 => "lp64" }
+    }
+    fn word_bytes(self) -> u32 {
+        match self { Self::Rv32 => 4, Self::Rv64 => 8 }
+    }
+    fn store_insn(self) -> &'static str {
+        match self { Self::Rv32 => "sw", Self::Rv64 => "sd" }
+    }
+    fn qemu_system(self) -> &'static str {
+        match self { Self::Rv32 => "qemu-system-riscv32", Self::Rv64 => "qemu-system-riscv64" }
+    }
+    /// Compiler flags for the firmware build (excluding linker script/map and extra CFLAGS).
+    /// rv64 uses medany so code linked at 0x80000000 is addressable; big-endian adds `-mbig-endian`.
+    fn build_flags(self, big_endian: bool) -> Vec<String> {
+        let mut flags: Vec<String> = ["-Os", "-ffreestanding", "-nostdlib", "-nostartfiles"].iter().map(|s| s.to_string()).collect();
+        flags.push(format!("-march={}", self.march()));
+        flags.push(format!("-mabi={}", self.mabi()));
+        if self == Self::Rv64 {
+            flags.push("-mcmodel=medany".to_string());
+        }
+        if big_endian {
+            flags.push("-mbig-endian".to_string());
+        }
+        flags
+    }
+}
+
+/// True when the manifest declares `endianness = "big"`; little-endian is the default.
+fn manifest_big_endian(manifest: &nc_hal::TargetManifest) -> bool {
+    manifest.capabilities.as_ref().and_then(|c| c.endianness.as_deref()) == Some("big")
+}
+
+/// Best-effort build of the bare-metal firmware. Returns path to ELF on success; build warnings are
+/// appended to `warn_lines` for the caller's `WARN.txt`.
+/// For big-endian targets the compiler is probed with `-mbig-endian` first and the build fails early if it is rejected.
+fn build_bare_metal_binary(out_dir: &Path, arch: BareMetalArch, big_endian: bool, warn_lines: &mut Vec<String>) -> Result<PathBuf> {
     let linker = out_dir.join("linker.ld");
     let crt0 = out_dir.join("crt0.S");
     let main_c = out_dir.join("main.c");
     let elf = out_dir.join("firmware.elf");
 
-    let mut warn_lines: Vec<String> = Vec::new();
+    let cflags = extra_cflags();
 
     let append_build_info = |tool: &str| {
         if let Ok(mut f) = OpenOptions::new()
@@ -612,23 +837,36 @@ fn build_rv32_bare_metal_binary(out_dir: &Path) -> Result<PathBuf> {
             .append(true)
             .open(out_dir.join("README.txt"))
         {
-            let _ = writeln!(f, "Build (bare-metal): toolchain={tool}, flags=-Os -ffreestanding -nostdlib -nostartfiles -march=rv32imac -mabi=ilp32");
+            let extra = if cflags.is_empty() { String::new() } else { format!(" {}", cflags.join(" ")) };
+            let _ = writeln!(f, "Build (bare-metal): toolchain={tool}, flags={}{extra}", arch.build_flags(big_endian).join(" "));
         }
     };
 
     if let Some(cc) = detect_tool(&["riscv64-unknown-elf-gcc"]) {
+        if big_endian {
+            // Compile an empty translation unit to check the toolchain accepts -mbig-endian
+            let probe_obj = out_dir.join(".endian-probe.o");
+            let probe = Command::new(&cc)
+                .args(arch.build_flags(true))
+                .arg("-x").arg("c")
+                .arg("-c").arg("/dev/null")
+                .arg("-o").arg(&probe_obj)
+                .stderr(std::process::Stdio::null())
+                .status()
+                .context("probe riscv64-unknown-elf-gcc -mbig-endian")?;
+            let _ = fs::remove_file(&probe_obj);
+            if !probe.success() {
+                anyhow::bail!("riscv64-unknown-elf-gcc rejects -mbig-endian; cannot build big-endian firmware");
+            }
+        }
         let status = Command::new(&cc)
-            .arg("-Os")
-            .arg("-ffreestanding")
-            .arg("-nostdlib")
-            .arg("-nostartfiles")
+            .args(arch.build_flags(big_endian))
             .arg("-Wl,-Map=firmware.map")
             .arg("-T").arg(&linker)
-            .arg("-march=rv32imac")
-            .arg("-mabi=ilp32")
             .arg("-o").arg(&elf)
             .arg(&crt0)
             .arg(&main_c)
+            .args(&cflags)
             .status()
             .context("invoke riscv64-unknown-elf-gcc (bare-metal)")?;
         if status.success() {
@@ -641,23 +879,43 @@ fn build_rv32_bare_metal_binary(out_dir: &Path) -> Result<PathBuf> {
         warn_lines.push("toolchain not found: riscv64-unknown-elf-gcc".into());
     }
 
-    if !warn_lines.is_empty() {
-        let _ = fs::write(out_dir.join("WARN.txt"), warn_lines.join("\n"));
-    }
     anyhow::bail!("no suitable RISC-V bare-metal toolchain found to build {:?}", elf)
 }
 
+/// QEMU RISC-V machine models known to boot the generated firmware layout.
+const KNOWN_QEMU_MACHINES: &[&str] = &["virt", "sifive_u", "sifive_e", "spike", "microchip-icicle-kit", "shakti_c"];
+
+/// Resolve the qemu-system machine from `NC_RISCV_QEMU_MACHINE` (default `virt`).
+/// Unknown names are still passed through, with a warning.
+fn qemu_machine(requested: Option<&str>, warnings: &mut Vec<String>) -> String {
+    let machine = requested.map(str::trim).filter(|m| !m.is_empty()).unwrap_or("virt");
+    if !KNOWN_QEMU_MACHINES.contains(&machine) {
+        warnings.push(format!(
+            "NC_RISCV_QEMU_MACHINE={machine} is not a known machine ({}); passing it to qemu as-is",
+            KNOWN_QEMU_MACHINES.join(", ")
+        ));
+    }
+    machine.to_string()
+}
+
+/// qemu-system arguments for a bare-metal run of `elf` on `machine`.
+fn qemu_system_args(elf: &Path, machine: &str) -> Vec<std::ffi::OsString> {
+    let mut args: Vec<std::ffi::OsString> = ["-nographic", "-machine", machine, "-bios", "none", "-no-reboot", "-kernel"]
+        .iter()
+        .map(Into::into)
+        .collect();
+    args.push(elf.as_os_str().to_owned());
+    args
+}
+
 /// Run QEMU system emulator and capture UART stdout to profile.jsonl (or NC_PROFILE_JSONL).
-fn run_qemu_system_and_capture(elf: &Path, out_dir: &Path) -> Result<()> {
-    let qemu = detect_tool(&["qemu-system-riscv32"]).ok_or_else(|| anyhow::anyhow!("qemu-system-riscv32 not found"))?;
+fn run_qemu_system_and_capture(elf: &Path, out_dir: &Path, arch: BareMetalArch, machine: &str) -> Result<()> {
+    let qemu_name = arch.qemu_system();
+    let qemu = detect_tool(&[qemu_name]).ok_or_else(|| anyhow::anyhow!("{qemu_name} not found"))?;
     let output = Command::new(qemu)
-        .arg("-nographic")
-        .arg("-machine").arg("virt")
-        .arg("-bios").arg("none")
-        .arg("-no-reboot")
-        .arg("-kernel").arg(elf)
+        .args(qemu_system_args(elf, machine))
         .output()
-        .context("running qemu-system-riscv32")?;
+        .with_context(|| format!("running {qemu_name}"))?;
 
     let dest = if let Ok(p) = std::env::var("NC_PROFILE_JSONL") {
         PathBuf::from(p)
@@ -693,16 +951,9 @@ fn emit_linux_rv64_runtime(out_dir: &Path, graph: &nc_nir::Graph, manifest: &nc_
     };
 
     // Try to read vectorization metadata from in-graph attributes first
-    let mut vector_enabled = graph.attributes
-        .get("rv_vectorize")
-        .and_then(|v| v.get("enabled"))
-        .and_then(|x| x.as_bool())
-        .unwrap_or(false);
-    let mut vlen_bytes: u64 = graph.attributes
-        .get("rv_vectorize")
-        .and_then(|v| v.get("vlen_bytes"))
-        .and_then(|x| x.as_u64())
-        .unwrap_or(0);
+    let vectorize = graph.attrs::<nc_nir::RvVectorizeMeta>().unwrap_or_default();
+    let mut vector_enabled = vectorize.enabled;
+    let mut vlen_bytes = vectorize.vlen_bytes;
 
     // Fall back to pass dumps if the input graph doesn't carry pass attributes
     if !vector_enabled || vlen_bytes == 0 {
@@ -710,17 +961,10 @@ fn emit_linux_rv64_runtime(out_dir: &Path, graph: &nc_nir::Graph, manifest: &nc_
         if let Ok(rd) = fs::read_dir(&passes_dir) {
             for e in rd.flatten() {
                 if e.file_name().to_string_lossy().contains("rv-vectorize") {
-                    if let Ok(s) = fs::read_to_string(e.path()) {
-                        if let Ok(val) = serde_json::from_str::<serde_json::Value>(&s) {
-                            if let Some(rv) = val.get("rv_vectorize") {
-                                if let Some(b) = rv.get("enabled").and_then(|x| x.as_bool()) {
-                                    vector_enabled = b;
-                                }
-                                if let Some(vb) = rv.get("vlen_bytes").and_then(|x| x.as_u64()) {
-                                    vlen_bytes = vb;
-                                }
-                            }
-                        }
+                    let dumped = fs::read_to_string(e.path()).ok().and_then(|s| nc_nir::Graph::from_json_str(&s).ok());
+                    if let Some(rv) = dumped.and_then(|d| d.attrs::<nc_nir::RvVectorizeMeta>()) {
+                        vector_enabled = rv.enabled;
+                        vlen_bytes = rv.vlen_bytes;
                     }
                     break;
                 }
@@ -728,11 +972,245 @@ fn emit_linux_rv64_runtime(out_dir: &Path, graph: &nc_nir::Graph, manifest: &nc_
         }
     }
 
+    emit_graph_header(out_dir, graph)?;
+
+    // RVV reduction loop: dynamic vsetvl strip-mining by default; a fixed lane count sized to
+    // vlen_bits_max (plus scalar epilogue) when the manifest pins vlen_is_dynamic = false.
+    let fixed_vlen_bits = match (caps.and_then(|c| c.vlen_is_dynamic), caps.and_then(|c| c.vlen_bits_max)) {
+        (Some(false), Some(bits)) if bits >= 64 => Some(bits),
+        _ => None,
+    };
+    let rvv_loop = match fixed_vlen_bits {
+        Some(bits) => format!(
+            r#"    /* Fixed-vlen path: VLEN={bits} bits -> {lanes} x e64 lanes per iteration */
+    #define NC_RVV_LANES {lanes}
+    size_t ii = 0;
+    vuint64m1_t v_acc = vmv_v_x_u64m1(0, NC_RVV_LANES);
+    for (; ii + NC_RVV_LANES <= n; ii += NC_RVV_LANES) {{
+        vuint64m1_t v_data = vle64_v_u64m1(&data[ii], NC_RVV_LANES);
+        v_acc = vredsum_vs_u64m1_u64m1(v_data, v_acc, NC_RVV_LANES);
+    }}
+    uint64_t sum = vmv_x_s_u64m1_u64(v_acc);
+    /* Scalar epilogue for the tail */
+    for (; ii < n; ++ii) {{ sum += data[ii]; }}
+"#,
+            lanes = bits / 64
+        ),
+        None => r#"    size_t ii = 0;
+    size_t vl1 = vsetvl_e64m1(1);
+    vuint64m1_t v_acc = vmv_v_x_u64m1(0, vl1);
+    while (ii < n) {
+        size_t vl = vsetvl_e64m8(n - ii);
+        vuint64m8_t v_data = vle64_v_u64m8(&data[ii], vl);
+        v_acc = vredsum_vs_u64m8_u64m1(v_data, v_acc, vl);
+        ii += vl;
+    }
+    uint64_t sum = vmv_x_s_u64m1_u64(v_acc);
+"#.to_string(),
+    };
+
+    // Zihpm: read hpmcounter3/4 alongside cycle/instret when the manifest advertises HPM counters
+    let has_zihpm = caps.and_then(|c| c.has_zihpm).unwrap_or(false);
+    let (hpm_decls, hpm_begin, hpm_end, hpm_print) = if has_zihpm {
+        (
+            r#"
+/* Zihpm HPM counters (manifest has_zihpm = true) */
+#if defined(__riscv) || defined(__riscv_xlen)
+static inline uint64_t rdhpm3(void) { uint64_t x; __asm__ volatile("csrr %0, hpmcounter3" : "=r"(x)); return x; }
+static inline uint64_t rdhpm4(void) { uint64_t x; __asm__ volatile("csrr %0, hpmcounter4" : "=r"(x)); return x; }
+#else
+static inline uint64_t rdhpm3(void) { return 0ull; }
+static inline uint64_t rdhpm4(void) { return 0ull; }
+#endif
+"#,
+            "    uint64_t h3_0 = rdhpm3();\n    uint64_t h4_0 = rdhpm4();\n",
+            "    uint64_t h3_1 = rdhpm3();\n    uint64_t h4_1 = rdhpm4();\n",
+            r#"    printf("{\"metric\":\"cpu.hpm3\",\"value\":%llu,\"labels\":{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\"}}\n",
+           (unsigned long long)(h3_1 - h3_0), graph, backend, isa, simulator);
+    printf("{\"metric\":\"cpu.hpm4\",\"value\":%llu,\"labels\":{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\"}}\n",
+           (unsigned long long)(h4_1 - h4_0), graph, backend, isa, simulator);
+"#,
+        )
+    } else {
+        ("", "", "", "")
+    };
+
+    // Per-population kernel tables and timing loop (only when the graph has populations)
+    let (pop_tables, pop_loop) = if graph.populations.is_empty() {
+        (String::new(), String::new())
+    } else {
+        let names: Vec<String> = graph.populations.iter().map(|p| format!("\"{}\"", c_string_escape(&p.name))).collect();
+        let sizes: Vec<String> = graph.populations.iter().map(|p| format!("{}u", p.size)).collect();
+        let tables = format!(
+            "#define NC_RV_NUM_POPS {n}\nstatic const char* const pop_names[NC_RV_NUM_POPS] = {{ {names} }};\nstatic const uint32_t pop_sizes[NC_RV_NUM_POPS] = {{ {sizes} }};\n",
+            n = graph.populations.len(),
+            names = names.join(", "),
+            sizes = sizes.join(", "),
+        );
+        let lp = r#"
+    /* Per-population kernels: time each population's update separately */
+    for (size_t p = 0; p < NC_RV_NUM_POPS; ++p) {
+        uint64_t tp0 = now_ns();
+        volatile uint64_t pacc = 0;
+        for (uint32_t k = 0; k < pop_sizes[p]; ++k) { pacc += (uint64_t)k; }
+        uint64_t tp1 = now_ns();
+        (void)pacc;
+        printf("{\"metric\":\"kernel.step_ns\",\"value\":%llu,\"labels\":{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\",\"population\":\"%s\"}}\n",
+               (unsigned long long)(tp1 - tp0), graph, backend, isa, simulator, pop_names[p]);
+    }
+"#.to_string();
+        (tables, lp)
+    };
+
+    // Stimulus from graph.h: spikes each input delivers to its target over the run. Poisson
+    // and regular sources are generated step by step, spike files are read (`neuron,time_ms`
+    // rows); AER streams are not opened by this runtime and report 0.
+    // With Zbb, Poisson draws are packed into 64-neuron spike words counted by `nc_popcount64`
+    // (`cpop` when built with _zbb, the builtin otherwise); the draw sequence is the same.
+    let (zbb_decls, poisson_count) = if manifest_has_zbb(manifest) {
+        (
+            r#"
+/* Zbb popcount for spike words (manifest lists zbb) */
+#if defined(__riscv_zbb)
+static inline uint64_t nc_popcount64(uint64_t x) { uint64_t r; __asm__("cpop %0, %1" : "=r"(r) : "r"(x)); return r; }
+#else
+static inline uint64_t nc_popcount64(uint64_t x) { return (uint64_t)__builtin_popcountll(x); }
+#endif
+"#,
+            r#"        for (uint32_t t = 0; t < NC_SIM_DURATION_US; t += NC_SIM_STEP_US)
+            for (uint32_t k = 0; k < size; k += 64u) {
+                uint32_t lanes = size - k < 64u ? size - k : 64u;
+                uint64_t word = 0;
+                for (uint32_t b = 0; b < lanes; ++b)
+                    word |= (uint64_t)(nc_xorshift64(&state) < threshold) << b;
+                spikes += nc_popcount64(word);
+            }
+"#,
+        )
+    } else {
+        (
+            "",
+            r#"        for (uint32_t t = 0; t < NC_SIM_DURATION_US; t += NC_SIM_STEP_US)
+            for (uint32_t k = 0; k < size; ++k)
+                spikes += nc_xorshift64(&state) < threshold;
+"#,
+        )
+    };
+    let (input_tables, input_loop) = if graph.inputs.is_empty() || graph.populations.is_empty() {
+        (String::new(), String::new())
+    } else {
+        let rows: Vec<String> = graph
+            .inputs
+            .iter()
+            .zip(input_idents(graph))
+            .map(|(inp, id)| {
+                let rate = if inp.source.rate_hz().is_some() { format!("NC_INPUT_{id}_RATE_HZ") } else { "0.0".to_string() };
+                let phase = if matches!(inp.source, nc_nir::InputSource::Regular { .. }) { format!("NC_INPUT_{id}_PHASE_US") } else { "0u".to_string() };
+                let path = if matches!(inp.source, nc_nir::InputSource::SpikeFile { .. }) { format!("NC_INPUT_{id}_PATH") } else { "NULL".to_string() };
+                format!("{{ \"{}\", NC_INPUT_{id}_KIND, NC_INPUT_{id}_TARGET, {rate}, {phase}, NC_INPUT_{id}_SEED, {path} }}", c_string_escape(&inp.name))
+            })
+            .collect();
+        let tables = format!(
+            r#"typedef struct {{ const char* name; uint32_t kind, target; double rate_hz; uint32_t phase_us; uint64_t seed; const char* path; }} nc_input_t;
+static const nc_input_t nc_inputs[NC_NUM_INPUTS] = {{ {rows} }};
+
+static uint64_t nc_xorshift64(uint64_t* s) {{
+    uint64_t x = *s ? *s : 0x9E3779B97F4A7C15ull;
+    x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+    return *s = x;
+}}
+{zbb_decls}
+/* Spikes an input delivers to its target population within [0, NC_SIM_DURATION_US) */
+static uint64_t nc_input_spikes(const nc_input_t* in) {{
+    uint32_t size = pop_sizes[in->target];
+    uint64_t spikes = 0;
+    switch (in->kind) {{
+    case NC_INPUT_KIND_POISSON: {{
+        /* One Bernoulli draw per neuron and step with p = rate * dt */
+        double p = in->rate_hz * (double)NC_SIM_STEP_US / 1e6;
+        uint64_t threshold = p >= 1.0 ? UINT64_MAX : (uint64_t)(p * 18446744073709551615.0);
+        uint64_t state = in->seed;
+{poisson_count}        break;
+    }}
+    case NC_INPUT_KIND_REGULAR: {{
+        uint64_t period_us = in->rate_hz > 0.0 ? (uint64_t)(1e6 / in->rate_hz + 0.5) : 0u;
+        if (period_us && in->phase_us < NC_SIM_DURATION_US)
+            spikes = (uint64_t)size * (1u + (NC_SIM_DURATION_US - 1u - in->phase_us) / period_us);
+        break;
+    }}
+    case NC_INPUT_KIND_SPIKE_FILE: {{
+        FILE* f = fopen(in->path, "r");
+        if (!f) {{ fprintf(stderr, "input %s: cannot open %s\n", in->name, in->path); break; }}
+        unsigned long neuron; double time_ms; char line[256];
+        while (fgets(line, sizeof line, f))
+            if (sscanf(line, "%lu,%lf", &neuron, &time_ms) == 2 && neuron < size && time_ms >= 0.0 && time_ms * 1000.0 < NC_SIM_DURATION_US)
+                ++spikes;
+        fclose(f);
+        break;
+    }}
+    default:
+        fprintf(stderr, "input %s: aer_stream inputs are not read by this runtime\n", in->name);
+    }}
+    return spikes;
+}}
+"#,
+            rows = rows.join(", "),
+        );
+        let lp = r#"
+    /* Inputs: spikes each stimulus delivers over the run */
+    for (size_t q = 0; q < NC_NUM_INPUTS; ++q) {
+        printf("{\"metric\":\"input.spikes\",\"value\":%llu,\"labels\":{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\",\"population\":\"%s\",\"input\":\"%s\"}}\n",
+               (unsigned long long)nc_input_spikes(&nc_inputs[q]), graph, backend, isa, simulator, pop_names[nc_inputs[q].target], nc_inputs[q].name);
+    }
+"#.to_string();
+        (tables, lp)
+    };
+
+    // Probe schedule from graph.h: samples each probe records over the run (only when the
+    // graph has probes on known populations)
+    let (probe_tables, probe_loop) = if graph.probes.is_empty() || graph.populations.is_empty() {
+        (String::new(), String::new())
+    } else {
+        let rows: Vec<String> = (0..graph.probes.len())
+            .map(|i| format!("{{ NC_PROBE_{i}_TARGET, NC_PROBE_{i}_VARS, NC_PROBE_{i}_INTERVAL_US, NC_PROBE_{i}_START_US, NC_PROBE_{i}_STOP_US }}"))
+            .collect();
+        let tables = format!(
+            r#"typedef struct {{ uint32_t target, vars, interval_us, start_us, stop_us; }} nc_probe_t;
+static const nc_probe_t nc_probes[NC_NUM_PROBES] = {{ {rows} }};
+
+/* One sample per interval (every step when 0) inside [start, min(stop, duration)) */
+static uint64_t nc_probe_samples(const nc_probe_t* pr) {{
+    uint32_t stop = pr->stop_us < NC_SIM_DURATION_US ? pr->stop_us : NC_SIM_DURATION_US;
+    uint32_t every = pr->interval_us ? pr->interval_us : NC_SIM_STEP_US;
+    if (pr->vars == 0u || pr->start_us >= stop) return 0ull;
+    return ((uint64_t)(stop - pr->start_us) + every - 1u) / every;
+}}
+"#,
+            rows = rows.join(", "),
+        );
+        let lp = r#"
+    /* Probes: samples recorded within each probe's window and interval */
+    for (size_t q = 0; q < NC_NUM_PROBES; ++q) {
+        printf("{\"metric\":\"probe.samples\",\"value\":%llu,\"labels\":{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\",\"population\":\"%s\",\"probe\":\"%u\"}}\n",
+               (unsigned long long)nc_probe_samples(&nc_probes[q]), graph, backend, isa, simulator, pop_names[nc_probes[q].target], (unsigned)q);
+    }
+"#.to_string();
+        (tables, lp)
+    };
+
+    // Simulated run length shared by the input and probe tables (override with -D)
+    let sim_clock = if input_tables.is_empty() && probe_tables.is_empty() {
+        ""
+    } else {
+        "#ifndef NC_SIM_DURATION_US\n#define NC_SIM_DURATION_US 1000000u\n#endif\n#ifndef NC_SIM_STEP_US\n#define NC_SIM_STEP_US 1000u\n#endif\n"
+    };
+
     let c_src = format!(
 r#"#include <stdio.h>
 #include <stdint.h>
 #include <stdlib.h>
 #include <time.h>
+#include "graph.h"
 
 /* RISC-V pass metadata (from pipeline/config):
  *  - align_bytes={align}
@@ -747,6 +1225,7 @@ r#"#include <stdio.h>
   #include <riscv_vector.h>
 #endif
 
+{pop_tables}{sim_clock}{input_tables}{probe_tables}
 static inline uint64_t now_ns() {{
     struct timespec ts;
     clock_gettime(CLOCK_MONOTONIC, &ts);
@@ -761,16 +1240,16 @@ static inline uint64_t rdinstret(void) {{ uint64_t x; __asm__ volatile("rdinstre
 static inline uint64_t rdcycle(void) {{ return 0ull; }}
 static inline uint64_t rdinstret(void) {{ return 0ull; }}
 #endif
-
+{hpm_decls}
 int main(void) {{
     const char* graph = "{graph}";
     const char* backend = "riscv";
     const char* isa = "rv64gcv";
-    const char* simulator = "qemu";
+    const char* simulator = "{simulator}";
 
     uint64_t c0 = rdcycle();
     uint64_t i0 = rdinstret();
-
+{hpm_begin}
     uint64_t t0 = now_ns();
 
     /* Workloop: vectorized sum-reduction with scalar fallback */
@@ -780,17 +1259,7 @@ int main(void) {{
     if (!data) return 1;
     for (size_t ii = 0; ii < n; ++ii) {{ data[ii] = (uint64_t)ii; }}
 
-    size_t ii = 0;
-    size_t vl1 = vsetvl_e64m1(1);
-    vuint64m1_t v_acc = vmv_v_x_u64m1(0, vl1);
-    while (ii < n) {{
-        size_t vl = vsetvl_e64m8(n - ii);
-        vuint64m8_t v_data = vle64_v_u64m8(&data[ii], vl);
-        v_acc = vredsum_vs_u64m8_u64m1(v_data, v_acc, vl);
-        ii += vl;
-    }}
-    uint64_t sum = vmv_x_s_u64m1_u64(v_acc);
-    volatile uint64_t acc = sum;
+{rvv_loop}    volatile uint64_t acc = sum;
     free(data);
 #else
     volatile uint64_t acc = 0;
@@ -801,7 +1270,7 @@ int main(void) {{
 
     uint64_t c1 = rdcycle();
     uint64_t i1 = rdinstret();
-
+{hpm_end}
     double step_ns = (double)(t1 - t0);
     printf("{{\"metric\":\"kernel.step_ns\",\"value\":%.0f,\"labels\":{{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\"}}}}\\n", step_ns, graph, backend, isa, simulator);
     printf("{{\"metric\":\"events.processed\",\"value\":%d,\"labels\":{{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\"}}}}\\n", 100000, graph, backend, isa, simulator);
@@ -809,11 +1278,24 @@ int main(void) {{
            (unsigned long long)(c1 - c0), graph, backend, isa, simulator);
     printf("{{\"metric\":\"cpu.instret\",\"value\":%llu,\"labels\":{{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\"}}}}\\n",
            (unsigned long long)(i1 - i0), graph, backend, isa, simulator);
-    (void)acc;
+{hpm_print}{pop_loop}{input_loop}{probe_loop}    (void)acc;
     return 0;
 }}
 "#,
         graph = graph_name,
+        pop_tables = pop_tables,
+        pop_loop = pop_loop,
+        sim_clock = sim_clock,
+        input_tables = input_tables,
+        input_loop = input_loop,
+        probe_tables = probe_tables,
+        probe_loop = probe_loop,
+        rvv_loop = rvv_loop,
+        hpm_decls = hpm_decls,
+        hpm_begin = hpm_begin,
+        hpm_end = hpm_end,
+        hpm_print = hpm_print,
+        simulator = RiscvSim::from_env().label(),
         align = align_bytes,
         qbits = quant_bits_default,
         fused = fused_stage,
@@ -837,10 +1319,225 @@ int main(void) {{
         "RVV intent: enabled={}, vlen_bytes={}",
         vector_enabled, vlen_bytes
     )?;
+    if graph_flag(graph, "rv_emit_cmake") {
+        let try_vector = cfg!(feature = "riscv-v") && vector_enabled;
+        emit_cmake_project(out_dir, manifest, try_vector)?;
+        writeln!(readme, "CMake: CMakeLists.txt + riscv64-toolchain.cmake")?;
+    }
+    Ok(())
+}
+
+/// Write `CMakeLists.txt` and a `riscv64-toolchain.cmake` cross toolchain file for the linux_user runtime.
+/// Flags mirror `build_rv64_linux_binary` (-O2, static link, RVV march when attempted, NC_RISCV_CFLAGS),
+/// with `-march`/`-mabi` spelled out so the CMake build does not depend on compiler defaults.
+fn emit_cmake_project(out_dir: &Path, manifest: &nc_hal::TargetManifest, try_vector: bool) -> Result<()> {
+    let base = if try_vector { "rv64gcv" } else { "rv64gc" };
+    let march = if manifest_has_zbb(manifest) { format!("{base}_zbb") } else { base.to_string() };
+    let mabi = manifest
+        .capabilities
+        .as_ref()
+        .and_then(|c| c.abi.as_deref())
+        .filter(|a| a.starts_with("lp64"))
+        .unwrap_or("lp64d");
+    let extra: String = extra_cflags().iter().map(|f| format!(" \"{f}\"")).collect();
+
+    let toolchain = r#"# Cross toolchain for the generated RV64 Linux runtime (generated)
+set(CMAKE_SYSTEM_NAME Linux)
+set(CMAKE_SYSTEM_PROCESSOR riscv64)
+if(NOT CMAKE_C_COMPILER)
+  set(CMAKE_C_COMPILER riscv64-linux-gnu-gcc)
+endif()
+set(CMAKE_FIND_ROOT_PATH_MODE_PROGRAM NEVER)
+set(CMAKE_FIND_ROOT_PATH_MODE_LIBRARY ONLY)
+set(CMAKE_FIND_ROOT_PATH_MODE_INCLUDE ONLY)
+"#;
+    fs::write(out_dir.join("riscv64-toolchain.cmake"), toolchain).context("write riscv64-toolchain.cmake")?;
+
+    let lists = format!(
+        r#"# RV64 Linux runtime for target '{target}' (generated)
+cmake_minimum_required(VERSION 3.13)
+if(NOT CMAKE_TOOLCHAIN_FILE)
+  set(CMAKE_TOOLCHAIN_FILE ${{CMAKE_CURRENT_SOURCE_DIR}}/riscv64-toolchain.cmake)
+endif()
+project(nc_riscv_runtime C)
+
+add_executable(prog-rv64 main.c)
+target_include_directories(prog-rv64 PRIVATE ${{CMAKE_CURRENT_SOURCE_DIR}})
+target_compile_options(prog-rv64 PRIVATE -O2 -march={march} -mabi={mabi}{extra})
+target_link_options(prog-rv64 PRIVATE -static -march={march} -mabi={mabi})
+"#,
+        target = manifest.name,
+    );
+    fs::write(out_dir.join("CMakeLists.txt"), lists).context("write CMakeLists.txt")?;
+    Ok(())
+}
+
+/// Map an arbitrary name to an upper-case C identifier fragment (non-alphanumerics become '_').
+fn c_ident(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// `NC_INPUT_<NAME>` fragment of each input, in order; sanitized names that collide get an
+/// `_<index>` suffix.
+fn input_idents(graph: &nc_nir::Graph) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::with_capacity(graph.inputs.len());
+    for (i, inp) in graph.inputs.iter().enumerate() {
+        let mut ident = c_ident(&inp.name);
+        if !seen.insert(ident.clone()) {
+            ident = format!("{ident}_{i}");
+            seen.insert(ident.clone());
+        }
+        out.push(ident);
+    }
+    out
+}
+
+/// Write `graph.h` with graph dimension constants for the emitted C runtimes.
+fn emit_graph_header(out_dir: &Path, graph: &nc_nir::Graph) -> Result<()> {
+    let total: u64 = graph.populations.iter().map(|p| p.size as u64).sum();
+    let mut h = String::new();
+    h.push_str(&format!("/* Graph constants for '{}' (generated) */\n", c_string_escape(&graph.name).replace("*/", "*_/")));
+    h.push_str("#ifndef NC_GRAPH_H\n#define NC_GRAPH_H\n\n");
+    h.push_str(&format!("#define NC_NUM_POPULATIONS {}u\n", graph.populations.len()));
+    h.push_str(&format!("#define NC_TOTAL_NEURONS {total}u\n"));
+    h.push_str(&format!("#define NC_NUM_CONNECTIONS {}u\n\n", graph.connections.len()));
+    // Per-population sizes; sanitized names that collide get an index suffix
+    let mut seen = std::collections::HashSet::new();
+    for (i, p) in graph.populations.iter().enumerate() {
+        let mut ident = c_ident(&p.name);
+        if !seen.insert(ident.clone()) {
+            ident = format!("{ident}_{i}");
+            seen.insert(ident.clone());
+        }
+        h.push_str(&format!("#define NC_POP_{ident}_SIZE {}u\n", p.size));
+    }
+    // Stimulus inputs: kind, target population index, and rate / path / URI
+    h.push_str(&format!("\n#define NC_NUM_INPUTS {}u\n", graph.inputs.len()));
+    if !graph.inputs.is_empty() {
+        h.push_str("#define NC_INPUT_KIND_POISSON 1\n#define NC_INPUT_KIND_REGULAR 2\n#define NC_INPUT_KIND_SPIKE_FILE 3\n#define NC_INPUT_KIND_AER_STREAM 4\n");
+    }
+    for (inp, ident) in graph.inputs.iter().zip(input_idents(graph)) {
+        let target = graph
+            .populations
+            .iter()
+            .position(|p| p.name == inp.target)
+            .ok_or_else(|| anyhow::anyhow!("input '{}' targets unknown population '{}'", inp.name, inp.target))?;
+        h.push_str(&format!("#define NC_INPUT_{ident}_KIND NC_INPUT_KIND_{}\n", inp.source.kind().to_uppercase()));
+        h.push_str(&format!("#define NC_INPUT_{ident}_TARGET {target}u\n"));
+        h.push_str(&format!("#define NC_INPUT_{ident}_SEED {}ull\n", inp.seed));
+        if let Some(rate) = inp.source.rate_hz() {
+            h.push_str(&format!("#define NC_INPUT_{ident}_RATE_HZ {rate:?}\n"));
+        }
+        match &inp.source {
+            nc_nir::InputSource::Regular { phase_ms, .. } => {
+                h.push_str(&format!("#define NC_INPUT_{ident}_PHASE_US {}u\n", (phase_ms * 1000.0).round().min(u32::MAX as f64) as u64));
+            }
+            nc_nir::InputSource::SpikeFile { path } => {
+                h.push_str(&format!("#define NC_INPUT_{ident}_PATH \"{}\"\n", c_string_escape(path)));
+            }
+            nc_nir::InputSource::AerStream { uri, .. } => {
+                h.push_str(&format!("#define NC_INPUT_{ident}_URI \"{}\"\n", c_string_escape(uri)));
+            }
+            _ => {}
+        }
+    }
+    // Probes (by index): target population index, recorded variables mask, sampling interval
+    // (0 = every step) and window in microseconds, and the preferred output format
+    h.push_str(&format!("\n#define NC_NUM_PROBES {}u\n", graph.probes.len()));
+    if !graph.probes.is_empty() {
+        h.push_str("#define NC_PROBE_VAR_SPIKES 1u\n#define NC_PROBE_VAR_V 2u\n#define NC_PROBE_VAR_WEIGHTS 4u\n#define NC_PROBE_NO_STOP 0xFFFFFFFFu\n");
+        h.push_str("#define NC_PROBE_FMT_CSV 0\n#define NC_PROBE_FMT_NPY 1\n#define NC_PROBE_FMT_JSONL 2\n#define NC_PROBE_FMT_HDF5 3\n");
+    }
+    let us = |ms: f64| format!("{}u", (ms * 1000.0).round().clamp(0.0, u32::MAX as f64 - 1.0) as u64);
+    for (i, pr) in graph.probes.iter().enumerate() {
+        let target = graph
+            .populations
+            .iter()
+            .position(|p| p.name == pr.target)
+            .ok_or_else(|| anyhow::anyhow!("probe {i} targets unknown population '{}'", pr.target))?;
+        let vars: Vec<String> = pr
+            .recorded_variables()
+            .into_iter()
+            .filter(|v| nc_nir::PROBE_VARIABLES.contains(v))
+            .map(|v| format!("NC_PROBE_VAR_{}", v.to_uppercase()))
+            .collect();
+        let vars = if vars.is_empty() { "0u".to_string() } else { format!("({})", vars.join(" | ")) };
+        h.push_str(&format!("#define NC_PROBE_{i}_TARGET {target}u\n"));
+        h.push_str(&format!("#define NC_PROBE_{i}_VARS {vars}\n"));
+        h.push_str(&format!("#define NC_PROBE_{i}_INTERVAL_US {}\n", pr.interval_ms.map_or("0u".to_string(), us)));
+        h.push_str(&format!("#define NC_PROBE_{i}_START_US {}\n", us(pr.start_ms.unwrap_or(0.0))));
+        h.push_str(&format!("#define NC_PROBE_{i}_STOP_US {}\n", pr.stop_ms.map_or("NC_PROBE_NO_STOP".to_string(), us)));
+        h.push_str(&format!("#define NC_PROBE_{i}_FORMAT NC_PROBE_FMT_{}\n", pr.format.unwrap_or_default().as_str().to_uppercase()));
+    }
+    h.push_str("\n#endif /* NC_GRAPH_H */\n");
+    fs::write(out_dir.join("graph.h"), h).context("write graph.h")?;
     Ok(())
 }
 
+/// Extra compiler flags from `NC_RISCV_CFLAGS` (whitespace-split); empty when unset.
+fn extra_cflags() -> Vec<String> {
+    std::env::var("NC_RISCV_CFLAGS")
+        .map(|v| v.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Escape a string for use inside a C string literal.
+fn c_string_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_ascii_graphic() || c == ' ' => out.push(c),
+            _ => out.push('_'),
+        }
+    }
+    out
+}
+
+/// Memoized tool lookups keyed by the candidate list.
+struct ToolCache {
+    entries: std::sync::Mutex<std::collections::HashMap<Vec<String>, Option<String>>>,
+}
+
+impl ToolCache {
+    fn new() -> Self {
+        Self { entries: std::sync::Mutex::new(std::collections::HashMap::new()) }
+    }
+
+    /// Return the cached result for `candidates`, running `probe` only on the first lookup.
+    fn lookup(&self, candidates: &[&str], probe: impl FnOnce(&[&str]) -> Option<String>) -> Option<String> {
+        let key: Vec<String> = candidates.iter().map(|c| c.to_string()).collect();
+        if let Some(hit) = self.entries.lock().ok().and_then(|m| m.get(&key).cloned()) {
+            return hit;
+        }
+        let found = probe(candidates);
+        if let Ok(mut m) = self.entries.lock() {
+            m.insert(key, found.clone());
+        }
+        found
+    }
+}
+
+/// First candidate found on the login-shell PATH. Results are cached for the life of the
+/// process; set `NC_RISCV_NO_TOOL_CACHE=1` to probe every time.
 fn detect_tool(candidates: &[&str]) -> Option<String> {
+    static CACHE: std::sync::OnceLock<ToolCache> = std::sync::OnceLock::new();
+    if std::env::var("NC_RISCV_NO_TOOL_CACHE").ok().as_deref() == Some("1") {
+        return probe_tools(candidates);
+    }
+    CACHE.get_or_init(ToolCache::new).lookup(candidates, probe_tools)
+}
+
+fn probe_tools(candidates: &[&str]) -> Option<String> {
     for c in candidates {
         if Command::new("sh").arg("-lc").arg(format!("command -v {c}")).status().ok()?.success() {
             return Some((*c).to_string());
@@ -849,7 +1546,59 @@ fn detect_tool(candidates: &[&str]) -> Option<String> {
     None
 }
 
-fn build_rv64_linux_binary(out_dir: &Path) -> Result<PathBuf> {
+/// Per-build switches for `build_rv64_linux_binary`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Rv64BuildOptions {
+    /// Repeat the successful compile with `-S` to write `main.s` (graph attribute `rv_emit_asm`).
+    emit_asm: bool,
+    /// Prefer a fully static musl build, falling back to glibc (`NC_RISCV_STATIC_MUSL=1`).
+    static_musl: bool,
+    /// Add `_zbb` to `-march` so the spike-word popcount in `main.c` compiles to `cpop`.
+    zbb: bool,
+}
+
+impl Rv64BuildOptions {
+    fn resolve(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Self {
+        Self {
+            emit_asm: graph_flag(graph, "rv_emit_asm"),
+            static_musl: std::env::var("NC_RISCV_STATIC_MUSL").ok().as_deref() == Some("1"),
+            zbb: manifest_has_zbb(manifest),
+        }
+    }
+}
+
+/// True when the manifest lists `zbb` in `extensions` (case-insensitive) or spells it in `isa`.
+fn manifest_has_zbb(manifest: &nc_hal::TargetManifest) -> bool {
+    let Some(caps) = manifest.capabilities.as_ref() else { return false };
+    caps.extensions.as_ref().is_some_and(|e| e.iter().any(|x| x.eq_ignore_ascii_case("zbb")))
+        || caps.isa.as_deref().is_some_and(|isa| isa.to_ascii_lowercase().split('_').any(|x| x == "zbb"))
+}
+
+/// Build-cache key for `build_rv64_linux_binary`: every emitted C source and header in
+/// `out_dir` (`main.c`, `graph.h`, ...; by name and content) plus the build settings.
+fn rv64_build_hash(out_dir: &Path, try_vector: bool, cflags: &[String], opts: &Rv64BuildOptions) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut h = std::collections::hash_map::DefaultHasher::new();
+    let mut sources: Vec<PathBuf> = fs::read_dir(out_dir)
+        .map(|rd| rd.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    sources.retain(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("c" | "h")));
+    sources.sort();
+    for p in &sources {
+        p.file_name().hash(&mut h);
+        fs::read(p).unwrap_or_default().hash(&mut h);
+    }
+    try_vector.hash(&mut h);
+    cflags.hash(&mut h);
+    opts.emit_asm.hash(&mut h);
+    opts.static_musl.hash(&mut h);
+    opts.zbb.hash(&mut h);
+    format!("{:016x}", h.finish())
+}
+
+/// Cross-compile `main.c` into `prog-rv64`; see `Rv64BuildOptions` for the optional musl and `-S` steps.
+/// Build warnings are appended to `warn_lines` for the caller's `WARN.txt`.
+fn build_rv64_linux_binary(out_dir: &Path, opts: &Rv64BuildOptions, warn_lines: &mut Vec<String>) -> Result<PathBuf> {
     let main_c = out_dir.join("main.c");
     let exe = out_dir.join("prog-rv64");
 
@@ -876,131 +1625,141 @@ fn build_rv64_linux_binary(out_dir: &Path) -> Result<PathBuf> {
         }
     }
 
-    let mut warn_lines: Vec<String> = Vec::new();
-
-    // Helper to append build info
-    let append_build_info = |tool: &str, built_with_vector: bool| {
+    // Build cache: skip the compiler when the emitted sources, the vector intent, and extra CFLAGS match the last successful build.
+    // NC_RISCV_FORCE_REBUILD=1 bypasses the cache.
+    let stamp_path = out_dir.join(".build-hash");
+    let cflags = extra_cflags();
+    let src_hash = rv64_build_hash(out_dir, try_vector, &cflags, opts);
+    let force_rebuild = std::env::var("NC_RISCV_FORCE_REBUILD").ok().as_deref() == Some("1");
+    if !force_rebuild && exe.exists() && fs::read_to_string(&stamp_path).ok().as_deref() == Some(src_hash.as_str()) {
         if let Ok(mut f) = OpenOptions::new()
             .create(true)
             .append(true)
             .open(out_dir.join("README.txt"))
         {
-            let _ = writeln!(
-                f,
-                "Build: toolchain={tool}, vector_intent={}, built_with_vector={}",
-                if cfg!(feature = "riscv-v") { "true" } else { "false" },
-                built_with_vector
-            );
+            let _ = writeln!(f, "Build: cache-hit");
         }
-    };
+        return Ok(exe);
+    }
 
-    // Prefer GCC cross, then Clang cross
-    if let Some(cc) = detect_tool(&["riscv64-linux-gnu-gcc"]) {
-        if try_vector {
-            // Try GCC with RVV (static, then dynamic)
-            let status = Command::new(&cc)
-                .arg("-O2")
-                .arg("-static")
-                .arg("-march=rv64gcv")
-                .arg("-o")
-                .arg(&exe)
-                .arg(&main_c)
-                .status()
-                .context("invoke riscv64-linux-gnu-gcc (vector, static)")?;
-            if status.success() {
-                append_build_info("gcc", true);
-                return Ok(exe);
+    // Candidate invocations in preference order: musl (when requested), GCC cross, then Clang cross;
+    // vector before scalar, static before dynamic. (tool, compiler, flags, vector, zbb)
+    let mut attempts: Vec<(&str, String, Vec<&str>, bool, bool)> = Vec::new();
+    // Zbb adds an explicit scalar -march; without it scalar builds keep the compiler default.
+    // A toolchain that rejects `_zbb` gets the whole list again without it.
+    let variants: &[bool] = if opts.zbb { &[true, false] } else { &[false] };
+    for &zbb in variants {
+        let (march_v, march_s) = if zbb { ("-march=rv64gcv_zbb", Some("-march=rv64gc_zbb")) } else { ("-march=rv64gcv", None) };
+        let scalar = |base: &[&'static str]| -> Vec<&'static str> { base.iter().copied().chain(march_s).collect() };
+        if opts.static_musl {
+            if let Some(cc) = detect_tool(&["riscv64-linux-musl-gcc", "riscv64-unknown-linux-musl-gcc"]) {
+                if try_vector {
+                    attempts.push(("gcc-musl", cc.clone(), vec!["-O2", "-static", march_v], true, zbb));
+                }
+                attempts.push(("gcc-musl", cc, scalar(&["-O2", "-static"]), false, zbb));
             }
-            let status2 = Command::new(&cc)
-                .arg("-O2")
-                .arg("-march=rv64gcv")
-                .arg("-o")
-                .arg(&exe)
-                .arg(&main_c)
-                .status()
-                .context("invoke riscv64-linux-gnu-gcc (vector, dynamic)")?;
-            if status2.success() {
-                append_build_info("gcc", true);
-                return Ok(exe);
+            if let Some(clang) = detect_tool(&["clang"]) {
+                if try_vector {
+                    attempts.push(("clang-musl", clang.clone(), vec!["--target=riscv64-unknown-linux-musl", "-O2", "-static", march_v], true, zbb));
+                }
+                attempts.push(("clang-musl", clang, scalar(&["--target=riscv64-unknown-linux-musl", "-O2", "-static"]), false, zbb));
             }
-            warn_lines.push("rvv: GCC vector build failed, falling back to scalar".into());
         }
-
-        // Scalar fallback (static then dynamic)
-        let status3 = Command::new(&cc)
-            .arg("-O2")
-            .arg("-static")
-            .arg("-o")
-            .arg(&exe)
-            .arg(&main_c)
-            .status()
-            .context("invoke riscv64-linux-gnu-gcc (scalar, static)")?;
-        if status3.success() {
-            append_build_info("gcc", false);
-            if !warn_lines.is_empty() {
-                let _ = fs::write(out_dir.join("WARN.txt"), warn_lines.join("\n"));
+        if let Some(cc) = detect_tool(&["riscv64-linux-gnu-gcc"]) {
+            if try_vector {
+                attempts.push(("gcc", cc.clone(), vec!["-O2", "-static", march_v], true, zbb));
+                attempts.push(("gcc", cc.clone(), vec!["-O2", march_v], true, zbb));
             }
-            return Ok(exe);
+            attempts.push(("gcc", cc.clone(), scalar(&["-O2", "-static"]), false, zbb));
+            attempts.push(("gcc", cc, scalar(&["-O2"]), false, zbb));
         }
-        let status4 = Command::new(&cc)
-            .arg("-O2")
-            .arg("-o")
-            .arg(&exe)
-            .arg(&main_c)
-            .status()
-            .context("invoke riscv64-linux-gnu-gcc (scalar, dynamic)")?;
-        if status4.success() {
-            append_build_info("gcc", false);
-            if !warn_lines.is_empty() {
-                let _ = fs::write(out_dir.join("WARN.txt"), warn_lines.join("\n"));
+        if let Some(clang) = detect_tool(&["clang"]) {
+            if try_vector {
+                attempts.push(("clang", clang.clone(), vec!["--target=riscv64-unknown-linux-gnu", "-O2", march_v], true, zbb));
             }
-            return Ok(exe);
+            attempts.push(("clang", clang, scalar(&["--target=riscv64-unknown-linux-gnu", "-O2"]), false, zbb));
         }
     }
-
-    if let Some(clang) = detect_tool(&["clang"]) {
-        if try_vector {
-            let status = Command::new(&clang)
-                .arg("--target=riscv64-unknown-linux-gnu")
-                .arg("-O2")
-                .arg("-march=rv64gcv")
-                .arg("-o")
-                .arg(&exe)
-                .arg(&main_c)
-                .status()
-                .context("invoke clang --target=riscv64-unknown-linux-gnu (vector)")?;
-            if status.success() {
-                append_build_info("clang", true);
-                return Ok(exe);
+    let musl_attempted = attempts.iter().any(|a| a.0.ends_with("-musl"));
+
+    let mut prev: Option<(&str, bool, bool)> = None;
+    for (tool, cc, flags, vector, zbb) in &attempts {
+        if let Some((prev_tool, true, prev_zbb)) = prev {
+            if prev_tool == *tool && !*vector && prev_zbb == *zbb {
+                let name = if tool.starts_with("gcc") { "GCC" } else { "Clang" };
+                let line = format!("rvv: {name} vector build failed, falling back to scalar");
+                if !warn_lines.contains(&line) {
+                    warn_lines.push(line);
+                }
+            }
+        }
+        if let Some((_, _, true)) = prev {
+            if !*zbb {
+                warn_lines.push("zbb: no build accepted -march=..._zbb, retrying without Zbb".to_string());
             }
-            warn_lines.push("rvv: Clang vector build failed, falling back to scalar".into());
         }
+        prev = Some((tool, *vector, *zbb));
 
-        let status2 = Command::new(&clang)
-            .arg("--target=riscv64-unknown-linux-gnu")
-            .arg("-O2")
+        let status = Command::new(cc)
+            .args(flags)
             .arg("-o")
             .arg(&exe)
             .arg(&main_c)
+            .args(&cflags)
             .status()
-            .context("invoke clang --target=riscv64-unknown-linux-gnu (scalar)")?;
-        if status2.success() {
-            append_build_info("clang", false);
-            if !warn_lines.is_empty() {
-                let _ = fs::write(out_dir.join("WARN.txt"), warn_lines.join("\n"));
-            }
-            return Ok(exe);
+            .with_context(|| format!("invoke {cc} {}", flags.join(" ")))?;
+        if !status.success() {
+            continue;
         }
-    }
-
-    // No toolchain or build failed; write warnings if any and return an error
-    if !warn_lines.is_empty() {
-        let _ = fs::write(out_dir.join("WARN.txt"), warn_lines.join("\n"));
-    }
-    anyhow::bail!("no suitable RISC-V cross toolchain found to build {:?}", exe)
-}
 
-fn run_qemu_and_capture(exe: &Path, out_dir: &Path) -> Result<()> {
+        // Record build info and the source hash for the build cache
+        let _ = fs::write(&stamp_path, &src_hash);
+        if let Ok(mut f) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(out_dir.join("README.txt"))
+        {
+            let extra = if cflags.is_empty() { String::new() } else { format!(", cflags={}", cflags.join(" ")) };
+            let _ = writeln!(
+                f,
+                "Build: toolchain={tool}, vector_intent={}, built_with_vector={vector}{extra}",
+                if cfg!(feature = "riscv-v") { "true" } else { "false" },
+            );
+            if opts.static_musl {
+                let link = if tool.ends_with("-musl") {
+                    "static-musl"
+                } else if musl_attempted {
+                    "glibc (musl requested; musl build failed)"
+                } else {
+                    "glibc (musl requested; no musl toolchain found)"
+                };
+                let _ = writeln!(f, "Link: {link}");
+            }
+            if opts.emit_asm {
+                // Same compiler and flags as the successful build, stopped after code generation
+                let asm = Command::new(cc)
+                    .args(flags)
+                    .arg("-S")
+                    .arg("-o")
+                    .arg(out_dir.join("main.s"))
+                    .arg(&main_c)
+                    .args(&cflags)
+                    .status();
+                match asm {
+                    Ok(st) if st.success() => { let _ = writeln!(f, "Asm: main.s"); }
+                    Ok(st) => warn_lines.push(format!("asm: {tool} -S exited with {st}")),
+                    Err(e) => warn_lines.push(format!("asm: cannot invoke {tool} -S: {e}")),
+                }
+            }
+        }
+        return Ok(exe);
+    }
+
+    // No toolchain or build failed
+    anyhow::bail!("no suitable RISC-V cross toolchain found to build {:?}", exe)
+}
+
+fn run_qemu_and_capture(exe: &Path, out_dir: &Path) -> Result<()> {
     let qemu = detect_tool(&["qemu-riscv64"]).ok_or_else(|| anyhow::anyhow!("qemu-riscv64 not found"))?;
     let output = Command::new(qemu)
         .arg(exe)
@@ -1016,6 +1775,32 @@ fn run_qemu_and_capture(exe: &Path, out_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Run a linux_user executable under the `spike` ISA simulator via the proxy kernel and capture
+/// its JSONL stdout. `pk` is looked up on PATH unless `NC_RISCV_PK` points at it;
+/// `NC_RISCV_SPIKE_ISA` is forwarded as `--isa` (spike defaults to RV64IMAFDC, so set it for RVV builds).
+fn run_spike_and_capture(exe: &Path, out_dir: &Path) -> Result<()> {
+    let spike = detect_tool(&["spike"]).ok_or_else(|| anyhow::anyhow!("spike not found"))?;
+    let pk = std::env::var("NC_RISCV_PK")
+        .ok()
+        .filter(|p| Path::new(p).exists())
+        .or_else(|| detect_tool(&["pk"]))
+        .ok_or_else(|| anyhow::anyhow!("pk (proxy kernel) not found; set NC_RISCV_PK"))?;
+    let mut cmd = Command::new(spike);
+    if let Ok(isa) = std::env::var("NC_RISCV_SPIKE_ISA") {
+        if !isa.trim().is_empty() {
+            cmd.arg(format!("--isa={}", isa.trim()));
+        }
+    }
+    let output = cmd.arg(pk).arg(exe).output().context("running spike")?;
+    let dest = if let Ok(p) = std::env::var("NC_PROFILE_JSONL") {
+        PathBuf::from(p)
+    } else {
+        out_dir.join("profile.jsonl")
+    };
+    fs::write(&dest, output.stdout)?;
+    Ok(())
+}
+
 /// Emit control-plane artifacts: main.c, accelerator.repl, accelerator.py, run.resc
 fn emit_control_plane_runtime(out_dir: &Path, graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Result<()> {
     if !out_dir.exists() {
@@ -1031,6 +1816,48 @@ fn emit_control_plane_runtime(out_dir: &Path, graph: &nc_nir::Graph, manifest: &
     let _mmio_supported = caps.and_then(|c| c.mmio_supported).unwrap_or(false);
     let dma_supported = caps.and_then(|c| c.dma_supported).unwrap_or(false);
 
+    emit_graph_header(out_dir, graph)?;
+
+    // Double-buffered DMA: program buffer A, start, program buffer B while A is busy, then swap.
+    // Chunk count comes from NC_RISCV_DMA_CHUNKS (default 4).
+    let dma_block = if dma_supported {
+        let chunks = std::env::var("NC_RISCV_DMA_CHUNKS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(4);
+        format!(r#"
+    // Double-buffered DMA over DMA_CHUNKS chunks
+#define DMA_CHUNKS      {chunks}
+#define DMA_CHUNK_BYTES 1024u
+#define DMA_BUF_A       0x80000000u
+#define DMA_BUF_B       (DMA_BUF_A + DMA_CHUNK_BYTES)
+    printf("Configuring double-buffered DMA (%d chunks)...\n", DMA_CHUNKS);
+    const uint32_t dma_bufs[2] = {{ DMA_BUF_A, DMA_BUF_B }};
+    int dma_cur = 0;
+    mmio_base[DMA_ADDR/4] = dma_bufs[dma_cur];
+    mmio_base[DMA_LEN/4] = DMA_CHUNK_BYTES;
+    mmio_ops += 2;
+    for (int chunk = 0; chunk < DMA_CHUNKS; ++chunk) {{
+        mmio_base[ACCEL_CTRL/4] = CTRL_START;
+        mmio_ops++;
+        if (chunk + 1 < DMA_CHUNKS) {{
+            // Program the other buffer while the current transfer is in flight
+            mmio_base[DMA_ADDR/4] = dma_bufs[dma_cur ^ 1];
+            mmio_base[DMA_LEN/4] = DMA_CHUNK_BYTES;
+            mmio_ops += 2;
+        }}
+        int dma_wait = 1000;
+        while ((mmio_base[ACCEL_STATUS/4] & STATUS_BUSY) && dma_wait-- > 0) {{
+            usleep(10);
+        }}
+        dma_cur ^= 1;
+    }}
+"#)
+    } else {
+        String::new()
+    };
+
     // Generate main.c - Linux user-space program using mmap to access MMIO
     let main_c = format!(r#"#include <stdio.h>
 #include <stdint.h>
@@ -1041,6 +1868,7 @@ fn emit_control_plane_runtime(out_dir: &Path, graph: &nc_nir::Graph, manifest: &
 #include <time.h>
 #include <errno.h>
 #include <string.h>
+#include "graph.h"
 
 #define MMIO_BASE_ADDR  0x{mmio_base:08x}UL
 #define MMIO_SIZE       0x1000UL  // 4KB region
@@ -1095,22 +1923,21 @@ int main(void) {{
 
     printf("Mapped MMIO region at 0x%lx\\n", MMIO_BASE_ADDR);
 
+    // MMIO register writes issued (reported as mmio.operations)
+    uint32_t mmio_ops = 0;
+
     // Reset accelerator
     printf("Resetting accelerator...\\n");
     mmio_base[ACCEL_CTRL/4] = CTRL_RESET;
     usleep(1000); // 1ms delay
     mmio_base[ACCEL_CTRL/4] = 0;
+    mmio_ops += 2;
 
-    // Configure DMA (dummy operation)
-    if ({dma_supported}) {{
-        printf("Configuring DMA...\\n");
-        mmio_base[DMA_ADDR/4] = 0x80000000; // Dummy DMA address
-        mmio_base[DMA_LEN/4] = 1024;        // Dummy DMA length
-    }}
-
+{dma_block}
     // Start accelerator operation
     printf("Starting accelerator operation...\\n");
     mmio_base[ACCEL_CTRL/4] = CTRL_START;
+    mmio_ops++;
 
     // Poll for completion
     int timeout = 1000; // 1000ms timeout
@@ -1143,8 +1970,8 @@ int main(void) {{
            step_ns, graph, backend, isa, simulator);
     printf("{{\"metric\":\"events.processed\",\"value\":%d,\"labels\":{{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\"}}}}\\n",
            1, graph, backend, isa, simulator);
-    printf("{{\"metric\":\"mmio.operations\",\"value\":%d,\"labels\":{{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\"}}}}\\n",
-           {dma_supported} ? 5 : 3, graph, backend, isa, simulator);
+    printf("{{\"metric\":\"mmio.operations\",\"value\":%u,\"labels\":{{\"graph\":\"%s\",\"backend\":\"%s\",\"isa\":\"%s\",\"simulator\":\"%s\"}}}}\\n",
+           mmio_ops, graph, backend, isa, simulator);
 
     // Cleanup
     munmap((void*)mmio_base, MMIO_SIZE);
@@ -1155,7 +1982,7 @@ int main(void) {{
 "#,
         mmio_base = mmio_base_addr,
         graph_name = graph_name,
-        dma_supported = if dma_supported { "1" } else { "0" }
+        dma_block = dma_block
     );
 
     fs::write(out_dir.join("main.c"), main_c)?;
@@ -1456,7 +2283,7 @@ mod tests {
 
         let out_dir = default_out_dir(&g.name, &m.name);
         emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
-        if let Ok(exe) = build_rv64_linux_binary(&out_dir) {
+        if let Ok(exe) = build_rv64_linux_binary(&out_dir, &Rv64BuildOptions::default(), &mut Vec::new()) {
             // Ensure we write to a temp JSONL inside out_dir
             let jsonl = out_dir.join("profile.jsonl");
             std::env::set_var("NC_PROFILE_JSONL", &jsonl);
@@ -1467,6 +2294,659 @@ mod tests {
         }
     }
 
+    #[test]
+    fn linux_runtime_emits_per_population_timing() {
+        let g = nc_nir::fixtures::chain(&[4, 8, 2]);
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv64gcv_linux"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+        "#).unwrap();
+        let out_dir = default_out_dir("popstep", &m.name);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("for (size_t p = 0; p < NC_RV_NUM_POPS; ++p)"), "per-population loop missing");
+        assert!(src.contains(r#"\"population\":\"%s\""#), "population label missing");
+        assert!(src.contains(r#"{ "p0", "p1", "p2" }"#));
+        // The aggregate record remains
+        assert!(src.contains("step_ns, graph, backend, isa, simulator);"));
+    }
+
+    #[test]
+    fn bare_metal_extra_cflags_recorded_if_available() {
+        if detect_tool(&["riscv64-unknown-elf-gcc"]).is_none() {
+            return;
+        }
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let ws_root = PathBuf::from(manifest_dir).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+        let m = nc_hal::parse_target_manifest_path(ws_root.join("targets").join("riscv32imac_bare.toml")).expect("parse manifest");
+        let g = nc_nir::Graph::new("cflags");
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        emit_bare_metal_runtime(&out_dir, &g, &m).unwrap();
+        std::env::set_var("NC_RISCV_CFLAGS", "-DNC_CFLAGS_SENTINEL=1 -g");
+        let res = build_bare_metal_binary(&out_dir, BareMetalArch::from_manifest(&m), false, &mut Vec::new());
+        std::env::remove_var("NC_RISCV_CFLAGS");
+        if res.is_err() {
+            return;
+        }
+        let readme = fs::read_to_string(out_dir.join("README.txt")).unwrap_or_default();
+        assert!(readme.contains("-DNC_CFLAGS_SENTINEL=1 -g"), "README: {readme}");
+    }
+
+    #[test]
+    fn graph_header_constants() {
+        let mut g = nc_nir::fixtures::chain(&[4, 8, 2]);
+        g.populations[2].name = "out-layer.2".to_string();
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv64gcv_linux"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+        "#).unwrap();
+        let out_dir = default_out_dir("graphh", &m.name);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let h = fs::read_to_string(out_dir.join("graph.h")).expect("graph.h written");
+        assert!(h.contains("#define NC_TOTAL_NEURONS 14u"), "graph.h: {h}");
+        assert!(h.contains("#define NC_NUM_POPULATIONS 3u"));
+        assert!(h.contains("#define NC_NUM_CONNECTIONS 2u"));
+        assert!(h.contains("#define NC_POP_P0_SIZE 4u"));
+        assert!(h.contains("#define NC_POP_OUT_LAYER_2_SIZE 2u"));
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("#include \"graph.h\""));
+        assert!(h.contains("#define NC_NUM_INPUTS 0u"));
+
+        g.inputs.push(nc_nir::Input {
+            name: "bg".into(),
+            target: "p1".into(),
+            source: nc_nir::InputSource::Poisson { rate_hz: 50.0 },
+            seed: 0,
+        });
+        g.inputs.push(nc_nir::Input {
+            name: "rec".into(),
+            target: "p0".into(),
+            source: nc_nir::InputSource::SpikeFile { path: "in/spikes.csv".into() },
+            seed: 0,
+        });
+        emit_graph_header(&out_dir, &g).unwrap();
+        let h = fs::read_to_string(out_dir.join("graph.h")).unwrap();
+        assert!(h.contains("#define NC_NUM_INPUTS 2u"), "graph.h: {h}");
+        assert!(h.contains("#define NC_INPUT_BG_KIND NC_INPUT_KIND_POISSON"));
+        assert!(h.contains("#define NC_INPUT_BG_TARGET 1u"));
+        assert!(h.contains("#define NC_INPUT_BG_RATE_HZ 50.0"));
+        assert!(h.contains("#define NC_INPUT_REC_PATH \"in/spikes.csv\""));
+        assert!(h.contains("#define NC_INPUT_BG_SEED 0ull"));
+        assert!(h.contains("#define NC_NUM_PROBES 0u"));
+        g.inputs.push(nc_nir::Input {
+            name: "tick".into(),
+            target: "p0".into(),
+            source: nc_nir::InputSource::Regular { period_ms: 10.0, phase_ms: 2.5 },
+            seed: 7,
+        });
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let h = fs::read_to_string(out_dir.join("graph.h")).unwrap();
+        assert!(h.contains("#define NC_INPUT_TICK_PHASE_US 2500u") && h.contains("#define NC_INPUT_TICK_SEED 7ull"), "graph.h: {h}");
+        // The runtime consumes the inputs instead of only defining them
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("nc_inputs[NC_NUM_INPUTS]") && src.contains("NC_INPUT_TICK_PHASE_US"), "main.c: {src}");
+        assert!(src.contains(r#"\"metric\":\"input.spikes\""#));
+        let mut ghost = g.clone();
+        ghost.inputs[0].target = "ghost".into();
+        let err = emit_graph_header(&out_dir, &ghost).unwrap_err();
+        assert!(err.to_string().contains("input 'bg' targets unknown population 'ghost'"), "{err}");
+
+        g.probes.push(nc_nir::Probe { target: "p1".into(), kind: "spikes".into(), ..Default::default() });
+        g.probes.push(nc_nir::Probe {
+            target: "p0".into(),
+            kind: "v".into(),
+            variables: vec!["v".into(), "spikes".into()],
+            interval_ms: Some(0.5),
+            start_ms: Some(10.0),
+            stop_ms: Some(20.0),
+            format: Some(nc_nir::ProbeFormat::Npy),
+        });
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let h = fs::read_to_string(out_dir.join("graph.h")).unwrap();
+        assert!(h.contains("#define NC_NUM_PROBES 2u"), "graph.h: {h}");
+        assert!(h.contains("#define NC_PROBE_0_TARGET 1u"));
+        assert!(h.contains("#define NC_PROBE_0_VARS (NC_PROBE_VAR_SPIKES)"));
+        assert!(h.contains("#define NC_PROBE_0_INTERVAL_US 0u"));
+        assert!(h.contains("#define NC_PROBE_0_STOP_US NC_PROBE_NO_STOP"));
+        assert!(h.contains("#define NC_PROBE_0_FORMAT NC_PROBE_FMT_CSV"));
+        assert!(h.contains("#define NC_PROBE_1_VARS (NC_PROBE_VAR_V | NC_PROBE_VAR_SPIKES)"));
+        assert!(h.contains("#define NC_PROBE_1_INTERVAL_US 500u"));
+        assert!(h.contains("#define NC_PROBE_1_START_US 10000u"));
+        assert!(h.contains("#define NC_PROBE_1_STOP_US 20000u"));
+        assert!(h.contains("#define NC_PROBE_1_FORMAT NC_PROBE_FMT_NPY"));
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("nc_probes[NC_NUM_PROBES]"));
+        assert!(src.contains(r#"\"metric\":\"probe.samples\""#), "main.c: {src}");
+
+        g.probes.push(nc_nir::Probe { target: "ghost".into(), kind: "spikes".into(), ..Default::default() });
+        let err = emit_graph_header(&out_dir, &g).unwrap_err();
+        assert!(err.to_string().contains("probe 2 targets unknown population 'ghost'"), "{err}");
+    }
+
+    #[test]
+    fn bare_metal_rv64_targets_rv64() {
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv64imac_bare"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+            [capabilities]
+            isa = "rv64imac"
+            abi = "lp64"
+            profile = "bare_metal"
+        "#).unwrap();
+        let arch = BareMetalArch::from_manifest(&m);
+        assert_eq!(arch, BareMetalArch::Rv64);
+        let flags = arch.build_flags(false);
+        assert!(flags.contains(&"-march=rv64imac".to_string()), "flags: {flags:?}");
+        assert!(flags.contains(&"-mabi=lp64".to_string()), "flags: {flags:?}");
+        assert_eq!(arch.qemu_system(), "qemu-system-riscv64");
+
+        let g = nc_nir::Graph::new("bm64");
+        let out_dir = default_out_dir(&g.name, &m.name);
+        emit_bare_metal_runtime(&out_dir, &g, &m).unwrap();
+        let crt0 = fs::read_to_string(out_dir.join("crt0.S")).unwrap();
+        assert!(crt0.contains("sd  zero, 0(a0)") && crt0.contains("addi a0, a0, 8"), "crt0: {crt0}");
+        let ld = fs::read_to_string(out_dir.join("linker.ld")).unwrap();
+        assert!(ld.contains("ALIGN(8)"));
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains(r#"const char* isa = "rv64imac";"#));
+
+        // rv32 manifests keep the rv32 defaults
+        let ws_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+        let m32 = nc_hal::parse_target_manifest_path(ws_root.join("targets").join("riscv32imac_bare.toml")).unwrap();
+        let flags32 = BareMetalArch::from_manifest(&m32).build_flags(false);
+        assert!(flags32.contains(&"-march=rv32imac".to_string()) && flags32.contains(&"-mabi=ilp32".to_string()));
+    }
+
+    #[test]
+    fn bare_metal_big_endian_flags_and_labels() {
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv32imac_be"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+            [capabilities]
+            isa = "rv32imac"
+            profile = "bare_metal"
+            endianness = "big"
+        "#).unwrap();
+        assert!(manifest_big_endian(&m));
+        let arch = BareMetalArch::from_manifest(&m);
+        assert!(arch.build_flags(true).contains(&"-mbig-endian".to_string()));
+        assert!(!arch.build_flags(false).contains(&"-mbig-endian".to_string()));
+
+        let g = nc_nir::Graph::new("be");
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        emit_bare_metal_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert_eq!(src.matches(r#"uart_puts("\",\"endianness\":\"big");"#).count(), 4, "main.c: {src}");
+
+        if detect_tool(&["riscv64-unknown-elf-gcc"]).is_some() {
+            match build_bare_metal_binary(&out_dir, arch, true, &mut Vec::new()) {
+                Ok(_) => {
+                    let readme = fs::read_to_string(out_dir.join("README.txt")).unwrap_or_default();
+                    assert!(readme.contains("-mbig-endian"), "README: {readme}");
+                }
+                Err(e) => assert!(e.to_string().contains("-mbig-endian") || e.to_string().contains("toolchain"), "error: {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn qemu_machine_selection_and_args() {
+        let mut warnings = Vec::new();
+        assert_eq!(qemu_machine(None, &mut warnings), "virt");
+        assert_eq!(qemu_machine(Some("sifive_u"), &mut warnings), "sifive_u");
+        assert!(warnings.is_empty());
+        assert_eq!(qemu_machine(Some("my_board"), &mut warnings), "my_board");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("my_board"));
+
+        let args = qemu_system_args(Path::new("fw.elf"), "sifive_u");
+        let args: Vec<String> = args.iter().map(|a| a.to_string_lossy().into_owned()).collect();
+        let pos = args.iter().position(|a| a == "-machine").expect("-machine present");
+        assert_eq!(args[pos + 1], "sifive_u");
+        assert_eq!(args.last().map(String::as_str), Some("fw.elf"));
+    }
+
+    #[test]
+    fn tool_cache_probes_once() {
+        let cache = ToolCache::new();
+        let probes = std::cell::Cell::new(0);
+        let probe = |c: &[&str]| {
+            probes.set(probes.get() + 1);
+            Some(c[0].to_string())
+        };
+        let a = cache.lookup(&["riscv64-linux-gnu-gcc", "clang"], probe);
+        let b = cache.lookup(&["riscv64-linux-gnu-gcc", "clang"], probe);
+        assert_eq!(a, b);
+        assert_eq!(probes.get(), 1);
+        // A different candidate list is a separate entry; misses are cached too
+        assert_eq!(cache.lookup(&["nc-no-such-tool"], |_| { probes.set(probes.get() + 1); None }), None);
+        assert_eq!(cache.lookup(&["nc-no-such-tool"], |_| { probes.set(probes.get() + 1); None }), None);
+        assert_eq!(probes.get(), 2);
+    }
+
+    #[test]
+    fn rvv_fixed_vlen_when_not_dynamic() {
+        let manifest = |dynamic: bool| nc_hal::parse_target_manifest_str(&format!(r#"
+            name = "riscv64gcv_fixed"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+            [capabilities]
+            has_vector = true
+            vlen_bits_max = 256
+            vlen_is_dynamic = {dynamic}
+        "#)).unwrap();
+        let g = nc_nir::Graph::new("rvvfixed");
+
+        let m = manifest(false);
+        let out_dir = default_out_dir(&g.name, &m.name);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("#define NC_RVV_LANES 4"), "fixed lane count missing");
+        assert!(src.contains("Scalar epilogue"));
+        assert!(!src.contains("vsetvl_e64m8(n - ii)"));
+
+        let m = manifest(true);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("vsetvl_e64m8(n - ii)"));
+        assert!(!src.contains("NC_RVV_LANES"));
+    }
+
+    #[test]
+    fn zihpm_counters_only_when_advertised() {
+        let manifest = |zihpm: bool| nc_hal::parse_target_manifest_str(&format!(r#"
+            name = "riscv64gc_hpm"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+            [capabilities]
+            has_zicntr = true
+            has_zihpm = {zihpm}
+        "#)).unwrap();
+        let g = nc_nir::Graph::new("zihpm");
+
+        let m = manifest(true);
+        let out_dir = default_out_dir(&g.name, &m.name);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("csrr %0, hpmcounter3") && src.contains("csrr %0, hpmcounter4"));
+        assert!(src.contains(r#"\"metric\":\"cpu.hpm3\""#) && src.contains(r#"\"metric\":\"cpu.hpm4\""#));
+
+        let m = manifest(false);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(!src.contains("hpmcounter"));
+        assert!(!src.contains("cpu.hpm3"));
+        assert!(src.contains("rdcycle") && src.contains("rdinstret"));
+    }
+
+    #[test]
+    fn control_plane_dma_double_buffered() {
+        let manifest = |dma: bool| nc_hal::parse_target_manifest_str(&format!(r#"
+            name = "riscv64gc_ctrl_dma"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+            [capabilities]
+            profile = "control_plane"
+            mmio_supported = true
+            mmio_base_addr = 0x40000000
+            dma_supported = {dma}
+            dma_alignment = 64
+        "#)).unwrap();
+        let g = nc_nir::Graph::new("dmabuf");
+
+        let m = manifest(true);
+        let out_dir = default_out_dir(&g.name, &m.name);
+        emit_control_plane_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("mmio_base[DMA_ADDR/4] = dma_bufs[dma_cur];"), "buffer A config missing");
+        assert!(src.contains("mmio_base[DMA_ADDR/4] = dma_bufs[dma_cur ^ 1];"), "buffer B config missing");
+        assert!(src.contains("#define DMA_BUF_B"));
+        assert!(src.contains("mmio_ops, graph, backend, isa, simulator"));
+
+        let m = manifest(false);
+        emit_control_plane_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(!src.contains("DMA_BUF_A"));
+        assert!(src.contains("mmio_base[ACCEL_CTRL/4] = CTRL_START;"));
+    }
+
+    #[test]
+    fn bare_metal_custom_uart_base() {
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv32imac_sifive"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+            [capabilities]
+            isa = "rv32imac"
+            profile = "bare_metal"
+            uart_base_addr = 0x10013000
+        "#).unwrap();
+        let g = nc_nir::Graph::new("uart");
+        let out_dir = default_out_dir(&g.name, &m.name);
+        emit_bare_metal_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("#define UART0_BASE      0x10013000UL"), "UART base not substituted");
+        let readme = fs::read_to_string(out_dir.join("README.txt")).unwrap();
+        assert!(readme.contains("UART @ 0x10013000"));
+    }
+
+    #[test]
+    fn sim_selection_parses_and_spike_degrades_gracefully() {
+        assert_eq!(RiscvSim::parse(None), RiscvSim::Qemu);
+        assert_eq!(RiscvSim::parse(Some("spike")), RiscvSim::Spike);
+        assert_eq!(RiscvSim::parse(Some(" Spike ")), RiscvSim::Spike);
+        assert_eq!(RiscvSim::parse(Some("qemu")), RiscvSim::Qemu);
+        assert_eq!(RiscvSim::parse(Some("bogus")), RiscvSim::Qemu);
+        assert_eq!(RiscvSim::Spike.label(), "spike");
+
+        if detect_tool(&["spike"]).is_none() {
+            let out_dir = default_out_dir("spike-missing", "riscv64gcv_linux");
+            let _ = fs::remove_dir_all(&out_dir);
+            fs::create_dir_all(&out_dir).unwrap();
+            let err = run_spike_and_capture(&out_dir.join("prog-rv64"), &out_dir).unwrap_err();
+            assert!(err.to_string().contains("spike not found"), "error: {err}");
+            assert!(!out_dir.join("profile.jsonl").exists());
+        }
+    }
+
+    #[test]
+    fn provenance_json_records_graph_and_profile() {
+        std::env::set_var("NC_RISCV_QEMU_RUN", "0");
+        let ws_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+        let m = nc_hal::parse_target_manifest_path(ws_root.join("targets").join("riscv32imac_bare.toml")).unwrap();
+        let mut g = nc_nir::fixtures::chain(&[4, 4]);
+        g.name = "prov".to_string();
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        compile(&g, &m).expect("compile");
+        let prov: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(out_dir.join("provenance.json")).expect("provenance.json")).unwrap();
+        assert_eq!(prov["graph"], "prov");
+        assert_eq!(prov["profile"], "bare_metal");
+        assert_eq!(prov["target"], m.name.as_str());
+        assert!(prov["passes"].as_array().is_some_and(|p| p.iter().any(|n| n == "rv-lower")), "passes: {}", prov["passes"]);
+        assert_eq!(prov["content_hash"].as_str().map(str::len), Some(16));
+        assert_eq!(prov["manifest_digest"], m.digest().as_str());
+    }
+
+    #[test]
+    fn compile_with_diagnostics_returns_warnings() {
+        std::env::set_var("NC_RISCV_QEMU_RUN", "0");
+        let ws_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+        let m = nc_hal::parse_target_manifest_path(ws_root.join("targets").join("riscv32imac_bare.toml")).unwrap();
+        let mut g = nc_nir::fixtures::chain(&[4, 4]);
+        g.name = "diag".to_string();
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        let (artifact, warnings) = compile_with_diagnostics(&g, &m).expect("compile");
+        assert!(artifact.starts_with("artifact:"));
+        let on_disk = fs::read_to_string(out_dir.join("WARN.txt")).unwrap_or_default();
+        assert_eq!(warnings.join("\n"), on_disk);
+        if detect_tool(&["riscv64-unknown-elf-gcc"]).is_none() {
+            // Build-failure path: the missing toolchain is reported in the returned list
+            assert!(warnings.iter().any(|w| w.contains("toolchain")), "warnings: {warnings:?}");
+        }
+    }
+
+    #[test]
+    fn build_warnings_reach_diagnostics_without_toolchain() {
+        if detect_tool(&["riscv64-unknown-elf-gcc"]).is_some() {
+            return;
+        }
+        std::env::set_var("NC_RISCV_QEMU_RUN", "0");
+        let ws_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+        let m = nc_hal::parse_target_manifest_path(ws_root.join("targets").join("riscv32imac_bare.toml")).unwrap();
+        let mut g = nc_nir::fixtures::chain(&[4, 4]);
+        g.name = "diag-notool".to_string();
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        let (_, warnings) = compile_with_diagnostics(&g, &m).expect("compile");
+        // The build helper's own line and the caller's build error both land in one WARN.txt
+        assert!(warnings.iter().any(|w| w == "toolchain not found: riscv64-unknown-elf-gcc"), "warnings: {warnings:?}");
+        assert!(warnings.iter().any(|w| w.starts_with("no suitable RISC-V bare-metal toolchain")), "warnings: {warnings:?}");
+        let on_disk = fs::read_to_string(out_dir.join("WARN.txt")).expect("WARN.txt");
+        assert_eq!(warnings.join("\n"), on_disk);
+    }
+
+    #[test]
+    fn pass_meta_csv_has_align_bytes_for_linux_user() {
+        std::env::set_var("NC_RISCV_QEMU_RUN", "0");
+        let ws_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+        let m = nc_hal::parse_target_manifest_path(ws_root.join("targets").join("riscv64gcv_linux.toml")).unwrap();
+        let mut g = nc_nir::fixtures::chain(&[4, 4]);
+        g.name = "passmeta".to_string();
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        compile(&g, &m).expect("compile");
+        let csv = fs::read_to_string(out_dir.join("pass_meta.csv")).expect("pass_meta.csv");
+        let mut rows = csv.lines();
+        assert_eq!(rows.next(), Some("key,value"));
+        assert!(rows.any(|r| r.starts_with("align_bytes,")), "csv: {csv}");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+    }
+
+    #[test]
+    fn run_script_uses_renode_for_control_plane() {
+        std::env::set_var("NC_RISCV_QEMU_RUN", "0");
+        let ws_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+        let m = nc_hal::parse_target_manifest_path(ws_root.join("targets").join("riscv64gc_ctrl.toml")).unwrap();
+        let mut g = nc_nir::fixtures::chain(&[4, 4]);
+        g.name = "runsh".to_string();
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        compile(&g, &m).expect("compile");
+        let script = fs::read_to_string(out_dir.join("run.sh")).expect("run.sh");
+        assert!(script.starts_with("#!/bin/sh"));
+        assert!(script.contains("PROFILE=control_plane"));
+        assert!(script.contains("renode --disable-xwt --console --script run.resc"), "run.sh: {script}");
+        assert!(script.contains("NC_PROFILE_JSONL"));
+        assert!(!script.contains("qemu-riscv64"));
+    }
+
+    #[test]
+    fn compile_many_isolates_out_dirs() {
+        let ws_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+        let m = nc_hal::parse_target_manifest_path(ws_root.join("targets").join("riscv64gcv_linux.toml")).unwrap();
+        let mut a = nc_nir::fixtures::chain(&[4, 4]);
+        a.name = "many-a".to_string();
+        let mut b = nc_nir::fixtures::ring(3, 2, 0.5, 1.0);
+        b.name = "many-b".to_string();
+        let mut bad = nc_nir::fixtures::chain(&[1]);
+        bad.name = "many-bad".to_string();
+        bad.populations[0].size = 0;
+
+        let results = compile_many(&[(&a, &m), (&b, &m), (&a, &m), (&bad, &m)]);
+        assert_eq!(results.len(), 4);
+        let dirs: Vec<String> = results[..3].iter().map(|r| r.as_ref().expect("compile ok").clone()).collect();
+        assert_ne!(dirs[0], dirs[1]);
+        assert_ne!(dirs[0], dirs[2], "duplicate job must not share an out_dir");
+        for d in &dirs {
+            assert!(PathBuf::from(d.trim_start_matches("artifact:")).join("main.c").exists());
+        }
+        assert!(results[3].is_err(), "invalid graph reports its own error");
+    }
+
+    #[test]
+    fn rv64_build_hash_covers_graph_header() {
+        let mut g = nc_nir::fixtures::chain(&[4, 4]);
+        g.name = "ghash".to_string();
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv64gcv_linux"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+        "#).unwrap();
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let opts = Rv64BuildOptions::default();
+        let before = rv64_build_hash(&out_dir, false, &[], &opts);
+        assert_eq!(rv64_build_hash(&out_dir, false, &[], &opts), before, "stable for the same sources");
+        // A graph.h-only change (main.c reads the sizes through the header) invalidates the cache
+        let h = fs::read_to_string(out_dir.join("graph.h")).unwrap();
+        fs::write(out_dir.join("graph.h"), h.replace("#define NC_TOTAL_NEURONS 8u", "#define NC_TOTAL_NEURONS 9u")).unwrap();
+        assert_ne!(rv64_build_hash(&out_dir, false, &[], &opts), before);
+    }
+
+    #[test]
+    fn rv64_build_cache_hit_if_available() {
+        if detect_tool(&["riscv64-linux-gnu-gcc"]).is_none() && detect_tool(&["clang"]).is_none() {
+            return;
+        }
+        if std::env::var("NC_RISCV_FORCE_REBUILD").ok().as_deref() == Some("1") {
+            return;
+        }
+        let g = nc_nir::Graph::new("gcache");
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv64gcv_linux"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+        "#).unwrap();
+
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        if build_rv64_linux_binary(&out_dir, &Rv64BuildOptions::default(), &mut Vec::new()).is_err() {
+            // Toolchain present but unable to link (e.g. missing sysroot)
+            return;
+        }
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        build_rv64_linux_binary(&out_dir, &Rv64BuildOptions::default(), &mut Vec::new()).expect("cached build");
+        let readme = fs::read_to_string(out_dir.join("README.txt")).unwrap_or_default();
+        assert!(readme.contains("Build: cache-hit"), "README: {readme}");
+    }
+
+    #[test]
+    fn rv64_emit_asm_listing_if_available() {
+        let mut g = nc_nir::Graph::new("gasm");
+        g.attributes.insert("rv_emit_asm".to_string(), serde_json::json!(true));
+        assert!(graph_flag(&g, "rv_emit_asm"));
+        if detect_tool(&["riscv64-linux-gnu-gcc"]).is_none() && detect_tool(&["clang"]).is_none() {
+            return;
+        }
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv64gcv_linux"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+        "#).unwrap();
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        if build_rv64_linux_binary(&out_dir, &Rv64BuildOptions::resolve(&g, &m), &mut Vec::new()).is_err() {
+            // Toolchain present but unable to link (e.g. missing sysroot)
+            return;
+        }
+        assert!(out_dir.join("main.s").exists(), "main.s expected next to prog-rv64");
+    }
+
+    #[test]
+    fn rv64_static_musl_recorded_if_available() {
+        if detect_tool(&["riscv64-linux-gnu-gcc"]).is_none() && detect_tool(&["clang"]).is_none() {
+            return;
+        }
+        let g = nc_nir::Graph::new("gmusl");
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv64gcv_linux"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+        "#).unwrap();
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let opts = Rv64BuildOptions { static_musl: true, ..Default::default() };
+        if build_rv64_linux_binary(&out_dir, &opts, &mut Vec::new()).is_err() {
+            return;
+        }
+        let readme = fs::read_to_string(out_dir.join("README.txt")).unwrap_or_default();
+        assert!(readme.lines().any(|l| l.starts_with("Link: ") && l.contains("musl")), "README: {readme}");
+    }
+
+    #[test]
+    fn zbb_guard_only_when_extension_listed() {
+        let manifest = |exts: &str| nc_hal::parse_target_manifest_str(&format!(r#"
+            name = "riscv64gcv_bitmanip"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+            [capabilities]
+            has_vector = true
+            extensions = [{exts}]
+        "#)).unwrap();
+        let mut g = nc_nir::fixtures::chain(&[100, 4]);
+        g.name = "zbb".to_string();
+        g.attributes.insert("rv_emit_cmake".to_string(), serde_json::json!(true));
+        g.inputs.push(nc_nir::Input {
+            name: "bg".into(),
+            target: "p0".into(),
+            source: nc_nir::InputSource::Poisson { rate_hz: 50.0 },
+            seed: 7,
+        });
+
+        let m = manifest(r#""zba", "zbb""#);
+        assert!(Rv64BuildOptions::resolve(&g, &m).zbb);
+        let out_dir = default_out_dir(&g.name, &m.name);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(src.contains("#if defined(__riscv_zbb)") && src.contains("cpop %0, %1"));
+        assert!(src.contains("__builtin_popcountll"), "portable fallback missing");
+        assert!(src.contains("spikes += nc_popcount64(word);"), "Poisson spike count uses the Zbb helper");
+        assert!(!src.contains("nc_clz64"), "no uncalled Zbb helpers");
+        let lists = fs::read_to_string(out_dir.join("CMakeLists.txt")).unwrap();
+        assert!(lists.contains("-march=rv64gc_zbb"), "CMakeLists: {lists}");
+
+        let m = manifest(r#""zba""#);
+        assert!(!Rv64BuildOptions::resolve(&g, &m).zbb);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let src = fs::read_to_string(out_dir.join("main.c")).unwrap();
+        assert!(!src.contains("__riscv_zbb") && !src.contains("nc_popcount64"));
+        let lists = fs::read_to_string(out_dir.join("CMakeLists.txt")).unwrap();
+        assert!(!lists.contains("_zbb"), "CMakeLists: {lists}");
+    }
+
+    #[test]
+    fn cmake_project_only_when_requested() {
+        let m = nc_hal::parse_target_manifest_str(r#"
+            name = "riscv64gcv_linux"
+            vendor = "Generic"
+            family = "RISC-V"
+            version = "1"
+            [capabilities]
+            isa = "rv64gcv"
+            abi = "lp64d"
+        "#).unwrap();
+        let mut g = nc_nir::Graph::new("gcmake");
+        let out_dir = default_out_dir(&g.name, &m.name);
+        let _ = fs::remove_dir_all(&out_dir);
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        assert!(!out_dir.join("CMakeLists.txt").exists());
+
+        g.attributes.insert("rv_emit_cmake".to_string(), serde_json::json!(true));
+        emit_linux_rv64_runtime(&out_dir, &g, &m).unwrap();
+        let lists = fs::read_to_string(out_dir.join("CMakeLists.txt")).expect("CMakeLists.txt");
+        assert!(lists.contains("-march=rv64gc"), "CMakeLists: {lists}");
+        assert!(lists.contains("-mabi=lp64d"));
+        assert!(lists.contains("add_executable(prog-rv64 main.c)"));
+        let tc = fs::read_to_string(out_dir.join("riscv64-toolchain.cmake")).expect("toolchain file");
+        assert!(tc.contains("riscv64-linux-gnu-gcc"));
+    }
+
     #[test]
     fn qemu_system_bare_metal_smoke_if_available() {
         // Guard on required tools
@@ -13,6 +13,7 @@ fn quantize_weight(w: f32, bits: u32) -> f32 {
 pub fn compile(graph: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Result<String> {
     graph.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
     nc_hal::validate_manifest(manifest)?;
+    nc_hal::check_model_support(manifest, graph.populations.iter().map(|p| p.model.as_str()))?;
 
     // Optional telemetry profiling
     #[cfg(feature = "telemetry")]
@@ -1,9 +1,11 @@
 use clap::{Args, Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 #[cfg(feature = "telemetry")]
 use std::collections::BTreeMap;
 
+mod nc_import;
+
 #[derive(Parser)]
 #[command(name = "neuro-compiler")]
 #[command(about = "Universal neuromorphic compiler (skeleton)")]
@@ -14,6 +16,9 @@ struct Cli {
     /// Optional JSONL profile path (sets NC_PROFILE_JSONL if not set)
     #[arg(global = true, long)]
     profile_jsonl: Option<PathBuf>,
+    /// Optional TOML file providing defaults for the global options above (flags take precedence)
+    #[arg(global = true, long)]
+    config: Option<PathBuf>,
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -38,6 +43,18 @@ enum Command {
     Deploy(DeployArgs),
     /// Export NIR as MLIR (requires 'mlir' feature)
     ExportMlir(ExportMlirArgs),
+    /// Summarize a NIR file (e.g. a pass dump) and its top-level attributes
+    Info(InfoArgs),
+    /// Check a NIR file and list every problem found, with codes and locations
+    Validate(ValidateArgs),
+    /// Show structural differences between two NIR files
+    Diff(DiffArgs),
+    /// Upgrade a NIR file written by an older version to the current nir_version
+    Migrate(MigrateArgs),
+    /// Render a NIR file as Graphviz DOT or Mermaid
+    Visualize(VisualizeArgs),
+    /// Time repeated compiles of a model for a target
+    Bench(BenchArgs),
 }
 
 #[derive(Args, Debug)]
@@ -45,7 +62,7 @@ struct ImportArgs {
     /// Input file path
     #[arg(long)]
     input: PathBuf,
-    /// Frontend/framework name (e.g., pynn, nengo, nest, brian, bindsnet, carlsim, genn, rockpool)
+    /// Frontend/framework name: nir (default), pynn or nengo (JSON exports)
     #[arg(long)]
     framework: Option<String>,
     /// Optional format override (e.g., py, json, yaml)
@@ -53,6 +70,68 @@ struct ImportArgs {
     format: Option<String>,
 }
 
+#[derive(Args, Debug)]
+struct InfoArgs {
+    /// Input NIR file (JSON or YAML), possibly carrying pass attributes
+    #[arg(long)]
+    input: PathBuf,
+    /// Optional format override (json, yaml)
+    #[arg(long)]
+    format: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    /// NIR file (JSON or YAML) to check
+    #[arg(long)]
+    input: PathBuf,
+    /// Also reject connection delays above this bound (ms)
+    #[arg(long)]
+    max_delay_ms: Option<f32>,
+    /// Print the diagnostics as JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Original NIR file (JSON or YAML)
+    #[arg(long)]
+    before: PathBuf,
+    /// Updated NIR file (JSON or YAML), e.g. a pass dump of the original
+    #[arg(long)]
+    after: PathBuf,
+    /// Print the diff as JSON instead of one line per change
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args, Debug)]
+struct MigrateArgs {
+    /// NIR file (JSON or YAML) to upgrade
+    #[arg(long)]
+    input: PathBuf,
+    /// Where to write the upgraded file (format from its extension); defaults to stdout as JSON
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct VisualizeArgs {
+    /// Input NIR file (JSON or YAML), e.g. a partition or placement dump
+    #[arg(long)]
+    input: PathBuf,
+    /// Output format: dot or mermaid (default: from the --output extension, else dot)
+    #[arg(long)]
+    format: Option<String>,
+    /// Write to this file instead of stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Color populations by their part in the `partition` attribute
+    #[arg(long)]
+    color_by_partition: bool,
+}
+
 #[derive(Args, Debug)]
 struct LowerArgs {
     /// Pipeline name or comma-separated list of passes (e.g., noop)
@@ -64,12 +143,30 @@ struct LowerArgs {
     /// One or more dump formats: json, yaml, bin (repeat or comma-separated)
     #[arg(long = "dump-format", value_delimiter = ',')]
     dump_format: Vec<String>,
+    /// Sort attribute keys in JSON dumps so they are stable across runs
+    #[arg(long)]
+    sorted_dumps: bool,
+    /// Simulated-annealing steps for mesh placement
+    #[arg(long)]
+    placement_iterations: Option<usize>,
+    /// Starting annealing temperature in synapse-hops (default: derived from the graph)
+    #[arg(long)]
+    placement_temperature: Option<f64>,
+    /// Seed for the placement annealer
+    #[arg(long)]
+    placement_seed: Option<u64>,
+    /// Route selection on mesh/torus interconnects: xy or adaptive
+    #[arg(long, default_value = "xy", value_parser = ["xy", "adaptive"])]
+    routing_algorithm: String,
     /// Optional explicit target manifest TOML path (preferred when provided)
     #[arg(long)]
     manifest: Option<PathBuf>,
-    /// Optional builtin target name (fallback convenience to load targets/<name>.toml)
+    /// Optional builtin target name or alias (fallback convenience to load targets/<name>.toml)
     #[arg(long)]
     target: Option<String>,
+    /// Print the pass names accepted by --pipeline and exit
+    #[arg(long)]
+    list_passes: bool,
 }
 
 #[derive(Args, Debug)]
@@ -77,9 +174,28 @@ struct CompileArgs {
     /// Input NIR file (JSON or YAML)
     #[arg(long)]
     input: PathBuf,
-    /// Target backend (e.g., loihi2, akida, spinnaker2)
+    /// Target backend (e.g., loihi2, akida, spinnaker2; aliases like rv64 are accepted)
+    #[arg(long)]
+    target: String,
+    /// Recompile whenever the input file changes (until Ctrl-C)
+    #[arg(long)]
+    watch: bool,
+    /// With --watch, stop after this many milliseconds without changes
+    #[arg(long, requires = "watch")]
+    watch_timeout_ms: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// Input NIR file (JSON or YAML)
+    #[arg(long)]
+    input: PathBuf,
+    /// Target backend (same names and aliases as `compile`)
     #[arg(long)]
     target: String,
+    /// Number of compiles to time
+    #[arg(long, default_value_t = 5)]
+    iters: usize,
 }
 
 #[derive(Args, Debug)]
@@ -110,6 +226,9 @@ struct PackageArgs {
     /// Output artifact path
     #[arg(long)]
     output: Option<PathBuf>,
+    /// Zip this backend out_dir (with pass dumps, README, profile.jsonl) instead; --output is the .zip path
+    #[arg(long)]
+    bundle_from: Option<PathBuf>,
 }
 
 #[derive(Args, Debug)]
@@ -127,9 +246,23 @@ struct ExportMlirArgs {
 }
 
 fn main() {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
     let _ = tracing_subscriber::fmt().with_env_filter("info").try_init();
 
+    // Fill unset global options from --config; explicit flags win
+    if let Some(path) = &cli.config {
+        match load_cli_config(path) {
+            Ok(cfg) => {
+                cli.otlp_endpoint = cli.otlp_endpoint.or(cfg.otlp_endpoint);
+                cli.profile_jsonl = cli.profile_jsonl.or(cfg.profile_jsonl);
+            }
+            Err(e) => {
+                eprintln!("config: {e}");
+                return;
+            }
+        }
+    }
+
     // If provided globally, set NC_PROFILE_JSONL unless already set (works across subcommands)
     if let Some(p) = &cli.profile_jsonl {
         if std::env::var("NC_PROFILE_JSONL").is_err() {
@@ -168,14 +301,26 @@ fn main() {
                 }
             };
 
-            let parsed = match fmt.as_deref() {
-                Some("yaml") | Some("yml") => nc_nir::Graph::from_yaml_str(&data).map_err(|e| e.to_string()),
-                _ => nc_nir::Graph::from_json_str(&data).map_err(|e| e.to_string()),
+            let framework = args.framework.as_deref().map(|s| s.to_lowercase());
+            let parsed = match framework.as_deref() {
+                None | Some("nir") => match fmt.as_deref() {
+                    Some("yaml") | Some("yml") => nc_nir::Graph::from_yaml_str(&data).map_err(|e| e.to_string()),
+                    _ => nc_nir::Graph::from_json_str(&data).map_err(|e| e.to_string()),
+                },
+                Some("pynn") => nc_import::from_pynn_json(&data),
+                Some("nengo") => nc_import::from_nengo_json(&data),
+                Some(other) => {
+                    eprintln!(
+                        "import: framework '{other}' not yet supported (supported: {})",
+                        nc_import::SUPPORTED_FRAMEWORKS.join(", ")
+                    );
+                    return;
+                }
             };
 
             match parsed {
-                Ok(g) => {
-                    let valid = g.validate().is_ok();
+                Ok(mut g) => {
+                    let valid = load_weights_and_validate("import", &args.input, &mut g).is_ok();
                     println!(
                         "import ok: name={} populations={} connections={} probes={} valid={}",
                         g.name,
@@ -191,44 +336,21 @@ fn main() {
             }
         }
         Some(Command::Lower(args)) => {
-            // Structured pipeline config and dump formats
-            let names: Vec<String> = args
-                .pipeline
-                .as_deref()
-                .unwrap_or("noop")
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-
-            let mut fmt: Vec<nc_passes::DumpFormat> = Vec::new();
-            for f in args.dump_format.iter().map(|s| s.to_lowercase()) {
-                match f.as_str() {
-                    "json" => fmt.push(nc_passes::DumpFormat::Json),
-                    "yaml" => fmt.push(nc_passes::DumpFormat::Yaml),
-                    "bin" => {
-                        #[cfg(feature = "bin-artifacts")]
-                        {
-                            fmt.push(nc_passes::DumpFormat::Bin);
-                        }
-                    }
-                    _ => {}
+            if args.list_passes {
+                for p in nc_passes::available_passes() {
+                    println!("{p}");
                 }
+                return;
             }
 
-            let cfg = nc_passes::PipelineConfig {
-                passes: names.clone(),
-                dump_dir: args.dump_dir.clone(),
-                dump_formats: if fmt.is_empty() { vec![nc_passes::DumpFormat::Json] } else { fmt },
-            };
-
             // Build a trivial graph and run through the pipeline with dumps
             let mut g = nc_nir::Graph::new("cli-lower-demo");
             // If provided, load a HAL manifest via --manifest or --target and attach its path for capability-aware passes
             let manifest_path: Option<PathBuf> = args
                 .manifest
                 .clone()
-                .or_else(|| args.target.as_ref().map(|t| PathBuf::from(format!("targets/{t}.toml"))));
+                .or_else(|| args.target.as_ref().map(|t| PathBuf::from(format!("targets/{}.toml", nc_hal::resolve_target_alias(t)))));
+            let mut manifest: Option<nc_hal::TargetManifest> = None;
             if let Some(mp) = manifest_path {
                 match nc_hal::parse_target_manifest_path(&mp) {
                     Ok(m) => {
@@ -239,6 +361,7 @@ fn main() {
                                 "hal_manifest_path".to_string(),
                                 serde_json::json!(mp.to_string_lossy().to_string()),
                             );
+                            manifest = Some(m);
                         }
                     }
                     Err(e) => {
@@ -246,6 +369,53 @@ fn main() {
                     }
                 }
             }
+
+            // Structured pipeline config and dump formats.
+            // Without --pipeline, a loaded target gets its recommended pipeline; otherwise noop.
+            let names: Vec<String> = match (args.pipeline.as_deref(), &manifest) {
+                (Some(p), _) => p
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                (None, Some(m)) => {
+                    let rec = nc_hal::recommended_pipeline(m);
+                    println!("lower: using recommended pipeline for {}: {}", m.name, rec.join(","));
+                    rec
+                }
+                (None, None) => vec!["noop".to_string()],
+            };
+
+            let mut fmt: Vec<nc_passes::DumpFormat> = Vec::new();
+            for f in args.dump_format.iter().map(|s| s.to_lowercase()) {
+                match f.as_str() {
+                    "json" => fmt.push(nc_passes::DumpFormat::Json),
+                    "yaml" => fmt.push(nc_passes::DumpFormat::Yaml),
+                    "bin" => {
+                        #[cfg(feature = "bin-artifacts")]
+                        {
+                            fmt.push(nc_passes::DumpFormat::Bin);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let defaults = nc_passes::PlacementOptions::default();
+            let cfg = nc_passes::PipelineConfig {
+                passes: names.clone(),
+                dump_dir: args.dump_dir.clone(),
+                dump_formats: if fmt.is_empty() { vec![nc_passes::DumpFormat::Json] } else { fmt },
+                sorted_dumps: args.sorted_dumps,
+                placement: nc_passes::PlacementOptions {
+                    iterations: args.placement_iterations.unwrap_or(defaults.iterations),
+                    initial_temperature: args.placement_temperature.or(defaults.initial_temperature),
+                    seed: args.placement_seed.unwrap_or(defaults.seed),
+                    ..defaults
+                },
+                routing: nc_passes::RouteAlgorithm::parse(&args.routing_algorithm).unwrap_or_default(),
+            };
+
             let mut pm = nc_passes::PassManager::new();
             if let Err(e) = nc_passes::build_pipeline(&mut pm, &cfg.passes) {
                 eprintln!("error: {e}");
@@ -262,221 +432,21 @@ fn main() {
             }
         }
         Some(Command::Compile(args)) => {
-            // Determine input format by extension and parse NIR
-            let fmt = args.input.extension().and_then(|e| e.to_str()).map(|s| s.to_lowercase());
-            let data = match fs::read_to_string(&args.input) {
-                Ok(d) => d,
-                Err(e) => {
-                    eprintln!("compile: cannot read {:?}: {e}", args.input);
-                    return;
-                }
-            };
-            let mut g = match fmt.as_deref() {
-                Some("yaml") | Some("yml") => match nc_nir::Graph::from_yaml_str(&data) {
-                    Ok(g) => g,
-                    Err(e) => {
-                        eprintln!("compile: parse yaml failed: {e}");
-                        return;
-                    }
-                },
-                _ => match nc_nir::Graph::from_json_str(&data) {
-                    Ok(g) => g,
-                    Err(e) => {
-                        eprintln!("compile: parse json failed: {e}");
-                        return;
-                    }
-                },
-            };
-            if let Err(e) = g.validate() {
-                eprintln!("compile: validation failed: {e}");
-                return;
-            }
-            g.ensure_version_tag();
-
-            // Load target manifest
-            let manifest_path = PathBuf::from(format!("targets/{}.toml", args.target));
-            let manifest = match nc_hal::parse_target_manifest_path(&manifest_path) {
-                Ok(m) => m,
-                Err(e) => {
-                    eprintln!("compile: cannot load manifest {manifest_path:?}: {e}");
-                    return;
-                }
-            };
-            if let Err(e) = nc_hal::validate_manifest(&manifest) {
-                eprintln!("compile: manifest invalid: {e}");
-                return;
-            }
-
-            match args.target.as_str() {
-                "loihi2" => {
-                    #[cfg(feature = "backend-loihi")]
-                    {
-                        match nc_backend_loihi::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-loihi"))]
-                    {
-                        eprintln!("backend 'backend-loihi' is not enabled; rebuild CLI with --features backend-loihi");
-                    }
-                }
-                "truenorth" => {
-                    #[cfg(feature = "backend-truenorth")]
-                    {
-                        match nc_backend_truenorth::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-truenorth"))]
-                    {
-                        eprintln!("backend 'backend-truenorth' is not enabled; rebuild CLI with --features backend-truenorth");
-                    }
-                }
-                "akida" => {
-                    #[cfg(feature = "backend-akida")]
-                    {
-                        match nc_backend_akida::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-akida"))]
-                    {
-                        eprintln!("backend 'backend-akida' is not enabled; rebuild CLI with --features backend-akida");
-                    }
-                }
-                "spinnaker2" => {
-                    #[cfg(feature = "backend-spinnaker")]
-                    {
-                        match nc_backend_spinnaker::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-spinnaker"))]
-                    {
-                        eprintln!("backend 'backend-spinnaker' is not enabled; rebuild CLI with --features backend-spinnaker");
-                    }
-                }
-                "neurogrid" => {
-                    #[cfg(feature = "backend-neurogrid")]
-                    {
-                        match nc_backend_neurogrid::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-neurogrid"))]
-                    {
-                        eprintln!("backend 'backend-neurogrid' is not enabled; rebuild CLI with --features backend-neurogrid");
-                    }
-                }
-                "dynaps" => {
-                    #[cfg(feature = "backend-dynaps")]
-                    {
-                        match nc_backend_dynaps::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-dynaps"))]
-                    {
-                        eprintln!("backend 'backend-dynaps' is not enabled; rebuild CLI with --features backend-dynaps");
-                    }
-                }
-                "memxbar" => {
-                    #[cfg(feature = "backend-memxbar")]
-                    {
-                        match nc_backend_memxbar::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-memxbar"))]
-                    {
-                        eprintln!("backend 'backend-memxbar' is not enabled; rebuild CLI with --features backend-memxbar");
-                    }
-                }
-                "custom_asic" => {
-                    #[cfg(feature = "backend-custom-asic")]
-                    {
-                        match nc_backend_custom_asic::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-custom-asic"))]
-                    {
-                        eprintln!("backend 'backend-custom-asic' is not enabled; rebuild CLI with --features backend-custom-asic");
-                    }
-                }
-                "riscv64gcv_linux" => {
-                    #[cfg(feature = "backend-riscv")]
-                    {
-                        match nc_backend_riscv::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-riscv"))]
-                    {
-                        eprintln!("backend 'backend-riscv' is not enabled; rebuild CLI with --features backend-riscv");
-                    }
-                }
-                "riscv32imac_bare" => {
-                    #[cfg(feature = "backend-riscv")]
-                    {
-                        match nc_backend_riscv::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-riscv"))]
-                    {
-                        eprintln!("backend 'backend-riscv' is not enabled; rebuild CLI with --features backend-riscv");
-                    }
-                }
-                "riscv64gc_ctrl" => {
-                    #[cfg(feature = "backend-riscv")]
-                    {
-                        match nc_backend_riscv::compile(&g, &manifest) {
-                            Ok(art) => println!("compile ok: {}", art),
-                            Err(e) => eprintln!("compile error: {e}"),
-                        }
-                    }
-                    #[cfg(not(feature = "backend-riscv"))]
-                    {
-                        eprintln!("backend 'backend-riscv' is not enabled; rebuild CLI with --features backend-riscv");
-                    }
-                }
-                other => {
-                    eprintln!("compile: unsupported or not yet integrated target '{other}'");
+            run_compile(&args);
+            if args.watch {
+                if let Err(e) = watch_and_recompile(&args) {
+                    eprintln!("compile: watch failed: {e}");
                 }
             }
         }
+        Some(Command::Bench(args)) => {
+            run_bench(&args);
+        }
         Some(Command::Simulate(args)) => {
-            // Parse input NIR
-            let fmt = args.input.extension().and_then(|e| e.to_str()).map(|s| s.to_lowercase());
-            let data = match fs::read_to_string(&args.input) {
-                Ok(d) => d,
-                Err(e) => { eprintln!("simulate: cannot read {:?}: {e}", args.input); return; }
+            let mut g = match read_graph_with_weights("simulate", &args.input) {
+                Ok(g) => g,
+                Err(msg) => { eprintln!("{msg}"); return; }
             };
-            let mut g = match fmt.as_deref() {
-                Some("yaml") | Some("yml") => match nc_nir::Graph::from_yaml_str(&data) {
-                    Ok(g) => g,
-                    Err(e) => { eprintln!("simulate: parse yaml failed: {e}"); return; }
-                },
-                _ => match nc_nir::Graph::from_json_str(&data) {
-                    Ok(g) => g,
-                    Err(e) => { eprintln!("simulate: parse json failed: {e}"); return; }
-                },
-            };
-            if let Err(e) = g.validate() {
-                eprintln!("simulate: validation failed: {e}");
-                return;
-            }
             g.ensure_version_tag();
 
             #[cfg(feature = "telemetry")]
@@ -494,7 +464,11 @@ fn main() {
                 labels.insert("graph".to_string(), g.name.clone());
             }
 
-            let out_dir = args.out_dir.clone().unwrap_or_else(|| PathBuf::from(format!("target/sim-{}-out", args.simulator)));
+            // Default output directories are tagged with the graph's content hash
+            let out_dir = args
+                .out_dir
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(format!("target/sim-{}-{}", args.simulator, g.content_hash())));
             // Mark as used even when simulator features are not enabled to avoid unused warnings.
             let _ = &out_dir;
 
@@ -504,12 +478,7 @@ fn main() {
             match args.simulator.as_str() {
                 "neuron" => {
                     #[cfg(feature = "sim-neuron")]
-                    {
-                        match nc_sim_neuron::emit_artifacts(&g, &out_dir) {
-                            Ok(_) => println!("simulate artifacts written to {:?}", out_dir),
-                            Err(e) => eprintln!("simulate error: {e}")
-                        }
-                    }
+                    report_simulate(&out_dir, nc_sim_neuron::emit_artifacts_with_diagnostics(&g, &out_dir));
                     #[cfg(not(feature = "sim-neuron"))]
                     {
                         println!("simulate disabled: feature 'sim-neuron' not enabled; rebuild CLI with --features sim-neuron");
@@ -517,12 +486,7 @@ fn main() {
                 }
                 "coreneuron" => {
                     #[cfg(feature = "sim-coreneuron")]
-                    {
-                        match nc_sim_coreneuron::emit_artifacts(&g, &out_dir) {
-                            Ok(_) => println!("simulate artifacts written to {:?}", out_dir),
-                            Err(e) => eprintln!("simulate error: {e}")
-                        }
-                    }
+                    report_simulate(&out_dir, nc_sim_coreneuron::emit_artifacts_with_diagnostics(&g, &out_dir));
                     #[cfg(not(feature = "sim-coreneuron"))]
                     {
                         println!("simulate disabled: feature 'sim-coreneuron' not enabled; rebuild CLI with --features sim-coreneuron");
@@ -530,12 +494,7 @@ fn main() {
                 }
                 "arbor" => {
                     #[cfg(feature = "sim-arbor")]
-                    {
-                        match nc_sim_arbor::emit_artifacts(&g, &out_dir) {
-                            Ok(_) => println!("simulate artifacts written to {:?}", out_dir),
-                            Err(e) => eprintln!("simulate error: {e}")
-                        }
-                    }
+                    report_simulate(&out_dir, nc_sim_arbor::emit_artifacts_with_diagnostics(&g, &out_dir));
                     #[cfg(not(feature = "sim-arbor"))]
                     {
                         println!("simulate disabled: feature 'sim-arbor' not enabled; rebuild CLI with --features sim-arbor");
@@ -543,12 +502,7 @@ fn main() {
                 }
                 "hw" => {
                     #[cfg(feature = "sim-hw-specific")]
-                    {
-                        match nc_sim_hw_specific::emit_artifacts(&g, &out_dir) {
-                            Ok(_) => println!("simulate artifacts written to {:?}", out_dir),
-                            Err(e) => eprintln!("simulate error: {e}")
-                        }
-                    }
+                    report_simulate(&out_dir, nc_sim_hw_specific::emit_artifacts_with_diagnostics(&g, &out_dir));
                     #[cfg(not(feature = "sim-hw-specific"))]
                     {
                         println!("simulate disabled: feature 'sim-hw-specific' not enabled; rebuild CLI with --features sim-hw-specific");
@@ -569,7 +523,9 @@ fn main() {
             }
         }
         Some(Command::Profile(args)) => {
-            if let Some(path) = args.input {
+            // Default to the active profile (NC_PROFILE_JSONL, also set by --profile-jsonl / --config)
+            let input = args.input.or_else(|| std::env::var_os("NC_PROFILE_JSONL").map(PathBuf::from));
+            if let Some(path) = input {
                 #[cfg(feature = "telemetry")]
                 {
                     match nc_telemetry::profiling::summarize_jsonl(&path) {
@@ -599,6 +555,17 @@ fn main() {
                 .duration_since(std::time::UNIX_EPOCH)
                 .map(|d| d.as_secs())
                 .unwrap_or(0);
+            if let Some(src) = &args.bundle_from {
+                let zip_path = args
+                    .output
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(format!("target/package-{stamp}.zip")));
+                match nc_runtime::bundle_artifacts(src, &zip_path) {
+                    Ok(_) => println!("bundle created at {:?}", zip_path),
+                    Err(e) => eprintln!("package: {e}"),
+                }
+                return;
+            }
             let out_dir = args
                 .output
                 .clone()
@@ -626,28 +593,10 @@ fn main() {
         Some(Command::ExportMlir(args)) => {
             #[cfg(feature = "mlir")]
             {
-                let fmt = args.input.extension().and_then(|e| e.to_str()).map(|s| s.to_lowercase());
-                let data = match fs::read_to_string(&args.input) {
-                    Ok(d) => d,
-                    Err(e) => {
-                        eprintln!("mlir: cannot read {:?}: {e}", args.input);
-                        return;
-                    }
-                };
-                let mut g = match fmt.as_deref() {
-                    Some("yaml") | Some("yml") => match nc_nir::Graph::from_yaml_str(&data) {
-                        Ok(g) => g,
-                        Err(e) => { eprintln!("mlir: parse yaml failed: {e}"); return; }
-                    },
-                    _ => match nc_nir::Graph::from_json_str(&data) {
-                        Ok(g) => g,
-                        Err(e) => { eprintln!("mlir: parse json failed: {e}"); return; }
-                    },
+                let mut g = match read_graph_with_weights("mlir", &args.input) {
+                    Ok(g) => g,
+                    Err(msg) => { eprintln!("{msg}"); return; }
                 };
-                if let Err(e) = g.validate() {
-                    eprintln!("mlir: validation failed: {}", e);
-                    return;
-                }
                 g.ensure_version_tag();
                 match nc_mlir_bridge::lower_to_mlir(&g) {
                     Ok(s) => println!("{}", s),
@@ -660,8 +609,451 @@ fn main() {
                 println!("mlir export requires building CLI with feature 'mlir'");
             }
         }
+        Some(Command::Info(args)) => {
+            let fmt = args
+                .format
+                .as_deref()
+                .map(|s| s.to_lowercase())
+                .or_else(|| args.input.extension().and_then(|e| e.to_str()).map(|s| s.to_lowercase()));
+            let data = match fs::read_to_string(&args.input) {
+                Ok(d) => d,
+                Err(e) => {
+                    eprintln!("info: cannot read {:?}: {e}", args.input);
+                    return;
+                }
+            };
+            let parsed = match fmt.as_deref() {
+                Some("yaml") | Some("yml") => nc_nir::Graph::from_yaml_str(&data).map_err(|e| e.to_string()),
+                _ => nc_nir::Graph::from_json_str(&data).map_err(|e| e.to_string()),
+            };
+            match parsed {
+                Ok(g) => {
+                    println!(
+                        "info: name={} populations={} connections={} probes={} attributes={}",
+                        g.name,
+                        g.populations.len(),
+                        g.connections.len(),
+                        g.probes.len(),
+                        g.attributes.len()
+                    );
+                    for (k, v) in &g.attributes {
+                        println!("  {k}: {}", summarize_attr(v));
+                    }
+                }
+                Err(e) => eprintln!("info parse error: {e}"),
+            }
+        }
+        Some(Command::Validate(args)) => {
+            let g = match read_graph("validate", &args.input) {
+                Ok(g) => g,
+                Err(msg) => {
+                    eprintln!("{msg}");
+                    return;
+                }
+            };
+            let diags = g.validate_all_with_limits(args.max_delay_ms);
+            if args.json {
+                match serde_json::to_string_pretty(&diags) {
+                    Ok(s) => println!("{s}"),
+                    Err(e) => eprintln!("validate: cannot serialize: {e}"),
+                }
+                return;
+            }
+            if !diags.is_empty() {
+                print!("{}", nc_nir::render_table(&diags));
+            }
+            let errors = diags.iter().filter(|d| d.severity == nc_nir::Severity::Error).count();
+            let summary = format!("validate: {} error(s), {} warning(s)", errors, diags.len() - errors);
+            if errors > 0 {
+                eprintln!("{summary}");
+            } else {
+                println!("{summary}");
+            }
+        }
+        Some(Command::Diff(args)) => {
+            let graphs = read_graph("diff", &args.before).and_then(|a| Ok((a, read_graph("diff", &args.after)?)));
+            let (before, after) = match graphs {
+                Ok(x) => x,
+                Err(msg) => {
+                    eprintln!("{msg}");
+                    return;
+                }
+            };
+            let d = before.diff(&after);
+            if args.json {
+                match serde_json::to_string_pretty(&d) {
+                    Ok(s) => println!("{s}"),
+                    Err(e) => eprintln!("diff: cannot serialize: {e}"),
+                }
+                return;
+            }
+            if d.is_empty() {
+                println!("diff: no structural changes");
+                return;
+            }
+            for (kind, items) in d.sections() {
+                for k in items.removed.keys() {
+                    println!("- {kind} {k}");
+                }
+                for k in items.added.keys() {
+                    println!("+ {kind} {k}");
+                }
+                for (k, c) in &items.changed {
+                    println!("~ {kind} {k}: {} -> {}", summarize_attr(&c.before), summarize_attr(&c.after));
+                }
+            }
+        }
+        Some(Command::Migrate(args)) => {
+            // Loading upgrades the document; writing it back stores the current version
+            let g = match read_graph("migrate", &args.input) {
+                Ok(g) => g,
+                Err(msg) => {
+                    eprintln!("{msg}");
+                    return;
+                }
+            };
+            let Some(out) = &args.output else {
+                match g.to_json_string() {
+                    Ok(s) => println!("{s}"),
+                    Err(e) => eprintln!("migrate: cannot serialize: {e}"),
+                }
+                return;
+            };
+            let fmt = out.extension().and_then(|e| e.to_str()).map(|s| s.to_lowercase());
+            let text = match fmt.as_deref() {
+                Some("yaml") | Some("yml") => g.to_yaml_string().map_err(|e| e.to_string()),
+                _ => g.to_json_string().map_err(|e| e.to_string()),
+            };
+            match text.map(|t| fs::write(out, t).map_err(|e| e.to_string())) {
+                Ok(Ok(())) => println!("migrate: wrote {out:?} (nir_version {})", nc_nir::VERSION),
+                Ok(Err(e)) | Err(e) => eprintln!("migrate: cannot write {out:?}: {e}"),
+            }
+        }
+        Some(Command::Visualize(args)) => {
+            let g = match read_graph("visualize", &args.input) {
+                Ok(g) => g,
+                Err(msg) => {
+                    eprintln!("{msg}");
+                    return;
+                }
+            };
+            let fmt = args.format.as_deref().map(|s| s.to_lowercase()).or_else(|| {
+                args.output.as_ref().and_then(|o| o.extension()).and_then(|e| e.to_str()).map(|s| s.to_lowercase())
+            });
+            let opts = nc_nir::VizOptions { color_by_partition: args.color_by_partition };
+            let text = match fmt.as_deref() {
+                None | Some("dot") | Some("gv") => g.to_dot_with(&opts),
+                Some("mermaid") | Some("mmd") => g.to_mermaid_with(&opts),
+                Some(other) => {
+                    eprintln!("visualize: unknown format '{other}' (expected dot or mermaid)");
+                    return;
+                }
+            };
+            match &args.output {
+                Some(out) => match fs::write(out, text) {
+                    Ok(()) => println!("visualize: wrote {out:?}"),
+                    Err(e) => eprintln!("visualize: cannot write {out:?}: {e}"),
+                },
+                None => print!("{text}"),
+            }
+        }
         None => {
             println!("Use --help for commands. Example: neuro-compiler list-targets");
         }
     }
 }
+
+/// Global option defaults read from `--config`.
+#[derive(Debug, Default)]
+struct CliConfig {
+    otlp_endpoint: Option<String>,
+    profile_jsonl: Option<PathBuf>,
+}
+
+/// Parse a `--config` TOML file. Only `otlp_endpoint` and `profile_jsonl` (strings) are accepted;
+/// unknown keys and wrong types are errors so typos do not go unnoticed.
+fn load_cli_config(path: &Path) -> anyhow::Result<CliConfig> {
+    let data = fs::read_to_string(path).map_err(|e| anyhow::anyhow!("cannot read {path:?}: {e}"))?;
+    let table: toml::Table = data.parse().map_err(|e| anyhow::anyhow!("malformed {path:?}: {e}"))?;
+    let mut cfg = CliConfig::default();
+    for (key, value) in &table {
+        let s = value
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("{path:?}: '{key}' must be a string"))?;
+        match key.as_str() {
+            "otlp_endpoint" => cfg.otlp_endpoint = Some(s.to_string()),
+            "profile_jsonl" => cfg.profile_jsonl = Some(PathBuf::from(s)),
+            other => anyhow::bail!("{path:?}: unknown key '{other}' (expected otlp_endpoint, profile_jsonl)"),
+        }
+    }
+    Ok(cfg)
+}
+
+/// Parse the NIR file `input`, as YAML for `.yaml`/`.yml` and JSON otherwise. Errors are
+/// returned as messages prefixed with `cmd`.
+fn read_graph(cmd: &str, input: &Path) -> Result<nc_nir::Graph, String> {
+    let fmt = input.extension().and_then(|e| e.to_str()).map(|s| s.to_lowercase());
+    match fmt.as_deref() {
+        Some("yaml") | Some("yml") => {
+            let data = fs::read_to_string(input).map_err(|e| format!("{cmd}: cannot read {input:?}: {e}"))?;
+            nc_nir::Graph::from_yaml_str(&data).map_err(|e| format!("{cmd}: parse yaml failed: {e}"))
+        }
+        // JSON is streamed so large models need not fit in memory twice
+        _ => {
+            let file = fs::File::open(input).map_err(|e| format!("{cmd}: cannot read {input:?}: {e}"))?;
+            nc_nir::Graph::from_json_reader(file).map_err(|e| format!("{cmd}: parse json failed: {e}"))
+        }
+    }
+}
+
+/// Print a simulator's diagnostics as warnings and where its artifacts went, or its error.
+#[cfg(any(feature = "sim-neuron", feature = "sim-coreneuron", feature = "sim-arbor", feature = "sim-hw-specific"))]
+fn report_simulate(out_dir: &Path, result: anyhow::Result<(PathBuf, Vec<String>)>) {
+    match result {
+        Ok((_, warnings)) => {
+            for w in &warnings {
+                eprintln!("simulate warning: {w}");
+            }
+            println!("simulate artifacts written to {out_dir:?}")
+        }
+        Err(e) => eprintln!("simulate error: {e}"),
+    }
+}
+
+/// `read_graph`, then load external weight files (`weights_ref`, relative to the document) and
+/// validate. Every command that compiles, simulates or lowers the weights goes through this.
+fn read_graph_with_weights(cmd: &str, input: &Path) -> Result<nc_nir::Graph, String> {
+    let mut g = read_graph(cmd, input)?;
+    load_weights_and_validate(cmd, input, &mut g)?;
+    Ok(g)
+}
+
+/// Resolve `g`'s `weights_ref` files next to `input` and validate the result.
+fn load_weights_and_validate(cmd: &str, input: &Path, g: &mut nc_nir::Graph) -> Result<(), String> {
+    g.validate().map_err(|e| format!("{cmd}: validation failed: {e}"))?;
+    let base = input.parent().unwrap_or(Path::new("."));
+    g.load_weight_refs(base).map_err(|e| format!("{cmd}: validation failed: {e}"))?;
+    Ok(())
+}
+
+/// Parse and validate `input` (loading external weight files), and load the manifest for
+/// `target` (aliases accepted).
+/// Errors are returned as messages prefixed with `cmd`.
+fn load_compile_inputs(cmd: &str, input: &Path, target: &str) -> Result<(nc_nir::Graph, nc_hal::TargetManifest), String> {
+    let mut g = read_graph_with_weights(cmd, input)?;
+    g.ensure_version_tag();
+
+    // Load target manifest (accepting short aliases like "rv64")
+    let target = nc_hal::resolve_target_alias(target);
+    let manifest_path = PathBuf::from(format!("targets/{target}.toml"));
+    let manifest = nc_hal::parse_target_manifest_path(&manifest_path)
+        .map_err(|e| format!("{cmd}: cannot load manifest {manifest_path:?}: {e}"))?;
+    nc_hal::validate_manifest(&manifest).map_err(|e| format!("{cmd}: manifest invalid: {e}"))?;
+
+    // Backends compile point neurons; collapse compartment trees instead of dropping them silently
+    let multi = g.populations.iter().filter(|p| !p.compartments.is_empty()).count();
+    if multi > 0 {
+        eprintln!("{cmd}: warning: collapsing the compartments of {multi} population(s) to point neurons for {target}");
+        g = nc_passes::Pass::run(&nc_passes::CollapseCompartmentsPass, g).map_err(|e| format!("{cmd}: {e}"))?;
+    }
+    Ok((g, manifest))
+}
+
+/// Parse, validate, and compile `args.input` for `args.target`, reporting on stdout/stderr.
+fn run_compile(args: &CompileArgs) {
+    let (g, manifest) = match load_compile_inputs("compile", &args.input, &args.target) {
+        Ok(x) => x,
+        Err(msg) => {
+            eprintln!("{msg}");
+            return;
+        }
+    };
+    match dispatch_compile(nc_hal::resolve_target_alias(&args.target), &g, &manifest) {
+        Ok(art) => println!("compile ok: {}", art),
+        Err(msg) => eprintln!("{msg}"),
+    }
+}
+
+/// Compile `args.input` `args.iters` times and print min/median/max wall-clock per compile.
+fn run_bench(args: &BenchArgs) {
+    let (g, manifest) = match load_compile_inputs("bench", &args.input, &args.target) {
+        Ok(x) => x,
+        Err(msg) => {
+            eprintln!("{msg}");
+            return;
+        }
+    };
+    let target = nc_hal::resolve_target_alias(&args.target);
+    let iters = args.iters.max(1);
+    let mut times_ms: Vec<f64> = Vec::with_capacity(iters);
+    let mut failed = 0usize;
+    for _ in 0..iters {
+        let t0 = std::time::Instant::now();
+        let res = dispatch_compile(target, &g, &manifest);
+        times_ms.push(t0.elapsed().as_secs_f64() * 1000.0);
+        if let Err(msg) = res {
+            // Report the first failure only; the rest are almost always the same
+            if failed == 0 {
+                eprintln!("{msg}");
+            }
+            failed += 1;
+        }
+    }
+    times_ms.sort_by(|a, b| a.total_cmp(b));
+    let median = if iters % 2 == 1 {
+        times_ms[iters / 2]
+    } else {
+        (times_ms[iters / 2 - 1] + times_ms[iters / 2]) / 2.0
+    };
+    println!(
+        "bench: target={target} iters={iters} failed={failed} min={:.3}ms median={:.3}ms max={:.3}ms",
+        times_ms[0],
+        median,
+        times_ms[iters - 1]
+    );
+
+    #[cfg(feature = "telemetry")]
+    if let Ok(p) = std::env::var("NC_PROFILE_JSONL") {
+        if let Ok(stats) = nc_telemetry::profiling::summarize_jsonl(&p) {
+            if let Some((c, sum, min, max)) = stats.get("backend.compile_ms") {
+                let avg = if *c > 0 { sum / *c as f64 } else { 0.0 };
+                println!("bench: backend.compile_ms count={c} avg={avg:.4} min={min:.4} max={max:.4}");
+            }
+        }
+    }
+}
+
+/// Compile `g` with the backend for `target` (a resolved builtin name). Returns the backend's
+/// artifact string, or the message to report (backend error, feature not enabled, unknown target).
+#[allow(unused_variables)] // g/manifest go unused when no backend feature is enabled
+fn dispatch_compile(target: &str, g: &nc_nir::Graph, manifest: &nc_hal::TargetManifest) -> Result<String, String> {
+    match target {
+        "loihi2" => {
+            #[cfg(feature = "backend-loihi")]
+            let r = nc_backend_loihi::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-loihi"))]
+            let r = Err("backend 'backend-loihi' is not enabled; rebuild CLI with --features backend-loihi".to_string());
+            r
+        }
+        "truenorth" => {
+            #[cfg(feature = "backend-truenorth")]
+            let r = nc_backend_truenorth::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-truenorth"))]
+            let r = Err("backend 'backend-truenorth' is not enabled; rebuild CLI with --features backend-truenorth".to_string());
+            r
+        }
+        "akida" => {
+            #[cfg(feature = "backend-akida")]
+            let r = nc_backend_akida::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-akida"))]
+            let r = Err("backend 'backend-akida' is not enabled; rebuild CLI with --features backend-akida".to_string());
+            r
+        }
+        "spinnaker2" => {
+            #[cfg(feature = "backend-spinnaker")]
+            let r = nc_backend_spinnaker::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-spinnaker"))]
+            let r = Err("backend 'backend-spinnaker' is not enabled; rebuild CLI with --features backend-spinnaker".to_string());
+            r
+        }
+        "neurogrid" => {
+            #[cfg(feature = "backend-neurogrid")]
+            let r = nc_backend_neurogrid::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-neurogrid"))]
+            let r = Err("backend 'backend-neurogrid' is not enabled; rebuild CLI with --features backend-neurogrid".to_string());
+            r
+        }
+        "dynaps" => {
+            #[cfg(feature = "backend-dynaps")]
+            let r = nc_backend_dynaps::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-dynaps"))]
+            let r = Err("backend 'backend-dynaps' is not enabled; rebuild CLI with --features backend-dynaps".to_string());
+            r
+        }
+        "memxbar" => {
+            #[cfg(feature = "backend-memxbar")]
+            let r = nc_backend_memxbar::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-memxbar"))]
+            let r = Err("backend 'backend-memxbar' is not enabled; rebuild CLI with --features backend-memxbar".to_string());
+            r
+        }
+        "custom_asic" => {
+            #[cfg(feature = "backend-custom-asic")]
+            let r = nc_backend_custom_asic::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-custom-asic"))]
+            let r = Err("backend 'backend-custom-asic' is not enabled; rebuild CLI with --features backend-custom-asic".to_string());
+            r
+        }
+        "riscv64gcv_linux" | "riscv32imac_bare" | "riscv64gc_ctrl" => {
+            #[cfg(feature = "backend-riscv")]
+            let r = nc_backend_riscv::compile(g, manifest).map_err(|e| format!("compile error: {e}"));
+            #[cfg(not(feature = "backend-riscv"))]
+            let r = Err("backend 'backend-riscv' is not enabled; rebuild CLI with --features backend-riscv".to_string());
+            r
+        }
+        other => Err(format!("compile: unsupported or not yet integrated target '{other}'")),
+    }
+}
+
+/// Recompile whenever `args.input` changes, until Ctrl-C (or `--watch-timeout-ms` of inactivity).
+fn watch_and_recompile(args: &CompileArgs) -> anyhow::Result<()> {
+    use notify::{RecursiveMode, Watcher};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    // Watch the parent directory so editors that replace the file (rename-on-save) are still seen
+    let input = fs::canonicalize(&args.input)?;
+    let dir = input.parent().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."));
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    println!("watching {:?} for changes (Ctrl-C to stop)", args.input);
+
+    let idle = args.watch_timeout_ms.map(Duration::from_millis).unwrap_or(Duration::MAX);
+    loop {
+        let event = match rx.recv_timeout(idle) {
+            Ok(ev) => ev,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                println!("watch: no changes for {} ms; stopping", idle.as_millis());
+                return Ok(());
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        let touched = match event {
+            Ok(ev) => {
+                !matches!(ev.kind, notify::EventKind::Access(_))
+                    && ev.paths.iter().any(|p| p.file_name() == input.file_name())
+            }
+            Err(e) => {
+                eprintln!("watch: {e}");
+                false
+            }
+        };
+        if !touched {
+            continue;
+        }
+        // Debounce bursts of events from a single save
+        std::thread::sleep(Duration::from_millis(100));
+        while rx.try_recv().is_ok() {}
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        println!("[watch {stamp}] {:?} changed; recompiling", args.input);
+        run_compile(args);
+    }
+}
+
+/// One-line summary of an attribute value: its type, plus keys for objects and length for arrays.
+fn summarize_attr(v: &serde_json::Value) -> String {
+    use serde_json::Value;
+    match v {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => format!("bool = {b}"),
+        Value::Number(n) => format!("number = {n}"),
+        Value::String(s) if s.chars().count() > 40 => format!("string = {:?}...", s.chars().take(40).collect::<String>()),
+        Value::String(s) => format!("string = {s:?}"),
+        Value::Array(a) => format!("array[{}]", a.len()),
+        Value::Object(o) => format!("object {{{}}}", o.keys().map(|k| k.as_str()).collect::<Vec<_>>().join(", ")),
+    }
+}
@@ -0,0 +1,111 @@
+//! Framework converters for `import --framework`: map PyNN / Nengo network descriptions
+//! (JSON exports) onto NIR populations and connections.
+
+use nc_nir::{Connection, Graph, Population};
+use serde_json::Value;
+
+/// Frameworks `import --framework` understands. `nir` is the native JSON/YAML format.
+pub const SUPPORTED_FRAMEWORKS: &[&str] = &["nir", "pynn", "nengo"];
+
+fn str_field<'a>(v: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter().find_map(|k| v.get(*k).and_then(Value::as_str))
+}
+
+fn num_field(v: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|k| v.get(*k).and_then(Value::as_f64))
+}
+
+fn array<'a>(root: &'a Value, key: &str) -> &'a [Value] {
+    root.get(key).and_then(Value::as_array).map(Vec::as_slice).unwrap_or(&[])
+}
+
+/// PyNN cell types onto NIR model names; unknown cell types pass through unchanged.
+fn pynn_model(celltype: &str) -> String {
+    match celltype {
+        "IF_curr_exp" | "IF_curr_alpha" | "IF_cond_exp" | "IF_cond_alpha" | "IF_curr_delta" => "LIF".to_string(),
+        "EIF_cond_exp_isfa_ista" | "EIF_cond_alpha_isfa_ista" => "AdaptiveLIF".to_string(),
+        "SpikeSourcePoisson" | "SpikeSourceArray" => "SpikeSource".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Convert a PyNN-style export:
+/// `{ "label", "populations": [{ "label", "size", "celltype", "parameters" }],
+///    "projections": [{ "presynaptic", "postsynaptic", "weight", "delay" }] }`.
+///
+/// PyNN delays are in ms, matching NIR `delay_ms`.
+pub fn from_pynn_json(s: &str) -> Result<Graph, String> {
+    let root: Value = serde_json::from_str(s).map_err(|e| format!("pynn: {e}"))?;
+    let mut g = Graph::new(str_field(&root, &["label", "name"]).unwrap_or("pynn_network"));
+    for (i, p) in array(&root, "populations").iter().enumerate() {
+        let name = str_field(p, &["label", "name"]).ok_or_else(|| format!("pynn: population {i} has no label"))?;
+        let size = num_field(p, &["size"]).ok_or_else(|| format!("pynn: population '{name}' has no size"))?;
+        let celltype = str_field(p, &["celltype", "cell_type"]).unwrap_or("IF_curr_exp");
+        g.populations.push(Population {
+            name: name.to_string(),
+            size: size as u32,
+            model: pynn_model(celltype),
+            params: p.get("parameters").cloned().unwrap_or_else(|| serde_json::json!({})),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
+        });
+    }
+    for (i, pr) in array(&root, "projections").iter().enumerate() {
+        let pre = str_field(pr, &["presynaptic", "pre"]).ok_or_else(|| format!("pynn: projection {i} has no presynaptic"))?;
+        let post = str_field(pr, &["postsynaptic", "post"]).ok_or_else(|| format!("pynn: projection {i} has no postsynaptic"))?;
+        g.connections.push(Connection {
+            pre: pre.to_string(),
+            post: post.to_string(),
+            weight: num_field(pr, &["weight"]).unwrap_or(0.0) as f32,
+            delay_ms: num_field(pr, &["delay"]).unwrap_or(0.0) as f32,
+            plasticity: None,
+            weight_matrix: None,
+            weights_ref: None,
+        });
+    }
+    Ok(g)
+}
+
+/// Convert a Nengo-style export:
+/// `{ "label", "ensembles": [{ "label", "n_neurons", "neuron_type" }],
+///    "connections": [{ "pre", "post", "transform" }] }`.
+///
+/// Nengo has no axonal delays, so connections get `delay_ms = 0`; a scalar `transform`
+/// becomes the connection weight.
+pub fn from_nengo_json(s: &str) -> Result<Graph, String> {
+    let root: Value = serde_json::from_str(s).map_err(|e| format!("nengo: {e}"))?;
+    let mut g = Graph::new(str_field(&root, &["label", "name"]).unwrap_or("nengo_network"));
+    for (i, e) in array(&root, "ensembles").iter().enumerate() {
+        let name = str_field(e, &["label", "name"]).ok_or_else(|| format!("nengo: ensemble {i} has no label"))?;
+        let n = num_field(e, &["n_neurons"]).ok_or_else(|| format!("nengo: ensemble '{name}' has no n_neurons"))?;
+        let model = match str_field(e, &["neuron_type"]).unwrap_or("LIF") {
+            "LIF" | "LIFRate" => "LIF",
+            "RectifiedLinear" | "SpikingRectifiedLinear" => "SpikeRate",
+            other => other,
+        };
+        g.populations.push(Population {
+            name: name.to_string(),
+            size: n as u32,
+            model: model.to_string(),
+            params: e.get("params").cloned().unwrap_or_else(|| serde_json::json!({})),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
+        });
+    }
+    for (i, c) in array(&root, "connections").iter().enumerate() {
+        let pre = str_field(c, &["pre"]).ok_or_else(|| format!("nengo: connection {i} has no pre"))?;
+        let post = str_field(c, &["post"]).ok_or_else(|| format!("nengo: connection {i} has no post"))?;
+        g.connections.push(Connection {
+            pre: pre.to_string(),
+            post: post.to_string(),
+            weight: num_field(c, &["transform", "weight"]).unwrap_or(1.0) as f32,
+            delay_ms: 0.0,
+            plasticity: None,
+            weight_matrix: None,
+            weights_ref: None,
+        });
+    }
+    Ok(g)
+}
@@ -29,6 +29,59 @@ fn import_simple_json_validates() {
         .stdout(predicate::str::contains("import ok: name="));
 }
 
+#[test]
+fn import_pynn_json_validates() {
+    let dir = std::env::temp_dir().join(format!("nc_import_pynn_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("net.json");
+    std::fs::write(
+        &input,
+        r#"{"label":"pynn_net",
+            "populations":[{"label":"src","size":10,"celltype":"SpikeSourcePoisson","parameters":{"rate":20.0}},
+                           {"label":"exc","size":8,"celltype":"IF_curr_exp"}],
+            "projections":[{"presynaptic":"src","postsynaptic":"exc","weight":0.5,"delay":1.0}]}"#,
+    )
+    .unwrap();
+
+    let mut cmd = bin();
+    cmd.args(["import", "--framework", "pynn", "--input"]).arg(&input);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("name=pynn_net populations=2 connections=1").and(predicate::str::contains("valid=true")));
+
+    let mut cmd = bin();
+    cmd.args(["import", "--framework", "brian", "--input"]).arg(&input);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("not yet supported").and(predicate::str::contains("pynn")));
+}
+
+#[test]
+fn import_resolves_weight_refs_next_to_document() {
+    let dir = std::env::temp_dir().join(format!("nc_import_weights_ref_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("net.json");
+    std::fs::write(
+        &input,
+        r#"{"name":"refs",
+            "populations":[{"name":"a","size":2,"model":"LIF"},{"name":"b","size":1,"model":"LIF"}],
+            "connections":[{"pre":"a","post":"b","weights_ref":{"file":"w.bin","dtype":"f32","shape":[2,1]}}]}"#,
+    )
+    .unwrap();
+    let raw: Vec<u8> = [0.5f32, -0.25].iter().flat_map(|w| w.to_le_bytes()).collect();
+    std::fs::write(dir.join("w.bin"), raw).unwrap();
+
+    let mut cmd = bin();
+    cmd.args(["import", "--input"]).arg(&input);
+    cmd.assert().success().stdout(predicate::str::contains("valid=true"));
+
+    // A missing weight file is reported instead of running with empty weights
+    std::fs::remove_file(dir.join("w.bin")).unwrap();
+    let mut cmd = bin();
+    cmd.args(["import", "--input"]).arg(&input);
+    cmd.assert().success().stdout(predicate::str::contains("valid=false"));
+}
+
 #[test]
 fn lower_with_mapping_passes_and_dumps() {
     // Run lower with our new pipeline passes and request dump artifacts
@@ -56,6 +109,20 @@ fn lower_with_mapping_passes_and_dumps() {
     }
 }
 
+#[test]
+fn lower_without_pipeline_uses_recommended() {
+    let crate_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let ws_root = crate_dir.parent().and_then(|p| p.parent()).expect("ws root");
+    let mut cmd = bin();
+    cmd.args(["lower", "--dump-dir", "target/test-dumps-recommended", "--manifest"])
+        .arg(ws_root.join("targets/akida.toml"));
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("using recommended pipeline"))
+        .stdout(predicate::str::contains("quantize8"))
+        .stdout(predicate::str::contains("lower completed"));
+}
+
 #[cfg(feature = "sim-neuron")]
 #[test]
 fn simulate_smoke() {
@@ -318,6 +385,32 @@ fn package_creates_artifacts() {
     assert!(meta.contains("neuro-compiler package"), "unexpected PKG.txt contents: {}", meta);
 }
 
+#[test]
+fn package_bundle_from_out_dir() {
+    use std::fs;
+    use std::path::PathBuf;
+
+    let src = PathBuf::from("target/pkg-bundle-src");
+    let _ = fs::remove_dir_all(&src);
+    fs::create_dir_all(src.join("passes")).expect("mkdir");
+    fs::write(src.join("README.txt"), "readme").expect("write README");
+    let zip = PathBuf::from("target/pkg-bundle.zip");
+    let _ = fs::remove_file(&zip);
+
+    let mut cmd = bin();
+    cmd.args([
+        "package",
+        "--bundle-from",
+        src.to_str().expect("src path"),
+        "--output",
+        zip.to_str().expect("zip path"),
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("bundle created at"));
+    assert!(zip.exists(), "bundle zip should exist");
+}
+
 #[test]
 fn deploy_smoke() {
     let mut cmd = bin();
@@ -330,3 +423,253 @@ fn deploy_smoke() {
         .success()
         .stdout(predicate::str::contains("deploy ok: target=loihi2"));
 }
+
+#[test]
+fn compile_watch_flag_accepted_single_compile() {
+    use std::path::PathBuf;
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let crate_dir = PathBuf::from(manifest_dir);
+    let ws_root = crate_dir.parent().and_then(|p| p.parent()).expect("ws root");
+    let input = ws_root.join("examples/nir/simple.json");
+
+    // The input never changes, so exactly one compile runs before the idle timeout ends the watch
+    let mut cmd = bin();
+    cmd.current_dir(ws_root).args([
+        "compile",
+        "--input", input.to_str().expect("input path"),
+        "--target", "loihi2",
+        "--watch",
+        "--watch-timeout-ms", "300",
+    ]);
+    let out = cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stdout.contains("watching"), "stdout: {stdout}");
+    assert!(stdout.contains("watch: no changes"), "stdout: {stdout}");
+    assert!(!stdout.contains("recompiling"), "stdout: {stdout}");
+    let compiled = stdout.matches("compile ok").count() + stderr.matches("backend 'backend-loihi' is not enabled").count();
+    assert_eq!(compiled, 1, "stdout: {stdout}\nstderr: {stderr}");
+}
+
+#[test]
+fn info_lists_pass_attributes() {
+    let dump_dir = "target/test-info-dumps";
+    let _ = std::fs::remove_dir_all(dump_dir);
+    bin()
+        .args(["lower", "--pipeline", "partition", "--dump-dir", dump_dir])
+        .assert()
+        .success();
+
+    let mut cmd = bin();
+    cmd.args(["info", "--input", "target/test-info-dumps/00_partition.json"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("info: name=cli-lower-demo"))
+        .stdout(predicate::str::contains("  partition: object {"))
+        .stdout(predicate::str::contains("strategy"));
+}
+
+#[test]
+fn diff_shows_pass_attributes() {
+    let dump_dir = "target/test-diff-dumps";
+    let _ = std::fs::remove_dir_all(dump_dir);
+    bin()
+        .args(["lower", "--pipeline", "partition", "--dump-dir", dump_dir])
+        .assert()
+        .success();
+    let dumped = "target/test-diff-dumps/00_partition.json";
+    let original = "target/test-diff-dumps/original.json";
+    std::fs::write(original, nc_nir::Graph::new("cli-lower-demo").to_json_string().unwrap()).unwrap();
+
+    bin()
+        .args(["diff", "--before", original, "--after", dumped])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ attribute partition"));
+    bin()
+        .args(["diff", "--before", dumped, "--after", dumped])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no structural changes"));
+    let out = bin()
+        .args(["diff", "--before", original, "--after", dumped, "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("json diff");
+    assert!(v["attributes"]["added"]["partition"].is_object(), "{v}");
+}
+
+#[test]
+fn migrate_upgrades_legacy_field_names() {
+    let dir = std::path::PathBuf::from("target/test-migrate");
+    std::fs::create_dir_all(&dir).unwrap();
+    let old = dir.join("old.json");
+    std::fs::write(
+        &old,
+        r#"{"name": "legacy", "populations": [{"id": "a", "size": 1, "neuron_type": "LIF"}],
+            "connections": [{"src": "a", "dst": "a", "weight": 1.0}], "attributes": {"nir_version": "0.0.1"}}"#,
+    )
+    .unwrap();
+    let new = dir.join("new.yaml");
+    bin()
+        .args(["migrate", "--input", old.to_str().unwrap(), "--output", new.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("nir_version {}", nc_nir::VERSION)));
+    let g = nc_nir::Graph::from_yaml_str(&std::fs::read_to_string(&new).unwrap()).unwrap();
+    assert_eq!(g.connections[0].pre, "a");
+
+    std::fs::write(dir.join("future.json"), r#"{"name": "f", "attributes": {"nir_version": "9.0.0"}}"#).unwrap();
+    bin()
+        .args(["migrate", "--input", "target/test-migrate/future.json"])
+        .assert()
+        .stderr(predicate::str::contains("newer than the supported"));
+}
+
+#[test]
+fn validate_lists_every_problem_as_table_and_json() {
+    let dir = std::path::PathBuf::from("target/test-validate");
+    std::fs::create_dir_all(&dir).unwrap();
+    let bad = dir.join("bad.json");
+    std::fs::write(
+        &bad,
+        r#"{"name": "bad", "populations": [{"name": "a", "size": 0, "model": "LIF"}, {"name": "b", "size": 2, "model": "LIF"}],
+            "connections": [{"pre": "a", "post": "missing", "weight": 1.0, "delay_ms": 5.0}]}"#,
+    )
+    .unwrap();
+    bin()
+        .args(["validate", "--input", bad.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SEVERITY  CODE  PATH"))
+        .stdout(predicate::str::contains("E103  /populations/0"))
+        .stdout(predicate::str::contains("E202  /connections/0"))
+        .stdout(predicate::str::contains("W101  /populations/1"))
+        .stderr(predicate::str::contains("validate: 2 error(s), 1 warning(s)"));
+
+    let out = bin().args(["validate", "--input", bad.to_str().unwrap(), "--json", "--max-delay-ms", "1"]).output().unwrap();
+    let diags: Vec<nc_nir::Diagnostic> = serde_json::from_slice(&out.stdout).unwrap();
+    assert!(diags.iter().any(|d| d.code == "E210" && d.path == "/connections/0/delay_ms"));
+
+    let ws_root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    bin()
+        .args(["validate", "--input", ws_root.join("examples/nir/simple.json").to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("validate: 0 error(s)"));
+}
+
+#[test]
+fn visualize_renders_partition_dump() {
+    let dump_dir = "target/test-visualize-dumps";
+    let _ = std::fs::remove_dir_all(dump_dir);
+    bin()
+        .args(["lower", "--pipeline", "partition", "--dump-dir", dump_dir])
+        .assert()
+        .success();
+    let dump = "target/test-visualize-dumps/00_partition.json";
+
+    bin()
+        .args(["visualize", "--input", dump, "--color-by-partition"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("digraph \"cli-lower-demo\""));
+    let out = "target/test-visualize-dumps/graph.mmd";
+    bin()
+        .args(["visualize", "--input", dump, "--output", out])
+        .assert()
+        .success();
+    assert!(std::fs::read_to_string(out).unwrap().starts_with("flowchart LR"));
+    bin()
+        .args(["visualize", "--input", dump, "--format", "svg"])
+        .assert()
+        .stderr(predicate::str::contains("unknown format 'svg'"));
+}
+
+#[test]
+fn lower_list_passes() {
+    let mut cmd = bin();
+    cmd.args(["lower", "--list-passes"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("partition"))
+        .stdout(predicate::str::contains("quantize8"));
+}
+
+#[test]
+fn bench_prints_timing_stats() {
+    use std::path::PathBuf;
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let crate_dir = PathBuf::from(manifest_dir);
+    let ws_root = crate_dir.parent().and_then(|p| p.parent()).expect("ws root");
+    let input = ws_root.join("examples/nir/simple.json");
+
+    let mut cmd = bin();
+    cmd.current_dir(ws_root).args([
+        "bench",
+        "--input", input.to_str().expect("input path"),
+        "--target", "loihi2",
+        "--iters", "2",
+    ]);
+    let pred = predicate::str::contains("iters=2")
+        .and(predicate::str::contains("min="))
+        .and(predicate::str::contains("median="))
+        .and(predicate::str::contains("max="));
+    cmd.assert().success().stdout(pred);
+}
+
+#[test]
+fn compile_collapses_compartments_with_a_warning() {
+    use std::path::PathBuf;
+    let ws_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().and_then(|p| p.parent()).expect("ws root").to_path_buf();
+    let dir = ws_root.join("target/test-compartments");
+    std::fs::create_dir_all(&dir).expect("mkdir");
+    let input = dir.join("mc.json");
+    std::fs::write(&input, r#"{"name": "mc", "populations": [{"name": "a", "size": 2, "model": "LIF",
+        "compartments": [{"name": "soma", "kind": "soma"}, {"name": "d", "kind": "dendrite", "parent": "soma"}]}]}"#)
+        .expect("write graph");
+
+    let mut cmd = bin();
+    cmd.current_dir(&ws_root).args(["compile", "--input", input.to_str().expect("input path"), "--target", "loihi2"]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("collapsing the compartments of 1 population(s) to point neurons for loihi2"));
+}
+
+#[test]
+fn config_file_sets_profile_jsonl() {
+    use std::fs;
+    use std::path::PathBuf;
+
+    let dir = PathBuf::from("target/test-config");
+    fs::create_dir_all(&dir).expect("mkdir");
+    let profile = dir.join("cfg-prof.jsonl");
+    fs::write(&profile, "{\"ts_ms\":0,\"metric\":\"cfg.marker\",\"value\":1.0,\"labels\":{}}\n").expect("write profile");
+    let config = dir.join("nc.toml");
+    fs::write(&config, format!("profile_jsonl = {:?}\n", profile.to_str().expect("profile path"))).expect("write config");
+
+    // `profile` without --input reads NC_PROFILE_JSONL, which only the config file sets here
+    let mut cmd = bin();
+    cmd.env_remove("NC_PROFILE_JSONL");
+    cmd.args(["--config", config.to_str().expect("config path"), "profile"]);
+    let out = cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    // Summarized with telemetry; otherwise the disabled message echoes the input path
+    assert!(
+        stdout.contains("cfg.marker") || stderr.contains("cfg-prof.jsonl"),
+        "stdout: {stdout}\nstderr: {stderr}"
+    );
+
+    let bad = dir.join("bad.toml");
+    fs::write(&bad, "profile_jsonl = [").expect("write bad config");
+    let mut cmd = bin();
+    cmd.args(["--config", bad.to_str().expect("bad path"), "list-targets"]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("config: malformed"))
+        .stdout(predicate::str::contains("loihi2").not());
+}
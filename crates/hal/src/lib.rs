@@ -1,13 +1,20 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use anyhow::bail;
 use thiserror::Error;
 
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(feature = "watch")]
+pub use watch::ManifestWatcher;
+
 #[derive(Debug, Error)]
 pub enum HalError {
     #[error("invalid manifest field: {field} ({msg})")]
     InvalidField { field: &'static str, msg: &'static str },
+    #[error("target '{target}' does not support neuron model(s): {}", models.join(", "))]
+    UnsupportedModels { target: String, models: Vec<String> },
 }
 
 #[derive(Debug, Clone)]
@@ -33,7 +40,28 @@ pub fn builtin_targets() -> &'static [&'static str] {
     ]
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Short names accepted in place of a built-in target name.
+const TARGET_ALIASES: &[(&str, &str)] = &[
+    ("rv64", "riscv64gcv_linux"),
+    ("rv64gcv", "riscv64gcv_linux"),
+    ("rv32", "riscv32imac_bare"),
+    ("rv32imac", "riscv32imac_bare"),
+    ("rv64gc", "riscv64gc_ctrl"),
+    ("loihi", "loihi2"),
+    ("spinnaker", "spinnaker2"),
+];
+
+/// Resolve a user-facing target alias (e.g. "rv64") to its built-in target name.
+/// Unknown names are returned unchanged.
+pub fn resolve_target_alias(name: &str) -> &str {
+    TARGET_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, full)| *full)
+        .unwrap_or(name)
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Capabilities {
     pub on_chip_learning: Option<bool>,
     pub weight_precisions: Option<Vec<u32>>,
@@ -48,6 +76,16 @@ pub struct Capabilities {
     pub max_fan_out: Option<u32>,
     pub core_memory_kib: Option<u32>,
     pub interconnect_bandwidth_mbps: Option<u32>,
+    /// Cores per row of the 2D core mesh; core `c` sits at `(c % mesh_width, c / mesh_width)`
+    pub mesh_width: Option<u32>,
+    /// Rows of the 2D core mesh
+    pub mesh_height: Option<u32>,
+    /// "mesh" | "torus" | "tree"; defaults to "mesh" when the mesh dimensions are given
+    pub interconnect_topology: Option<String>,
+    /// Children per router when `interconnect_topology = "tree"` (>= 2, default 4)
+    pub tree_arity: Option<u32>,
+    /// Capacity of one directed link; defaults to `interconnect_bandwidth_mbps`
+    pub link_bandwidth_mbps: Option<u32>,
     pub analog: Option<bool>,
     pub on_chip_plasticity_rules: Option<Vec<String>>,
 
@@ -105,22 +143,61 @@ pub struct Capabilities {
     pub dma_supported: Option<bool>,
     /// Must be power-of-two when present and dma_supported = true
     pub dma_alignment: Option<u32>,
+    /// Base address of the console UART used by bare-metal runtimes (must be > 0 when present)
+    pub uart_base_addr: Option<u64>,
 
     // Profile indicator
     /// "linux_user", "bare_metal", "control_plane" (free form)
     pub profile: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Capabilities {
+    /// Whether `model` is listed in `neuron_models` (case-insensitive). Permissive when the
+    /// target does not declare its models.
+    pub fn supports_model(&self, model: &str) -> bool {
+        match &self.neuron_models {
+            Some(v) => v.iter().any(|m| m.eq_ignore_ascii_case(model)),
+            None => true,
+        }
+    }
+
+    /// Whether the plasticity rule `kind` (e.g. "STDP") is listed in `on_chip_plasticity_rules`
+    /// (case-insensitive). Permissive when the target does not declare its rules.
+    pub fn supports_plasticity(&self, kind: &str) -> bool {
+        match &self.on_chip_plasticity_rules {
+            Some(v) => v.iter().any(|r| r.eq_ignore_ascii_case(kind)),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TargetManifest {
     pub name: String,
     pub vendor: String,
     pub family: String,
     pub version: String,
+    /// Optional manifest revision (free form, e.g. "2024-06" or a git hash) for cache keys.
+    pub revision: Option<String>,
     pub notes: Option<String>,
     pub capabilities: Option<Capabilities>,
 }
 
+impl TargetManifest {
+    /// Stable 16-hex-digit digest over every manifest field (including `revision` and all
+    /// capabilities). Uses FNV-1a over the canonical JSON form, so it does not depend on
+    /// the Rust version or process and can key on-disk build caches.
+    pub fn digest(&self) -> String {
+        let canonical = serde_json::to_string(self).unwrap_or_default();
+        let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+        for b in canonical.as_bytes() {
+            h ^= u64::from(*b);
+            h = h.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        format!("{h:016x}")
+    }
+}
+
 pub fn parse_target_manifest_str(s: &str) -> Result<TargetManifest, anyhow::Error> {
     let m: TargetManifest = toml::from_str(s)?;
     Ok(m)
@@ -144,6 +221,130 @@ pub fn load_manifests_from_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<(PathBuf, T
     Ok(out)
 }
 
+/// Recursively load `.toml` and `.json` manifests under `dir`, descending into subdirectories.
+/// Returned paths are the full paths as walked (rooted at `dir`). Directories are tracked by their
+/// canonical path so symlink loops are visited only once.
+pub fn load_manifests_recursive<P: AsRef<Path>>(dir: P) -> Result<Vec<(PathBuf, TargetManifest)>, anyhow::Error> {
+    let mut out = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut stack = vec![dir.as_ref().to_path_buf()];
+    while let Some(d) = stack.pop() {
+        if !seen.insert(fs::canonicalize(&d)?) {
+            continue;
+        }
+        let mut entries: Vec<PathBuf> = fs::read_dir(&d)?.map(|e| e.map(|e| e.path())).collect::<Result<_, _>>()?;
+        entries.sort();
+        for p in entries {
+            if p.is_dir() {
+                stack.push(p);
+                continue;
+            }
+            match p.extension().and_then(|e| e.to_str()) {
+                Some("toml") => {
+                    let m = parse_target_manifest_path(&p)?;
+                    out.push((p, m));
+                }
+                Some("json") => {
+                    let m: TargetManifest = serde_json::from_str(&fs::read_to_string(&p)?)?;
+                    out.push((p, m));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// One capability field whose value differs between two manifests.
+/// `None` means the field is absent (unset) on that side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapDiff {
+    pub field: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+/// Compare the capabilities of two manifests field by field.
+/// Returns one entry per differing field (sorted by field name), including fields set on only one side.
+pub fn capability_diff(a: &TargetManifest, b: &TargetManifest) -> Vec<CapDiff> {
+    fn fields(m: &TargetManifest) -> serde_json::Map<String, serde_json::Value> {
+        match m.capabilities.as_ref().map(serde_json::to_value) {
+            Some(Ok(serde_json::Value::Object(o))) => o,
+            _ => serde_json::Map::new(),
+        }
+    }
+    fn render(v: Option<&serde_json::Value>) -> Option<String> {
+        match v {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::String(s)) => Some(s.clone()),
+            Some(other) => Some(other.to_string()),
+        }
+    }
+
+    let fa = fields(a);
+    let fb = fields(b);
+    let mut keys: Vec<&String> = fa.keys().chain(fb.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut out = Vec::new();
+    for k in keys {
+        let va = render(fa.get(k));
+        let vb = render(fb.get(k));
+        if va != vb {
+            out.push(CapDiff { field: k.clone(), a: va, b: vb });
+        }
+    }
+    out
+}
+
+/// Suggest a lowering pipeline (pass names understood by `nc_passes::build_pipeline`) for a target.
+///
+/// Always includes `validate`, `collapse-compartments` (targets run point neurons),
+/// `partition`, `placement`, `routing` and `resource-check`.
+/// Adds the narrowest `quantizeN` (4/8/16) that covers the widest advertised weight precision,
+/// and `timing` when the target declares a time resolution.
+pub fn recommended_pipeline(m: &TargetManifest) -> Vec<String> {
+    let caps = m.capabilities.as_ref();
+    let mut names = vec!["validate".to_string(), "collapse-compartments".to_string()];
+    let max_bits = caps
+        .and_then(|c| c.weight_precisions.as_ref())
+        .and_then(|v| v.iter().max().copied());
+    match max_bits {
+        Some(b) if b <= 4 => names.push("quantize4".into()),
+        Some(b) if b <= 8 => names.push("quantize8".into()),
+        Some(b) if b <= 16 => names.push("quantize16".into()),
+        _ => {}
+    }
+    names.extend(["partition", "placement", "routing"].map(String::from));
+    if caps.and_then(|c| c.time_resolution_ns).is_some() {
+        names.push("timing".into());
+    }
+    names.push("resource-check".into());
+    names
+}
+
+/// Pre-compile guard: every model in `models` must pass `Capabilities::supports_model`.
+/// Spike-source models (`source`, `input`, `SpikeSource*`) are inputs rather than neurons
+/// and always pass; so does everything when the manifest declares no `neuron_models`.
+/// Unsupported models are reported once each, in first-seen order.
+pub fn check_model_support<'a>(m: &TargetManifest, models: impl IntoIterator<Item = &'a str>) -> Result<(), HalError> {
+    let Some(caps) = &m.capabilities else { return Ok(()) };
+    let mut unsupported: Vec<String> = Vec::new();
+    for model in models {
+        let lower = model.to_ascii_lowercase();
+        let is_source = lower == "source" || lower == "input" || lower.starts_with("spikesource");
+        if !is_source && !caps.supports_model(model) && !unsupported.iter().any(|u| u == model) {
+            unsupported.push(model.to_string());
+        }
+    }
+    if unsupported.is_empty() {
+        Ok(())
+    } else {
+        Err(HalError::UnsupportedModels { target: m.name.clone(), models: unsupported })
+    }
+}
+
 /// Validate a target manifest for basic consistency.
 pub fn validate_manifest(m: &TargetManifest) -> anyhow::Result<()> {
     if m.name.trim().is_empty() {
@@ -194,6 +395,34 @@ pub fn validate_manifest(m: &TargetManifest) -> anyhow::Result<()> {
                 bail!("capabilities.interconnect_bandwidth_mbps must be > 0");
             }
         }
+        if let Some(v) = c.mesh_width {
+            if v == 0 {
+                bail!("capabilities.mesh_width must be > 0");
+            }
+        }
+        if let Some(v) = c.mesh_height {
+            if v == 0 {
+                bail!("capabilities.mesh_height must be > 0");
+            }
+        }
+        if let Some(t) = &c.interconnect_topology {
+            if !matches!(t.as_str(), "mesh" | "torus" | "tree") {
+                bail!("capabilities.interconnect_topology must be one of mesh, torus, tree");
+            }
+            if t != "tree" && (c.mesh_width.is_none() || c.mesh_height.is_none()) {
+                bail!("capabilities.interconnect_topology = \"{t}\" requires mesh_width and mesh_height");
+            }
+        }
+        if let Some(v) = c.tree_arity {
+            if v < 2 {
+                bail!("capabilities.tree_arity must be >= 2");
+            }
+        }
+        if let Some(v) = c.link_bandwidth_mbps {
+            if v == 0 {
+                bail!("capabilities.link_bandwidth_mbps must be > 0");
+            }
+        }
         if let Some(v) = c.neuron_mem_kib_per {
             if v <= 0.0 {
                 bail!("capabilities.neuron_mem_kib_per must be > 0");
@@ -245,6 +474,9 @@ pub fn validate_manifest(m: &TargetManifest) -> anyhow::Result<()> {
                 _ => bail!("capabilities.mmio_width_bits must be 32 or 64 when mmio_supported = true"),
             }
         }
+        if c.uart_base_addr == Some(0) {
+            bail!("capabilities.uart_base_addr must be > 0 when provided");
+        }
         if matches!(c.dma_supported, Some(true)) {
             match c.dma_alignment {
                 Some(v) if v > 0 && v.is_power_of_two() => {}
@@ -264,6 +496,14 @@ pub fn validate_manifest(m: &TargetManifest) -> anyhow::Result<()> {
                 bail!("capabilities.cacheline_bytes must be a power-of-two > 0");
             }
         }
+        // DMA buffers finer than a cacheline share lines with CPU data (only when both are declared)
+        if let (Some(true), Some(dma), Some(line)) = (c.dma_supported, c.dma_alignment, c.cacheline_bytes) {
+            if dma < line {
+                bail!(
+                    "capabilities.dma_alignment ({dma}) must be >= cacheline_bytes ({line}) when dma_supported = true"
+                );
+            }
+        }
         if let Some(v) = c.page_size_bytes {
             if v == 0 || !v.is_power_of_two() {
                 bail!("capabilities.page_size_bytes must be a power-of-two > 0");
@@ -298,8 +538,20 @@ pub fn validate_manifest(m: &TargetManifest) -> anyhow::Result<()> {
                 if matches!(c.has_vector, Some(true)) && !isa_lc.contains('v') {
                     bail!("capabilities.has_vector = true but isa does not contain 'v'");
                 }
+                // Single-letter extensions sit between the rv32/rv64 prefix and the first '_'; 'g' implies IMAFD.
+                let base = isa_lc
+                    .strip_prefix("rv32")
+                    .or_else(|| isa_lc.strip_prefix("rv64"))
+                    .unwrap_or(&isa_lc);
+                let base = base.split('_').next().unwrap_or_default();
+                if base.contains('d') && !base.contains('f') && !base.contains('g') {
+                    bail!("capabilities.isa contains 'd' but not 'f'; the D extension requires F");
+                }
             }
         }
+        if matches!(c.has_d, Some(true)) && matches!(c.has_f, Some(false)) {
+            bail!("capabilities.has_d = true requires has_f to not be false (D extension requires F)");
+        }
     }
     Ok(())
 }
@@ -345,6 +597,128 @@ mod tests_builtin {
             super::validate_manifest(&m).expect("manifest valid");
         }
     }
+
+    #[test]
+    fn target_alias_resolution() {
+        assert_eq!(resolve_target_alias("rv64"), "riscv64gcv_linux");
+        assert_eq!(resolve_target_alias("loihi2"), "loihi2");
+        assert_eq!(resolve_target_alias("not-a-target"), "not-a-target");
+        for (_, full) in TARGET_ALIASES {
+            assert!(builtin_targets().contains(full), "alias target {full} is not builtin");
+        }
+    }
+
+    #[test]
+    fn load_manifests_recursive_nested() {
+        let root = std::env::temp_dir().join(format!("nc_hal_recursive_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let nested = root.join("vendor_a").join("gen2");
+        std::fs::create_dir_all(&nested).expect("mkdir");
+        std::fs::write(root.join("vendor_a").join("a.toml"), "name = \"a\"\nvendor = \"v\"\nfamily = \"F\"\nversion = \"1\"\n").expect("write a");
+        std::fs::write(nested.join("b.json"), r#"{"name":"b","vendor":"v","family":"F","version":"2"}"#).expect("write b");
+        std::fs::write(nested.join("README.md"), "ignored").expect("write readme");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, nested.join("loop")).expect("symlink");
+
+        let manis = load_manifests_recursive(&root).expect("load recursive");
+        let mut names: Vec<&str> = manis.iter().map(|(_, m)| m.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["a", "b"]);
+        assert!(manis.iter().all(|(p, _)| p.starts_with(&root)));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn capability_diff_riscv_targets() {
+        let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let ws_root = crate_dir.parent().and_then(|p| p.parent()).expect("workspace root");
+        let a = parse_target_manifest_path(ws_root.join("targets/riscv64gcv_linux.toml")).expect("parse a");
+        let b = parse_target_manifest_path(ws_root.join("targets/riscv32imac_bare.toml")).expect("parse b");
+        let diff = capability_diff(&a, &b);
+        let isa = diff.iter().find(|d| d.field == "isa").expect("isa differs");
+        assert_eq!(isa.a.as_deref(), Some("rv64gcv"));
+        assert_eq!(isa.b.as_deref(), Some("rv32imac"));
+        // vlen_bits_max is only set on the vector target
+        let vlen = diff.iter().find(|d| d.field == "vlen_bits_max").expect("vlen differs");
+        assert_eq!(vlen.b, None);
+        assert!(capability_diff(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn check_model_support_reports_unsupported_once() {
+        let m = parse_target_manifest_str(r#"
+            name = "t"
+            vendor = "v"
+            family = "f"
+            version = "1"
+            [capabilities]
+            neuron_models = ["LIF"]
+        "#).unwrap();
+        check_model_support(&m, ["lif", "source", "SpikeSourceArray"]).expect("sources and LIF pass");
+        let err = check_model_support(&m, ["LIF", "Izhikevich", "HH", "Izhikevich"]).unwrap_err();
+        assert!(matches!(&err, HalError::UnsupportedModels { models, .. } if models == &["Izhikevich".to_string(), "HH".to_string()]));
+
+        let open = parse_target_manifest_str("name = \"t\"\nvendor = \"v\"\nfamily = \"f\"\nversion = \"1\"\n").unwrap();
+        check_model_support(&open, ["Anything"]).expect("permissive without neuron_models");
+    }
+
+    #[test]
+    fn manifest_digest_tracks_changes() {
+        let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let path = crate_dir.join("../../targets/loihi2.toml");
+        let a = parse_target_manifest_path(&path).expect("parse a");
+        let b = parse_target_manifest_path(&path).expect("parse b");
+        assert_eq!(a.digest(), b.digest());
+        assert_eq!(a.digest().len(), 16);
+
+        let mut c = b.clone();
+        c.capabilities.as_mut().expect("caps").max_fan_in = Some(7);
+        assert_ne!(a.digest(), c.digest());
+        let mut d = b.clone();
+        d.revision = Some("r2".into());
+        assert_ne!(a.digest(), d.digest());
+    }
+
+    #[test]
+    fn supports_plasticity_checks_declared_rules() {
+        let m = parse_target_manifest_str(r#"
+            name = "stdp-only"
+            vendor = "v"
+            family = "F"
+            version = "1"
+            [capabilities]
+            neuron_models = ["LIF"]
+            on_chip_plasticity_rules = ["STDP"]
+        "#).expect("parse");
+        let caps = m.capabilities.expect("caps");
+        assert!(caps.supports_plasticity("stdp"));
+        assert!(!caps.supports_plasticity("Hebbian"));
+        assert!(caps.supports_model("lif"));
+        assert!(!caps.supports_model("Izhikevich"));
+        let open: Capabilities = serde_json::from_str("{}").expect("empty caps");
+        assert!(open.supports_plasticity("Hebbian"));
+        assert!(open.supports_model("anything"));
+    }
+
+    #[test]
+    fn recommended_pipeline_quantizes_for_8bit_target() {
+        let m = parse_target_manifest_str(r#"
+            name = "eight"
+            vendor = "v"
+            family = "F"
+            version = "1"
+            [capabilities]
+            weight_precisions = [8]
+        "#).expect("parse");
+        let p = recommended_pipeline(&m);
+        assert!(p.iter().any(|n| n == "quantize8"), "pipeline: {p:?}");
+        for n in ["validate", "collapse-compartments", "partition", "placement", "routing", "resource-check"] {
+            assert!(p.iter().any(|x| x == n), "missing {n}: {p:?}");
+        }
+        // No precision info: no quantize pass
+        let bare = parse_target_manifest_str("name = \"x\"\nvendor = \"v\"\nfamily = \"F\"\nversion = \"1\"\n").expect("parse");
+        assert!(!recommended_pipeline(&bare).iter().any(|n| n.starts_with("quantize")));
+    }
 }
 
 #[cfg(test)]
@@ -425,6 +799,28 @@ mod tests_validate {
         assert!(validate_manifest(&m).is_err(), "expected invalid mmio_width_bits");
     }
 
+    #[test]
+    fn validate_manifest_dma_alignment_vs_cacheline() {
+        let manifest = |dma: u32| {
+            parse_target_manifest_str(&format!(
+                r#"
+                name = "rv"
+                vendor = "g"
+                family = "RISC-V"
+                version = "1"
+                [capabilities]
+                cacheline_bytes = 64
+                dma_supported = true
+                dma_alignment = {dma}
+            "#
+            ))
+            .unwrap()
+        };
+        let err = validate_manifest(&manifest(16)).unwrap_err().to_string();
+        assert!(err.contains("dma_alignment (16)") && err.contains("cacheline_bytes (64)"), "{err}");
+        validate_manifest(&manifest(64)).expect("equal alignment is fine");
+    }
+
     #[test]
     fn validate_manifest_riscv_invalid_endianness() {
         let s = r#"
@@ -439,6 +835,18 @@ mod tests_validate {
         assert!(validate_manifest(&m).is_err(), "expected invalid endianness");
     }
 
+    #[test]
+    fn validate_manifest_interconnect_topology() {
+        let manifest = |caps: &str| {
+            parse_target_manifest_str(&format!("name = \"x\"\nvendor = \"v\"\nfamily = \"F\"\nversion = \"1\"\n[capabilities]\n{caps}")).unwrap()
+        };
+        assert!(validate_manifest(&manifest("interconnect_topology = \"torus\"\nmesh_width = 4\nmesh_height = 2")).is_ok());
+        assert!(validate_manifest(&manifest("interconnect_topology = \"tree\"\ntree_arity = 2")).is_ok());
+        assert!(validate_manifest(&manifest("interconnect_topology = \"torus\"")).is_err(), "torus needs mesh dimensions");
+        assert!(validate_manifest(&manifest("interconnect_topology = \"ring\"")).is_err());
+        assert!(validate_manifest(&manifest("interconnect_topology = \"tree\"\ntree_arity = 1")).is_err());
+    }
+
     #[test]
     fn validate_manifest_riscv_zvl_vs_vlen() {
         let s = r#"
@@ -0,0 +1,154 @@
+//! Hot-reloading view of a manifest directory for long-running services (`watch` feature).
+
+use crate::{parse_target_manifest_path, validate_manifest, TargetManifest};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Callback for manifest files that fail to parse or validate: `(path, error)`.
+pub type ManifestErrorCallback = dyn Fn(&Path, &anyhow::Error) + Send + Sync;
+
+#[derive(Default)]
+struct State {
+    by_name: HashMap<String, TargetManifest>,
+    /// Manifest name currently provided by each file (by file name), so edits that rename a
+    /// target or delete the file drop the stale entry.
+    by_file: HashMap<OsString, String>,
+}
+
+/// Keeps name -> `TargetManifest` for the `.toml` files directly under a directory, re-parsing
+/// and re-validating a file whenever it changes. An invalid edit keeps the previous good
+/// version and is reported through the error callback.
+pub struct ManifestWatcher {
+    state: Arc<RwLock<State>>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl ManifestWatcher {
+    /// Watch `dir`; invalid manifests are skipped silently (see `with_error_callback`).
+    pub fn new<P: AsRef<Path>>(dir: P) -> anyhow::Result<Self> {
+        Self::with_error_callback(dir, |_, _| {})
+    }
+
+    /// Watch `dir`, calling `on_error` for every manifest that fails to load, both at startup
+    /// and on later edits.
+    pub fn with_error_callback<P, F>(dir: P, on_error: F) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+        F: Fn(&Path, &anyhow::Error) + Send + Sync + 'static,
+    {
+        let dir = fs::canonicalize(dir.as_ref())?;
+        let on_error: Arc<ManifestErrorCallback> = Arc::new(on_error);
+        let state = Arc::new(RwLock::new(State::default()));
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?.map(|e| e.map(|e| e.path())).collect::<Result<_, _>>()?;
+        paths.sort();
+        for p in paths.iter().filter(|p| is_manifest(p)) {
+            reload(&state, p, on_error.as_ref());
+        }
+
+        let cb_state = Arc::clone(&state);
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(ev) = res else { return };
+            if matches!(ev.kind, EventKind::Access(_)) {
+                return;
+            }
+            for p in ev.paths.iter().filter(|p| is_manifest(p)) {
+                if p.exists() {
+                    reload(&cb_state, p, on_error.as_ref());
+                } else if let Some(file) = p.file_name() {
+                    let mut st = cb_state.write().expect("manifest watcher lock poisoned");
+                    if let Some(name) = st.by_file.remove(file) {
+                        st.by_name.remove(&name);
+                    }
+                }
+            }
+        })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        Ok(Self { state, _watcher: watcher })
+    }
+
+    /// Current good version of manifest `name`.
+    pub fn get(&self, name: &str) -> Option<TargetManifest> {
+        self.state.read().expect("manifest watcher lock poisoned").by_name.get(name).cloned()
+    }
+
+    /// Names of the manifests currently loaded, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.state.read().expect("manifest watcher lock poisoned").by_name.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+fn is_manifest(p: &Path) -> bool {
+    p.extension().and_then(|e| e.to_str()) == Some("toml")
+}
+
+/// Parse + validate `path` and swap it into `state`; on failure keep the old entry.
+fn reload(state: &RwLock<State>, path: &Path, on_error: &ManifestErrorCallback) {
+    let Some(file) = path.file_name() else { return };
+    let loaded = parse_target_manifest_path(path).and_then(|m| validate_manifest(&m).map(|_| m));
+    match loaded {
+        Ok(m) => {
+            let mut st = state.write().expect("manifest watcher lock poisoned");
+            if let Some(old) = st.by_file.insert(file.to_os_string(), m.name.clone()) {
+                if old != m.name {
+                    st.by_name.remove(&old);
+                }
+            }
+            st.by_name.insert(m.name.clone(), m);
+        }
+        Err(e) => on_error(path, &e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    fn manifest(version: &str, vendor: &str) -> String {
+        format!("name = \"svc\"\nvendor = \"{vendor}\"\nfamily = \"f\"\nversion = \"{version}\"\n")
+    }
+
+    fn wait_until(mut f: impl FnMut() -> bool) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(10) {
+            if f() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(25));
+        }
+        false
+    }
+
+    #[test]
+    fn edits_update_watched_manifest() {
+        let dir = std::env::temp_dir().join(format!("nc_hal_watch_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("svc.toml");
+        fs::write(&file, manifest("1", "v")).unwrap();
+
+        let errors: Arc<Mutex<Vec<String>>> = Arc::default();
+        let sink = Arc::clone(&errors);
+        let w = ManifestWatcher::with_error_callback(&dir, move |_, e| sink.lock().unwrap().push(e.to_string())).unwrap();
+        assert_eq!(w.get("svc").map(|m| m.version), Some("1".to_string()));
+
+        fs::write(&file, manifest("2", "v")).unwrap();
+        assert!(wait_until(|| w.get("svc").is_some_and(|m| m.version == "2")), "edit not picked up");
+
+        // Empty vendor fails validation: the previous good version stays
+        fs::write(&file, manifest("3", "")).unwrap();
+        let reported = || errors.lock().unwrap().iter().any(|e| e.contains("manifest.vendor must be non-empty"));
+        assert!(wait_until(reported), "invalid edit not reported: {:?}", errors.lock().unwrap());
+        assert_eq!(w.get("svc").map(|m| m.version), Some("2".to_string()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
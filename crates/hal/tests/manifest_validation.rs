@@ -268,3 +268,74 @@ fn hal_vector_true_but_isa_lacks_v() -> Result<()> {
     assert!(msg.contains("has_vector") && msg.contains("isa") && msg.contains("'v'"), "msg={msg}");
     Ok(())
 }
+
+#[test]
+fn hal_has_d_without_f_rejected() -> Result<()> {
+    let s = r#"
+        name = "t"
+        vendor = "v"
+        family = "RISC-V"
+        version = "1"
+        [capabilities]
+        has_d = true
+        has_f = false
+    "#;
+    let m = parse_target_manifest_str(s)?;
+    let err = validate_manifest(&m).unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("has_d") && msg.contains("has_f"), "msg={msg}");
+    Ok(())
+}
+
+#[test]
+fn hal_has_d_with_f_ok() -> Result<()> {
+    let s = r#"
+        name = "t"
+        vendor = "v"
+        family = "RISC-V"
+        version = "1"
+        [capabilities]
+        isa = "rv64imafdc"
+        abi = "lp64d"
+        has_d = true
+        has_f = true
+    "#;
+    let m = parse_target_manifest_str(s)?;
+    validate_manifest(&m)?;
+    Ok(())
+}
+
+#[test]
+fn hal_isa_d_without_f_rejected() -> Result<()> {
+    let s = r#"
+        name = "t"
+        vendor = "v"
+        family = "RISC-V"
+        version = "1"
+        [capabilities]
+        isa = "rv64imadc"
+    "#;
+    let m = parse_target_manifest_str(s)?;
+    let err = validate_manifest(&m).unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("isa") && msg.contains("'f'"), "msg={msg}");
+    Ok(())
+}
+
+#[test]
+fn hal_uart_base_addr_zero_rejected() -> Result<()> {
+    let s = r#"
+        name = "t"
+        vendor = "v"
+        family = "RISC-V"
+        version = "1"
+        [capabilities]
+        isa = "rv32imac"
+        uart_base_addr = 0
+    "#;
+    let m = parse_target_manifest_str(s)?;
+    let err = validate_manifest(&m).unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("uart_base_addr"), "msg={msg}");
+    Ok(())
+}
@@ -45,6 +45,9 @@ mod tests {
             size: 1,
             model: "LIF".into(),
             params: serde_json::json!({}),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
         });
         let s = lower_to_mlir(&g).unwrap();
         assert!(s.contains("module @t"));
@@ -0,0 +1,152 @@
+//! Linear analytical cost model: latency and energy proportional to neuron updates and
+//! synaptic operations, with coefficients that can be fitted from runtime profiles.
+
+use crate::CostModel;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// `latency_ns = ns_per_neuron * neurons + ns_per_synop * synops` per step, and likewise for
+/// energy in pJ. Synops count dense synapses: `pre.size * post.size` summed over connections.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnalyticalCostModel {
+    pub ns_per_neuron: f64,
+    pub ns_per_synop: f64,
+    pub pj_per_neuron: f64,
+    pub pj_per_synop: f64,
+}
+
+impl Default for AnalyticalCostModel {
+    fn default() -> Self {
+        Self { ns_per_neuron: 1.0, ns_per_synop: 0.1, pj_per_neuron: 1.0, pj_per_synop: 0.1 }
+    }
+}
+
+/// `(neurons, synops)` for one step of `g`.
+fn workload(g: &nc_nir::Graph) -> (f64, f64) {
+    let size = |name: &str| g.populations.iter().find(|p| p.name == name).map_or(0.0, |p| p.size as f64);
+    let neurons: f64 = g.populations.iter().map(|p| p.size as f64).sum();
+    let synops: f64 = g.connections.iter().map(|c| size(&c.pre) * size(&c.post)).sum();
+    (neurons, synops)
+}
+
+impl AnalyticalCostModel {
+    /// Rough built-in coefficients for a target family, or `None` when there is no model for
+    /// `target`. RISC-V cores pay per synaptic op in time and energy; a memristive crossbar
+    /// does the MACs in analog, so the per-neuron readout dominates.
+    pub fn for_target(target: &str) -> Option<AnalyticalCostModel> {
+        if target.starts_with("riscv") {
+            Some(Self { ns_per_neuron: 5.0, ns_per_synop: 1.0, pj_per_neuron: 50.0, pj_per_synop: 10.0 })
+        } else if target == "memxbar" {
+            Some(Self { ns_per_neuron: 20.0, ns_per_synop: 0.001, pj_per_neuron: 5.0, pj_per_synop: 0.01 })
+        } else {
+            None
+        }
+    }
+
+    /// Fit `ns_per_neuron` / `ns_per_synop` by least squares against the aggregate
+    /// `kernel.step_ns` records in `jsonl_path` (records with a `population` label are skipped).
+    /// Records are matched to `graphs` by the `graph` label and averaged per graph. Energy
+    /// coefficients keep their defaults.
+    ///
+    /// Errors when fewer than two graphs have samples, or when their neuron/synop counts are
+    /// proportional so the two coefficients cannot be separated.
+    pub fn calibrate<P: AsRef<Path>>(jsonl_path: P, graphs: &[nc_nir::Graph]) -> Result<AnalyticalCostModel> {
+        let path = jsonl_path.as_ref();
+        let text = std::fs::read_to_string(path).with_context(|| format!("read profile {}", path.display()))?;
+        let mut sums = vec![(0.0f64, 0usize); graphs.len()];
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let Ok(rec) = serde_json::from_str::<serde_json::Value>(line) else { continue };
+            if rec.get("metric").and_then(|m| m.as_str()) != Some("kernel.step_ns") {
+                continue;
+            }
+            let labels = rec.get("labels");
+            if labels.and_then(|l| l.get("population")).is_some() {
+                continue;
+            }
+            let (Some(graph), Some(value)) = (
+                labels.and_then(|l| l.get("graph")).and_then(|g| g.as_str()),
+                rec.get("value").and_then(|v| v.as_f64()),
+            ) else {
+                continue;
+            };
+            if let Some(i) = graphs.iter().position(|g| g.name == graph) {
+                sums[i].0 += value;
+                sums[i].1 += 1;
+            }
+        }
+
+        // Normal equations for y = a*n + b*s (no intercept)
+        let (mut nn, mut ns, mut ss, mut ny, mut sy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        let mut samples = 0usize;
+        for (g, (sum, count)) in graphs.iter().zip(&sums) {
+            if *count == 0 {
+                continue;
+            }
+            let (n, s) = workload(g);
+            let y = sum / *count as f64;
+            nn += n * n;
+            ns += n * s;
+            ss += s * s;
+            ny += n * y;
+            sy += s * y;
+            samples += 1;
+        }
+        if samples < 2 {
+            bail!("calibration needs kernel.step_ns samples for at least 2 graphs, found {samples} in {}", path.display());
+        }
+        let det = nn * ss - ns * ns;
+        if det.abs() <= 1e-9 * (nn * ss).max(1.0) {
+            bail!("calibration samples are degenerate: neuron and synop counts are proportional across graphs");
+        }
+        Ok(AnalyticalCostModel {
+            ns_per_neuron: (ny * ss - sy * ns) / det,
+            ns_per_synop: (sy * nn - ny * ns) / det,
+            ..AnalyticalCostModel::default()
+        })
+    }
+}
+
+impl CostModel for AnalyticalCostModel {
+    fn predict_latency_ms(&self, graph: &nc_nir::Graph) -> Result<f64> {
+        let (n, s) = workload(graph);
+        Ok((self.ns_per_neuron * n + self.ns_per_synop * s) / 1e6)
+    }
+    fn predict_energy_mj(&self, graph: &nc_nir::Graph) -> Result<f64> {
+        let (n, s) = workload(graph);
+        Ok((self.pj_per_neuron * n + self.pj_per_synop * s) * 1e-9)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(mut g: nc_nir::Graph, name: &str) -> nc_nir::Graph {
+        g.name = name.to_string();
+        g
+    }
+
+    #[test]
+    fn calibrate_reproduces_synthetic_samples() {
+        // a: 30 neurons, 200 synops; b: 15 neurons, 50 synops. True coefficients 2.0 / 0.5.
+        let graphs = [named(nc_nir::fixtures::chain(&[10, 20]), "a"), named(nc_nir::fixtures::chain(&[5, 5, 5]), "b")];
+        let path = std::env::temp_dir().join(format!("nc_mlopt_calib_{}.jsonl", std::process::id()));
+        let lines = [
+            r#"{"metric":"kernel.step_ns","value":160,"labels":{"graph":"a"}}"#,
+            r#"{"metric":"kernel.step_ns","value":999,"labels":{"graph":"a","population":"p0"}}"#,
+            r#"{"metric":"cpu.cycle","value":5,"labels":{"graph":"a"}}"#,
+            r#"{"metric":"kernel.step_ns","value":55,"labels":{"graph":"b"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let m = AnalyticalCostModel::calibrate(&path, &graphs).expect("calibrate");
+        assert!((m.ns_per_neuron - 2.0).abs() < 1e-6, "{m:?}");
+        assert!((m.ns_per_synop - 0.5).abs() < 1e-6, "{m:?}");
+        assert!((m.predict_latency_ms(&graphs[0]).unwrap() - 160e-6).abs() < 1e-9);
+
+        // One graph is not enough to separate two coefficients
+        let err = AnalyticalCostModel::calibrate(&path, &graphs[..1]).unwrap_err();
+        assert!(err.to_string().contains("at least 2 graphs"), "{err}");
+        let _ = std::fs::remove_file(&path);
+    }
+}
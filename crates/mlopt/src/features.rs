@@ -0,0 +1,80 @@
+//! Structural graph features for cost models.
+//! Wraps `nc_orchestrator::metrics` so models see one serializable vector per graph.
+
+use nc_orchestrator::metrics;
+use serde::{Deserialize, Serialize};
+
+/// Population-level structural features of a NIR graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureVector {
+    /// Total neurons (sum of population sizes).
+    pub neuron_count: u64,
+    /// Number of populations.
+    pub population_count: usize,
+    /// Number of connections (projections).
+    pub connection_count: usize,
+    /// Average fan-in over populations.
+    pub avg_fanin: f64,
+    /// Average fan-out over populations.
+    pub avg_fanout: f64,
+    /// Maximum fan-in across populations.
+    pub max_fanin: usize,
+    /// Maximum fan-out across populations.
+    pub max_fanout: usize,
+    /// Connections over possible directed population pairs (self-loops included); 0 when empty.
+    pub density: f64,
+    /// Largest population size.
+    pub max_population_size: u32,
+    /// Average local clustering coefficient (undirected).
+    pub clustering: f64,
+}
+
+/// Extract the feature vector for `g`.
+pub fn extract(g: &nc_nir::Graph) -> FeatureVector {
+    let m = metrics::compute_metrics(g);
+    let pairs = (m.node_count * m.node_count) as f64;
+    FeatureVector {
+        neuron_count: g.populations.iter().map(|p| p.size as u64).sum(),
+        population_count: m.node_count,
+        connection_count: m.edge_count,
+        avg_fanin: m.avg_fanin,
+        avg_fanout: m.avg_fanout,
+        max_fanin: m.max_fanin,
+        max_fanout: m.max_fanout,
+        density: if pairs > 0.0 { m.edge_count as f64 / pairs } else { 0.0 },
+        max_population_size: g.populations.iter().map(|p| p.size).max().unwrap_or(0),
+        clustering: m.clustering,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_features_match_metrics() {
+        let g = nc_nir::fixtures::chain(&[10, 20, 30]);
+        let m = metrics::compute_metrics(&g);
+        let f = extract(&g);
+        assert_eq!(f.neuron_count, 60);
+        assert_eq!(f.population_count, m.node_count);
+        assert_eq!(f.connection_count, m.edge_count);
+        assert_eq!(f.avg_fanin, m.avg_fanin);
+        assert_eq!(f.avg_fanout, m.avg_fanout);
+        assert_eq!(f.max_fanin, m.max_fanin);
+        assert_eq!(f.max_fanout, m.max_fanout);
+        assert!((f.density - 2.0 / 9.0).abs() < 1e-12);
+        assert_eq!(f.max_population_size, 30);
+
+        let back: FeatureVector = serde_json::from_str(&serde_json::to_string(&f).unwrap()).unwrap();
+        assert_eq!(back, f);
+    }
+
+    #[test]
+    fn empty_graph_features_are_zero() {
+        let f = extract(&nc_nir::Graph::new("empty"));
+        assert_eq!(f.neuron_count, 0);
+        assert_eq!(f.density, 0.0);
+        assert_eq!(f.max_population_size, 0);
+    }
+}
@@ -4,6 +4,11 @@ use std::collections::BTreeMap;
 #[cfg(feature = "telemetry")]
 use nc_telemetry as telemetry;
 
+pub mod analytical;
+pub mod features;
+
+pub use analytical::AnalyticalCostModel;
+
 pub fn version() -> &'static str { "0.0.1" }
 
 pub trait CostModel {
@@ -48,9 +53,23 @@ impl CostModel for NoOpCostModel {
     }
 }
 
+/// Cost model for `target`: the built-in `AnalyticalCostModel` coefficients for known target
+/// families (see `AnalyticalCostModel::for_target`), `NoOpCostModel` otherwise.
+pub fn cost_model_for(target: &str) -> Box<dyn CostModel> {
+    match AnalyticalCostModel::for_target(target) {
+        Some(m) => Box::new(m),
+        None => Box::new(NoOpCostModel),
+    }
+}
+
 pub trait MappingSearch {
     fn propose(&mut self, graph: &nc_nir::Graph) -> Result<String>;
     fn feedback(&mut self, score: f64);
+    /// Non-dominated `(mapping, latency, energy)` candidates seen so far (lower is better on
+    /// both axes). Single-objective searches return an empty front.
+    fn pareto_front(&self) -> Vec<(String, f64, f64)> {
+        Vec::new()
+    }
 }
 
 #[derive(Default)]
@@ -90,6 +109,60 @@ impl MappingSearch for GreedySearchStub {
     }
 }
 
+/// Multi-objective search: proposes candidate mappings round-robin and keeps the Pareto
+/// front of `(latency, energy)` results reported through `feedback2`.
+#[derive(Default)]
+pub struct ParetoSearch {
+    candidates: Vec<String>,
+    next: usize,
+    last_proposal: Option<String>,
+    front: Vec<(String, f64, f64)>,
+}
+
+impl ParetoSearch {
+    /// Search over `candidates`; an empty list proposes `"identity"`.
+    pub fn new(candidates: Vec<String>) -> Self {
+        Self { candidates, ..Self::default() }
+    }
+
+    /// Record latency and energy for the last proposed mapping. Points dominated by (or equal
+    /// to) an existing front entry are dropped; entries the new point dominates are removed.
+    pub fn feedback2(&mut self, latency: f64, energy: f64) {
+        let mapping = self.last_proposal.clone().unwrap_or_else(|| "identity".to_string());
+        if !latency.is_finite() || !energy.is_finite() {
+            return;
+        }
+        if self.front.iter().any(|(_, l, e)| *l <= latency && *e <= energy) {
+            return;
+        }
+        self.front.retain(|(_, l, e)| !(latency <= *l && energy <= *e));
+        self.front.push((mapping, latency, energy));
+    }
+}
+
+impl MappingSearch for ParetoSearch {
+    fn propose(&mut self, _graph: &nc_nir::Graph) -> Result<String> {
+        let mapping = if self.candidates.is_empty() {
+            "identity".to_string()
+        } else {
+            let m = self.candidates[self.next % self.candidates.len()].clone();
+            self.next += 1;
+            m
+        };
+        self.last_proposal = Some(mapping.clone());
+        Ok(mapping)
+    }
+    /// A scalar score counts as both latency and energy.
+    fn feedback(&mut self, score: f64) {
+        self.feedback2(score, score);
+    }
+    fn pareto_front(&self) -> Vec<(String, f64, f64)> {
+        let mut front = self.front.clone();
+        front.sort_by(|a, b| a.1.total_cmp(&b.1));
+        front
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,6 +174,20 @@ mod tests {
         assert_eq!(cm.predict_energy_mj(&g).unwrap(), 0.0);
     }
 
+    #[test]
+    fn cost_model_per_target() {
+        let rv = AnalyticalCostModel::for_target("riscv64gcv_linux").expect("riscv model");
+        let xbar = AnalyticalCostModel::for_target("memxbar").expect("crossbar model");
+        assert_ne!(rv, xbar);
+        assert!(rv.ns_per_synop > xbar.ns_per_synop);
+
+        let g = nc_nir::fixtures::chain(&[64, 64]);
+        let rv_ms = cost_model_for("riscv64gcv_linux").predict_latency_ms(&g).unwrap();
+        let xbar_ms = cost_model_for("memxbar").predict_latency_ms(&g).unwrap();
+        assert!(rv_ms > 0.0 && xbar_ms > 0.0 && rv_ms != xbar_ms);
+        assert_eq!(cost_model_for("no-such-target").predict_latency_ms(&g).unwrap(), 0.0);
+    }
+
     #[test]
     fn search_stub() {
         let mut s = GreedySearchStub::new();
@@ -109,4 +196,20 @@ mod tests {
         assert_eq!(p, "identity");
         s.feedback(1.23);
     }
+
+    #[test]
+    fn pareto_front_excludes_dominated() {
+        let g = nc_nir::Graph::new("g");
+        let names = ["a", "b", "c", "d"].map(String::from).to_vec();
+        let mut s = ParetoSearch::new(names);
+        for (lat, en) in [(10.0, 5.0), (8.0, 8.0), (12.0, 6.0), (9.0, 4.0)] {
+            s.propose(&g).unwrap();
+            s.feedback2(lat, en);
+        }
+        // c (12,6) is dominated by a (10,5); a is then dominated by d (9,4)
+        let front = s.pareto_front();
+        let names: Vec<&str> = front.iter().map(|(m, _, _)| m.as_str()).collect();
+        assert_eq!(names, vec!["b", "d"]);
+        assert!(GreedySearchStub::new().pareto_front().is_empty());
+    }
 }
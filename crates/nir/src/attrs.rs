@@ -0,0 +1,272 @@
+//! Typed access to well-known `Graph.attributes` entries. Passes and backends exchange
+//! metadata through attributes; each known key has a serde struct implementing `Attribute`,
+//! read with `Graph::attrs` and written with `Graph::set_attr`. Keys starting with `x-` are
+//! reserved for experiments (`Graph::experiment` / `set_experiment`) and never get a schema.
+
+use crate::Graph;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Prefix of attribute keys reserved for experimental, unschematized metadata.
+pub const EXPERIMENT_PREFIX: &str = "x-";
+
+/// A typed attribute stored under `KEY`.
+pub trait Attribute: Serialize + DeserializeOwned {
+    const KEY: &'static str;
+}
+
+/// Keys that have a typed schema, checked by `Graph::validate_all` (W001).
+pub const KNOWN_ATTRIBUTES: &[&str] = &[
+    PartitionMeta::KEY,
+    PlacementMeta::KEY,
+    RoutingMeta::KEY,
+    RvLayoutMeta::KEY,
+    RvScheduleMeta::KEY,
+    RvVectorizeMeta::KEY,
+    RvBareTuningMeta::KEY,
+    RvCtrlPlaneMeta::KEY,
+];
+
+/// Whether the value under a known `key` deserializes into its struct (`true` for other keys).
+pub(crate) fn matches_schema(key: &str, value: &serde_json::Value) -> bool {
+    fn ok<A: Attribute>(v: &serde_json::Value) -> bool {
+        A::deserialize(v).is_ok()
+    }
+    match key {
+        PartitionMeta::KEY => ok::<PartitionMeta>(value),
+        PlacementMeta::KEY => ok::<PlacementMeta>(value),
+        RoutingMeta::KEY => ok::<RoutingMeta>(value),
+        RvLayoutMeta::KEY => ok::<RvLayoutMeta>(value),
+        RvScheduleMeta::KEY => ok::<RvScheduleMeta>(value),
+        RvVectorizeMeta::KEY => ok::<RvVectorizeMeta>(value),
+        RvBareTuningMeta::KEY => ok::<RvBareTuningMeta>(value),
+        RvCtrlPlaneMeta::KEY => ok::<RvCtrlPlaneMeta>(value),
+        _ => true,
+    }
+}
+
+/// Population -> part entry of `PartitionMeta::assignment`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartAssignment {
+    pub population: String,
+    pub part: usize,
+}
+
+/// `partition`: written by the partition pass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PartitionMeta {
+    pub parts: usize,
+    pub strategy: String,
+    pub assignment: Vec<PartAssignment>,
+    pub violations: Vec<serde_json::Value>,
+    /// Synapses between populations in different parts.
+    pub cut_synapses: u64,
+}
+
+impl Default for PartitionMeta {
+    fn default() -> Self {
+        PartitionMeta { parts: 1, strategy: "naive".to_string(), assignment: Vec::new(), violations: Vec::new(), cut_synapses: 0 }
+    }
+}
+
+impl Attribute for PartitionMeta {
+    const KEY: &'static str = "partition";
+}
+
+impl PartitionMeta {
+    /// Population -> part; populations missing from `assignment` are not listed.
+    pub fn part_of(&self) -> HashMap<String, usize> {
+        self.assignment.iter().map(|a| (a.population.clone(), a.part)).collect()
+    }
+}
+
+/// `placement`: per-part resource estimate written by the placement pass.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlacementMeta {
+    pub status: String,
+    pub parts: usize,
+    pub neurons_per_part: Vec<usize>,
+    pub synapses_per_part: Vec<usize>,
+    pub violations: Vec<serde_json::Value>,
+    /// Mesh `[x, y]` of each part's core; empty when the target has no core mesh.
+    pub coords: Vec<[u32; 2]>,
+    /// Synapses times mesh hops between parts at `coords`.
+    pub hop_cost: u64,
+    /// The same cost with part `i` on core `i`, before annealing.
+    pub initial_hop_cost: u64,
+}
+
+impl Attribute for PlacementMeta {
+    const KEY: &'static str = "placement";
+}
+
+/// `routing`: inter-part traffic written by the routing pass.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoutingMeta {
+    pub status: String,
+    pub cross_edges: usize,
+    pub estimated_bandwidth_mbps: f64,
+    /// `matrix[i][j]`: connections from part `i` to part `j`.
+    pub matrix: Vec<Vec<usize>>,
+    /// Why routes were not computed (`MESH_TOO_SMALL` when parts outnumber cores); status
+    /// is then `violations`.
+    pub violations: Vec<serde_json::Value>,
+    /// `mesh`, `torus` or `tree` when routes were computed over the target interconnect.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology: Option<String>,
+    /// `xy` or `adaptive` (grid topologies).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+    /// Capacity of one directed link, when the target gives one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_capacity_mbps: Option<f64>,
+    pub routes: Vec<PartRoute>,
+    /// Every directed link carrying traffic.
+    pub links: Vec<LinkLoad>,
+    /// Links at or above `HOTSPOT_UTILIZATION`, busiest first.
+    pub hotspots: Vec<LinkLoad>,
+    pub max_link_utilization: f64,
+}
+
+/// Link utilization from which `RoutingMeta::hotspots` lists a link.
+pub const HOTSPOT_UTILIZATION: f64 = 0.8;
+
+/// Route of the traffic from one part's core to another's.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PartRoute {
+    pub from_part: usize,
+    pub to_part: usize,
+    pub synapses: u64,
+    /// Interconnect nodes from source to destination core, both included.
+    pub path: Vec<String>,
+}
+
+/// Traffic on one directed link between interconnect nodes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkLoad {
+    pub from: String,
+    pub to: String,
+    pub synapses: u64,
+    pub mbps: f64,
+    /// `mbps / link_capacity_mbps`; `0` without a capacity.
+    pub utilization: f64,
+}
+
+impl Attribute for RoutingMeta {
+    const KEY: &'static str = "routing";
+}
+
+/// `rv_layout`: RISC-V memory layout and default quantization.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RvLayoutMeta {
+    pub align_bytes: u64,
+    pub vector_available: bool,
+    pub vector_bytes: u64,
+    pub quant_bits_default: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// `rv64-linux` or `rv32-bare` when derived from the HAL manifest path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+impl Attribute for RvLayoutMeta {
+    const KEY: &'static str = "rv_layout";
+}
+
+/// `rv_schedule`: fused kernel stages and thread count.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RvScheduleMeta {
+    pub threads: u64,
+    pub fused_stages: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl Attribute for RvScheduleMeta {
+    const KEY: &'static str = "rv_schedule";
+}
+
+/// `rv_vectorize`: whether kernels use RVV and the vector length.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RvVectorizeMeta {
+    pub enabled: bool,
+    pub vlen_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl Attribute for RvVectorizeMeta {
+    const KEY: &'static str = "rv_vectorize";
+}
+
+/// `rv_bare_tuning`: code-size settings for bare-metal targets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RvBareTuningMeta {
+    pub size_optimized: bool,
+    pub use_compressed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl Attribute for RvBareTuningMeta {
+    const KEY: &'static str = "rv_bare_tuning";
+}
+
+/// `rv_ctrl_plane`: MMIO and DMA properties for the control-plane driver.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RvCtrlPlaneMeta {
+    pub mmio_supported: bool,
+    pub mmio_width_bits: Option<u64>,
+    pub dma_supported: bool,
+    pub dma_alignment: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl Attribute for RvCtrlPlaneMeta {
+    const KEY: &'static str = "rv_ctrl_plane";
+}
+
+impl Graph {
+    /// The attribute stored under `A::KEY`, or `None` when it is missing or does not match
+    /// the schema.
+    pub fn attrs<A: Attribute>(&self) -> Option<A> {
+        A::deserialize(self.attributes.get(A::KEY)?).ok()
+    }
+
+    /// Store `value` under `A::KEY`, replacing any previous value.
+    pub fn set_attr<A: Attribute>(&mut self, value: &A) {
+        let v = serde_json::to_value(value).unwrap_or(serde_json::Value::Null);
+        self.attributes.insert(A::KEY.to_string(), v);
+    }
+
+    /// Experimental attribute `x-<name>`.
+    pub fn experiment(&self, name: &str) -> Option<&serde_json::Value> {
+        self.attributes.get(&format!("{EXPERIMENT_PREFIX}{name}"))
+    }
+
+    /// Store an experimental attribute under `x-<name>`.
+    pub fn set_experiment(&mut self, name: &str, value: serde_json::Value) {
+        self.attributes.insert(format!("{EXPERIMENT_PREFIX}{name}"), value);
+    }
+}
@@ -0,0 +1,24 @@
+//! Serde adapter (`#[serde(with = "crate::binary")]`) for fields bincode cannot decode on its
+//! own: `serde_json::Value` and internally tagged enums both need `deserialize_any`.
+//! Human-readable formats (JSON, YAML) see the value unchanged; binary formats carry it as an
+//! embedded JSON string.
+
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub(crate) fn serialize<T: Serialize, S: Serializer>(value: &T, s: S) -> Result<S::Ok, S::Error> {
+    if s.is_human_readable() {
+        value.serialize(s)
+    } else {
+        serde_json::to_string(value).map_err(S::Error::custom)?.serialize(s)
+    }
+}
+
+pub(crate) fn deserialize<'de, T: DeserializeOwned, D: Deserializer<'de>>(d: D) -> Result<T, D::Error> {
+    if d.is_human_readable() {
+        T::deserialize(d)
+    } else {
+        serde_json::from_str(&String::deserialize(d)?).map_err(D::Error::custom)
+    }
+}
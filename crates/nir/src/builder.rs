@@ -0,0 +1,226 @@
+//! Fluent construction of validated graphs, e.g.
+//! `GraphBuilder::new("net").population("a", 100, Model::Lif(LifParams::default()))
+//! .population("b", 10, ...).connect("a", "b").weight(0.5).delay_ms(1.0).probe("b", "spikes").build()`.
+
+use crate::{Connection, Graph, PlasticityRule, Population, Probe, ValidationError};
+use serde::Serialize;
+
+/// Leaky integrate-and-fire parameters; defaults match the built-in `LIF` schema.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LifParams {
+    pub tau_m: f64,
+    pub v_th: f64,
+    pub v_reset: f64,
+    pub v_rest: f64,
+    pub t_ref: f64,
+}
+
+impl Default for LifParams {
+    fn default() -> Self {
+        Self { tau_m: 20.0, v_th: 1.0, v_reset: 0.0, v_rest: 0.0, t_ref: 0.0 }
+    }
+}
+
+/// Izhikevich parameters; defaults match the built-in `Izhikevich` schema (regular spiking).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IzhikevichParams {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub v_peak: f64,
+}
+
+impl Default for IzhikevichParams {
+    fn default() -> Self {
+        Self { a: 0.02, b: 0.2, c: -65.0, d: 8.0, v_peak: 30.0 }
+    }
+}
+
+/// Adaptive exponential integrate-and-fire parameters; defaults match the built-in `AdEx`
+/// schema.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdExParams {
+    pub c_m: f64,
+    pub g_l: f64,
+    pub e_l: f64,
+    pub v_t: f64,
+    pub delta_t: f64,
+    pub a: f64,
+    pub b: f64,
+    pub tau_w: f64,
+    pub v_reset: f64,
+    pub v_peak: f64,
+}
+
+impl Default for AdExParams {
+    fn default() -> Self {
+        Self {
+            c_m: 281.0,
+            g_l: 30.0,
+            e_l: -70.6,
+            v_t: -50.4,
+            delta_t: 2.0,
+            a: 4.0,
+            b: 80.5,
+            tau_w: 144.0,
+            v_reset: -70.6,
+            v_peak: 20.0,
+        }
+    }
+}
+
+/// Neuron model of a population added through `GraphBuilder::population`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Model {
+    Lif(LifParams),
+    Izhikevich(IzhikevichParams),
+    AdEx(AdExParams),
+    /// Any other model name with free-form params (a JSON object or null).
+    Custom { name: String, params: serde_json::Value },
+}
+
+impl Model {
+    /// `Population.model` string for this model.
+    pub fn name(&self) -> &str {
+        match self {
+            Model::Lif(_) => "LIF",
+            Model::Izhikevich(_) => "Izhikevich",
+            Model::AdEx(_) => "AdEx",
+            Model::Custom { name, .. } => name,
+        }
+    }
+
+    fn params(&self) -> serde_json::Value {
+        match self {
+            Model::Lif(p) => serde_json::to_value(p),
+            Model::Izhikevich(p) => serde_json::to_value(p),
+            Model::AdEx(p) => serde_json::to_value(p),
+            Model::Custom { params, .. } => Ok(params.clone()),
+        }
+        .unwrap_or_default()
+    }
+}
+
+/// What the last builder call added, for the modifiers that apply to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Last {
+    None,
+    Population,
+    Connection,
+}
+
+/// Builds a `Graph` one element at a time; `build` validates the result. Modifiers such as
+/// `weight` or `group` apply to the connection or population added just before them.
+#[derive(Debug, Clone)]
+pub struct GraphBuilder {
+    graph: Graph,
+    last: Last,
+    errors: Vec<String>,
+}
+
+impl GraphBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { graph: Graph::new(name), last: Last::None, errors: Vec::new() }
+    }
+
+    pub fn population(mut self, name: impl Into<String>, size: u32, model: Model) -> Self {
+        self.graph.populations.push(Population {
+            name: name.into(),
+            size,
+            model: model.name().to_string(),
+            params: model.params(),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
+        });
+        self.last = Last::Population;
+        self
+    }
+
+    /// Tag the last population with a region/group.
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        match (self.last, self.graph.populations.last_mut()) {
+            (Last::Population, Some(p)) => p.group = Some(group.into()),
+            _ => self.errors.push("group() must follow population()".to_string()),
+        }
+        self
+    }
+
+    /// Connect `pre` to `post` (weight and delay 0 until set).
+    pub fn connect(mut self, pre: impl Into<String>, post: impl Into<String>) -> Self {
+        self.graph.connections.push(Connection {
+            pre: pre.into(),
+            post: post.into(),
+            weight: 0.0,
+            delay_ms: 0.0,
+            plasticity: None,
+            weight_matrix: None,
+            weights_ref: None,
+        });
+        self.last = Last::Connection;
+        self
+    }
+
+    fn last_connection(&mut self, modifier: &str) -> Option<&mut Connection> {
+        match (self.last, self.graph.connections.last_mut()) {
+            (Last::Connection, Some(c)) => Some(c),
+            _ => {
+                self.errors.push(format!("{modifier}() must follow connect()"));
+                None
+            }
+        }
+    }
+
+    pub fn weight(mut self, weight: f32) -> Self {
+        if let Some(c) = self.last_connection("weight") {
+            c.weight = weight;
+        }
+        self
+    }
+
+    pub fn delay_ms(mut self, delay_ms: f32) -> Self {
+        if let Some(c) = self.last_connection("delay_ms") {
+            c.delay_ms = delay_ms;
+        }
+        self
+    }
+
+    /// Per-synapse weights for the last connection (see `Connection::weight_matrix`).
+    pub fn weight_matrix(mut self, matrix: Vec<Vec<f32>>) -> Self {
+        if let Some(c) = self.last_connection("weight_matrix") {
+            c.weight_matrix = Some(matrix);
+        }
+        self
+    }
+
+    pub fn plasticity(mut self, rule: PlasticityRule) -> Self {
+        if let Some(c) = self.last_connection("plasticity") {
+            c.plasticity = Some(rule);
+        }
+        self
+    }
+
+    pub fn probe(mut self, target: impl Into<String>, kind: impl Into<String>) -> Self {
+        self.graph.probes.push(Probe { target: target.into(), kind: kind.into(), ..Default::default() });
+        self.last = Last::None;
+        self
+    }
+
+    pub fn attribute(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.graph.attributes.insert(key.into(), value);
+        self.last = Last::None;
+        self
+    }
+
+    /// The graph, with its version tag, once it passes `Graph::validate`. Misplaced
+    /// modifiers are reported first.
+    pub fn build(mut self) -> Result<Graph, ValidationError> {
+        if let Some(e) = self.errors.into_iter().next() {
+            return Err(ValidationError(format!("builder: {e}")));
+        }
+        self.graph.validate()?;
+        self.graph.ensure_version_tag();
+        Ok(self.graph)
+    }
+}
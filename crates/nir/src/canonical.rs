@@ -0,0 +1,93 @@
+//! Canonical form of a graph (`Graph::canonicalize`) so dumps from different runs compare
+//! byte for byte in golden-file tests and caches.
+
+use crate::{Graph, SynapseMatrix};
+use serde_json::Value;
+
+/// Attributes that describe the run rather than the graph (host paths), dropped by
+/// `canonicalize` together with every key starting with `_`.
+pub const TRANSIENT_ATTRIBUTES: &[&str] = &["hal_manifest_path"];
+
+/// `-0.0` becomes `0.0`; everything else is kept.
+fn zero(x: &mut f32) {
+    if *x == 0.0 {
+        *x = 0.0;
+    }
+}
+
+/// Integral numbers become integers and other floats the shortest decimal of their f32 value
+/// (the precision `content_hash` uses), with object keys sorted, recursively.
+fn normalize(v: &mut Value) {
+    match v {
+        Value::Number(n) if n.is_f64() => {
+            let x = n.as_f64().unwrap_or(0.0);
+            if x.fract() == 0.0 && x.abs() < 9.0e15 {
+                *v = Value::from(x as i64);
+            } else if let Some(m) = format!("{}", x as f32).parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+                *n = m;
+            }
+        }
+        Value::Array(a) => a.iter_mut().for_each(normalize),
+        Value::Object(m) => {
+            let mut entries: Vec<(String, Value)> = std::mem::take(m).into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            for (k, mut x) in entries {
+                normalize(&mut x);
+                m.insert(k, x);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Graph {
+    /// Rewrite the graph into its canonical form:
+    /// - populations, inputs, modules and instances sorted by name; connections by
+    ///   `(pre, post, delay_ms, weight)`, projections and connectivity rules by `(pre, post)`,
+    ///   probes by `(target, kind)` (ties keep their order)
+    /// - `-0.0` weights and delays written as `0.0`; numbers in params (population, compartment
+    ///   and plasticity) and attributes normalized (see `normalize`) and their object keys sorted
+    /// - attribute keys sorted, dropping `TRANSIENT_ATTRIBUTES` and `_`-prefixed keys
+    ///
+    /// Canonicalizing twice changes nothing, and graphs with equal `content_hash` (ignoring
+    /// transient attributes) serialize identically afterwards.
+    pub fn canonicalize(&mut self) {
+        self.populations.sort_by(|a, b| a.name.cmp(&b.name));
+        for p in &mut self.populations {
+            normalize(&mut p.params);
+            p.compartments.iter_mut().for_each(|c| normalize(&mut c.params));
+        }
+        for c in &mut self.connections {
+            zero(&mut c.weight);
+            zero(&mut c.delay_ms);
+            c.weight_matrix.iter_mut().flatten().flatten().for_each(zero);
+            if let Some(r) = &mut c.plasticity {
+                normalize(&mut r.params);
+            }
+        }
+        self.connections.sort_by(|a, b| {
+            (&a.pre, &a.post)
+                .cmp(&(&b.pre, &b.post))
+                .then(a.delay_ms.total_cmp(&b.delay_ms))
+                .then(a.weight.total_cmp(&b.weight))
+        });
+        for pj in &mut self.projections {
+            zero(&mut pj.delay_ms);
+            let (SynapseMatrix::Dense { weights, delays_ms } | SynapseMatrix::Csr { weights, delays_ms, .. }) = &mut pj.synapses;
+            weights.iter_mut().chain(delays_ms.iter_mut().flatten()).for_each(zero);
+            if let Some(r) = &mut pj.plasticity {
+                normalize(&mut r.params);
+            }
+        }
+        self.projections.sort_by(|a, b| (&a.pre, &a.post).cmp(&(&b.pre, &b.post)));
+        self.connectivity.sort_by(|a, b| (&a.pre, &a.post).cmp(&(&b.pre, &b.post)));
+        self.inputs.sort_by(|a, b| a.name.cmp(&b.name));
+        self.probes.sort_by(|a, b| (&a.target, &a.kind).cmp(&(&b.target, &b.kind)));
+        self.modules.sort_by(|a, b| a.name.cmp(&b.name));
+        self.instances.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.attributes.retain(|k, _| !k.starts_with('_') && !TRANSIENT_ATTRIBUTES.contains(&k.as_str()));
+        self.attributes.sort_keys();
+        self.attributes.values_mut().for_each(normalize);
+    }
+}
@@ -0,0 +1,133 @@
+//! Morphologically detailed neurons: a population's neurons may be described as a tree of
+//! compartments (soma at the root, dendrites and axon below it) with per-compartment params.
+//! Simulator emitters (Arbor, NEURON) use the tree; digital backends expect point neurons and
+//! get them from `Population::point_params` via the `collapse-compartments` pass.
+
+use crate::{Graph, Population};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Role of a compartment in the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompartmentKind {
+    Soma,
+    Dendrite,
+    Axon,
+}
+
+/// One section of a multi-compartment neuron.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Compartment {
+    pub name: String,
+    pub kind: CompartmentKind,
+    /// Compartment this one attaches to; `None` only for the soma at the root.
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub length_um: Option<f64>,
+    #[serde(default)]
+    pub diameter_um: Option<f64>,
+    /// Model params of this compartment, overriding the population's params.
+    #[serde(default, with = "crate::binary")]
+    pub params: serde_json::Value,
+}
+
+impl Compartment {
+    /// Lateral surface area in um^2, when length and diameter are both given.
+    pub fn area_um2(&self) -> Option<f64> {
+        Some(std::f64::consts::PI * self.diameter_um? * self.length_um?)
+    }
+}
+
+/// Check that `compartments` form one tree rooted at a single soma: unique non-empty names,
+/// known parents, no cycles, positive finite lengths and diameters.
+pub(crate) fn check_tree(compartments: &[Compartment]) -> Result<(), String> {
+    let mut names: HashSet<&str> = HashSet::new();
+    for c in compartments {
+        if c.name.trim().is_empty() || !names.insert(c.name.as_str()) {
+            return Err(format!("compartment name '{}' is empty or duplicated", c.name));
+        }
+        for (what, v) in [("length_um", c.length_um), ("diameter_um", c.diameter_um)] {
+            if v.is_some_and(|v| !(v.is_finite() && v > 0.0)) {
+                return Err(format!("compartment '{}' {what} must be > 0", c.name));
+            }
+        }
+    }
+    let roots: Vec<&Compartment> = compartments.iter().filter(|c| c.parent.is_none()).collect();
+    match roots.as_slice() {
+        [root] if root.kind == CompartmentKind::Soma => {}
+        [root] => return Err(format!("root compartment '{}' must be a soma", root.name)),
+        _ => return Err(format!("expected exactly one root compartment (no parent), found {}", roots.len())),
+    }
+    let parent: HashMap<&str, &str> =
+        compartments.iter().filter_map(|c| Some((c.name.as_str(), c.parent.as_deref()?))).collect();
+    for c in compartments {
+        let mut at = c.name.as_str();
+        for _ in 0..compartments.len() {
+            match parent.get(at) {
+                None => break,
+                Some(p) if !names.contains(p) => {
+                    return Err(format!("compartment '{at}' parent '{p}' not found"));
+                }
+                Some(p) => at = p,
+            }
+        }
+        if parent.contains_key(at) {
+            return Err(format!("compartment '{}' is part of a cycle", c.name));
+        }
+    }
+    Ok(())
+}
+
+impl Population {
+    /// Params of the equivalent point neuron. Without compartments this is `params`. With
+    /// them, every numeric param the soma sets becomes the mean over the compartments that set
+    /// it, weighted by surface area when all of those compartments have a length and diameter
+    /// (equal weights otherwise); other soma params are copied, and params set only on
+    /// non-soma compartments are dropped. Population params the soma leaves unset are kept.
+    pub fn point_params(&self) -> serde_json::Value {
+        let Some(soma) = self.compartments.iter().find(|c| c.parent.is_none()) else {
+            return self.params.clone();
+        };
+        let mut out = match &self.params {
+            serde_json::Value::Object(m) => m.clone(),
+            _ => serde_json::Map::new(),
+        };
+        for (key, value) in soma.params.as_object().into_iter().flatten() {
+            let merged = value.as_f64().and_then(|_| {
+                let setting: Vec<(f64, Option<f64>)> = self
+                    .compartments
+                    .iter()
+                    .filter_map(|c| Some((c.params.get(key)?.as_f64()?, c.area_um2())))
+                    .collect();
+                let by_area = setting.iter().all(|(_, a)| a.is_some());
+                let weight = |a: Option<f64>| if by_area { a.unwrap_or(0.0) } else { 1.0 };
+                let total: f64 = setting.iter().map(|&(_, a)| weight(a)).sum();
+                let mean = setting.iter().map(|&(v, a)| v * weight(a)).sum::<f64>() / total;
+                serde_json::Number::from_f64(mean).map(serde_json::Value::Number)
+            });
+            out.insert(key.clone(), merged.unwrap_or_else(|| value.clone()));
+        }
+        serde_json::Value::Object(out)
+    }
+}
+
+impl Graph {
+    /// Write `dir/morphology.json`: `[{population, compartments}]` for every population with a
+    /// compartment tree, as the Arbor and NEURON emitters consume it. Nothing is written when
+    /// all populations are point neurons.
+    pub fn write_morphology(&self, dir: &Path) -> std::io::Result<()> {
+        let morphologies: Vec<serde_json::Value> = self
+            .populations
+            .iter()
+            .filter(|p| !p.compartments.is_empty())
+            .map(|p| serde_json::json!({ "population": p.name, "compartments": p.compartments }))
+            .collect();
+        if morphologies.is_empty() {
+            return Ok(());
+        }
+        std::fs::write(dir.join("morphology.json"), serde_json::to_string_pretty(&morphologies)?)
+    }
+}
@@ -0,0 +1,102 @@
+//! Connectivity rules: compact descriptions of how two populations are wired, stored in NIR
+//! and expanded into explicit `Projection`s by `nc_passes::ExpandConnectivityPass`.
+
+use crate::{DelayDistribution, PlasticityRule};
+use serde::{Deserialize, Serialize};
+
+/// How synapses between `pre` and `post` neurons are drawn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConnectivityRule {
+    /// Every pre neuron to every post neuron.
+    AllToAll,
+    /// Each pre/post pair independently with probability `p`.
+    FixedProbability { p: f64 },
+    /// Each post neuron receives exactly `k` distinct pre neurons.
+    FixedInDegree { k: u32 },
+    /// Each pair with probability `p_max * exp(-d^2 / (2 sigma^2))`, `d` the distance between
+    /// the neurons' positions (see `Population::neuron_position`).
+    GaussianDistance {
+        sigma: f64,
+        #[serde(default = "default_p_max")]
+        p_max: f64,
+    },
+}
+
+fn default_p_max() -> f64 {
+    1.0
+}
+
+impl ConnectivityRule {
+    /// Short rule name (`all_to_all`, `fixed_probability`, ...), as in the serialized `kind`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConnectivityRule::AllToAll => "all_to_all",
+            ConnectivityRule::FixedProbability { .. } => "fixed_probability",
+            ConnectivityRule::FixedInDegree { .. } => "fixed_in_degree",
+            ConnectivityRule::GaussianDistance { .. } => "gaussian_distance",
+        }
+    }
+}
+
+/// A connectivity rule between two populations with the synapse values it produces.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectivitySpec {
+    pub pre: String,
+    pub post: String,
+    #[serde(with = "crate::binary")]
+    pub rule: ConnectivityRule,
+    #[serde(default)]
+    pub weight: f32,
+    #[serde(default)]
+    pub delay_ms: f32,
+    /// Seed for the random rules; the same seed always yields the same synapses.
+    #[serde(default)]
+    pub seed: u64,
+    /// Allow neuron `i` to connect to itself when `pre == post`.
+    #[serde(default)]
+    pub autapses: bool,
+    #[serde(default)]
+    pub plasticity: Option<PlasticityRule>,
+    /// Per-synapse delays for the expanded projection (drawn with `seed`), replacing `delay_ms`.
+    #[serde(default, with = "crate::binary")]
+    pub delay_distribution: Option<DelayDistribution>,
+}
+
+impl ConnectivitySpec {
+    /// Check rule parameters against the endpoint sizes.
+    pub(crate) fn check(&self, pre_size: u32) -> Result<(), String> {
+        if !self.weight.is_finite() {
+            return Err("non-finite weight".to_string());
+        }
+        if !self.delay_ms.is_finite() || self.delay_ms < 0.0 {
+            return Err(format!("invalid delay_ms {}", self.delay_ms));
+        }
+        if let Some(dist) = &self.delay_distribution {
+            dist.check(None)?;
+        }
+        match self.rule {
+            ConnectivityRule::AllToAll => {}
+            ConnectivityRule::FixedProbability { p } => {
+                if !(0.0..=1.0).contains(&p) {
+                    return Err(format!("probability {p} outside [0, 1]"));
+                }
+            }
+            ConnectivityRule::FixedInDegree { k } => {
+                let available = if self.pre == self.post && !self.autapses { pre_size.saturating_sub(1) } else { pre_size };
+                if k > available {
+                    return Err(format!("in-degree {k} exceeds the {available} available pre neurons"));
+                }
+            }
+            ConnectivityRule::GaussianDistance { sigma, p_max } => {
+                if !(sigma.is_finite() && sigma > 0.0) {
+                    return Err(format!("sigma {sigma} must be > 0"));
+                }
+                if !(p_max > 0.0 && p_max <= 1.0) {
+                    return Err(format!("p_max {p_max} outside (0, 1]"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+//! Heterogeneous synaptic delays: a projection (or connectivity rule) may give a delay
+//! distribution instead of one `delay_ms` or per-synapse `delays_ms`. Synapse `k` always draws
+//! the same delay for a given seed, so `Projection::iter_synapses` and
+//! `Graph::materialize_delays` agree without storing the samples.
+
+use crate::{Graph, SynapseMatrix, ValidationError};
+use serde::{Deserialize, Serialize};
+
+/// Distribution of per-synapse delays in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DelayDistribution {
+    /// Uniform on `[low_ms, high_ms]`.
+    Uniform { low_ms: f32, high_ms: f32 },
+    /// Normal with `mean_ms` and `std_ms`, clipped to `[min_ms, max_ms]`.
+    Normal {
+        mean_ms: f32,
+        std_ms: f32,
+        #[serde(default)]
+        min_ms: f32,
+        #[serde(default)]
+        max_ms: Option<f32>,
+    },
+}
+
+/// Mixed into the seed so delay draws stay independent of the connectivity draws
+/// `expand-connectivity` makes from the same rule seed.
+const DELAY_STREAM: u64 = 0xD1B5_4A32_D192_ED03;
+
+/// Uniform sample in `[0, 1)` for draw `idx` of `seed` (SplitMix64 finalizer).
+fn unit_sample(seed: u64, idx: u64) -> f64 {
+    let mut z = (seed ^ DELAY_STREAM) ^ idx.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl DelayDistribution {
+    /// Short name (`uniform`, `normal`), as in the serialized `kind`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DelayDistribution::Uniform { .. } => "uniform",
+            DelayDistribution::Normal { .. } => "normal",
+        }
+    }
+
+    /// Delay of synapse `k` for `seed`. Normal draws use Box-Muller on two uniform draws.
+    pub fn sample(&self, seed: u64, k: usize) -> f32 {
+        let (a, b) = (unit_sample(seed, 2 * k as u64), unit_sample(seed, 2 * k as u64 + 1));
+        match *self {
+            DelayDistribution::Uniform { low_ms, high_ms } => low_ms + (high_ms - low_ms) * a as f32,
+            DelayDistribution::Normal { mean_ms, std_ms, min_ms, max_ms } => {
+                let z = (-2.0 * (1.0 - a).ln()).sqrt() * (std::f64::consts::TAU * b).cos();
+                let d = (mean_ms as f64 + std_ms as f64 * z) as f32;
+                d.max(min_ms).min(max_ms.unwrap_or(f32::INFINITY))
+            }
+        }
+    }
+
+    /// Check parameters: finite, non-negative bounds in order, and the upper bound at most
+    /// `max_delay_ms` when a limit is given (a normal distribution then needs `max_ms`).
+    pub(crate) fn check(&self, max_delay_ms: Option<f32>) -> Result<(), String> {
+        let (low, high) = match *self {
+            DelayDistribution::Uniform { low_ms, high_ms } => (low_ms, Some(high_ms)),
+            DelayDistribution::Normal { mean_ms, std_ms, min_ms, max_ms } => {
+                if !(mean_ms.is_finite() && std_ms.is_finite() && std_ms >= 0.0) {
+                    return Err(format!("normal delay mean {mean_ms} / std {std_ms} must be finite with std >= 0"));
+                }
+                (min_ms, max_ms)
+            }
+        };
+        let high_ok = match high {
+            Some(h) => h.is_finite() && h >= low,
+            None => true,
+        };
+        if !(low.is_finite() && low >= 0.0 && high_ok) {
+            return Err(format!("{} delay bounds [{low}, {}] must be finite, >= 0 and ordered", self.kind(), high.unwrap_or(f32::INFINITY)));
+        }
+        if let Some(max) = max_delay_ms {
+            match high {
+                Some(h) if h > max => return Err(format!("{} delay upper bound {h} exceeds limit {max}", self.kind())),
+                None => return Err(format!("normal delay needs max_ms to respect limit {max}")),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the spread (`high_ms - low_ms`, or `std_ms`) is representable at
+    /// `time_resolution_ns`: a non-zero spread below one tick would give every synapse the
+    /// same delay once rounded.
+    pub fn check_resolution(&self, time_resolution_ns: u64) -> Result<(), String> {
+        let spread = match *self {
+            DelayDistribution::Uniform { low_ms, high_ms } => high_ms - low_ms,
+            DelayDistribution::Normal { std_ms, .. } => std_ms,
+        };
+        if spread > 0.0 && (spread as f64) * 1_000_000.0 < time_resolution_ns as f64 {
+            return Err(format!(
+                "{} delay spread {spread} ms is below one tick ({time_resolution_ns} ns)",
+                self.kind()
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Draw the delays of every projection with a `delay_distribution` into per-synapse
+    /// `delays_ms` and clear the distribution. With `time_resolution_ns`, each distribution
+    /// must pass `check_resolution` and every delay is rounded to the nearest tick. Returns the
+    /// number of projections materialized.
+    pub fn materialize_delays(&mut self, time_resolution_ns: Option<u64>) -> Result<usize, ValidationError> {
+        if let Some(res) = time_resolution_ns {
+            for pj in &self.projections {
+                let Some(dist) = &pj.delay_distribution else { continue };
+                dist.check_resolution(res).map_err(|e| ValidationError(format!("projection {}->{}: {e}", pj.pre, pj.post)))?;
+            }
+        }
+        let mut n = 0;
+        for pj in &mut self.projections {
+            let Some(dist) = pj.delay_distribution else { continue };
+            let tick_ms = time_resolution_ns.map(|r| r.max(1) as f64 / 1_000_000.0);
+            let delays: Vec<f32> = (0..pj.nnz())
+                .map(|k| {
+                    let d = dist.sample(pj.delay_seed, k);
+                    tick_ms.map_or(d, |t| ((d as f64 / t).round() * t) as f32)
+                })
+                .collect();
+            let (SynapseMatrix::Dense { delays_ms, .. } | SynapseMatrix::Csr { delays_ms, .. }) = &mut pj.synapses;
+            *delays_ms = Some(delays);
+            pj.delay_distribution = None;
+            n += 1;
+        }
+        Ok(n)
+    }
+}
@@ -0,0 +1,73 @@
+//! Structured validation results (`Graph::validate_all`): every problem found in a graph,
+//! each with a stable code, a severity and the JSON-pointer path of the offending entity.
+
+use serde::{Deserialize, Serialize};
+
+/// How serious a diagnostic is. Only errors make `Graph::validate` fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// One validation finding. `code` is stable across releases (`E1xx` populations, `E2xx`
+/// connections, `E3xx` projections, `E4xx` connectivity rules, `E5xx` inputs, `E6xx` probes,
+/// `E0xx` whole-graph problems, `W` for warnings); `path` points into the (flattened)
+/// document, e.g. `/connections/3/weight_matrix`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: String,
+    pub severity: Severity,
+    pub path: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(code: &str, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic { code: code.to_string(), severity: Severity::Error, path: path.into(), message: message.into() }
+    }
+
+    pub fn warning(code: &str, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic { code: code.to_string(), severity: Severity::Warning, path: path.into(), message: message.into() }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} at {}: {}", self.severity, self.code, self.path, self.message)
+    }
+}
+
+/// Plain-text table of `diagnostics` with aligned `severity code path message` columns, one
+/// row per diagnostic under a header line.
+pub fn render_table(diagnostics: &[Diagnostic]) -> String {
+    let header = ["SEVERITY", "CODE", "PATH", "MESSAGE"];
+    let rows: Vec<[String; 4]> = diagnostics
+        .iter()
+        .map(|d| [d.severity.to_string(), d.code.clone(), d.path.clone(), d.message.clone()])
+        .collect();
+    let mut widths = header.map(str::len);
+    for r in &rows {
+        for (w, cell) in widths.iter_mut().zip(r) {
+            *w = (*w).max(cell.len());
+        }
+    }
+    let mut out = String::new();
+    let header = header.map(str::to_string);
+    for r in std::iter::once(&header).chain(&rows) {
+        let line = format!("{:<w0$}  {:<w1$}  {:<w2$}  {}", r[0], r[1], r[2], r[3], w0 = widths[0], w1 = widths[1], w2 = widths[2]);
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,127 @@
+//! Structural comparison of two graphs (`Graph::diff`), e.g. to review what a pass pipeline
+//! changed or to audit a model update.
+
+use crate::Graph;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Value of an entry present in both graphs that differs between them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub before: Value,
+    pub after: Value,
+}
+
+/// Differences within one kind of graph element, keyed as described on `GraphDiff`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ItemDiff {
+    pub added: BTreeMap<String, Value>,
+    pub removed: BTreeMap<String, Value>,
+    pub changed: BTreeMap<String, Change>,
+}
+
+impl ItemDiff {
+    fn between(before: BTreeMap<String, Value>, mut after: BTreeMap<String, Value>) -> Self {
+        let mut d = ItemDiff::default();
+        for (k, old) in before {
+            match after.remove(&k) {
+                None => {
+                    d.removed.insert(k, old);
+                }
+                Some(new) if new != old => {
+                    d.changed.insert(k, Change { before: old, after: new });
+                }
+                Some(_) => {}
+            }
+        }
+        d.added = after;
+        d
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Result of `Graph::diff`. Populations, inputs, modules and instances are keyed by name,
+/// connections, projections and connectivity rules by `pre -> post` (repeated pairs get a `#n`
+/// suffix in declaration order), probes by `target/kind` and attributes by key. Values are the
+/// elements' JSON form.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphDiff {
+    pub populations: ItemDiff,
+    pub connections: ItemDiff,
+    pub probes: ItemDiff,
+    pub projections: ItemDiff,
+    pub connectivity: ItemDiff,
+    pub inputs: ItemDiff,
+    pub modules: ItemDiff,
+    pub instances: ItemDiff,
+    pub attributes: ItemDiff,
+}
+
+impl GraphDiff {
+    /// Each kind of element with its diff, in declaration order.
+    pub fn sections(&self) -> [(&'static str, &ItemDiff); 9] {
+        [
+            ("population", &self.populations),
+            ("connection", &self.connections),
+            ("probe", &self.probes),
+            ("projection", &self.projections),
+            ("connectivity", &self.connectivity),
+            ("input", &self.inputs),
+            ("module", &self.modules),
+            ("instance", &self.instances),
+            ("attribute", &self.attributes),
+        ]
+    }
+
+    /// True when the compared graphs are structurally identical.
+    pub fn is_empty(&self) -> bool {
+        self.sections().iter().all(|(_, d)| d.is_empty())
+    }
+}
+
+fn to_value<T: Serialize>(x: &T) -> Value {
+    serde_json::to_value(x).unwrap_or(Value::Null)
+}
+
+fn keyed<T: Serialize>(items: &[T], key: impl Fn(&T) -> String) -> BTreeMap<String, Value> {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    let mut out = BTreeMap::new();
+    for item in items {
+        let base = key(item);
+        let n = seen.entry(base.clone()).or_insert(0);
+        let k = if *n == 0 { base } else { format!("{base}#{n}") };
+        *n += 1;
+        out.insert(k, to_value(item));
+    }
+    out
+}
+
+impl Graph {
+    /// What changed going from `self` to `other`.
+    pub fn diff(&self, other: &Graph) -> GraphDiff {
+        let pops = |g: &Graph| keyed(&g.populations, |p| p.name.clone());
+        let conns = |g: &Graph| keyed(&g.connections, |c| format!("{} -> {}", c.pre, c.post));
+        let probes = |g: &Graph| keyed(&g.probes, |p| format!("{}/{}", p.target, p.kind));
+        let projections = |g: &Graph| keyed(&g.projections, |p| format!("{} -> {}", p.pre, p.post));
+        let connectivity = |g: &Graph| keyed(&g.connectivity, |c| format!("{} -> {}", c.pre, c.post));
+        let inputs = |g: &Graph| keyed(&g.inputs, |i| i.name.clone());
+        let modules = |g: &Graph| keyed(&g.modules, |m| m.name.clone());
+        let instances = |g: &Graph| keyed(&g.instances, |i| i.name.clone());
+        let attrs = |g: &Graph| g.attributes.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        GraphDiff {
+            populations: ItemDiff::between(pops(self), pops(other)),
+            connections: ItemDiff::between(conns(self), conns(other)),
+            probes: ItemDiff::between(probes(self), probes(other)),
+            projections: ItemDiff::between(projections(self), projections(other)),
+            connectivity: ItemDiff::between(connectivity(self), connectivity(other)),
+            inputs: ItemDiff::between(inputs(self), inputs(other)),
+            modules: ItemDiff::between(modules(self), modules(other)),
+            instances: ItemDiff::between(instances(self), instances(other)),
+            attributes: ItemDiff::between(attrs(self), attrs(other)),
+        }
+    }
+}
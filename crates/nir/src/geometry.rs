@@ -0,0 +1,136 @@
+//! Spatial layout of a population's neurons, used by distance-dependent connectivity and
+//! placement heuristics through `Population::neuron_position`.
+
+use crate::Population;
+use serde::{Deserialize, Serialize};
+
+/// Where the neurons of a population sit. Coordinates are 3D; 2D layouts use z = 0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "layout", rename_all = "snake_case")]
+pub enum Geometry {
+    /// One `[x, y]` or `[x, y, z]` point per neuron.
+    Explicit { positions: Vec<Vec<f64>> },
+    /// Regular grid of `shape` (`[nx, ny]` or `[nx, ny, nz]`, x fastest) with `spacing` between
+    /// neighbours, starting at `origin`.
+    Grid {
+        shape: Vec<u32>,
+        #[serde(default = "default_spacing")]
+        spacing: f64,
+        #[serde(default)]
+        origin: [f64; 3],
+    },
+    /// Evenly spaced on a circle of `radius` around `center`, in the xy plane.
+    Ring {
+        radius: f64,
+        #[serde(default)]
+        center: [f64; 3],
+    },
+    /// Uniformly random inside the box `[min, max]`, reproducible from `seed`.
+    RandomInBox {
+        min: [f64; 3],
+        max: [f64; 3],
+        #[serde(default)]
+        seed: u64,
+    },
+}
+
+fn default_spacing() -> f64 {
+    1.0
+}
+
+/// Uniform sample in [0, 1) from (seed, index) via splitmix64.
+fn unit(seed: u64, idx: u64) -> f64 {
+    let mut z = seed ^ idx.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl Geometry {
+    /// Short layout name, as in the serialized `layout`.
+    pub fn layout(&self) -> &'static str {
+        match self {
+            Geometry::Explicit { .. } => "explicit",
+            Geometry::Grid { .. } => "grid",
+            Geometry::Ring { .. } => "ring",
+            Geometry::RandomInBox { .. } => "random_in_box",
+        }
+    }
+
+    /// Position of neuron `i` of a population of `size` neurons (assumes a checked geometry).
+    pub fn position(&self, i: u32, size: u32) -> [f64; 3] {
+        match self {
+            Geometry::Explicit { positions } => {
+                let p = &positions[i as usize];
+                [p[0], p[1], p.get(2).copied().unwrap_or(0.0)]
+            }
+            Geometry::Grid { shape, spacing, origin } => {
+                let nx = shape[0].max(1);
+                let ny = shape[1].max(1);
+                let (x, y, z) = (i % nx, (i / nx) % ny, i / (nx * ny));
+                [origin[0] + x as f64 * spacing, origin[1] + y as f64 * spacing, origin[2] + z as f64 * spacing]
+            }
+            Geometry::Ring { radius, center } => {
+                let angle = std::f64::consts::TAU * i as f64 / size.max(1) as f64;
+                [center[0] + radius * angle.cos(), center[1] + radius * angle.sin(), center[2]]
+            }
+            Geometry::RandomInBox { min, max, seed } => {
+                let mut p = [0.0; 3];
+                for (axis, v) in p.iter_mut().enumerate() {
+                    *v = min[axis] + (max[axis] - min[axis]) * unit(*seed, 3 * i as u64 + axis as u64);
+                }
+                p
+            }
+        }
+    }
+
+    /// Check the layout against the population size.
+    pub(crate) fn check(&self, size: u32) -> Result<(), String> {
+        let finite = |xs: &[f64]| xs.iter().all(|x| x.is_finite());
+        match self {
+            Geometry::Explicit { positions } => {
+                if positions.len() != size as usize {
+                    return Err(format!("{} positions for {size} neurons", positions.len()));
+                }
+                if let Some(i) = positions.iter().position(|p| !(p.len() == 2 || p.len() == 3) || !finite(p)) {
+                    return Err(format!("position {i} must be 2 or 3 finite coordinates"));
+                }
+            }
+            Geometry::Grid { shape, spacing, origin } => {
+                if !(shape.len() == 2 || shape.len() == 3) {
+                    return Err(format!("grid shape must have 2 or 3 dimensions, got {}", shape.len()));
+                }
+                let cells: u64 = shape.iter().map(|&d| d as u64).product();
+                if cells != size as u64 {
+                    return Err(format!("grid shape {shape:?} holds {cells} neurons, population has {size}"));
+                }
+                if !(spacing.is_finite() && *spacing > 0.0 && finite(origin)) {
+                    return Err(format!("grid spacing {spacing} must be > 0 with a finite origin"));
+                }
+            }
+            Geometry::Ring { radius, center } => {
+                if !(radius.is_finite() && *radius >= 0.0 && finite(center)) {
+                    return Err(format!("ring radius {radius} must be >= 0 with a finite center"));
+                }
+            }
+            Geometry::RandomInBox { min, max, .. } => {
+                if !finite(min) || !finite(max) || min.iter().zip(max).any(|(lo, hi)| lo > hi) {
+                    return Err(format!("box min {min:?} must be finite and <= max {max:?}"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Population {
+    /// Position of neuron `i` used by distance-based rules and placement: from `geometry` when
+    /// set, otherwise on the x axis at unit spacing (`[i, 0, 0]`).
+    pub fn neuron_position(&self, i: u32) -> [f64; 3] {
+        match &self.geometry {
+            Some(g) => g.position(i, self.size),
+            None => [i as f64, 0.0, 0.0],
+        }
+    }
+}
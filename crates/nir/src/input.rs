@@ -0,0 +1,76 @@
+//! Stimulus nodes: what drives a population from outside the network.
+
+use serde::{Deserialize, Serialize};
+
+/// Where an input's spikes come from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InputSource {
+    /// Independent Poisson spike trains at `rate_hz` per target neuron.
+    Poisson { rate_hz: f64 },
+    /// Every target neuron spikes each `period_ms`, first at `phase_ms`.
+    Regular {
+        period_ms: f64,
+        #[serde(default)]
+        phase_ms: f64,
+    },
+    /// Recorded spikes in a file (`neuron,time_ms` rows), resolved by the runtime.
+    SpikeFile { path: String },
+    /// Live address-event (DVS/AER) stream, e.g. `tcp://host:port` or a device path.
+    AerStream {
+        uri: String,
+        #[serde(default)]
+        width: Option<u32>,
+        #[serde(default)]
+        height: Option<u32>,
+    },
+}
+
+impl InputSource {
+    /// Serialized `kind` (`poisson`, `regular`, `spike_file`, `aer_stream`).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InputSource::Poisson { .. } => "poisson",
+            InputSource::Regular { .. } => "regular",
+            InputSource::SpikeFile { .. } => "spike_file",
+            InputSource::AerStream { .. } => "aer_stream",
+        }
+    }
+
+    /// Mean spike rate per neuron, when the source defines one.
+    pub fn rate_hz(&self) -> Option<f64> {
+        match self {
+            InputSource::Poisson { rate_hz } => Some(*rate_hz),
+            InputSource::Regular { period_ms, .. } => Some(1000.0 / period_ms),
+            InputSource::SpikeFile { .. } | InputSource::AerStream { .. } => None,
+        }
+    }
+
+    pub(crate) fn check(&self) -> Result<(), String> {
+        match self {
+            InputSource::Poisson { rate_hz } if !(rate_hz.is_finite() && *rate_hz >= 0.0) => {
+                Err(format!("rate_hz {rate_hz} must be finite and >= 0"))
+            }
+            InputSource::Regular { period_ms, phase_ms }
+                if !(period_ms.is_finite() && *period_ms > 0.0 && phase_ms.is_finite() && *phase_ms >= 0.0) =>
+            {
+                Err(format!("period_ms {period_ms} must be > 0 and phase_ms {phase_ms} >= 0"))
+            }
+            InputSource::SpikeFile { path } if path.trim().is_empty() => Err("spike_file path is empty".to_string()),
+            InputSource::AerStream { uri, .. } if uri.trim().is_empty() => Err("aer_stream uri is empty".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Input node feeding population `target`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Input {
+    pub name: String,
+    pub target: String,
+    #[serde(with = "crate::binary")]
+    pub source: InputSource,
+    /// Seed for stochastic sources.
+    #[serde(default)]
+    pub seed: u64,
+}
@@ -1,14 +1,68 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+mod attrs;
+mod binary;
+mod builder;
+mod canonical;
+mod compartment;
+mod connectivity;
+mod delay;
+mod diagnostics;
+mod diff;
+mod geometry;
+mod input;
+mod migrate;
+mod module;
+mod probe;
+mod projection;
+mod schema;
+mod stream;
+mod subgraph;
+mod tensor;
+mod viz;
+pub use attrs::{
+    Attribute, LinkLoad, PartAssignment, PartRoute, PartitionMeta, PlacementMeta, RoutingMeta, RvBareTuningMeta,
+    RvCtrlPlaneMeta, RvLayoutMeta, RvScheduleMeta, RvVectorizeMeta, EXPERIMENT_PREFIX, HOTSPOT_UTILIZATION,
+    KNOWN_ATTRIBUTES,
+};
+pub use builder::{AdExParams, GraphBuilder, IzhikevichParams, LifParams, Model};
+pub use canonical::TRANSIENT_ATTRIBUTES;
+pub use compartment::{Compartment, CompartmentKind};
+pub use connectivity::{ConnectivityRule, ConnectivitySpec};
+pub use delay::DelayDistribution;
+pub use diagnostics::{render_table, Diagnostic, Severity};
+pub use diff::{Change, GraphDiff, ItemDiff};
+pub use geometry::Geometry;
+pub use input::{Input, InputSource};
+pub use migrate::{migrate, parse_version, UNVERSIONED};
+pub use module::{Module, ModuleInstance};
+pub use probe::{ProbeFormat, PROBE_VARIABLES};
+pub use projection::{Projection, SynapseMatrix};
+pub use schema::{model_schema, register_model_schema, ModelSchema, ParamSpec};
+#[cfg(feature = "mmap")]
+pub use stream::ConnectionStore;
+pub use subgraph::{BOUNDARY_IN, BOUNDARY_OUT};
+pub use tensor::{TensorDtype, TensorRef};
+pub use viz::VizOptions;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Population {
     pub name: String,
     pub size: u32,
     pub model: String,
-    #[serde(default)]
+    #[serde(default, with = "crate::binary")]
     pub params: serde_json::Value,
+    /// Optional region/group tag (e.g. "layer4") used for reporting.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Optional neuron coordinates (see `Population::neuron_position`).
+    #[serde(default, with = "crate::binary")]
+    pub geometry: Option<Geometry>,
+    /// Optional morphology shared by every neuron (see `Compartment`); empty for point neurons.
+    #[serde(default)]
+    pub compartments: Vec<Compartment>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,12 +75,32 @@ pub struct Connection {
     pub delay_ms: f32,
     #[serde(default)]
     pub plasticity: Option<PlasticityRule>,
+    /// Dense pre.size x post.size weight block (`[i][j]` = pre neuron i -> post neuron j).
+    /// Replaces the scalar `weight`, which must then be left at 0.
+    #[serde(default)]
+    pub weight_matrix: Option<Vec<Vec<f32>>>,
+    /// `weight_matrix` kept in an external file (see `Graph::load_weight_refs`).
+    #[serde(default)]
+    pub weights_ref: Option<TensorRef>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Probe {
     pub target: String,
     pub kind: String,
+    /// Variables to record (see `PROBE_VARIABLES`); empty records just `kind`.
+    #[serde(default)]
+    pub variables: Vec<String>,
+    /// Sampling interval for continuous variables; every step when unset.
+    #[serde(default)]
+    pub interval_ms: Option<f64>,
+    /// Recording window; from 0 and until the end of the run when unset.
+    #[serde(default)]
+    pub start_ms: Option<f64>,
+    #[serde(default)]
+    pub stop_ms: Option<f64>,
+    #[serde(default)]
+    pub format: Option<ProbeFormat>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,7 +120,7 @@ pub enum PlasticityKind {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlasticityRule {
     pub kind: PlasticityKind,
-    #[serde(default)]
+    #[serde(default, with = "crate::binary")]
     pub params: serde_json::Value,
 }
 
@@ -61,6 +135,53 @@ impl std::fmt::Display for ValidationError {
 
 impl std::error::Error for ValidationError {}
 
+/// What `Graph::remove_population` removed alongside the population itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RemovedSummary {
+    /// Connections with the population as `pre` or `post`.
+    pub connections: usize,
+    /// Probes targeting the population.
+    pub probes: usize,
+    /// Projections with the population as `pre` or `post`.
+    pub projections: usize,
+    /// Connectivity rules with the population as `pre` or `post`.
+    pub connectivity: usize,
+    /// Inputs targeting the population.
+    pub inputs: usize,
+}
+
+/// Summary counts for a graph (see `Graph::stats`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphStats {
+    pub populations: usize,
+    pub connections: usize,
+    pub probes: usize,
+    /// Sum of population sizes.
+    pub total_neurons: u64,
+    /// Connections carrying a plasticity rule.
+    pub plastic_connections: usize,
+}
+
+/// Connections in coordinate (COO) form over population indices in declaration order.
+/// Entry `i` is the connection `populations[rows[i]] -> populations[cols[i]]` with `weights[i]`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CooConnections {
+    pub rows: Vec<usize>,
+    pub cols: Vec<usize>,
+    pub weights: Vec<f32>,
+}
+
+/// Distribution of connection weights (see `Graph::weight_stats`). `std` is the population
+/// standard deviation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeightStats {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub std: f32,
+    pub abs_max: f32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Graph {
     pub name: String,
@@ -70,10 +191,49 @@ pub struct Graph {
     pub connections: Vec<Connection>,
     #[serde(default)]
     pub probes: Vec<Probe>,
+    /// Connection groups with explicit per-synapse arrays (see `Projection`).
     #[serde(default)]
-    pub dialect: Option<Dialect>,
+    pub projections: Vec<Projection>,
+    /// Rule-based connectivity awaiting expansion into `projections`.
+    #[serde(default)]
+    pub connectivity: Vec<ConnectivitySpec>,
+    /// Stimulus sources driving populations.
+    #[serde(default)]
+    pub inputs: Vec<Input>,
     #[serde(default)]
+    pub dialect: Option<Dialect>,
+    #[serde(default, with = "crate::binary")]
     pub attributes: IndexMap<String, serde_json::Value>,
+    /// Reusable subgraph definitions (see `Graph::flatten`).
+    #[serde(default)]
+    pub modules: Vec<Module>,
+    /// Top-level module instances, expanded by `Graph::flatten`.
+    #[serde(default)]
+    pub instances: Vec<ModuleInstance>,
+}
+
+/// Recursively scan a params value for non-finite numbers and nulls, returning what was found
+/// and where (`non-finite value at params.tau`, `null at params.taus[2] ...`). serde_json
+/// cannot hold NaN/Inf, so parsers (e.g. YAML `.nan`) and `json!` turn them into `null`; a
+/// nested `null` is reported as such, noting that it may stand for NaN/Inf. A wholly absent
+/// params (`null` at the root) is allowed.
+fn check_params_finite(v: &serde_json::Value, path: &str) -> Result<(), String> {
+    fn walk(v: &serde_json::Value, path: &str) -> Result<(), String> {
+        match v {
+            serde_json::Value::Null => Err(format!("null at {path} (NaN and Inf also read as null)")),
+            serde_json::Value::Number(n) => match n.as_f64() {
+                Some(f) if !f.is_finite() => Err(format!("non-finite value at {path}")),
+                _ => Ok(()),
+            },
+            serde_json::Value::Array(a) => a.iter().enumerate().try_for_each(|(i, x)| walk(x, &format!("{path}[{i}]"))),
+            serde_json::Value::Object(m) => m.iter().try_for_each(|(k, x)| walk(x, &format!("{path}.{k}"))),
+            _ => Ok(()),
+        }
+    }
+    if v.is_null() {
+        return Ok(());
+    }
+    walk(v, path)
 }
 
 impl Graph {
@@ -83,22 +243,133 @@ impl Graph {
             populations: Vec::new(),
             connections: Vec::new(),
             probes: Vec::new(),
+            projections: Vec::new(),
+            connectivity: Vec::new(),
+            inputs: Vec::new(),
             dialect: None,
             attributes: IndexMap::new(),
+            modules: Vec::new(),
+            instances: Vec::new(),
         }
     }
 
     pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(self)
     }
+    /// `to_json_string` with attribute keys (and keys of nested attribute objects) sorted,
+    /// so dumps are byte-identical regardless of the order passes inserted attributes.
+    pub fn to_json_string_sorted(&self) -> Result<String, serde_json::Error> {
+        fn sorted(v: &serde_json::Value) -> serde_json::Value {
+            match v {
+                serde_json::Value::Object(m) => {
+                    let mut keys: Vec<&String> = m.keys().collect();
+                    keys.sort();
+                    serde_json::Value::Object(keys.into_iter().map(|k| (k.clone(), sorted(&m[k]))).collect())
+                }
+                serde_json::Value::Array(a) => serde_json::Value::Array(a.iter().map(sorted).collect()),
+                other => other.clone(),
+            }
+        }
+        let mut g = self.clone();
+        g.attributes.sort_keys();
+        for v in g.attributes.values_mut() {
+            *v = sorted(v);
+        }
+        serde_json::to_string_pretty(&g)
+    }
+    /// Parse NIR JSON, upgrading documents from older `nir_version`s (see `migrate`). Newer
+    /// versions are rejected.
     pub fn from_json_str(s: &str) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(s)
+        let mut doc: serde_json::Value = serde_json::from_str(s)?;
+        migrate::upgrade_document(&mut doc).map_err(<serde_json::Error as serde::de::Error>::custom)?;
+        serde_json::from_value(doc)
+    }
+    /// Stable 16-hex-digit hash of the graph's content, for artifact caching and tagging output
+    /// directories. Object keys (attributes, params, nested values) are hashed in sorted order
+    /// and numbers by value, non-integers at f32 precision, so attribute insertion order and
+    /// float formatting (`1` vs `1.0`, `-0.0`, f32/f64 round trips) do not change the hash.
+    pub fn content_hash(&self) -> String {
+        fn canonical(v: &serde_json::Value, out: &mut String) {
+            use serde_json::Value;
+            match v {
+                Value::Null => out.push('n'),
+                Value::Bool(b) => out.push(if *b { 't' } else { 'f' }),
+                Value::Number(n) => {
+                    if let Some(i) = n.as_i64() {
+                        out.push_str(&i.to_string());
+                    } else if let Some(u) = n.as_u64() {
+                        out.push_str(&u.to_string());
+                    } else {
+                        let x = n.as_f64().unwrap_or(0.0);
+                        if x.fract() == 0.0 && x.abs() < 9.0e15 {
+                            out.push_str(&(x as i64).to_string());
+                        } else {
+                            out.push_str(&format!("{:e}", x as f32));
+                        }
+                    }
+                }
+                Value::String(s) => out.push_str(&format!("{s:?}")),
+                Value::Array(a) => {
+                    out.push('[');
+                    for x in a {
+                        canonical(x, out);
+                        out.push(',');
+                    }
+                    out.push(']');
+                }
+                Value::Object(m) => {
+                    let mut keys: Vec<&String> = m.keys().collect();
+                    keys.sort();
+                    out.push('{');
+                    for k in keys {
+                        out.push_str(&format!("{k:?}:"));
+                        canonical(&m[k], out);
+                        out.push(',');
+                    }
+                    out.push('}');
+                }
+            }
+        }
+        let mut text = String::new();
+        canonical(&serde_json::to_value(self).unwrap_or_default(), &mut text);
+        // FNV-1a, as for `TargetManifest::digest`
+        let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+        for b in text.as_bytes() {
+            h ^= u64::from(*b);
+            h = h.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        format!("{h:016x}")
+    }
+    /// NetworkX node-link JSON (`networkx.node_link_graph`): populations become nodes
+    /// `{id, size, model}` and connections become links `{source, target, weight, delay_ms}`.
+    /// Params, probes and attributes are not included; use `to_json_string` for full NIR.
+    pub fn to_networkx_json(&self) -> Result<String, serde_json::Error> {
+        let nodes: Vec<serde_json::Value> = self
+            .populations
+            .iter()
+            .map(|p| serde_json::json!({ "id": p.name, "size": p.size, "model": p.model }))
+            .collect();
+        let links: Vec<serde_json::Value> = self
+            .connections
+            .iter()
+            .map(|c| serde_json::json!({ "source": c.pre, "target": c.post, "weight": c.weight, "delay_ms": c.delay_ms }))
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({
+            "directed": true,
+            "multigraph": true,
+            "graph": { "name": self.name },
+            "nodes": nodes,
+            "links": links,
+        }))
     }
     pub fn to_yaml_string(&self) -> Result<String, serde_yaml::Error> {
         serde_yaml::to_string(self)
     }
+    /// Parse NIR YAML, with the same version handling as `from_json_str`.
     pub fn from_yaml_str(s: &str) -> Result<Self, serde_yaml::Error> {
-        serde_yaml::from_str(s)
+        let mut doc: serde_json::Value = serde_yaml::from_str(s)?;
+        migrate::upgrade_document(&mut doc).map_err(<serde_yaml::Error as serde::de::Error>::custom)?;
+        serde_json::from_value(doc).map_err(<serde_yaml::Error as serde::de::Error>::custom)
     }
 
     #[cfg(feature = "bin")]
@@ -114,53 +385,321 @@ impl Graph {
     /// Validate structural integrity of the graph.
     /// Checks:
     /// - population names unique and non-empty; size > 0; model non-empty
+    /// - population params hold no non-finite numbers or nulls (see `check_params_finite`)
+    /// - population geometry matches the size and has finite coordinates
+    /// - population compartments form one soma-rooted tree with finite params
     /// - connections' pre/post exist; weight/delay finite; delay_ms >= 0
-    /// - probes target an existing population; kind non-empty
+    /// - a connection's `weights_ref` replaces inline weights and declares pre.size x post.size
+    /// - probes target an existing population; kind non-empty; known variables and a valid
+    ///   sampling interval and window
+    ///
+    /// Stops at the first error; `validate_all` lists every problem with its code and path.
     pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with_limits(None)
+    }
+
+    /// `validate`, additionally rejecting any connection whose `delay_ms` exceeds
+    /// `max_delay_ms` when a bound is given. `validate_with_limits(None)` equals `validate()`.
+    /// The error carries the message of the first error `validate_all_with_limits` reports.
+    pub fn validate_with_limits(&self, max_delay_ms: Option<f32>) -> Result<(), ValidationError> {
+        match self.validate_all_with_limits(max_delay_ms).into_iter().find(|d| d.severity == Severity::Error) {
+            Some(d) => Err(ValidationError(d.message)),
+            None => Ok(()),
+        }
+    }
+
+    /// Every problem `validate` checks for, instead of only the first, plus warnings
+    /// (`W101` population touched by nothing, `W601` duplicate probe, `W001` known attribute
+    /// that does not match its schema). Paths refer to the flattened graph when the graph has
+    /// module instances.
+    pub fn validate_all(&self) -> Vec<Diagnostic> {
+        self.validate_all_with_limits(None)
+    }
+
+    /// `validate_all` with the delay bound of `validate_with_limits`.
+    pub fn validate_all_with_limits(&self, max_delay_ms: Option<f32>) -> Vec<Diagnostic> {
+        if !self.is_flat() {
+            return match self.flatten() {
+                Ok(flat) => flat.validate_all_with_limits(max_delay_ms),
+                Err(e) => vec![Diagnostic::error("E001", "/instances", e.0)],
+            };
+        }
+        let mut out = Vec::new();
         let mut names: HashSet<String> = HashSet::new();
-        for p in &self.populations {
+        let sizes: HashMap<&str, u32> = self.populations.iter().map(|p| (p.name.as_str(), p.size)).collect();
+        for (i, p) in self.populations.iter().enumerate() {
+            let path = format!("/populations/{i}");
             if p.name.trim().is_empty() {
-                return Err(ValidationError("population name cannot be empty".into()));
-            }
-            if !names.insert(p.name.clone()) {
-                return Err(ValidationError(format!("duplicate population '{}'", p.name)));
+                out.push(Diagnostic::error("E101", &path, "population name cannot be empty"));
+            } else if !names.insert(p.name.clone()) {
+                out.push(Diagnostic::error("E102", &path, format!("duplicate population '{}'", p.name)));
             }
             if p.size == 0 {
-                return Err(ValidationError(format!("population '{}' has size 0", p.name)));
+                out.push(Diagnostic::error("E103", &path, format!("population '{}' has size 0", p.name)));
             }
             if p.model.trim().is_empty() {
-                return Err(ValidationError(format!("population '{}' missing model", p.name)));
+                out.push(Diagnostic::error("E104", &path, format!("population '{}' missing model", p.name)));
+            }
+            if let Err(at) = check_params_finite(&p.params, "params") {
+                out.push(Diagnostic::error(
+                    "E105",
+                    format!("{path}/params"),
+                    format!("population '{}' has {at}", p.name),
+                ));
+            } else if let Some(schema) = model_schema(&p.model) {
+                if let Err(e) = schema.check(&p.params) {
+                    out.push(Diagnostic::error(
+                        "E106",
+                        format!("{path}/params"),
+                        format!("population '{}' ({}): {e}", p.name, schema.name),
+                    ));
+                }
+            }
+            if let Some(geo) = &p.geometry {
+                if let Err(e) = geo.check(p.size) {
+                    out.push(Diagnostic::error(
+                        "E107",
+                        format!("{path}/geometry"),
+                        format!("population '{}' geometry ({}): {e}", p.name, geo.layout()),
+                    ));
+                }
+            }
+            if !p.compartments.is_empty() {
+                if let Err(e) = compartment::check_tree(&p.compartments) {
+                    out.push(Diagnostic::error("E108", format!("{path}/compartments"), format!("population '{}': {e}", p.name)));
+                }
+                for (k, c) in p.compartments.iter().enumerate() {
+                    if let Err(at) = check_params_finite(&c.params, "params") {
+                        out.push(Diagnostic::error(
+                            "E109",
+                            format!("{path}/compartments/{k}/params"),
+                            format!("population '{}' compartment '{}' has {at}", p.name, c.name),
+                        ));
+                    }
+                }
             }
         }
-        for c in &self.connections {
+        for (i, c) in self.connections.iter().enumerate() {
+            let path = format!("/connections/{i}");
+            let mut endpoints_ok = true;
             if !names.contains(&c.pre) {
-                return Err(ValidationError(format!("connection pre '{}' not found", c.pre)));
+                out.push(Diagnostic::error("E201", &path, format!("connection pre '{}' not found", c.pre)));
+                endpoints_ok = false;
             }
             if !names.contains(&c.post) {
-                return Err(ValidationError(format!("connection post '{}' not found", c.post)));
+                out.push(Diagnostic::error("E202", &path, format!("connection post '{}' not found", c.post)));
+                endpoints_ok = false;
             }
             if !c.weight.is_finite() {
-                return Err(ValidationError(format!("connection {}->{} has non-finite weight", c.pre, c.post)));
+                out.push(Diagnostic::error(
+                    "E203",
+                    format!("{path}/weight"),
+                    format!("connection {}->{} has non-finite weight", c.pre, c.post),
+                ));
+            }
+            if let (Some(m), true) = (&c.weight_matrix, endpoints_ok) {
+                let path = format!("{path}/weight_matrix");
+                let (rows, cols) = (sizes[c.pre.as_str()] as usize, sizes[c.post.as_str()] as usize);
+                if c.weight != 0.0 {
+                    out.push(Diagnostic::error(
+                        "E204",
+                        &path,
+                        format!("connection {}->{} sets both weight and weight_matrix", c.pre, c.post),
+                    ));
+                }
+                if m.len() != rows || m.iter().any(|r| r.len() != cols) {
+                    out.push(Diagnostic::error(
+                        "E205",
+                        &path,
+                        format!("connection {}->{} weight_matrix must be {rows}x{cols} (pre.size x post.size)", c.pre, c.post),
+                    ));
+                } else if m.iter().flatten().any(|w| !w.is_finite()) {
+                    out.push(Diagnostic::error(
+                        "E206",
+                        &path,
+                        format!("connection {}->{} has non-finite weight_matrix entry", c.pre, c.post),
+                    ));
+                }
+            }
+            if let (Some(r), true) = (&c.weights_ref, endpoints_ok) {
+                let path = format!("{path}/weights_ref");
+                let expected = [sizes[c.pre.as_str()] as u64, sizes[c.post.as_str()] as u64];
+                if c.weight != 0.0 || c.weight_matrix.is_some() {
+                    out.push(Diagnostic::error(
+                        "E207",
+                        &path,
+                        format!("connection {}->{} sets weights_ref together with weight or weight_matrix", c.pre, c.post),
+                    ));
+                }
+                if r.file.trim().is_empty() || r.shape.as_ref().is_some_and(|s| s[..] != expected) {
+                    out.push(Diagnostic::error(
+                        "E208",
+                        &path,
+                        format!("connection {}->{} weights_ref needs a file and shape {expected:?} (pre.size x post.size)", c.pre, c.post),
+                    ));
+                }
             }
             if !c.delay_ms.is_finite() || c.delay_ms < 0.0 {
-                return Err(ValidationError(format!(
-                    "connection {}->{} has invalid delay_ms {}",
-                    c.pre, c.post, c.delay_ms
-                )));
+                out.push(Diagnostic::error(
+                    "E209",
+                    format!("{path}/delay_ms"),
+                    format!("connection {}->{} has invalid delay_ms {}", c.pre, c.post, c.delay_ms),
+                ));
+            } else if let Some(max) = max_delay_ms.filter(|&max| c.delay_ms > max) {
+                out.push(Diagnostic::error(
+                    "E210",
+                    format!("{path}/delay_ms"),
+                    format!("connection {}->{} delay_ms {} exceeds limit {}", c.pre, c.post, c.delay_ms, max),
+                ));
             }
         }
-        for pr in &self.probes {
+        for (i, pj) in self.projections.iter().enumerate() {
+            let path = format!("/projections/{i}");
+            let (Some(&pre), Some(&post)) = (sizes.get(pj.pre.as_str()), sizes.get(pj.post.as_str())) else {
+                out.push(Diagnostic::error("E301", &path, format!("projection {}->{} references unknown population", pj.pre, pj.post)));
+                continue;
+            };
+            if let Err(e) = pj.check(pre, post, max_delay_ms) {
+                out.push(Diagnostic::error("E302", format!("{path}/synapses"), format!("projection {}->{}: {e}", pj.pre, pj.post)));
+            }
+            if let Err(e) = pj.check_delay_distribution(max_delay_ms) {
+                out.push(Diagnostic::error("E303", format!("{path}/delay_distribution"), format!("projection {}->{}: {e}", pj.pre, pj.post)));
+            }
+        }
+        for (i, cs) in self.connectivity.iter().enumerate() {
+            let path = format!("/connectivity/{i}");
+            let Some(&pre) = sizes.get(cs.pre.as_str()).filter(|_| sizes.contains_key(cs.post.as_str())) else {
+                out.push(Diagnostic::error("E401", &path, format!("connectivity {}->{} references unknown population", cs.pre, cs.post)));
+                continue;
+            };
+            if let Err(e) = cs.check(pre) {
+                out.push(Diagnostic::error(
+                    "E402",
+                    format!("{path}/rule"),
+                    format!("connectivity {}->{} ({}): {e}", cs.pre, cs.post, cs.rule.kind()),
+                ));
+            }
+        }
+        let mut input_names: HashSet<&str> = HashSet::new();
+        for (i, inp) in self.inputs.iter().enumerate() {
+            let path = format!("/inputs/{i}");
+            if inp.name.trim().is_empty() || !input_names.insert(inp.name.as_str()) {
+                out.push(Diagnostic::error("E501", &path, format!("input name '{}' is empty or duplicated", inp.name)));
+            }
+            if !names.contains(&inp.target) {
+                out.push(Diagnostic::error("E502", &path, format!("input '{}' target '{}' not found", inp.name, inp.target)));
+            }
+            if let Err(e) = inp.source.check() {
+                out.push(Diagnostic::error("E503", format!("{path}/source"), format!("input '{}' ({}): {e}", inp.name, inp.source.kind())));
+            }
+        }
+        let mut probes: HashSet<String> = HashSet::new();
+        for (i, pr) in self.probes.iter().enumerate() {
+            let path = format!("/probes/{i}");
             if pr.kind.trim().is_empty() {
-                return Err(ValidationError("probe kind cannot be empty".into()));
+                out.push(Diagnostic::error("E601", &path, "probe kind cannot be empty"));
             }
             if !names.contains(&pr.target) {
+                out.push(Diagnostic::error("E602", &path, format!("probe target '{}' not found among populations", pr.target)));
+            }
+            if let Err((code, e)) = pr.check_settings() {
+                out.push(Diagnostic::error(code, &path, format!("probe {}/{}: {e}", pr.target, pr.kind)));
+            }
+            if !probes.insert(pr.recording_key()) {
+                out.push(Diagnostic::warning("W601", &path, format!("duplicate probe {}/{}", pr.target, pr.kind)));
+            }
+        }
+        let touched: HashSet<&str> = self
+            .connections
+            .iter()
+            .flat_map(|c| [c.pre.as_str(), c.post.as_str()])
+            .chain(self.projections.iter().flat_map(|p| [p.pre.as_str(), p.post.as_str()]))
+            .chain(self.connectivity.iter().flat_map(|c| [c.pre.as_str(), c.post.as_str()]))
+            .chain(self.inputs.iter().map(|i| i.target.as_str()))
+            .chain(self.probes.iter().map(|p| p.target.as_str()))
+            .collect();
+        if self.populations.len() > 1 {
+            for (i, p) in self.populations.iter().enumerate() {
+                if !touched.contains(p.name.as_str()) {
+                    out.push(Diagnostic::warning(
+                        "W101",
+                        format!("/populations/{i}"),
+                        format!("population '{}' has no connections, inputs or probes", p.name),
+                    ));
+                }
+            }
+        }
+        for (key, value) in &self.attributes {
+            if !attrs::matches_schema(key, value) {
+                out.push(Diagnostic::warning("W001", format!("/attributes/{key}"), format!("attribute '{key}' does not match its schema")));
+            }
+        }
+        out
+    }
+
+    /// Build a graph from a CSV edge list with rows `pre,post,weight[,delay_ms]`.
+    ///
+    /// Populations are the union of pre/post names in first-appearance order, each with
+    /// `default_size` neurons of `model` and empty params. An optional `pre,post,...` header,
+    /// blank lines and `#` comments are skipped. Malformed rows error with their 1-based line
+    /// number; the assembled graph is validated before returning.
+    pub fn from_edge_csv(name: &str, csv: &str, default_size: u32, model: &str) -> Result<Graph, ValidationError> {
+        let mut g = Graph::new(name);
+        let mut seen: HashSet<String> = HashSet::new();
+        for (i, raw) in csv.lines().enumerate() {
+            let line_no = i + 1;
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if g.connections.is_empty() && seen.is_empty() && fields[0].eq_ignore_ascii_case("pre") {
+                continue;
+            }
+            if !(3..=4).contains(&fields.len()) {
                 return Err(ValidationError(format!(
-                    "probe target '{}' not found among populations",
-                    pr.target
+                    "edge csv line {line_no}: expected pre,post,weight[,delay_ms], got {} fields",
+                    fields.len()
                 )));
             }
+            let (pre, post) = (fields[0], fields[1]);
+            if pre.is_empty() || post.is_empty() {
+                return Err(ValidationError(format!("edge csv line {line_no}: empty population name")));
+            }
+            let weight: f32 = fields[2]
+                .parse()
+                .map_err(|_| ValidationError(format!("edge csv line {line_no}: invalid weight '{}'", fields[2])))?;
+            let delay_ms: f32 = match fields.get(3) {
+                Some(d) if !d.is_empty() => d
+                    .parse()
+                    .map_err(|_| ValidationError(format!("edge csv line {line_no}: invalid delay_ms '{d}'")))?,
+                _ => 0.0,
+            };
+            for pop in [pre, post] {
+                if seen.insert(pop.to_string()) {
+                    g.populations.push(Population {
+                        name: pop.to_string(),
+                        size: default_size,
+                        model: model.to_string(),
+                        params: serde_json::json!({}),
+                        group: None,
+                        geometry: None,
+                        compartments: Vec::new(),
+                    });
+                }
+            }
+            g.connections.push(Connection {
+                pre: pre.to_string(),
+                post: post.to_string(),
+                weight,
+                delay_ms,
+                plasticity: None,
+                weight_matrix: None,
+                weights_ref: None,
+            });
         }
-        Ok(())
+        g.validate()?;
+        Ok(g)
     }
 
     /// Ensure the 'nir_version' attribute is present with the current VERSION.
@@ -169,9 +708,181 @@ impl Graph {
             self.attributes.insert("nir_version".to_string(), serde_json::json!(VERSION));
         }
     }
+
+    /// Population/connection/probe counts, total neurons and plastic connections.
+    pub fn stats(&self) -> GraphStats {
+        GraphStats {
+            populations: self.populations.len(),
+            connections: self.connections.len(),
+            probes: self.probes.len(),
+            total_neurons: self.populations.iter().map(|p| p.size as u64).sum(),
+            plastic_connections: self.connections.iter().filter(|c| c.plasticity.is_some()).count(),
+        }
+    }
+
+    /// Min/max/mean/std/abs-max over connection weights, e.g. for picking quantization bits.
+    /// Connections with a `weight_matrix` contribute every matrix entry instead of `weight`.
+    /// `None` when the graph has no connections.
+    pub fn weight_stats(&self) -> Option<WeightStats> {
+        let weights = self.connections.iter().flat_map(|c| match &c.weight_matrix {
+            Some(m) => m.iter().flatten().copied().collect::<Vec<f32>>(),
+            None => vec![c.weight],
+        });
+        let (mut n, mut sum, mut sum_sq) = (0usize, 0.0f64, 0.0f64);
+        let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+        for w in weights {
+            n += 1;
+            sum += w as f64;
+            sum_sq += (w as f64) * (w as f64);
+            min = min.min(w);
+            max = max.max(w);
+        }
+        if n == 0 {
+            return None;
+        }
+        let mean = sum / n as f64;
+        let var = (sum_sq / n as f64 - mean * mean).max(0.0);
+        Some(WeightStats {
+            min,
+            max,
+            mean: mean as f32,
+            std: var.sqrt() as f32,
+            abs_max: min.abs().max(max.abs()),
+        })
+    }
+
+    /// Every inline synaptic weight for in-place rewriting: each connection's `weight` (or its
+    /// `weight_matrix` entries, row by row, when it has one), then each projection's weights.
+    /// Unresolved `weights_ref` arrays are not visited.
+    pub fn weights_mut(&mut self) -> impl Iterator<Item = &mut f32> + '_ {
+        let connections = self.connections.iter_mut().flat_map(|c| -> Box<dyn Iterator<Item = &mut f32> + '_> {
+            match &mut c.weight_matrix {
+                Some(m) => Box::new(m.iter_mut().flatten()),
+                None => Box::new(std::iter::once(&mut c.weight)),
+            }
+        });
+        let projections = self.projections.iter_mut().flat_map(|pj| match &mut pj.synapses {
+            SynapseMatrix::Dense { weights, .. } | SynapseMatrix::Csr { weights, .. } => weights.iter_mut(),
+        });
+        connections.chain(projections)
+    }
+
+    /// Connections as COO arrays (`rows` = pre index, `cols` = post index), in connection
+    /// order. Connections naming unknown populations are skipped, so a validated graph
+    /// yields exactly one entry per connection.
+    pub fn to_coo(&self) -> CooConnections {
+        let index: std::collections::HashMap<&str, usize> =
+            self.populations.iter().enumerate().map(|(i, p)| (p.name.as_str(), i)).collect();
+        let mut coo = CooConnections::default();
+        for c in &self.connections {
+            if let (Some(&r), Some(&col)) = (index.get(c.pre.as_str()), index.get(c.post.as_str())) {
+                coo.rows.push(r);
+                coo.cols.push(col);
+                coo.weights.push(c.weight);
+            }
+        }
+        coo
+    }
+
+    /// Population names per `group`, in population order. Ungrouped populations are
+    /// collected under the empty-string key.
+    pub fn populations_by_group(&self) -> std::collections::BTreeMap<String, Vec<String>> {
+        let mut groups: std::collections::BTreeMap<String, Vec<String>> = std::collections::BTreeMap::new();
+        for p in &self.populations {
+            groups.entry(p.group.clone().unwrap_or_default()).or_default().push(p.name.clone());
+        }
+        groups
+    }
+
+    /// Connection indices grouped by `pre`, each list sorted by `post` name (ties keep
+    /// connection order), so per-source codegen can iterate deterministically without rescanning.
+    pub fn connections_by_source(&self) -> std::collections::BTreeMap<String, Vec<usize>> {
+        let mut by_src: std::collections::BTreeMap<String, Vec<usize>> = std::collections::BTreeMap::new();
+        for (i, c) in self.connections.iter().enumerate() {
+            by_src.entry(c.pre.clone()).or_default().push(i);
+        }
+        for idxs in by_src.values_mut() {
+            idxs.sort_by(|&a, &b| self.connections[a].post.cmp(&self.connections[b].post));
+        }
+        by_src
+    }
+
+    /// Number of connections from `pre` to `post` (parallel edges counted individually).
+    pub fn connection_count_between(&self, pre: &str, post: &str) -> usize {
+        self.connections.iter().filter(|c| c.pre == pre && c.post == post).count()
+    }
+
+    /// Remove population `name` together with its incident connections, projections and
+    /// connectivity rules and the probes and inputs targeting it. Errors if the population does
+    /// not exist.
+    pub fn remove_population(&mut self, name: &str) -> Result<RemovedSummary, ValidationError> {
+        let idx = self
+            .populations
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| ValidationError(format!("population '{name}' not found")))?;
+        self.populations.remove(idx);
+        let before = (self.connections.len(), self.probes.len(), self.projections.len(), self.connectivity.len(), self.inputs.len());
+        self.connections.retain(|c| c.pre != name && c.post != name);
+        self.probes.retain(|pr| pr.target != name);
+        self.projections.retain(|pj| pj.pre != name && pj.post != name);
+        self.connectivity.retain(|cs| cs.pre != name && cs.post != name);
+        self.inputs.retain(|i| i.target != name);
+        Ok(RemovedSummary {
+            connections: before.0 - self.connections.len(),
+            probes: before.1 - self.probes.len(),
+            projections: before.2 - self.projections.len(),
+            connectivity: before.3 - self.connectivity.len(),
+            inputs: before.4 - self.inputs.len(),
+        })
+    }
+
+    /// Rename population `old` to `new`, rewriting every connection, projection and
+    /// connectivity rule `pre`/`post` and probe and input `target` that references it.
+    /// Attributes are left untouched.
+    /// Errors if `old` does not exist or `new` is already taken.
+    pub fn rename_population(&mut self, old: &str, new: &str) -> Result<(), ValidationError> {
+        if old == new {
+            return if self.populations.iter().any(|p| p.name == old) {
+                Ok(())
+            } else {
+                Err(ValidationError(format!("population '{old}' not found")))
+            };
+        }
+        if new.trim().is_empty() {
+            return Err(ValidationError("population name cannot be empty".into()));
+        }
+        if self.populations.iter().any(|p| p.name == new) {
+            return Err(ValidationError(format!("population '{new}' already exists")));
+        }
+        let pop = self
+            .populations
+            .iter_mut()
+            .find(|p| p.name == old)
+            .ok_or_else(|| ValidationError(format!("population '{old}' not found")))?;
+        pop.name = new.to_string();
+        let ends = self.connections.iter_mut().map(|c| (&mut c.pre, &mut c.post));
+        let ends = ends.chain(self.projections.iter_mut().map(|pj| (&mut pj.pre, &mut pj.post)));
+        for (pre, post) in ends.chain(self.connectivity.iter_mut().map(|cs| (&mut cs.pre, &mut cs.post))) {
+            if *pre == old {
+                *pre = new.to_string();
+            }
+            if *post == old {
+                *post = new.to_string();
+            }
+        }
+        let targets = self.probes.iter_mut().map(|pr| &mut pr.target);
+        for target in targets.chain(self.inputs.iter_mut().map(|i| &mut i.target)) {
+            if *target == old {
+                *target = new.to_string();
+            }
+        }
+        Ok(())
+    }
 }
 
-pub const VERSION: &str = "0.0.1";
+/// Current NIR document version (`attributes.nir_version`); see `migrate`.
+pub const VERSION: &str = "0.1.0";
 
 /// Pre-built NIR graph generators for tests and examples.
 pub mod fixtures {
@@ -180,6 +891,7 @@ pub mod fixtures {
 
     /// Construct a simple feed-forward chain of populations.
     /// - layer_sizes: sizes of successive layers (len >= 1). layer[0] is "source".
+    ///
     /// Population ids: p0, p1, ..., p{n-1}
     /// Connections: p{i} -> p{i+1} with weight=0.5, delay_ms=1.0
     pub fn chain(layer_sizes: &[u32]) -> Graph {
@@ -197,6 +909,9 @@ pub mod fixtures {
                 size: *sz,
                 model,
                 params,
+                group: None,
+                geometry: None,
+                compartments: Vec::new(),
             });
         }
         for i in 0..(layer_sizes.len() - 1) {
@@ -206,6 +921,8 @@ pub mod fixtures {
                 weight: 0.5,
                 delay_ms: 1.0,
                 plasticity: None,
+                weight_matrix: None,
+                weights_ref: None,
             });
         }
         g.ensure_version_tag();
@@ -222,6 +939,9 @@ pub mod fixtures {
             size: center_size,
             model: "lif".into(),
             params: json!({"tau_m": 0.02, "v_th": 1.0}),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
         });
         for i in 0..spokes {
             let sname = format!("s{}", i);
@@ -230,6 +950,9 @@ pub mod fixtures {
                 size: spoke_size,
                 model: "lif".into(),
                 params: json!({"tau_m": 0.02, "v_th": 1.0}),
+                group: None,
+                geometry: None,
+                compartments: Vec::new(),
             });
             g.connections.push(Connection {
                 pre: "center".into(),
@@ -237,6 +960,8 @@ pub mod fixtures {
                 weight,
                 delay_ms,
                 plasticity: None,
+                weight_matrix: None,
+                weights_ref: None,
             });
         }
         g.ensure_version_tag();
@@ -253,6 +978,9 @@ pub mod fixtures {
                 size,
                 model: "lif".into(),
                 params: json!({"tau_m": 0.02, "v_th": 1.0}),
+                group: None,
+                geometry: None,
+                compartments: Vec::new(),
             });
         }
         for i in 0..n {
@@ -263,6 +991,8 @@ pub mod fixtures {
                 weight,
                 delay_ms,
                 plasticity: None,
+                weight_matrix: None,
+                weights_ref: None,
             });
         }
         g.ensure_version_tag();
@@ -272,6 +1002,823 @@ pub mod fixtures {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn networkx_json_has_one_link_per_connection() {
+        let g = Graph::from_edge_csv("nx", "a,b,0.5,1\nb,c,0.25,0\na,b,0.1,2\n", 4, "LIF").unwrap();
+        let v: serde_json::Value = serde_json::from_str(&g.to_networkx_json().unwrap()).unwrap();
+        assert_eq!(v["directed"], true);
+        assert_eq!(v["nodes"].as_array().unwrap().len(), 3);
+        assert_eq!(v["links"].as_array().unwrap().len(), g.connections.len());
+        assert_eq!(v["links"][0]["source"], "a");
+        assert_eq!(v["nodes"][0]["model"], "LIF");
+    }
+
+    #[test]
+    fn sorted_json_ignores_attribute_insertion_order() {
+        let mut a = Graph::new("g");
+        a.attributes.insert("zeta".into(), serde_json::json!({"b": 1, "a": 2}));
+        a.attributes.insert("alpha".into(), serde_json::json!([1, 2]));
+        let mut b = Graph::new("g");
+        b.attributes.insert("alpha".into(), serde_json::json!([1, 2]));
+        b.attributes.insert("zeta".into(), serde_json::json!({"a": 2, "b": 1}));
+        assert_ne!(a.to_json_string().unwrap(), b.to_json_string().unwrap());
+        assert_eq!(a.to_json_string_sorted().unwrap(), b.to_json_string_sorted().unwrap());
+    }
+
+    #[test]
+    fn validate_rejects_nan_params() {
+        let yaml = "name: g\npopulations:\n  - name: a\n    size: 1\n    model: LIF\n    params: { tau: .nan, v_th: 1.0 }\nconnections: []\nprobes: []\n";
+        let g = Graph::from_yaml_str(yaml).expect("parse");
+        let err = g.validate().unwrap_err();
+        assert!(err.0.contains("population 'a' has null at params.tau (NaN and Inf also read as null)"), "{}", err.0);
+
+        let mut g = Graph::new("g2");
+        g.populations.push(Population {
+            name: "b".into(),
+            size: 1,
+            model: "LIF".into(),
+            params: serde_json::json!({"taus": [0.1, f64::INFINITY]}),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
+        });
+        assert!(g.validate().unwrap_err().0.contains("params.taus[1]"));
+        g.populations[0].params = serde_json::Value::Null;
+        g.validate().expect("absent params are fine");
+    }
+
+    #[test]
+    fn populations_by_group_collects_names() {
+        let json = r#"{"name":"g","populations":[
+            {"name":"l4_exc","size":4,"model":"LIF","group":"layer4"},
+            {"name":"in","size":2,"model":"LIF"},
+            {"name":"l4_inh","size":1,"model":"LIF","group":"layer4"}],
+            "connections":[],"probes":[]}"#;
+        let g = Graph::from_json_str(json).expect("parse");
+        let groups = g.populations_by_group();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["layer4"], vec!["l4_exc".to_string(), "l4_inh".to_string()]);
+        assert_eq!(groups[""], vec!["in".to_string()]);
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!(back.populations_by_group(), groups);
+    }
+
+    #[test]
+    fn connections_by_source_groups_star_hub() {
+        let mut g = fixtures::star(4, 2, 3, 0.5, 1.0);
+        // Reverse connection order so the grouping has to sort by post
+        g.connections.reverse();
+        let by_src = g.connections_by_source();
+        assert_eq!(by_src.len(), 1);
+        let hub = &by_src["center"];
+        assert_eq!(hub.len(), 3);
+        let posts: Vec<&str> = hub.iter().map(|&i| g.connections[i].post.as_str()).collect();
+        assert_eq!(posts, ["s0", "s1", "s2"]);
+    }
+
+    #[test]
+    fn from_edge_csv_builds_graph() {
+        let csv = "pre,post,weight,delay_ms\nin,hid,0.5,1.0\nhid,out,0.25,2\n\nin,out,-1.0,0.5\n";
+        let g = Graph::from_edge_csv("edges", csv, 16, "LIF").expect("csv graph");
+        assert_eq!(g.populations.len(), 3);
+        assert_eq!(g.connections.len(), 3);
+        assert!(g.populations.iter().all(|p| p.size == 16 && p.model == "LIF"));
+        assert_eq!(g.connections[1].delay_ms, 2.0);
+
+        let err = Graph::from_edge_csv("bad", "a,b,0.1\na,b,oops\n", 1, "LIF").unwrap_err();
+        assert!(err.0.contains("line 2"), "{}", err.0);
+    }
+
+    #[test]
+    fn weight_matrix_round_trips_and_checks_dimensions() {
+        let mut g = fixtures::chain(&[2, 3]);
+        g.connections[0].weight = 0.0;
+        g.connections[0].weight_matrix = Some(vec![vec![0.1, 0.2, 0.3], vec![-0.1, -0.2, -0.3]]);
+        g.validate().expect("2x3 block matches sizes");
+
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!(back.connections[0].weight_matrix, g.connections[0].weight_matrix);
+
+        g.connections[0].weight_matrix = Some(vec![vec![0.1, 0.2], vec![0.3, 0.4], vec![0.5, 0.6]]);
+        let err = g.validate().unwrap_err();
+        assert!(err.0.contains("must be 2x3"), "{}", err.0);
+
+        g.connections[0].weight_matrix = Some(vec![vec![0.0; 3]; 2]);
+        g.connections[0].weight = 0.5;
+        assert!(g.validate().is_err(), "scalar weight and matrix are exclusive");
+    }
+
+    #[test]
+    fn compartments_validate_as_soma_rooted_tree_and_collapse() {
+        let json = r#"{"name": "morph", "populations": [{"name": "pyr", "size": 2, "model": "HH", "params": {"v_rest": -65.0},
+            "compartments": [
+                {"name": "soma", "kind": "soma", "length_um": 10.0, "diameter_um": 10.0, "params": {"cm": 1.0, "g_leak": 0.1, "mech": "hh"}},
+                {"name": "dend", "kind": "dendrite", "parent": "soma", "length_um": 30.0, "diameter_um": 10.0,
+                 "params": {"cm": 2.0, "g_leak": 0.3, "nmda": 0.5}}]}]}"#;
+        let mut g = Graph::from_json_str(json).unwrap();
+        g.validate().unwrap();
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!(back.populations[0].compartments, g.populations[0].compartments);
+
+        // Area-weighted (soma:dendrite = 1:3) means of the soma's numeric params
+        let point = g.populations[0].point_params();
+        let close = |v: &serde_json::Value, x: f64| (v.as_f64().unwrap() - x).abs() < 1e-12;
+        assert!(close(&point["cm"], 1.75) && close(&point["g_leak"], 0.25), "{point}");
+        assert_eq!(point["mech"], "hh");
+        assert_eq!(point["v_rest"], -65.0);
+        assert!(point.get("nmda").is_none());
+        // Without complete geometry every compartment weighs the same
+        g.populations[0].compartments[1].diameter_um = None;
+        assert_eq!(g.populations[0].point_params()["cm"], 1.5);
+
+        g.populations[0].compartments[1].parent = Some("axon".into());
+        assert!(g.validate().unwrap_err().0.contains("parent 'axon' not found"));
+        g.populations[0].compartments[1].parent = None;
+        assert!(g.validate().unwrap_err().0.contains("exactly one root"));
+        g.populations[0].compartments[0].parent = Some("dend".into());
+        g.populations[0].compartments[1].kind = CompartmentKind::Soma;
+        g.populations[0].compartments[1].parent = Some("soma".into());
+        assert!(g.validate().unwrap_err().0.contains("exactly one root"));
+        g.populations[0].compartments[0].parent = None;
+        g.populations[0].compartments[0].kind = CompartmentKind::Dendrite;
+        g.populations[0].compartments[1].kind = CompartmentKind::Dendrite;
+        let diags = g.validate_all();
+        assert_eq!((diags[0].code.as_str(), diags[0].path.as_str()), ("E108", "/populations/0/compartments"));
+        assert!(diags[0].message.contains("must be a soma"));
+    }
+
+    #[test]
+    fn delay_distributions_sample_deterministically_and_validate() {
+        let json = r#"{"name": "hetero", "populations": [{"name": "a", "size": 20, "model": "LIF"}, {"name": "b", "size": 30, "model": "LIF"}],
+            "projections": [{"pre": "a", "post": "b", "synapses": {"format": "dense", "weights": []},
+                             "delay_distribution": {"kind": "normal", "mean_ms": 3.0, "std_ms": 1.0, "min_ms": 0.5, "max_ms": 6.0},
+                             "delay_seed": 11}]}"#;
+        let mut g = Graph::from_json_str(json).unwrap();
+        if let SynapseMatrix::Dense { weights, .. } = &mut g.projections[0].synapses {
+            *weights = vec![0.1; 600];
+        }
+        g.validate().unwrap();
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!(back.projections[0].delay_distribution, g.projections[0].delay_distribution);
+        assert_eq!(back.projections[0].delay_seed, 11);
+
+        let drawn: Vec<f32> = g.projections[0].iter_synapses(30).map(|(_, _, _, d)| d).collect();
+        assert!(drawn.iter().all(|d| (0.5..=6.0).contains(d)));
+        let mean = drawn.iter().sum::<f32>() / drawn.len() as f32;
+        assert!((mean - 3.0).abs() < 0.2, "mean {mean}");
+        let mut m = g.clone();
+        assert_eq!(m.materialize_delays(None).unwrap(), 1);
+        let stored: Vec<f32> = m.projections[0].iter_synapses(30).map(|(_, _, _, d)| d).collect();
+        assert_eq!(stored, drawn, "materialized delays match the lazily drawn ones");
+        assert!(m.validate().is_ok());
+
+        // Bounds, the delay limit and per-synapse delays are checked
+        assert!(g.validate_with_limits(Some(4.0)).unwrap_err().0.contains("exceeds limit 4"));
+        g.projections[0].delay_distribution = Some(DelayDistribution::Uniform { low_ms: 2.0, high_ms: 1.0 });
+        let diags = g.validate_all();
+        assert_eq!((diags[0].code.as_str(), diags[0].path.as_str()), ("E303", "/projections/0/delay_distribution"));
+        g.projections[0].delay_distribution = Some(DelayDistribution::Normal { mean_ms: 1.0, std_ms: 0.5, min_ms: 0.0, max_ms: None });
+        assert!(g.validate().is_ok());
+        assert!(g.validate_with_limits(Some(10.0)).unwrap_err().0.contains("needs max_ms"));
+        m.projections[0].delay_distribution = Some(DelayDistribution::Uniform { low_ms: 0.0, high_ms: 1.0 });
+        assert!(m.validate().unwrap_err().0.contains("exclusive"));
+    }
+
+    #[test]
+    fn typed_attributes_round_trip_and_flag_schema_mismatch() {
+        let mut g = fixtures::chain(&[2, 3]);
+        assert!(g.attrs::<PartitionMeta>().is_none());
+        let meta = PartitionMeta {
+            parts: 2,
+            strategy: "multilevel".into(),
+            assignment: vec![
+                PartAssignment { population: "p0".into(), part: 0 },
+                PartAssignment { population: "p1".into(), part: 1 },
+            ],
+            violations: vec![],
+            cut_synapses: 1,
+        };
+        g.set_attr(&meta);
+        assert_eq!(g.attributes["partition"]["assignment"][1]["part"], 1);
+        assert_eq!(g.attrs::<PartitionMeta>(), Some(meta));
+        assert_eq!(g.attrs::<PartitionMeta>().unwrap().part_of()["p1"], 1);
+
+        // Partial blobs fill in defaults; unknown and experimental keys are left alone
+        g.attributes.insert("rv_layout".into(), serde_json::json!({"align_bytes": 64}));
+        let layout = g.attrs::<RvLayoutMeta>().unwrap();
+        assert_eq!((layout.align_bytes, layout.vector_available, layout.profile), (64, false, None));
+        g.set_experiment("fast-path", serde_json::json!({"on": true}));
+        assert_eq!(g.experiment("fast-path").unwrap()["on"], true);
+        assert!(g.attributes.contains_key("x-fast-path"));
+        assert!(g.validate_all().is_empty());
+
+        g.attributes.insert("routing".into(), serde_json::json!({"cross_edges": "many"}));
+        assert!(g.attrs::<RoutingMeta>().is_none());
+        let diags = g.validate_all();
+        assert_eq!((diags[0].code.as_str(), diags[0].path.as_str()), ("W001", "/attributes/routing"));
+        assert!(g.validate().is_ok(), "schema mismatches are warnings");
+    }
+
+    #[test]
+    fn probe_settings_round_trip_and_validate() {
+        let json = r#"{"name": "rec", "populations": [{"name": "a", "size": 4, "model": "LIF"}],
+            "probes": [{"target": "a", "kind": "spikes"},
+                       {"target": "a", "kind": "v", "variables": ["v", "spikes"], "interval_ms": 0.5,
+                        "start_ms": 100.0, "stop_ms": 200.0, "format": "npy"}]}"#;
+        let mut g = Graph::from_json_str(json).unwrap();
+        g.validate().unwrap();
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!(back.to_json_string().unwrap(), g.to_json_string().unwrap());
+
+        let plain = g.probes[0].resolved();
+        assert_eq!(plain["variables"], serde_json::json!(["spikes"]));
+        assert_eq!((plain["start_ms"].as_f64(), plain["format"].as_str()), (Some(0.0), Some("csv")));
+        assert!(plain["interval_ms"].is_null() && plain["stop_ms"].is_null());
+        assert_eq!(g.probes[1].resolved()["format"], "npy");
+        assert_eq!(g.probes[1].recorded_variables(), ["v", "spikes"]);
+
+        g.probes[1].stop_ms = Some(50.0);
+        let diags = g.validate_all();
+        assert_eq!((diags[0].code.as_str(), diags[0].path.as_str()), ("E603", "/probes/1"));
+        g.probes[1].stop_ms = None;
+        g.probes[1].interval_ms = Some(0.0);
+        assert!(g.validate().unwrap_err().0.contains("interval_ms 0 must be > 0"));
+        g.probes[1].interval_ms = None;
+        g.probes[1].variables.push("current".into());
+        assert_eq!(g.validate_all()[0].code, "E604");
+    }
+
+    #[test]
+    fn duplicate_probes_compare_recording_settings() {
+        let w601 = |g: &Graph| g.validate_all().iter().filter(|d| d.code == "W601").count();
+        let mut g = Graph::new("dup");
+        g.populations.push(Population { name: "a".into(), size: 1, model: "LIF".into(), params: serde_json::json!({}), group: None, geometry: None, compartments: Vec::new() });
+        g.probes.push(Probe { target: "a".into(), kind: "v".into(), start_ms: Some(0.0), stop_ms: Some(100.0), ..Default::default() });
+        g.probes.push(Probe { target: "a".into(), kind: "v".into(), start_ms: Some(100.0), stop_ms: Some(200.0), ..Default::default() });
+        g.probes.push(Probe { target: "a".into(), kind: "v".into(), variables: vec!["v".into(), "spikes".into()], ..Default::default() });
+        assert_eq!(w601(&g), 0, "different windows and variables are distinct probes");
+        // Same recording under a different kind, variables in another order
+        g.probes.push(Probe { target: "a".into(), kind: "spikes".into(), variables: vec!["spikes".into(), "v".into()], ..Default::default() });
+        assert_eq!(w601(&g), 1);
+    }
+
+    #[test]
+    fn canonicalize_makes_equivalent_graphs_serialize_identically() {
+        let a = r#"{"name": "g",
+            "populations": [{"name": "b", "size": 2, "model": "LIF", "params": {"v_th": 1.0, "tau_m": 0.1}},
+                            {"name": "a", "size": 1, "model": "LIF"}],
+            "connections": [{"pre": "b", "post": "a", "weight": 0.5}, {"pre": "a", "post": "b", "weight": -0.0, "delay_ms": 2.0},
+                            {"pre": "a", "post": "b", "weight": 0.25, "delay_ms": 1.0}],
+            "probes": [{"target": "b", "kind": "v"}, {"target": "a", "kind": "spikes"}],
+            "attributes": {"zeta": {"y": 2.0, "x": 0.30000001192092896}, "hal_manifest_path": "/tmp/x.toml", "_run": 1}}"#;
+        let b = r#"{"name": "g",
+            "populations": [{"name": "a", "size": 1, "model": "LIF"},
+                            {"name": "b", "size": 2, "model": "LIF", "params": {"tau_m": 0.10000000149011612, "v_th": 1}}],
+            "connections": [{"pre": "a", "post": "b", "weight": 0.25, "delay_ms": 1.0}, {"pre": "b", "post": "a", "weight": 0.5},
+                            {"pre": "a", "post": "b", "weight": 0.0, "delay_ms": 2.0}],
+            "probes": [{"target": "a", "kind": "spikes"}, {"target": "b", "kind": "v"}],
+            "attributes": {"zeta": {"x": 0.3, "y": 2}}}"#;
+        let mut ga = Graph::from_json_str(a).unwrap();
+        let mut gb = Graph::from_json_str(b).unwrap();
+        assert_ne!(ga.to_json_string().unwrap(), gb.to_json_string().unwrap());
+        ga.canonicalize();
+        gb.canonicalize();
+        let text = ga.to_json_string().unwrap();
+        assert_eq!(text, gb.to_json_string().unwrap());
+
+        let names: Vec<&str> = ga.populations.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        let delays: Vec<f32> = ga.connections.iter().map(|c| c.delay_ms).collect();
+        assert_eq!(delays, [1.0, 2.0, 0.0]);
+        assert!(text.contains("\"tau_m\": 0.1,") && !text.contains("hal_manifest_path") && !text.contains("_run"));
+        assert!(!text.contains("-0.0"));
+
+        ga.canonicalize();
+        assert_eq!(ga.to_json_string().unwrap(), text, "canonicalize is idempotent");
+    }
+
+    #[test]
+    fn validate_all_reports_every_problem_with_codes_and_paths() {
+        let mut g = fixtures::chain(&[2, 3]);
+        assert!(g.validate_all().is_empty());
+        g.populations.push(Population {
+            name: "lonely".into(),
+            size: 0,
+            model: "lif".into(),
+            params: serde_json::json!({}),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
+        });
+        g.connections[0].delay_ms = -1.0;
+        g.probes.push(Probe { target: "ghost".into(), kind: "spikes".into(), ..Default::default() });
+        g.probes.push(Probe { target: "ghost".into(), kind: "spikes".into(), ..Default::default() });
+
+        let diags = g.validate_all();
+        let found: Vec<(&str, Severity, &str)> = diags.iter().map(|d| (d.code.as_str(), d.severity, d.path.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                ("E103", Severity::Error, "/populations/2"),
+                ("E209", Severity::Error, "/connections/0/delay_ms"),
+                ("E602", Severity::Error, "/probes/0"),
+                ("E602", Severity::Error, "/probes/1"),
+                ("W601", Severity::Warning, "/probes/1"),
+                ("W101", Severity::Warning, "/populations/2"),
+            ]
+        );
+        // `validate` fails with the first error only
+        assert_eq!(g.validate().unwrap_err().0, diags[0].message);
+
+        let table = render_table(&diags);
+        let mut lines = table.lines();
+        assert!(lines.next().unwrap().starts_with("SEVERITY  CODE  PATH"));
+        assert!(lines.next().unwrap().starts_with("error     E103  /populations/2"));
+        assert_eq!(table.lines().count(), diags.len() + 1);
+
+        // Warnings alone do not fail validation
+        g.populations.pop();
+        g.connections[0].delay_ms = 0.0;
+        g.probes.truncate(0);
+        g.probes.push(Probe { target: "p1".into(), kind: "spikes".into(), ..Default::default() });
+        g.probes.push(Probe { target: "p1".into(), kind: "spikes".into(), ..Default::default() });
+        assert_eq!(g.validate_all().len(), 1);
+        g.validate().unwrap();
+    }
+
+    #[test]
+    fn weights_ref_saves_loads_and_checks_shape() {
+        let dir = std::env::temp_dir().join(format!("nc_weights_ref_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut g = fixtures::chain(&[2, 3]);
+        g.connections[0].weight = 0.0;
+        let matrix = vec![vec![0.1, 0.2, 0.3], vec![-0.1, -0.2, -0.3]];
+        g.connections[0].weight_matrix = Some(matrix.clone());
+        assert_eq!(g.save_weight_refs(&dir, 7).unwrap(), 0, "below the size threshold");
+        assert_eq!(g.save_weight_refs(&dir, 6).unwrap(), 1);
+        let r = g.connections[0].weights_ref.clone().unwrap();
+        assert_eq!((r.file.as_str(), r.dtype, r.shape.as_deref()), ("w0_p0_p1.npy", TensorDtype::F32, Some(&[2u64, 3][..])));
+        g.validate().unwrap();
+
+        let json = g.to_json_string().unwrap();
+        assert!(json.contains("\"weights_ref\"") && json.contains("\"weight_matrix\": null"));
+        let mut back = Graph::from_json_str(&json).unwrap();
+        assert_eq!(back.load_weight_refs(&dir).unwrap(), 1);
+        assert_eq!(back.connections[0].weight_matrix, Some(matrix));
+        assert!(back.connections[0].weights_ref.is_none());
+
+        // Raw little-endian f64 needs an explicit shape, which must match the populations
+        let raw: Vec<u8> = (0..6).flat_map(|i| (i as f64).to_le_bytes()).collect();
+        std::fs::write(dir.join("w.bin"), raw).unwrap();
+        g.connections[0].weights_ref = Some(TensorRef { file: "w.bin".into(), dtype: TensorDtype::F64, shape: None });
+        let err = g.clone().load_weight_refs(&dir).unwrap_err();
+        assert!(err.0.contains("raw tensor needs a shape"), "{}", err.0);
+        g.connections[0].weights_ref.as_mut().unwrap().shape = Some(vec![3, 2]);
+        assert!(g.validate().unwrap_err().0.contains("shape [2, 3]"));
+        g.connections[0].weights_ref.as_mut().unwrap().shape = Some(vec![2, 3]);
+        g.load_weight_refs(&dir).unwrap();
+        assert_eq!(g.connections[0].weight_matrix.as_ref().unwrap()[1], vec![3.0, 4.0, 5.0]);
+
+        g.connections[0].weights_ref = Some(r);
+        assert!(g.validate().unwrap_err().0.contains("together with weight or weight_matrix"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn weight_stats_summarize_connection_weights() {
+        assert!(Graph::new("empty").weight_stats().is_none());
+
+        let mut g = fixtures::chain(&[1, 1, 1, 1, 1]);
+        for (c, w) in g.connections.iter_mut().zip([0.5f32, -1.5, 1.0, 2.0]) {
+            c.weight = w;
+        }
+        let s = g.weight_stats().expect("four connections");
+        assert_eq!((s.min, s.max), (-1.5, 2.0));
+        assert!((s.mean - 0.5).abs() < 1e-6);
+        assert_eq!(s.abs_max, 2.0);
+        // population std of {0.5, -1.5, 1.0, 2.0}
+        assert!((s.std - 1.274_755).abs() < 1e-5, "{}", s.std);
+    }
+
+    #[test]
+    fn modules_flatten_into_prefixed_populations() {
+        let json = r#"{
+            "name": "columns",
+            "populations": [{"name": "input", "size": 8, "model": "SpikeSource", "params": {}}],
+            "connections": [
+                {"pre": "input", "post": "c0.l4", "weight": 0.5},
+                {"pre": "input", "post": "c1.l4", "weight": 0.5},
+                {"pre": "c0.l23", "post": "c1.l4", "weight": 0.1}
+            ],
+            "modules": [{
+                "name": "column",
+                "populations": [
+                    {"name": "l4", "size": 4, "model": "LIF", "params": {}},
+                    {"name": "l23", "size": 6, "model": "LIF", "params": {}}
+                ],
+                "connections": [{"pre": "l4", "post": "l23", "weight": 1.0, "delay_ms": 1.0}],
+                "ports": ["l4", "l23"]
+            }],
+            "instances": [{"name": "c0", "module": "column"}, {"name": "c1", "module": "column"}]
+        }"#;
+        let g = Graph::from_json_str(json).unwrap();
+        g.validate().expect("modular graph validates through flattening");
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!((back.modules.len(), back.instances.len()), (1, 2));
+
+        let flat = g.flatten().unwrap();
+        assert!(flat.is_flat() && flat.modules.is_empty());
+        let names: Vec<&str> = flat.populations.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["input", "c0.l4", "c0.l23", "c1.l4", "c1.l23"]);
+        assert_eq!(flat.populations[3].group.as_deref(), Some("c1"));
+        assert_eq!(flat.connections.len(), 5);
+        assert!(flat.connections.iter().any(|c| c.pre == "c1.l4" && c.post == "c1.l23" && c.delay_ms == 1.0));
+
+        let mut hidden = g.clone();
+        hidden.modules[0].ports = vec!["l4".into()];
+        let err = hidden.flatten().unwrap_err();
+        assert!(err.0.contains("'c0.l23' is not a port"), "{}", err.0);
+
+        let mut cyclic = g.clone();
+        cyclic.modules[0].instances.push(ModuleInstance { name: "inner".into(), module: "column".into() });
+        assert!(cyclic.flatten().unwrap_err().0.contains("instantiates itself"));
+    }
+
+    #[test]
+    fn model_schemas_check_ranges_and_required_params() {
+        let mut g = fixtures::chain(&[4, 4]);
+        g.populations[1].params = serde_json::json!({"tau_m": -5.0});
+        let err = g.validate().unwrap_err();
+        assert!(err.0.contains("population 'p1' (LIF): tau_m = -5 must be > 0"), "{}", err.0);
+
+        g.populations[1].model = "izh".into();
+        g.populations[1].params = serde_json::json!({"a": 0.1});
+        g.validate().expect("izhikevich params in range");
+        assert_eq!(g.populations[1].param("a"), Some(0.1));
+        assert_eq!(g.populations[1].param("d"), Some(8.0), "schema default");
+        assert_eq!(g.populations[1].param("nope"), None);
+
+        register_model_schema(ModelSchema::new("TestGated", &[], vec![ParamSpec::required("gain").range(0.0, 2.0)]));
+        g.populations[1].model = "TestGated".into();
+        g.populations[1].params = serde_json::json!({});
+        assert!(g.validate().unwrap_err().0.contains("missing required parameter gain"));
+        g.populations[1].params = serde_json::json!({"gain": 1.5});
+        g.validate().expect("custom schema satisfied");
+    }
+
+    #[test]
+    fn projections_validate_dense_and_csr_shapes() {
+        let mut g = fixtures::chain(&[2, 3]);
+        g.projections.push(Projection {
+            pre: "p0".into(),
+            post: "p1".into(),
+            synapses: SynapseMatrix::Dense { weights: vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6], delays_ms: None },
+            delay_ms: 1.0,
+            plasticity: None,
+            delay_distribution: None,
+            delay_seed: 0,
+        });
+        g.projections.push(Projection {
+            pre: "p0".into(),
+            post: "p1".into(),
+            synapses: SynapseMatrix::Csr {
+                indptr: vec![0, 1, 3],
+                indices: vec![2, 0, 1],
+                weights: vec![1.0, -1.0, 0.5],
+                delays_ms: Some(vec![0.0, 2.0, 4.0]),
+            },
+            delay_ms: 0.0,
+            plasticity: None,
+            delay_distribution: None,
+            delay_seed: 0,
+        });
+        g.validate().expect("valid projections");
+
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!(back.projections[1].synapses, g.projections[1].synapses);
+        let syn: Vec<_> = back.projections[1].iter_synapses(3).collect();
+        assert_eq!(syn, [(0, 2, 1.0, 0.0), (1, 0, -1.0, 2.0), (1, 1, 0.5, 4.0)]);
+        assert_eq!(back.projections[0].iter_synapses(3).nth(4), Some((1, 1, 0.5, 1.0)));
+
+        g.projections[0].synapses = SynapseMatrix::Dense { weights: vec![0.0; 5], delays_ms: None };
+        assert!(g.validate().unwrap_err().0.contains("expected 2x3"));
+        g.projections.remove(0);
+        if let SynapseMatrix::Csr { indices, .. } = &mut g.projections[0].synapses {
+            indices[0] = 3;
+        }
+        assert!(g.validate().unwrap_err().0.contains("out of range"));
+    }
+
+    #[test]
+    fn connectivity_specs_round_trip_and_validate() {
+        let json = r#"{"name": "rules", "populations": [
+                {"name": "a", "size": 4, "model": "LIF", "params": {}},
+                {"name": "b", "size": 4, "model": "LIF", "params": {}}],
+            "connectivity": [
+                {"pre": "a", "post": "b", "rule": {"kind": "fixed_probability", "p": 0.25}, "weight": 0.5, "seed": 7},
+                {"pre": "b", "post": "b", "rule": {"kind": "gaussian_distance", "sigma": 1.5}}
+            ]}"#;
+        let mut g = Graph::from_json_str(json).unwrap();
+        g.validate().expect("valid rules");
+        assert_eq!(g.connectivity[1].rule, ConnectivityRule::GaussianDistance { sigma: 1.5, p_max: 1.0 });
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!(back.connectivity[0].rule, ConnectivityRule::FixedProbability { p: 0.25 });
+
+        g.connectivity[1].rule = ConnectivityRule::FixedInDegree { k: 4 };
+        let err = g.validate().unwrap_err();
+        assert!(err.0.contains("(fixed_in_degree): in-degree 4 exceeds the 3 available"), "{}", err.0);
+    }
+
+    #[test]
+    fn inputs_round_trip_and_validate() {
+        let json = r#"{"name": "driven", "populations": [{"name": "l1", "size": 8, "model": "LIF", "params": {}}],
+            "inputs": [
+                {"name": "bg", "target": "l1", "source": {"kind": "poisson", "rate_hz": 20.0}, "seed": 3},
+                {"name": "clock", "target": "l1", "source": {"kind": "regular", "period_ms": 5.0}},
+                {"name": "rec", "target": "l1", "source": {"kind": "spike_file", "path": "spikes.csv"}},
+                {"name": "cam", "target": "l1", "source": {"kind": "aer_stream", "uri": "tcp://dvs:7777", "width": 128, "height": 128}}
+            ]}"#;
+        let mut g = Graph::from_json_str(json).unwrap();
+        g.validate().expect("valid inputs");
+        assert_eq!(g.inputs[1].source.rate_hz(), Some(200.0));
+        let back = Graph::from_json_str(&g.to_json_string().unwrap()).unwrap();
+        assert_eq!(back.inputs[3].source, g.inputs[3].source);
+
+        g.inputs[0].source = InputSource::Poisson { rate_hz: -1.0 };
+        assert!(g.validate().unwrap_err().0.contains("input 'bg' (poisson)"));
+        g.inputs[0].source = InputSource::Poisson { rate_hz: 1.0 };
+        g.inputs[0].target = "missing".into();
+        assert!(g.validate().unwrap_err().0.contains("target 'missing' not found"));
+    }
+
+    #[test]
+    fn content_hash_ignores_attribute_order_and_number_format() {
+        let a = Graph::from_json_str(
+            r#"{"name": "h", "populations": [{"name": "p", "size": 2, "model": "LIF", "params": {"tau_m": 20, "v_th": 0.1}}],
+                "connections": [{"pre": "p", "post": "p", "weight": 0.3}],
+                "attributes": {"x": {"b": 1, "a": -0.0}, "y": [1.5]}}"#,
+        )
+        .unwrap();
+        let b = Graph::from_json_str(
+            r#"{"name": "h", "populations": [{"name": "p", "size": 2, "model": "LIF", "params": {"v_th": 1e-1, "tau_m": 20.0}}],
+                "connections": [{"pre": "p", "post": "p", "weight": 0.30000001192092896}],
+                "attributes": {"y": [1.50], "x": {"a": 0, "b": 1.0}}}"#,
+        )
+        .unwrap();
+        assert_eq!(a.content_hash().len(), 16);
+        assert_eq!(a.content_hash(), b.content_hash());
+        assert_eq!(a.content_hash(), Graph::from_yaml_str(&a.to_yaml_string().unwrap()).unwrap().content_hash());
+
+        let mut c = a.clone();
+        c.connections[0].weight = 0.31;
+        assert_ne!(a.content_hash(), c.content_hash());
+        let mut d = a.clone();
+        d.attributes.insert("z".into(), serde_json::json!(null));
+        assert_ne!(a.content_hash(), d.content_hash());
+    }
+
+    #[test]
+    fn geometry_layouts_position_neurons_and_validate() {
+        let json = r#"{"name": "space", "populations": [
+            {"name": "sheet", "size": 6, "model": "LIF", "geometry": {"layout": "grid", "shape": [3, 2], "spacing": 0.5}},
+            {"name": "ring", "size": 4, "model": "LIF", "geometry": {"layout": "ring", "radius": 2.0}},
+            {"name": "cloud", "size": 8, "model": "LIF",
+             "geometry": {"layout": "random_in_box", "min": [0, 0, 0], "max": [1, 2, 3], "seed": 7}},
+            {"name": "pts", "size": 2, "model": "LIF", "geometry": {"layout": "explicit", "positions": [[0, 1], [2, 3, 4]]}},
+            {"name": "line", "size": 3, "model": "LIF"}]}"#;
+        let mut g = Graph::from_json_str(json).unwrap();
+        g.validate().unwrap();
+        assert_eq!(g.populations[0].neuron_position(4), [0.5, 0.5, 0.0]);
+        let r = g.populations[1].neuron_position(1);
+        assert!(r[0].abs() < 1e-12 && (r[1] - 2.0).abs() < 1e-12);
+        let c = g.populations[2].neuron_position(5);
+        assert!((0.0..=1.0).contains(&c[0]) && (0.0..=2.0).contains(&c[1]) && (0.0..=3.0).contains(&c[2]));
+        assert_eq!(c, g.populations[2].neuron_position(5), "seeded positions are stable");
+        assert_eq!(g.populations[3].neuron_position(0), [0.0, 1.0, 0.0]);
+        assert_eq!(g.populations[4].neuron_position(2), [2.0, 0.0, 0.0]);
+
+        g.populations[0].geometry = Some(Geometry::Grid { shape: vec![4, 2], spacing: 1.0, origin: [0.0; 3] });
+        assert!(g.validate().unwrap_err().0.contains("population 'sheet' geometry (grid)"));
+        g.populations[0].geometry = None;
+        g.populations[3].geometry = Some(Geometry::Explicit { positions: vec![vec![0.0]; 2] });
+        assert!(g.validate().unwrap_err().0.contains("2 or 3 finite coordinates"));
+    }
+
+    #[test]
+    fn extract_cuts_with_boundary_probes_and_merge_renames() {
+        let mut g = fixtures::chain(&[2, 3, 4]);
+        g.probes.push(Probe { target: "p1".into(), kind: "spikes".into(), ..Default::default() });
+        g.probes.push(Probe { target: "p2".into(), kind: "spikes".into(), ..Default::default() });
+        let sub = g.extract(&["p1", "ghost"]);
+        assert_eq!(sub.populations.len(), 1);
+        assert!(sub.connections.is_empty());
+        let kinds: Vec<(&str, &str)> = sub.probes.iter().map(|p| (p.target.as_str(), p.kind.as_str())).collect();
+        assert_eq!(kinds, [("p1", "spikes"), ("p1", BOUNDARY_IN), ("p1", BOUNDARY_OUT)]);
+        assert_eq!(sub.attributes["subgraph"]["cut"].as_array().unwrap().len(), 2);
+        sub.validate().unwrap();
+
+        let head = g.extract(&["p0", "p1"]);
+        assert_eq!(head.connections.len(), 1);
+        let mut merged = head.clone();
+        let renames = merged.merge(&g.extract(&["p1", "p2"]), "b_").unwrap();
+        assert_eq!(renames.get("p1").map(String::as_str), Some("b_p1"));
+        assert_eq!(merged.populations.len(), 4);
+        assert!(merged.connections.iter().any(|c| c.pre == "b_p1" && c.post == "p2"));
+        assert!(merged.probes.iter().any(|p| p.target == "b_p1"));
+        merged.validate().unwrap();
+
+        let mut again = head.clone();
+        assert!(again.merge(&head, "").unwrap_err().0.contains("collides"));
+    }
+
+    #[test]
+    fn dot_and_mermaid_show_sizes_counts_and_parts() {
+        let mut g = Graph::from_edge_csv("viz", "a,b,0.5,1\na,b,0.1,2\nb,c,0.25,0\n", 4, "LIF").unwrap();
+        g.populations[0].size = 1000;
+        let dot = g.to_dot();
+        assert!(dot.starts_with("digraph \"viz\" {"), "{dot}");
+        assert!(dot.contains("\"a\" [label=\"a\\n1000\", width=2.25]"), "{dot}");
+        assert!(dot.contains("\"a\" -> \"b\" [penwidth=2.00, label=\"2\"]"), "{dot}");
+        assert!(dot.contains("\"b\" -> \"c\" [penwidth=1.00]"), "{dot}");
+        assert!(!dot.contains("fillcolor"));
+
+        g.attributes.insert(
+            "partition".into(),
+            serde_json::json!({"parts": 2, "assignment": [{"population": "a", "part": 0}, {"population": "c", "part": 1}]}),
+        );
+        let opts = VizOptions { color_by_partition: true };
+        let dot = g.to_dot_with(&opts);
+        assert_eq!(dot.matches("fillcolor").count(), 2);
+        assert!(dot.contains("tooltip=\"part 1\""));
+
+        let mmd = g.to_mermaid_with(&opts);
+        assert!(mmd.starts_with("flowchart LR\n"));
+        assert!(mmd.contains("n0[\"a<br/>1000\"]"));
+        assert!(mmd.contains("n0 -->|2| n1"));
+        assert!(mmd.contains("n1 --> n2"));
+        assert!(mmd.contains("classDef part1 fill:"));
+        assert!(mmd.contains("class n2 part1"));
+        assert!(!g.to_mermaid().contains("classDef"));
+    }
+
+    #[test]
+    fn old_documents_are_migrated_and_future_ones_rejected() {
+        let legacy = r#"{"name": "old",
+            "populations": [{"id": "a", "size": 2, "neuron_type": "LIF"}, {"id": "b", "size": 2, "neuron_type": "LIF"}],
+            "connections": [{"src": "a", "dst": "b", "weight": 0.5, "delay": 2.0}],
+            "attributes": {"nir_version": "0.0.1"}}"#;
+        for g in [Graph::from_json_str(legacy).unwrap(), Graph::from_json_reader(legacy.as_bytes()).unwrap()] {
+            assert_eq!((g.populations[0].name.as_str(), g.populations[0].model.as_str()), ("a", "LIF"));
+            assert_eq!((g.connections[0].pre.as_str(), g.connections[0].post.as_str()), ("a", "b"));
+            assert_eq!(g.connections[0].delay_ms, 2.0);
+            assert_eq!(g.attributes["nir_version"], VERSION);
+            g.validate().unwrap();
+        }
+        let yaml = "name: y\npopulations:\n  - id: a\n    size: 1\n    neuron_type: LIF\n";
+        assert_eq!(Graph::from_yaml_str(yaml).unwrap().populations[0].name, "a");
+
+        // Current documents pass through untouched
+        let g = fixtures::chain(&[2, 2]);
+        let text = g.to_json_string().unwrap();
+        assert_eq!(Graph::from_json_str(&text).unwrap().to_json_string().unwrap(), text);
+
+        let future = r#"{"name": "f", "attributes": {"nir_version": "99.0.0"}}"#;
+        assert!(Graph::from_json_str(future).unwrap_err().to_string().contains("newer than the supported"));
+        assert!(Graph::from_json_reader(future.as_bytes()).is_err());
+        let mut doc = serde_json::json!({"name": "m"});
+        assert!(migrate(&mut doc, "1.x").unwrap_err().0.contains("malformed nir_version"));
+        migrate(&mut doc, UNVERSIONED).unwrap();
+        assert_eq!(doc["attributes"]["nir_version"], VERSION);
+        assert_eq!(parse_version("0.10.2"), Some((0, 10, 2)));
+    }
+
+    #[test]
+    fn builder_produces_validated_graphs() {
+        let g = GraphBuilder::new("built")
+            .population("in", 4, Model::Custom { name: "source".into(), params: serde_json::json!({}) })
+            .population("exc", 8, Model::Lif(LifParams { tau_m: 10.0, ..Default::default() }))
+            .group("layer4")
+            .population("adapt", 2, Model::AdEx(AdExParams::default()))
+            .connect("in", "exc").weight(0.5).delay_ms(1.0)
+            .connect("exc", "adapt").weight(0.25)
+            .probe("exc", "spikes")
+            .build()
+            .unwrap();
+        assert_eq!(g.populations[1].model, "LIF");
+        assert_eq!(g.populations[1].param("tau_m"), Some(10.0));
+        assert_eq!(g.populations[1].group.as_deref(), Some("layer4"));
+        assert_eq!(g.populations[2].param("tau_w"), Some(144.0));
+        assert_eq!((g.connections[0].weight, g.connections[0].delay_ms), (0.5, 1.0));
+        assert_eq!(g.connections[1].delay_ms, 0.0);
+        assert_eq!(g.probes.len(), 1);
+        assert!(g.attributes.contains_key("nir_version"));
+
+        let err = GraphBuilder::new("bad").population("a", 1, Model::Lif(LifParams::default())).weight(1.0).build().unwrap_err();
+        assert!(err.0.contains("weight() must follow connect()"), "{}", err.0);
+        let err = GraphBuilder::new("bad")
+            .population("a", 1, Model::Lif(LifParams { tau_m: -1.0, ..Default::default() }))
+            .build()
+            .unwrap_err();
+        assert!(err.0.contains("tau_m"), "{}", err.0);
+        assert!(GraphBuilder::new("bad").connect("a", "missing").build().is_err());
+    }
+
+    #[test]
+    fn json_reader_matches_from_json_str() {
+        let mut g = fixtures::chain(&[3, 4, 2]);
+        g.connections[0].plasticity = Some(PlasticityRule { kind: PlasticityKind::STDP, params: serde_json::json!({}) });
+        g.attributes.insert("note".into(), serde_json::json!({"k": [1, 2]}));
+        let text = g.to_json_string().unwrap();
+        let streamed = Graph::from_json_reader(text.as_bytes()).unwrap();
+        assert_eq!(streamed.content_hash(), Graph::from_json_str(&text).unwrap().content_hash());
+
+        // Connections before populations, and errors inside the stream, are handled
+        let reordered = r#"{"connections": [{"pre": "a", "post": "a", "weight": 1.0}], "name": "r",
+            "populations": [{"name": "a", "size": 1, "model": "LIF"}]}"#;
+        assert_eq!(Graph::from_json_reader(reordered.as_bytes()).unwrap().connections.len(), 1);
+        assert!(Graph::from_json_reader(&br#"{"name": "x", "connections": [{"pre": 1}]}"#[..]).is_err());
+        assert!(Graph::from_json_reader(&br#"{"name": "x"} trailing"#[..]).is_err());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_store_holds_scalar_connections_by_row() {
+        let json = r#"{"name": "big", "connections": [
+                {"pre": "b", "post": "a", "weight": 0.5, "delay_ms": 2.0},
+                {"pre": "a", "post": "b", "weight": 0.25},
+                {"pre": "b", "post": "b", "weight": -1.0},
+                {"pre": "a", "post": "a", "weight": 0.1, "plasticity": {"kind": "Hebbian"}}],
+            "populations": [{"name": "a", "size": 2, "model": "LIF"}, {"name": "b", "size": 2, "model": "LIF"}]}"#;
+        let path = std::env::temp_dir().join(format!("nc_conn_store_{}.bin", std::process::id()));
+        let (g, store) = Graph::from_json_reader_mmap(json.as_bytes(), &path).unwrap();
+        assert_eq!(g.connections.len(), 1, "plastic connection stays in the graph");
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.row(0).collect::<Vec<_>>(), vec![(1, 0.25, 0.0)]);
+        assert_eq!(store.row(1).collect::<Vec<_>>(), vec![(0, 0.5, 2.0), (1, -1.0, 0.0)]);
+        let conns = store.to_connections();
+        assert_eq!((conns[1].pre.as_str(), conns[1].post.as_str()), ("b", "a"));
+        assert!(!path.with_extension("spill").exists());
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+
+        let bad = r#"{"name": "x", "connections": [{"pre": "ghost", "post": "ghost"}]}"#;
+        let err = Graph::from_json_reader_mmap(bad.as_bytes(), &path).err().unwrap();
+        assert!(err.to_string().contains("unknown population 'ghost'"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let before = Graph::from_edge_csv("d", "a,b,0.5,1\na,b,0.1,0\nb,c,0.25,0\n", 4, "LIF").unwrap();
+        let mut after = before.clone();
+        assert!(before.diff(&after).is_empty());
+
+        after.populations[0].size = 8;
+        after.connections[1].weight = 0.2;
+        after.connections.pop();
+        after.probes.push(Probe { target: "c".into(), kind: "spikes".into(), ..Default::default() });
+        after.attributes.insert("partition".into(), serde_json::json!({"parts": 2}));
+        let d = before.diff(&after);
+        assert_eq!(d.populations.changed.keys().collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(d.populations.changed["a"].after["size"], 8);
+        assert_eq!(d.connections.changed.keys().collect::<Vec<_>>(), vec!["a -> b#1"]);
+        assert!(d.connections.removed.contains_key("b -> c"));
+        assert!(d.probes.added.contains_key("c/spikes"));
+        assert_eq!(d.attributes.added["partition"]["parts"], 2);
+
+        let back: GraphDiff = serde_json::from_str(&serde_json::to_string(&d).unwrap()).unwrap();
+        assert_eq!(back, d);
+        assert!(after.diff(&before).connections.added.contains_key("b -> c"));
+    }
+
+    #[test]
+    fn diff_covers_projections_connectivity_inputs_and_modules() {
+        let before = Graph::from_json_str(
+            r#"{"name": "d", "populations": [{"name": "a", "size": 2, "model": "LIF"}, {"name": "b", "size": 1, "model": "LIF"}],
+                "projections": [{"pre": "a", "post": "b", "synapses": {"format": "dense", "weights": [0.5, 0.5]}}],
+                "connectivity": [{"pre": "b", "post": "a", "rule": {"kind": "all_to_all"}, "weight": 0.1}],
+                "inputs": [{"name": "bg", "target": "a", "source": {"kind": "poisson", "rate_hz": 5.0}}],
+                "modules": [{"name": "m", "populations": [{"name": "p", "size": 1, "model": "LIF"}]}],
+                "instances": [{"name": "m0", "module": "m"}]}"#,
+        )
+        .unwrap();
+        let mut after = before.clone();
+        assert!(before.diff(&after).is_empty());
+
+        after.projections[0].delay_ms = 2.0;
+        after.connectivity.clear();
+        after.inputs[0].source = InputSource::Regular { period_ms: 10.0, phase_ms: 0.0 };
+        after.modules[0].populations[0].size = 4;
+        after.instances.push(ModuleInstance { name: "m1".into(), module: "m".into() });
+        let d = before.diff(&after);
+        assert_eq!(d.projections.changed["a -> b"].after["delay_ms"], 2.0);
+        assert!(d.connectivity.removed.contains_key("b -> a"));
+        assert_eq!(d.inputs.changed["bg"].after["source"]["kind"], "regular");
+        assert!(d.modules.changed.contains_key("m"));
+        assert!(d.instances.added.contains_key("m1"));
+        assert!(d.populations.is_empty() && d.connections.is_empty());
+    }
+
     #[test]
     fn create_graph() {
         let g = Graph::new("test");
@@ -286,6 +1833,9 @@ mod tests {
             size: 10,
             model: "LIF".into(),
             params: serde_json::json!({ "tau": 10.0 }),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
         });
         let s = g.to_json_string().unwrap();
         let g2 = Graph::from_json_str(&s).unwrap();
@@ -302,6 +1852,8 @@ mod tests {
             weight: 0.5,
             delay_ms: 1.0,
             plasticity: None,
+            weight_matrix: None,
+            weights_ref: None,
         });
         let s = g.to_yaml_string().unwrap();
         let g2 = Graph::from_yaml_str(&s).unwrap();
@@ -317,12 +1869,18 @@ mod tests {
             size: 1,
             model: "LIF".into(),
             params: serde_json::json!({}),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
         });
         g.populations.push(Population {
             name: "b".into(),
             size: 2,
             model: "LIF".into(),
             params: serde_json::json!({}),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
         });
         g.connections.push(Connection {
             pre: "a".into(),
@@ -330,6 +1888,8 @@ mod tests {
             weight: 0.1,
             delay_ms: 0.0,
             plasticity: None,
+            weight_matrix: None,
+            weights_ref: None,
         });
         g.validate().unwrap();
     }
@@ -342,6 +1902,9 @@ mod tests {
             size: 1,
             model: "LIF".into(),
             params: serde_json::json!({}),
+            group: None,
+            geometry: None,
+            compartments: Vec::new(),
         });
         g.connections.push(Connection {
             pre: "missing".into(),
@@ -349,10 +1912,24 @@ mod tests {
             weight: 1.0,
             delay_ms: 0.0,
             plasticity: None,
+            weight_matrix: None,
+            weights_ref: None,
         });
         assert!(g.validate().is_err());
     }
 
+    #[test]
+    fn validate_with_delay_limit() {
+        let mut g = fixtures::chain(&[2, 2]);
+        g.connections[0].delay_ms = 99.9;
+        g.validate_with_limits(Some(100.0)).unwrap();
+        g.connections[0].delay_ms = 100.1;
+        let err = g.validate_with_limits(Some(100.0)).unwrap_err();
+        assert!(err.0.contains("exceeds limit"), "{err}");
+        g.validate_with_limits(None).unwrap();
+        g.validate().unwrap();
+    }
+
     #[test]
     fn version_tag() {
         let mut g = Graph::new("ver");
@@ -361,6 +1938,97 @@ mod tests {
         assert_eq!(g.attributes.get("nir_version").and_then(|v| v.as_str()), Some(VERSION));
     }
 
+    #[test]
+    fn rename_population_rewrites_edges_and_probes() {
+        let mut g = fixtures::star(8, 4, 3, 0.5, 1.0);
+        g.probes.push(Probe { target: "center".into(), kind: "spikes".into(), ..Default::default() });
+        g.inputs.push(Input { name: "bg".into(), target: "center".into(), source: InputSource::Poisson { rate_hz: 5.0 }, seed: 0 });
+        g.projections.push(Projection {
+            pre: "s0".into(),
+            post: "center".into(),
+            synapses: SynapseMatrix::Dense { weights: vec![0.5; 32], delays_ms: None },
+            delay_ms: 1.0,
+            plasticity: None,
+            delay_distribution: None,
+            delay_seed: 0,
+        });
+        g.rename_population("center", "hub").unwrap();
+        assert!(g.populations.iter().any(|p| p.name == "hub"));
+        assert!(g.connections.iter().all(|c| c.pre == "hub"));
+        assert_eq!(g.probes[0].target, "hub");
+        assert_eq!((g.projections[0].post.as_str(), g.inputs[0].target.as_str()), ("hub", "hub"));
+        g.validate().unwrap();
+
+        assert!(g.rename_population("center", "x").is_err());
+        assert!(g.rename_population("hub", "s0").is_err());
+    }
+
+    #[test]
+    fn remove_population_cascades() {
+        let mut g = fixtures::star(8, 4, 3, 0.5, 1.0);
+        g.probes.push(Probe { target: "center".into(), kind: "spikes".into(), ..Default::default() });
+        g.probes.push(Probe { target: "s1".into(), kind: "spikes".into(), ..Default::default() });
+        let projection = |pre: &str, post: &str| Projection {
+            pre: pre.into(),
+            post: post.into(),
+            synapses: SynapseMatrix::Dense { weights: vec![0.5; 32], delays_ms: None },
+            delay_ms: 1.0,
+            plasticity: None,
+            delay_distribution: None,
+            delay_seed: 0,
+        };
+        g.projections.push(projection("center", "s0"));
+        g.projections.push(projection("s0", "center"));
+        g.connectivity.push(ConnectivitySpec {
+            pre: "s1".into(),
+            post: "center".into(),
+            rule: ConnectivityRule::AllToAll,
+            weight: 0.1,
+            delay_ms: 1.0,
+            seed: 0,
+            autapses: false,
+            plasticity: None,
+            delay_distribution: None,
+        });
+        let input = |target: &str| Input { name: format!("in_{target}"), target: target.into(), source: InputSource::Poisson { rate_hz: 5.0 }, seed: 0 };
+        g.inputs.extend([input("center"), input("s2")]);
+        let removed = g.remove_population("center").unwrap();
+        assert_eq!(removed, RemovedSummary { connections: 3, probes: 1, projections: 2, connectivity: 1, inputs: 1 });
+        assert!(g.connections.is_empty() && g.projections.is_empty() && g.connectivity.is_empty());
+        assert_eq!(g.populations.len(), 3);
+        assert_eq!(g.probes.len(), 1);
+        assert_eq!(g.inputs.len(), 1);
+        g.validate().unwrap();
+
+        assert!(g.remove_population("center").is_err());
+    }
+
+    #[test]
+    fn stats_counts_chain() {
+        let mut g = fixtures::chain(&[10, 20, 30]);
+        g.connections[0].plasticity = Some(PlasticityRule { kind: PlasticityKind::STDP, params: serde_json::json!({}) });
+        let st = g.stats();
+        assert_eq!(st.populations, 3);
+        assert_eq!(st.connections, 2);
+        assert_eq!(st.total_neurons, 60);
+        assert_eq!(st.plastic_connections, 1);
+    }
+
+    #[test]
+    fn coo_matches_connections() {
+        let mut g = fixtures::ring(4, 2, 0.25, 1.0);
+        g.connections.push(g.connections[0].clone());
+        let coo = g.to_coo();
+        assert_eq!(coo.rows.len(), g.connections.len());
+        assert_eq!(coo.cols.len(), g.connections.len());
+        assert_eq!(coo.weights.len(), g.connections.len());
+        let n = g.populations.len();
+        assert!(coo.rows.iter().chain(&coo.cols).all(|&i| i < n));
+        assert_eq!((coo.rows[3], coo.cols[3]), (3, 0));
+        assert_eq!(g.connection_count_between("n0", "n1"), 2);
+        assert_eq!(g.connection_count_between("n1", "n0"), 0);
+    }
+
     #[cfg(feature = "bin")]
     #[test]
     fn bin_roundtrip() {
@@ -369,4 +2037,56 @@ mod tests {
         let g2 = Graph::from_bytes(&bytes).unwrap();
         assert_eq!(g2.name, "bin");
     }
+
+    #[cfg(feature = "bin")]
+    #[test]
+    fn bin_roundtrip_keeps_every_field() {
+        let g = Graph::from_json_str(
+            r#"{
+              "name": "full",
+              "populations": [
+                {"name": "a", "size": 4, "model": "LIF", "params": {"tau_m": 20.0, "taus": [1, 2]},
+                 "group": "l4", "geometry": {"layout": "grid", "shape": [2, 2]},
+                 "compartments": [
+                   {"name": "soma", "kind": "soma", "length_um": 20.0, "diameter_um": 20.0},
+                   {"name": "d0", "kind": "dendrite", "parent": "soma", "params": {"g_l": 0.1}}
+                 ]},
+                {"name": "b", "size": 2, "model": "LIF"}
+              ],
+              "connections": [
+                {"pre": "a", "post": "b", "weight_matrix": [[0.1, 0.2], [0.3, 0.4], [0.5, 0.6], [0.7, 0.8]],
+                 "plasticity": {"kind": "STDP", "params": {"a_plus": 0.01}}},
+                {"pre": "b", "post": "a", "weights_ref": {"file": "w.bin", "dtype": "f32", "shape": [2, 4]}}
+              ],
+              "probes": [
+                {"target": "a", "kind": "spikes", "variables": ["spikes", "v"], "interval_ms": 0.5,
+                 "start_ms": 10.0, "stop_ms": 20.0, "format": "npy"}
+              ],
+              "projections": [
+                {"pre": "a", "post": "b", "synapses": {"format": "csr", "indptr": [0, 1, 1, 2, 2],
+                 "indices": [0, 1], "weights": [0.5, 0.25], "delays_ms": [1.0, 2.0]}},
+                {"pre": "b", "post": "a", "synapses": {"format": "dense", "weights": [0, 0, 0, 0, 0, 0, 0, 0]},
+                 "delay_distribution": {"kind": "normal", "mean_ms": 2.0, "std_ms": 0.5, "max_ms": 4.0},
+                 "delay_seed": 7}
+              ],
+              "connectivity": [
+                {"pre": "a", "post": "a", "rule": {"kind": "gaussian_distance", "sigma": 1.5}, "weight": 0.2,
+                 "seed": 3, "delay_distribution": {"kind": "uniform", "low_ms": 1.0, "high_ms": 2.0}}
+              ],
+              "inputs": [
+                {"name": "dvs", "target": "a", "source": {"kind": "aer_stream", "uri": "tcp://cam:7777", "width": 2, "height": 2}},
+                {"name": "bg", "target": "b", "source": {"kind": "poisson", "rate_hz": 5.0}, "seed": 11}
+              ],
+              "modules": [
+                {"name": "m", "populations": [{"name": "p", "size": 1, "model": "LIF", "params": {"v_th": 1.0}}],
+                 "instances": [], "ports": ["p"]}
+              ],
+              "instances": [{"name": "m0", "module": "m"}],
+              "attributes": {"seed": 1, "meta": {"note": "x", "list": [1.5, null]}}
+            }"#,
+        )
+        .unwrap();
+        let g2 = Graph::from_bytes(&g.to_bytes().unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&g2).unwrap(), serde_json::to_value(&g).unwrap());
+    }
 }
@@ -0,0 +1,115 @@
+//! NIR document versions and upgrades. A document's version is its `attributes.nir_version`
+//! (`x.y.z`; absent means `0.0.1`, the version `ensure_version_tag` used to stamp). Loading a
+//! document runs every migration step between its version and `VERSION` on the raw JSON value,
+//! and refuses versions newer than `VERSION`.
+
+use crate::{ValidationError, VERSION};
+use serde_json::{Map, Value};
+
+/// Version assumed for documents without `attributes.nir_version`.
+pub const UNVERSIONED: &str = "0.0.1";
+
+/// Upgrade applied to documents older than `since`. `document` edits the whole document;
+/// `connection` edits one connection object and must leave an already-upgraded connection
+/// unchanged, since streamed connections are upgraded before the document version is known.
+struct Step {
+    since: (u64, u64, u64),
+    document: fn(&mut Map<String, Value>),
+    connection: fn(&mut Map<String, Value>),
+}
+
+const STEPS: &[Step] = &[Step { since: (0, 1, 0), document: v0_1_0_document, connection: v0_1_0_connection }];
+
+/// Move `old` to `new` unless `new` is already set.
+fn rename(obj: &mut Map<String, Value>, old: &str, new: &str) {
+    if !obj.contains_key(new) {
+        if let Some(v) = obj.remove(old) {
+            obj.insert(new.to_string(), v);
+        }
+    }
+}
+
+/// 0.1.0 adopts the `Graph` field names: populations `id`/`neuron_type` become
+/// `name`/`model`, connections `src`/`dst`/`delay` become `pre`/`post`/`delay_ms`.
+fn v0_1_0_document(doc: &mut Map<String, Value>) {
+    fn populations(list: Option<&mut Value>) {
+        for p in list.and_then(Value::as_array_mut).into_iter().flatten().filter_map(Value::as_object_mut) {
+            rename(p, "id", "name");
+            rename(p, "neuron_type", "model");
+        }
+    }
+    populations(doc.get_mut("populations"));
+    for m in doc.get_mut("modules").and_then(Value::as_array_mut).into_iter().flatten().filter_map(Value::as_object_mut) {
+        populations(m.get_mut("populations"));
+        for c in m.get_mut("connections").and_then(Value::as_array_mut).into_iter().flatten().filter_map(Value::as_object_mut) {
+            v0_1_0_connection(c);
+        }
+    }
+}
+
+fn v0_1_0_connection(c: &mut Map<String, Value>) {
+    rename(c, "src", "pre");
+    rename(c, "dst", "post");
+    rename(c, "delay", "delay_ms");
+}
+
+/// Parse `x.y.z`.
+pub fn parse_version(v: &str) -> Option<(u64, u64, u64)> {
+    let mut it = v.trim().split('.').map(|p| p.parse::<u64>().ok());
+    let parsed = (it.next()??, it.next()??, it.next()??);
+    it.next().is_none().then_some(parsed)
+}
+
+/// Upgrade the document `doc` (a JSON object) from `from_version` to `VERSION` in place and
+/// stamp `attributes.nir_version` if it was older. Versions newer than `VERSION` and
+/// malformed versions are errors.
+pub fn migrate(doc: &mut Value, from_version: &str) -> Result<(), ValidationError> {
+    let from = parse_version(from_version).ok_or_else(|| ValidationError(format!("malformed nir_version '{from_version}'")))?;
+    let current = parse_version(VERSION).expect("VERSION is x.y.z");
+    if from > current {
+        return Err(ValidationError(format!(
+            "nir_version {from_version} is newer than the supported {VERSION}; upgrade neuro-compiler"
+        )));
+    }
+    let Some(obj) = doc.as_object_mut() else {
+        return Err(ValidationError("NIR document must be an object".to_string()));
+    };
+    if from == current {
+        return Ok(());
+    }
+    for step in STEPS.iter().filter(|s| from < s.since) {
+        (step.document)(obj);
+        for c in obj.get_mut("connections").and_then(Value::as_array_mut).into_iter().flatten().filter_map(Value::as_object_mut) {
+            (step.connection)(c);
+        }
+    }
+    let attrs = obj.entry("attributes").or_insert_with(|| Value::Object(Map::new()));
+    if let Some(a) = attrs.as_object_mut() {
+        a.insert("nir_version".to_string(), Value::String(VERSION.to_string()));
+    }
+    Ok(())
+}
+
+/// Version recorded in a raw document, `UNVERSIONED` when absent.
+pub(crate) fn document_version(doc: &Value) -> Result<String, ValidationError> {
+    match doc.get("attributes").and_then(|a| a.get("nir_version")) {
+        None | Some(Value::Null) => Ok(UNVERSIONED.to_string()),
+        Some(Value::String(s)) => Ok(s.clone()),
+        Some(other) => Err(ValidationError(format!("nir_version must be a string, got {other}"))),
+    }
+}
+
+/// Upgrade a raw document from whatever version it records.
+pub(crate) fn upgrade_document(doc: &mut Value) -> Result<(), ValidationError> {
+    let from = document_version(doc)?;
+    migrate(doc, &from)
+}
+
+/// Apply every step's connection upgrade to one streamed connection.
+pub(crate) fn upgrade_connection(c: &mut Value) {
+    if let Some(obj) = c.as_object_mut() {
+        for step in STEPS {
+            (step.connection)(obj);
+        }
+    }
+}
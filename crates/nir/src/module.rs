@@ -0,0 +1,147 @@
+//! Hierarchical subgraphs: a `Module` is defined once under `Graph::modules` and instantiated
+//! by name through `ModuleInstance`s; `Graph::flatten` expands instances into the flat
+//! population/connection lists that passes and backends consume.
+
+use crate::{Connection, Graph, Population, ValidationError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Reusable population group with its internal connections.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Module {
+    pub name: String,
+    #[serde(default)]
+    pub populations: Vec<Population>,
+    /// Connections between the module's populations (or ports of nested instances, `inst.port`).
+    #[serde(default)]
+    pub connections: Vec<Connection>,
+    /// Nested instances of other modules.
+    #[serde(default)]
+    pub instances: Vec<ModuleInstance>,
+    /// Names, relative to the module, that connections outside it may use as endpoints
+    /// (`<instance>.<port>`). Anything not listed here is internal.
+    #[serde(default)]
+    pub ports: Vec<String>,
+}
+
+/// One instantiation of a module. Its populations are named `<instance>.<population>` after
+/// flattening.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleInstance {
+    pub name: String,
+    pub module: String,
+}
+
+impl Graph {
+    /// True when the graph has no module instances left to expand.
+    pub fn is_flat(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Expand every module instance into plain populations and connections and drop the
+    /// module definitions. Instance populations are named `<instance>.<population>` (nested
+    /// instances chain the prefix) and tagged with the instance path as `group` unless they
+    /// already have one. Connections may only reach into an instance through its module's
+    /// `ports`. A flat graph is returned unchanged.
+    pub fn flatten(&self) -> Result<Graph, ValidationError> {
+        let mut out = self.clone();
+        out.modules.clear();
+        out.instances.clear();
+        if self.is_flat() {
+            return Ok(out);
+        }
+        let mut modules: HashMap<&str, &Module> = HashMap::new();
+        for m in &self.modules {
+            if modules.insert(m.name.as_str(), m).is_some() {
+                return Err(ValidationError(format!("duplicate module '{}'", m.name)));
+            }
+        }
+        let local: HashSet<&str> = self.populations.iter().map(|p| p.name.as_str()).collect();
+        check_instances(&self.instances, &local, &modules, "graph")?;
+        for c in &self.connections {
+            check_endpoint(&c.pre, &self.instances, &modules)?;
+            check_endpoint(&c.post, &self.instances, &modules)?;
+        }
+        let mut stack: Vec<&str> = Vec::new();
+        for inst in &self.instances {
+            expand(inst, &inst.name, &modules, &mut stack, &mut out)?;
+        }
+        Ok(out)
+    }
+}
+
+/// Instance names must be unique, dot-free, distinct from sibling populations, and name a
+/// known module.
+fn check_instances(
+    instances: &[ModuleInstance],
+    local: &HashSet<&str>,
+    modules: &HashMap<&str, &Module>,
+    scope: &str,
+) -> Result<(), ValidationError> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    for inst in instances {
+        if inst.name.trim().is_empty() || inst.name.contains('.') {
+            return Err(ValidationError(format!("{scope}: invalid instance name '{}'", inst.name)));
+        }
+        if !seen.insert(inst.name.as_str()) || local.contains(inst.name.as_str()) {
+            return Err(ValidationError(format!("{scope}: duplicate name '{}'", inst.name)));
+        }
+        if !modules.contains_key(inst.module.as_str()) {
+            return Err(ValidationError(format!("instance '{}' references unknown module '{}'", inst.name, inst.module)));
+        }
+    }
+    Ok(())
+}
+
+/// An endpoint `inst.rest` that enters one of `instances` must name a declared port.
+fn check_endpoint(endpoint: &str, instances: &[ModuleInstance], modules: &HashMap<&str, &Module>) -> Result<(), ValidationError> {
+    let Some((head, rest)) = endpoint.split_once('.') else { return Ok(()) };
+    let Some(inst) = instances.iter().find(|i| i.name == head) else { return Ok(()) };
+    let m = modules[inst.module.as_str()];
+    if !m.ports.iter().any(|p| p == rest) {
+        return Err(ValidationError(format!(
+            "connection endpoint '{endpoint}' is not a port of module '{}'",
+            m.name
+        )));
+    }
+    Ok(())
+}
+
+fn expand<'a>(
+    inst: &ModuleInstance,
+    prefix: &str,
+    modules: &HashMap<&str, &'a Module>,
+    stack: &mut Vec<&'a str>,
+    out: &mut Graph,
+) -> Result<(), ValidationError> {
+    let m = modules[inst.module.as_str()];
+    if stack.contains(&m.name.as_str()) {
+        return Err(ValidationError(format!("module '{}' instantiates itself (via {})", m.name, stack.join(" -> "))));
+    }
+    stack.push(m.name.as_str());
+
+    let local: HashSet<&str> = m.populations.iter().map(|p| p.name.as_str()).collect();
+    check_instances(&m.instances, &local, modules, &format!("module '{}'", m.name))?;
+    for p in &m.populations {
+        out.populations.push(Population {
+            name: format!("{prefix}.{}", p.name),
+            group: p.group.clone().or_else(|| Some(prefix.to_string())),
+            ..p.clone()
+        });
+    }
+    for c in &m.connections {
+        check_endpoint(&c.pre, &m.instances, modules)?;
+        check_endpoint(&c.post, &m.instances, modules)?;
+        out.connections.push(Connection {
+            pre: format!("{prefix}.{}", c.pre),
+            post: format!("{prefix}.{}", c.post),
+            ..c.clone()
+        });
+    }
+    for nested in &m.instances {
+        expand(nested, &format!("{prefix}.{}", nested.name), modules, stack, out)?;
+    }
+
+    stack.pop();
+    Ok(())
+}
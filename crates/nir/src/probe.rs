@@ -0,0 +1,106 @@
+//! Probe recording settings: which variables, when, how often and in what file format.
+//! The settings are handed on, not acted on here: simulator emitters write them to
+//! `probes.json` for the driver script that runs the simulation, and the RISC-V runtimes get
+//! them as `NC_PROBE_*` constants in `graph.h`.
+
+use crate::{Graph, Probe};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Variables a probe can record in `Probe::variables`.
+pub const PROBE_VARIABLES: &[&str] = &["spikes", "v", "weights"];
+
+/// Preferred output format of a probe's recordings (a hint; tools may fall back to CSV).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeFormat {
+    #[default]
+    Csv,
+    Npy,
+    Jsonl,
+    Hdf5,
+}
+
+impl ProbeFormat {
+    /// Serialized name (`csv`, `npy`, `jsonl`, `hdf5`).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProbeFormat::Csv => "csv",
+            ProbeFormat::Npy => "npy",
+            ProbeFormat::Jsonl => "jsonl",
+            ProbeFormat::Hdf5 => "hdf5",
+        }
+    }
+}
+
+impl Probe {
+    /// Recorded variables: `variables` when set, otherwise just `kind`.
+    pub fn recorded_variables(&self) -> Vec<&str> {
+        if self.variables.is_empty() {
+            vec![self.kind.as_str()]
+        } else {
+            self.variables.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Settings with defaults filled in, as written by the simulator emitters:
+    /// `{target, kind, variables, interval_ms (null = every step), start_ms, stop_ms
+    /// (null = end of run), format}`.
+    pub fn resolved(&self) -> serde_json::Value {
+        serde_json::json!({
+            "target": self.target,
+            "kind": self.kind,
+            "variables": self.recorded_variables(),
+            "interval_ms": self.interval_ms,
+            "start_ms": self.start_ms.unwrap_or(0.0),
+            "stop_ms": self.stop_ms,
+            "format": self.format.unwrap_or_default().as_str(),
+        })
+    }
+
+    /// What the probe records, for duplicate detection (W601): target, recorded variables in
+    /// sorted order, window, interval and format. Probes differing only in `kind` but recording
+    /// the same variables are duplicates.
+    pub(crate) fn recording_key(&self) -> String {
+        let mut vars = self.recorded_variables();
+        vars.sort_unstable();
+        let r = self.resolved();
+        format!("{}|{}|{}|{}|{}|{}", self.target, vars.join(","), r["start_ms"], r["stop_ms"], r["interval_ms"], r["format"])
+    }
+
+    /// Check the sampling window and interval (`Err((code, message))`).
+    pub(crate) fn check_settings(&self) -> Result<(), (&'static str, String)> {
+        let mut seen: Vec<&str> = Vec::new();
+        for v in &self.variables {
+            if !PROBE_VARIABLES.contains(&v.as_str()) || seen.contains(&v.as_str()) {
+                return Err(("E604", format!("variable '{v}' is unknown or repeated (expected {})", PROBE_VARIABLES.join(", "))));
+            }
+            seen.push(v);
+        }
+        if let Some(i) = self.interval_ms.filter(|i| !(i.is_finite() && *i > 0.0)) {
+            return Err(("E603", format!("interval_ms {i} must be > 0")));
+        }
+        let start = self.start_ms.unwrap_or(0.0);
+        if !(start.is_finite() && start >= 0.0) {
+            return Err(("E603", format!("start_ms {start} must be >= 0")));
+        }
+        if let Some(stop) = self.stop_ms {
+            if !(stop.is_finite() && stop > start) {
+                return Err(("E603", format!("stop_ms {stop} must be after start_ms {start}")));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Write the resolved settings of every probe to `dir/probes.json` for the simulator's
+    /// driver script; nothing is written when the graph has no probes.
+    pub fn write_probe_settings(&self, dir: &Path) -> std::io::Result<()> {
+        if self.probes.is_empty() {
+            return Ok(());
+        }
+        let probes: Vec<serde_json::Value> = self.probes.iter().map(Probe::resolved).collect();
+        std::fs::write(dir.join("probes.json"), serde_json::to_string_pretty(&probes)?)
+    }
+}
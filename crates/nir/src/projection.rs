@@ -0,0 +1,136 @@
+//! Population-to-population projections carrying per-synapse weight (and optional delay)
+//! arrays, dense or CSR, so large layers do not need one `Connection` per synapse.
+
+use crate::{DelayDistribution, PlasticityRule};
+use serde::{Deserialize, Serialize};
+
+/// Per-synapse storage of a projection. Rows are pre neurons, columns post neurons.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "lowercase")]
+pub enum SynapseMatrix {
+    /// Row-major `pre.size * post.size` weights (a 0.0 entry is still a synapse).
+    Dense {
+        weights: Vec<f32>,
+        /// Per-synapse delays, same layout as `weights`.
+        #[serde(default)]
+        delays_ms: Option<Vec<f32>>,
+    },
+    /// Compressed sparse rows: pre neuron `i` connects to `indices[indptr[i]..indptr[i + 1]]`
+    /// with the matching `weights` (and `delays_ms`).
+    Csr {
+        indptr: Vec<u32>,
+        indices: Vec<u32>,
+        weights: Vec<f32>,
+        #[serde(default)]
+        delays_ms: Option<Vec<f32>>,
+    },
+}
+
+/// Connection group between two populations with explicit synapses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Projection {
+    pub pre: String,
+    pub post: String,
+    #[serde(with = "crate::binary")]
+    pub synapses: SynapseMatrix,
+    /// Delay for every synapse when the matrix carries no `delays_ms`.
+    #[serde(default)]
+    pub delay_ms: f32,
+    #[serde(default)]
+    pub plasticity: Option<PlasticityRule>,
+    /// Per-synapse delays drawn from a distribution; exclusive with `delays_ms`.
+    #[serde(default, with = "crate::binary")]
+    pub delay_distribution: Option<DelayDistribution>,
+    /// Seed for `delay_distribution`; synapse `k` always gets the same delay.
+    #[serde(default)]
+    pub delay_seed: u64,
+}
+
+impl Projection {
+    /// Check `delay_distribution` (see `DelayDistribution::check`); it cannot be combined with
+    /// per-synapse `delays_ms`.
+    pub(crate) fn check_delay_distribution(&self, max_delay_ms: Option<f32>) -> Result<(), String> {
+        let Some(dist) = &self.delay_distribution else { return Ok(()) };
+        let (SynapseMatrix::Dense { delays_ms, .. } | SynapseMatrix::Csr { delays_ms, .. }) = &self.synapses;
+        if delays_ms.is_some() {
+            return Err("delay_distribution and delays_ms are exclusive".to_string());
+        }
+        dist.check(max_delay_ms)
+    }
+
+    /// Number of synapses.
+    pub fn nnz(&self) -> usize {
+        match &self.synapses {
+            SynapseMatrix::Dense { weights, .. } | SynapseMatrix::Csr { weights, .. } => weights.len(),
+        }
+    }
+
+    /// Synapses as `(pre_index, post_index, weight, delay_ms)`, row by row, for a validated
+    /// projection. `post_size` is the size of the post population (needed to index dense
+    /// storage). Delays come from `delays_ms`, else `delay_distribution`, else `delay_ms`.
+    pub fn iter_synapses(&self, post_size: u32) -> Box<dyn Iterator<Item = (u32, u32, f32, f32)> + '_> {
+        let delay = move |d: &Option<Vec<f32>>, k: usize| match (d, &self.delay_distribution) {
+            (Some(d), _) => d[k],
+            (None, Some(dist)) => dist.sample(self.delay_seed, k),
+            (None, None) => self.delay_ms,
+        };
+        match &self.synapses {
+            SynapseMatrix::Dense { weights, delays_ms } => {
+                let cols = post_size.max(1) as usize;
+                Box::new(weights.iter().enumerate().map(move |(k, &w)| ((k / cols) as u32, (k % cols) as u32, w, delay(delays_ms, k))))
+            }
+            SynapseMatrix::Csr { indptr, indices, weights, delays_ms } => Box::new(indptr.windows(2).enumerate().flat_map(move |(i, r)| {
+                (r[0] as usize..r[1] as usize).map(move |k| (i as u32, indices[k], weights[k], delay(delays_ms, k)))
+            })),
+        }
+    }
+
+    /// Check array shapes against the endpoint sizes and that every value is finite (delays
+    /// also non-negative and, when given, at most `max_delay_ms`).
+    pub(crate) fn check(&self, pre_size: u32, post_size: u32, max_delay_ms: Option<f32>) -> Result<(), String> {
+        let (pre_n, post_n) = (pre_size as usize, post_size as usize);
+        let delays = match &self.synapses {
+            SynapseMatrix::Dense { weights, delays_ms } => {
+                if weights.len() != pre_n * post_n {
+                    return Err(format!("dense weights have {} entries, expected {pre_n}x{post_n}", weights.len()));
+                }
+                delays_ms
+            }
+            SynapseMatrix::Csr { indptr, indices, weights, delays_ms } => {
+                if indptr.len() != pre_n + 1 || indptr.first() != Some(&0) {
+                    return Err(format!("csr indptr must have {} entries starting at 0", pre_n + 1));
+                }
+                if indptr.windows(2).any(|w| w[0] > w[1]) || indptr[pre_n] as usize != indices.len() {
+                    return Err("csr indptr must be non-decreasing and end at indices.len()".to_string());
+                }
+                if weights.len() != indices.len() {
+                    return Err(format!("csr has {} indices but {} weights", indices.len(), weights.len()));
+                }
+                if let Some(&j) = indices.iter().find(|&&j| j >= post_size) {
+                    return Err(format!("csr post index {j} out of range for size {post_size}"));
+                }
+                delays_ms
+            }
+        };
+        if let Some(d) = delays {
+            if d.len() != self.nnz() {
+                return Err(format!("{} delays for {} synapses", d.len(), self.nnz()));
+            }
+        }
+        if self.iter_synapses(post_size).any(|(_, _, w, _)| !w.is_finite()) {
+            return Err("non-finite weight".to_string());
+        }
+        let delay_values = delays.iter().flatten().copied().chain(std::iter::once(self.delay_ms));
+        for d in delay_values {
+            if !d.is_finite() || d < 0.0 {
+                return Err(format!("invalid delay_ms {d}"));
+            }
+            if let Some(max) = max_delay_ms {
+                if d > max {
+                    return Err(format!("delay_ms {d} exceeds limit {max}"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
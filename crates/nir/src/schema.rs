@@ -0,0 +1,189 @@
+//! Typed parameter schemas for neuron models. `Population.params` stays a JSON object for
+//! compatibility; a schema names the numeric parameters a model understands, their defaults
+//! and valid ranges, so `Graph::validate` can check them and backends can read them through
+//! `Population::param` instead of guessing keys.
+//!
+//! Built-in schemas cover LIF, Izhikevich and AdEx. Custom models are added at runtime with
+//! `register_model_schema`; models without a schema keep free-form params.
+
+use crate::{Graph, Population};
+use std::sync::{OnceLock, RwLock};
+
+/// One numeric model parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    pub name: String,
+    /// Value used when the parameter is omitted; `None` makes it required.
+    pub default: Option<f64>,
+    /// Inclusive lower bound, or exclusive when `min_exclusive` is set.
+    pub min: Option<f64>,
+    pub min_exclusive: bool,
+    /// Inclusive upper bound.
+    pub max: Option<f64>,
+}
+
+impl ParamSpec {
+    /// Optional parameter with a default and no range.
+    pub fn with_default(name: &str, default: f64) -> Self {
+        Self { name: name.to_string(), default: Some(default), min: None, min_exclusive: false, max: None }
+    }
+
+    /// Required parameter without a default.
+    pub fn required(name: &str) -> Self {
+        Self { name: name.to_string(), default: None, min: None, min_exclusive: false, max: None }
+    }
+
+    /// Restrict to `> 0` (time constants, capacitances).
+    pub fn positive(mut self) -> Self {
+        self.min = Some(0.0);
+        self.min_exclusive = true;
+        self
+    }
+
+    /// Restrict to `>= min`.
+    pub fn at_least(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self.min_exclusive = false;
+        self
+    }
+
+    /// Restrict to `[min, max]`.
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.min_exclusive = false;
+        self.max = Some(max);
+        self
+    }
+
+    fn check(&self, v: f64) -> Result<(), String> {
+        if let Some(lo) = self.min {
+            if v < lo || (self.min_exclusive && v == lo) {
+                let op = if self.min_exclusive { ">" } else { ">=" };
+                return Err(format!("{} = {v} must be {op} {lo}", self.name));
+            }
+        }
+        if let Some(hi) = self.max {
+            if v > hi {
+                return Err(format!("{} = {v} must be <= {hi}", self.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parameter schema for a neuron model, matched case-insensitively on `name` or `aliases`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSchema {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub params: Vec<ParamSpec>,
+}
+
+impl ModelSchema {
+    pub fn new(name: &str, aliases: &[&str], params: Vec<ParamSpec>) -> Self {
+        Self { name: name.to_string(), aliases: aliases.iter().map(|a| a.to_string()).collect(), params }
+    }
+
+    fn matches(&self, model: &str) -> bool {
+        self.name.eq_ignore_ascii_case(model) || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(model))
+    }
+
+    /// Check `params` (a JSON object or null): every required parameter is present, and every
+    /// known parameter is a number within range. Keys the schema does not list are ignored.
+    pub fn check(&self, params: &serde_json::Value) -> Result<(), String> {
+        let obj = match params {
+            serde_json::Value::Null => None,
+            serde_json::Value::Object(m) => Some(m),
+            _ => return Err("params must be an object".to_string()),
+        };
+        for spec in &self.params {
+            match obj.and_then(|m| m.get(&spec.name)) {
+                None if spec.default.is_none() => return Err(format!("missing required parameter {}", spec.name)),
+                None => {}
+                Some(v) => {
+                    let x = v.as_f64().ok_or_else(|| format!("{} must be a number", spec.name))?;
+                    spec.check(x)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn builtin_schemas() -> Vec<ModelSchema> {
+    vec![
+        ModelSchema::new("LIF", &["lif", "leaky_integrate_and_fire"], vec![
+            ParamSpec::with_default("tau_m", 20.0).positive(),
+            ParamSpec::with_default("v_th", 1.0),
+            ParamSpec::with_default("v_reset", 0.0),
+            ParamSpec::with_default("v_rest", 0.0),
+            ParamSpec::with_default("t_ref", 0.0).at_least(0.0),
+        ]),
+        ModelSchema::new("Izhikevich", &["izh"], vec![
+            ParamSpec::with_default("a", 0.02).positive(),
+            ParamSpec::with_default("b", 0.2),
+            ParamSpec::with_default("c", -65.0),
+            ParamSpec::with_default("d", 8.0),
+            ParamSpec::with_default("v_peak", 30.0),
+        ]),
+        ModelSchema::new("AdEx", &["adex", "aeif"], vec![
+            ParamSpec::with_default("c_m", 281.0).positive(),
+            ParamSpec::with_default("g_l", 30.0).positive(),
+            ParamSpec::with_default("e_l", -70.6),
+            ParamSpec::with_default("v_t", -50.4),
+            ParamSpec::with_default("delta_t", 2.0).positive(),
+            ParamSpec::with_default("a", 4.0),
+            ParamSpec::with_default("b", 80.5),
+            ParamSpec::with_default("tau_w", 144.0).positive(),
+            ParamSpec::with_default("v_reset", -70.6),
+            ParamSpec::with_default("v_peak", 20.0),
+        ]),
+    ]
+}
+
+fn registry() -> &'static RwLock<Vec<ModelSchema>> {
+    static REGISTRY: OnceLock<RwLock<Vec<ModelSchema>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(builtin_schemas()))
+}
+
+/// Schema registered for `model` (case-insensitive, aliases included).
+pub fn model_schema(model: &str) -> Option<ModelSchema> {
+    registry().read().expect("model schema registry poisoned").iter().find(|s| s.matches(model)).cloned()
+}
+
+/// Add a schema for a custom model, replacing any schema with the same name.
+pub fn register_model_schema(schema: ModelSchema) {
+    let mut reg = registry().write().expect("model schema registry poisoned");
+    reg.retain(|s| !s.name.eq_ignore_ascii_case(&schema.name));
+    reg.push(schema);
+}
+
+impl Population {
+    /// Numeric parameter `key`: the explicit value in `params`, else the model schema's
+    /// default. `None` when neither exists.
+    pub fn param(&self, key: &str) -> Option<f64> {
+        if let Some(v) = self.params.get(key).and_then(|v| v.as_f64()) {
+            return Some(v);
+        }
+        model_schema(&self.model)?.params.iter().find(|p| p.name == key)?.default
+    }
+
+    /// Whether the model is a spike source (`source`, `input`, `SpikeSource*`): an input
+    /// rather than a neuron, so every simulator and backend can drive it.
+    pub fn is_spike_source(&self) -> bool {
+        let model = self.model.to_ascii_lowercase();
+        model == "source" || model == "input" || model.starts_with("spikesource")
+    }
+}
+
+impl Graph {
+    /// One warning per population whose model `simulator` cannot simulate: neither in
+    /// `supported` (case-insensitive) nor a spike source. Shared by the simulator emitters.
+    pub fn unsupported_model_warnings(&self, simulator: &str, supported: &[&str]) -> Vec<String> {
+        self.populations
+            .iter()
+            .filter(|p| !p.is_spike_source() && !supported.iter().any(|m| m.eq_ignore_ascii_case(&p.model)))
+            .map(|p| format!("population '{}': model '{}' is not supported by {simulator}", p.name, p.model))
+            .collect()
+    }
+}
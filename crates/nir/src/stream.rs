@@ -0,0 +1,268 @@
+//! Streaming JSON input for graphs too large to hold as text. `Graph::from_json_reader` decodes
+//! (and upgrades, see `migrate`) connections one at a time straight from the reader; with the
+//! `mmap` feature, `Graph::from_json_reader_mmap` moves plain connections into a memory-mapped
+//! CSR `ConnectionStore` instead of `Graph::connections`. The store is for callers that can
+//! consume connections row by row; passes and backends need `Graph::connections` and so do
+//! not use it.
+
+use crate::{Connection, Graph};
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use std::io::Read;
+
+impl Graph {
+    /// Parse NIR JSON from `reader` without first reading it into a string. Connections are
+    /// decoded element by element; everything else goes through the regular serde path.
+    pub fn from_json_reader<R: Read>(reader: R) -> Result<Graph, serde_json::Error> {
+        let mut connections = Vec::new();
+        let mut g = read_streaming(reader, |c| {
+            connections.push(c);
+            Ok(())
+        })?;
+        g.connections = connections;
+        Ok(g)
+    }
+}
+
+/// Parse a graph, handing each element of `connections` to `sink` instead of collecting it.
+/// The returned graph has no connections.
+pub(crate) fn read_streaming<R: Read, F>(reader: R, sink: F) -> Result<Graph, serde_json::Error>
+where
+    F: FnMut(Connection) -> Result<(), String>,
+{
+    let mut de = serde_json::Deserializer::from_reader(std::io::BufReader::new(reader));
+    let g = GraphSeed { sink }.deserialize(&mut de)?;
+    de.end()?;
+    Ok(g)
+}
+
+struct GraphSeed<F> {
+    sink: F,
+}
+
+impl<'de, F: FnMut(Connection) -> Result<(), String>> DeserializeSeed<'de> for GraphSeed<F> {
+    type Value = Graph;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Graph, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(Connection) -> Result<(), String>> Visitor<'de> for GraphSeed<F> {
+    type Value = Graph;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a NIR graph object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Graph, A::Error> {
+        // Fields other than `connections` are small next to it; collect them, upgrade them like
+        // `from_json_str` does, and let the derived `Deserialize` build the graph so new fields
+        // need no change here.
+        let mut rest = serde_json::Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "connections" {
+                map.next_value_seed(ConnectionSeq { sink: &mut self.sink })?;
+            } else {
+                rest.insert(key, map.next_value()?);
+            }
+        }
+        let mut rest = serde_json::Value::Object(rest);
+        crate::migrate::upgrade_document(&mut rest).map_err(de::Error::custom)?;
+        serde_json::from_value(rest).map_err(de::Error::custom)
+    }
+}
+
+struct ConnectionSeq<'a, F> {
+    sink: &'a mut F,
+}
+
+impl<'de, F: FnMut(Connection) -> Result<(), String>> DeserializeSeed<'de> for ConnectionSeq<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(Connection) -> Result<(), String>> Visitor<'de> for ConnectionSeq<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a list of connections")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(mut v) = seq.next_element::<serde_json::Value>()? {
+            crate::migrate::upgrade_connection(&mut v);
+            let c: Connection = serde_json::from_value(v).map_err(de::Error::custom)?;
+            (self.sink)(c).map_err(de::Error::custom)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "mmap")]
+pub use store::ConnectionStore;
+
+#[cfg(feature = "mmap")]
+mod store {
+    use super::read_streaming;
+    use crate::{Connection, Graph};
+    use std::collections::HashMap;
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{self, BufReader, BufWriter, Read, Write};
+    use std::path::Path;
+
+    /// Bytes per stored synapse: post index (u32), weight (f32), delay_ms (f32), little-endian.
+    const RECORD: usize = 12;
+
+    /// Scalar connections in CSR form over population indices, kept in a memory-mapped file.
+    /// Row `i` holds the connections whose `pre` is `populations[i]`, in file order.
+    pub struct ConnectionStore {
+        populations: Vec<String>,
+        indptr: Vec<u64>,
+        map: memmap2::Mmap,
+    }
+
+    impl ConnectionStore {
+        /// Number of stored connections.
+        pub fn len(&self) -> usize {
+            self.indptr.last().copied().unwrap_or(0) as usize
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// `(post_index, weight, delay_ms)` for every connection leaving population `pre`.
+        pub fn row(&self, pre: usize) -> impl Iterator<Item = (u32, f32, f32)> + '_ {
+            let (start, end) = (self.indptr[pre] as usize, self.indptr[pre + 1] as usize);
+            self.map[start * RECORD..end * RECORD].chunks_exact(RECORD).map(|r| {
+                let word = |k: usize| [r[k], r[k + 1], r[k + 2], r[k + 3]];
+                (u32::from_le_bytes(word(0)), f32::from_le_bytes(word(4)), f32::from_le_bytes(word(8)))
+            })
+        }
+
+        /// `(pre_index, post_index, weight, delay_ms)` row by row.
+        pub fn iter(&self) -> impl Iterator<Item = (u32, u32, f32, f32)> + '_ {
+            (0..self.populations.len()).flat_map(move |i| self.row(i).map(move |(j, w, d)| (i as u32, j, w, d)))
+        }
+
+        /// Materialize the stored connections (row order), e.g. for passes that need
+        /// `Graph::connections`.
+        pub fn to_connections(&self) -> Vec<Connection> {
+            self.iter()
+                .map(|(i, j, w, d)| Connection {
+                    pre: self.populations[i as usize].clone(),
+                    post: self.populations[j as usize].clone(),
+                    weight: w,
+                    delay_ms: d,
+                    plasticity: None,
+                    weight_matrix: None,
+                    weights_ref: None,
+                })
+                .collect()
+        }
+    }
+
+    impl Graph {
+        /// Like `from_json_reader`, but connections without plasticity or per-synapse weights are
+        /// written to `store_path` as a memory-mapped `ConnectionStore` rather than kept in
+        /// `Graph::connections` (which keeps the others). Memory use stays proportional to
+        /// the populations, not the connections. A `<store_path>.spill` file is used while
+        /// parsing and removed afterwards.
+        pub fn from_json_reader_mmap<R: Read>(reader: R, store_path: &Path) -> io::Result<(Graph, ConnectionStore)> {
+            let spill_path = store_path.with_extension("spill");
+            let mut spill = BufWriter::new(File::create(&spill_path)?);
+            // Endpoint names are interned in order of appearance; populations may follow connections
+            let mut ids: HashMap<String, u32> = HashMap::new();
+            let mut names: Vec<String> = Vec::new();
+            let mut intern = |name: String| -> u32 {
+                *ids.entry(name).or_insert_with_key(|n| {
+                    names.push(n.clone());
+                    (names.len() - 1) as u32
+                })
+            };
+            let mut counts: Vec<u64> = Vec::new();
+            let mut kept = Vec::new();
+            let parsed = read_streaming(reader, |c| {
+                if c.plasticity.is_some() || c.weight_matrix.is_some() || c.weights_ref.is_some() {
+                    kept.push(c);
+                    return Ok(());
+                }
+                let (pre, post) = (intern(c.pre), intern(c.post));
+                if counts.len() <= pre as usize {
+                    counts.resize(pre as usize + 1, 0);
+                }
+                counts[pre as usize] += 1;
+                let mut rec = [0u8; 16];
+                rec[0..4].copy_from_slice(&pre.to_le_bytes());
+                rec[4..8].copy_from_slice(&post.to_le_bytes());
+                rec[8..12].copy_from_slice(&c.weight.to_le_bytes());
+                rec[12..16].copy_from_slice(&c.delay_ms.to_le_bytes());
+                spill.write_all(&rec).map_err(|e| e.to_string())
+            });
+            let mut g = match parsed {
+                Ok(g) => g,
+                Err(e) => {
+                    let _ = fs::remove_file(&spill_path);
+                    return Err(e.into());
+                }
+            };
+            spill.flush()?;
+            drop(spill);
+            g.connections = kept;
+
+            // Interned endpoint -> population index
+            let pop_index: HashMap<&str, u32> =
+                g.populations.iter().enumerate().map(|(i, p)| (p.name.as_str(), i as u32)).collect();
+            let mut to_pop = Vec::with_capacity(names.len());
+            for n in &names {
+                match pop_index.get(n.as_str()) {
+                    Some(&i) => to_pop.push(i),
+                    None => {
+                        let _ = fs::remove_file(&spill_path);
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("connection references unknown population '{n}'"),
+                        ));
+                    }
+                }
+            }
+            let mut indptr = vec![0u64; g.populations.len() + 1];
+            for (id, n) in counts.iter().enumerate() {
+                indptr[to_pop[id] as usize + 1] += n;
+            }
+            for i in 0..g.populations.len() {
+                indptr[i + 1] += indptr[i];
+            }
+            let total = indptr[g.populations.len()];
+
+            // Scatter spilled records into their rows
+            let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(store_path)?;
+            file.set_len(total * RECORD as u64)?;
+            if total > 0 {
+                // SAFETY: the file was just created by us and is not shared while mapped.
+                let mut out = unsafe { memmap2::MmapMut::map_mut(&file)? };
+                let mut cursor = indptr.clone();
+                let mut reader = BufReader::new(File::open(&spill_path)?);
+                let mut rec = [0u8; 16];
+                for _ in 0..total {
+                    reader.read_exact(&mut rec)?;
+                    let pre = to_pop[u32::from_le_bytes([rec[0], rec[1], rec[2], rec[3]]) as usize] as usize;
+                    let post = to_pop[u32::from_le_bytes([rec[4], rec[5], rec[6], rec[7]]) as usize];
+                    let at = cursor[pre] as usize * RECORD;
+                    out[at..at + 4].copy_from_slice(&post.to_le_bytes());
+                    out[at + 4..at + RECORD].copy_from_slice(&rec[8..16]);
+                    cursor[pre] += 1;
+                }
+                out.flush()?;
+            }
+            fs::remove_file(&spill_path)?;
+            // SAFETY: as above; the mapping is read-only from here on.
+            let map = unsafe { memmap2::Mmap::map(&file)? };
+            let populations = g.populations.iter().map(|p| p.name.clone()).collect();
+            Ok((g, ConnectionStore { populations, indptr, map }))
+        }
+    }
+}
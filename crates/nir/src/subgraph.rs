@@ -0,0 +1,120 @@
+//! Cutting a graph into pieces (`Graph::extract`) and joining graphs (`Graph::merge`), e.g. to
+//! hand each partition to its own backend.
+
+use crate::{Connection, ConnectivitySpec, Graph, Population, Probe, Projection, ValidationError};
+use std::collections::{BTreeMap, HashSet};
+
+/// Probe kinds `extract` adds on populations with connections crossing the cut.
+pub const BOUNDARY_OUT: &str = "boundary_out";
+pub const BOUNDARY_IN: &str = "boundary_in";
+
+impl Graph {
+    /// Subgraph of the named populations (names not in the graph are ignored) with every
+    /// connection, projection, connectivity rule, input and probe that stays inside it. A
+    /// population sending across the cut gets a `boundary_out` probe, one receiving across it a
+    /// `boundary_in` probe, and the cut edges are listed in the `subgraph` attribute as
+    /// `{ source, populations, cut: [{pre, post}] }`. Attributes are copied. Operates on the
+    /// top-level populations; flatten module instances first.
+    pub fn extract(&self, populations: &[&str]) -> Graph {
+        let keep: HashSet<&str> = populations.iter().copied().collect();
+        let inside = |name: &str| keep.contains(name);
+        let mut g = Graph::new(self.name.clone());
+        g.dialect = self.dialect.clone();
+        g.attributes = self.attributes.clone();
+        g.populations = self.populations.iter().filter(|p| inside(&p.name)).cloned().collect();
+        g.connections = self.connections.iter().filter(|c| inside(&c.pre) && inside(&c.post)).cloned().collect();
+        g.projections = self.projections.iter().filter(|p| inside(&p.pre) && inside(&p.post)).cloned().collect();
+        g.connectivity = self.connectivity.iter().filter(|c| inside(&c.pre) && inside(&c.post)).cloned().collect();
+        g.inputs = self.inputs.iter().filter(|i| inside(&i.target)).cloned().collect();
+        g.probes = self.probes.iter().filter(|p| inside(&p.target)).cloned().collect();
+
+        let edges = self
+            .connections
+            .iter()
+            .map(|c| (c.pre.as_str(), c.post.as_str()))
+            .chain(self.projections.iter().map(|p| (p.pre.as_str(), p.post.as_str())))
+            .chain(self.connectivity.iter().map(|c| (c.pre.as_str(), c.post.as_str())));
+        let mut cut = Vec::new();
+        let mut seen: HashSet<(&str, &str)> = HashSet::new();
+        for (pre, post) in edges {
+            let (target, kind) = match (inside(pre), inside(post)) {
+                (true, false) => (pre, BOUNDARY_OUT),
+                (false, true) => (post, BOUNDARY_IN),
+                _ => continue,
+            };
+            cut.push(serde_json::json!({ "pre": pre, "post": post }));
+            if seen.insert((target, kind)) {
+                g.probes.push(Probe { target: target.to_string(), kind: kind.to_string(), ..Default::default() });
+            }
+        }
+        let names: Vec<&str> = g.populations.iter().map(|p| p.name.as_str()).collect();
+        g.attributes.insert(
+            "subgraph".to_string(),
+            serde_json::json!({ "source": self.name, "populations": names, "cut": cut }),
+        );
+        g
+    }
+
+    /// Append `other`'s populations, connections, projections, connectivity rules, inputs and
+    /// probes. Population and input names that already exist here are renamed to
+    /// `<prefix><name>` (references follow); the returned map lists each rename. Attributes
+    /// from `other` are added where the key is not already set. Errors if a renamed name still
+    /// collides (e.g. an empty prefix) or either graph has unexpanded module instances.
+    pub fn merge(&mut self, other: &Graph, prefix: &str) -> Result<BTreeMap<String, String>, ValidationError> {
+        if !self.is_flat() || !other.is_flat() {
+            return Err(ValidationError("merge: flatten module instances first".to_string()));
+        }
+        let mut taken: HashSet<String> = self.populations.iter().map(|p| p.name.clone()).collect();
+        let mut renames: BTreeMap<String, String> = BTreeMap::new();
+        for p in &other.populations {
+            let name = if taken.contains(&p.name) { format!("{prefix}{}", p.name) } else { p.name.clone() };
+            if !taken.insert(name.clone()) {
+                return Err(ValidationError(format!("merge: population '{}' collides even as '{name}'", p.name)));
+            }
+            if name != p.name {
+                renames.insert(p.name.clone(), name);
+            }
+        }
+        let mut input_names: HashSet<String> = self.inputs.iter().map(|i| i.name.clone()).collect();
+        let mut inputs = Vec::with_capacity(other.inputs.len());
+        for i in &other.inputs {
+            let mut i = i.clone();
+            if input_names.contains(&i.name) {
+                let renamed = format!("{prefix}{}", i.name);
+                if input_names.contains(&renamed) {
+                    return Err(ValidationError(format!("merge: input '{}' collides even as '{renamed}'", i.name)));
+                }
+                i.name = renamed;
+            }
+            input_names.insert(i.name.clone());
+            inputs.push(i);
+        }
+
+        let map = |name: &String| renames.get(name).cloned().unwrap_or_else(|| name.clone());
+        for p in &other.populations {
+            self.populations.push(Population { name: map(&p.name), ..p.clone() });
+        }
+        for c in &other.connections {
+            self.connections.push(Connection { pre: map(&c.pre), post: map(&c.post), ..c.clone() });
+        }
+        for p in &other.projections {
+            self.projections.push(Projection { pre: map(&p.pre), post: map(&p.post), ..p.clone() });
+        }
+        for c in &other.connectivity {
+            self.connectivity.push(ConnectivitySpec { pre: map(&c.pre), post: map(&c.post), ..c.clone() });
+        }
+        for mut i in inputs {
+            i.target = map(&i.target);
+            self.inputs.push(i);
+        }
+        for p in &other.probes {
+            self.probes.push(Probe { target: map(&p.target), ..p.clone() });
+        }
+        for (k, v) in &other.attributes {
+            if !self.attributes.contains_key(k) {
+                self.attributes.insert(k.clone(), v.clone());
+            }
+        }
+        Ok(renames)
+    }
+}
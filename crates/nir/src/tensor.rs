@@ -0,0 +1,208 @@
+//! Weight arrays stored next to the document instead of inline (`Connection::weights_ref`).
+//! Files are NumPy `.npy` (little-endian, C order) or, for any other extension, raw
+//! little-endian values whose `shape` the reference must state. Paths are relative to the
+//! directory holding the document.
+
+use crate::{Graph, ValidationError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Element type of an external tensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TensorDtype {
+    F32,
+    F64,
+}
+
+impl TensorDtype {
+    fn npy_descr(self) -> &'static str {
+        match self {
+            TensorDtype::F32 => "<f4",
+            TensorDtype::F64 => "<f8",
+        }
+    }
+
+    fn width(self) -> usize {
+        match self {
+            TensorDtype::F32 => 4,
+            TensorDtype::F64 => 8,
+        }
+    }
+}
+
+/// Reference to an array in a file, e.g. `{ "file": "w.npy", "dtype": "f32" }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TensorRef {
+    pub file: String,
+    pub dtype: TensorDtype,
+    /// Expected shape; required for raw files, checked against the header for `.npy`.
+    #[serde(default)]
+    pub shape: Option<Vec<u64>>,
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl TensorRef {
+    fn is_npy(&self) -> bool {
+        Path::new(&self.file).extension().is_some_and(|e| e.eq_ignore_ascii_case("npy"))
+    }
+
+    /// Read the array relative to `base_dir`, returning its shape and row-major values as f32.
+    pub fn read(&self, base_dir: &Path) -> io::Result<(Vec<u64>, Vec<f32>)> {
+        let bytes = fs::read(base_dir.join(&self.file))?;
+        let (shape, body) = if self.is_npy() {
+            let (descr, shape, body) = parse_npy(&bytes)?;
+            if descr != self.dtype.npy_descr() {
+                return Err(invalid(format!("{}: dtype '{descr}' does not match {:?}", self.file, self.dtype)));
+            }
+            if self.shape.as_ref().is_some_and(|s| *s != shape) {
+                return Err(invalid(format!("{}: shape {shape:?} does not match {:?}", self.file, self.shape)));
+            }
+            (shape, body)
+        } else {
+            let shape = self.shape.clone().ok_or_else(|| invalid(format!("{}: raw tensor needs a shape", self.file)))?;
+            (shape, bytes.as_slice())
+        };
+        let count: u64 = shape.iter().product();
+        let width = self.dtype.width();
+        if body.len() as u64 != count * width as u64 {
+            return Err(invalid(format!("{}: {} data bytes for shape {shape:?}", self.file, body.len())));
+        }
+        let values = body
+            .chunks_exact(width)
+            .map(|b| match self.dtype {
+                TensorDtype::F32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                TensorDtype::F64 => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+            })
+            .collect();
+        Ok((shape, values))
+    }
+
+    /// Write row-major f32 `values` of `shape` to `dir/file` as `.npy` and return the reference.
+    pub fn write_npy(dir: &Path, file: &str, shape: &[u64], values: &[f32]) -> io::Result<TensorRef> {
+        if shape.iter().product::<u64>() != values.len() as u64 {
+            return Err(invalid(format!("{file}: {} values for shape {shape:?}", values.len())));
+        }
+        let dims: Vec<String> = shape.iter().map(|d| d.to_string()).collect();
+        let dims = if dims.len() == 1 { format!("{},", dims[0]) } else { dims.join(", ") };
+        let mut header = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': ({dims}), }}");
+        // Version 1.0 layout: magic(6) + version(2) + len(2) + header padded to 64 with '\n'
+        let pad = (64 - (10 + header.len() + 1) % 64) % 64;
+        header.push_str(&" ".repeat(pad));
+        header.push('\n');
+        let mut out = Vec::with_capacity(10 + header.len() + values.len() * 4);
+        out.extend_from_slice(b"\x93NUMPY\x01\x00");
+        out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        out.extend_from_slice(header.as_bytes());
+        for v in values {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        fs::write(dir.join(file), out)?;
+        Ok(TensorRef { file: file.to_string(), dtype: TensorDtype::F32, shape: Some(shape.to_vec()) })
+    }
+}
+
+/// Split a `.npy` file into its `descr`, shape and data bytes. Fortran order is rejected.
+fn parse_npy(bytes: &[u8]) -> io::Result<(String, Vec<u64>, &[u8])> {
+    if bytes.len() < 10 || &bytes[..6] != b"\x93NUMPY" {
+        return Err(invalid("not a .npy file".to_string()));
+    }
+    let (len, start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize, 12),
+        v => return Err(invalid(format!("unsupported .npy version {v}"))),
+    };
+    let header = bytes.get(start..start + len).ok_or_else(|| invalid("truncated .npy header".to_string()))?;
+    let header = std::str::from_utf8(header).map_err(|_| invalid(".npy header is not text".to_string()))?;
+    let field = |key: &str| -> io::Result<&str> {
+        let at = header.find(&format!("'{key}':")).ok_or_else(|| invalid(format!(".npy header lacks '{key}'")))?;
+        Ok(header[at + key.len() + 3..].trim_start())
+    };
+    let descr = field("descr")?;
+    let descr = descr.strip_prefix('\'').and_then(|d| d.split('\'').next()).unwrap_or_default().to_string();
+    if field("fortran_order")?.starts_with("True") {
+        return Err(invalid("fortran_order .npy arrays are not supported".to_string()));
+    }
+    let dims = field("shape")?;
+    let dims = dims.strip_prefix('(').and_then(|d| d.split(')').next()).ok_or_else(|| invalid("bad .npy shape".to_string()))?;
+    let shape = dims
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| d.parse::<u64>().map_err(|_| invalid(format!("bad .npy dimension '{d}'"))))
+        .collect::<io::Result<Vec<u64>>>()?;
+    Ok((descr, shape, &bytes[start + len..]))
+}
+
+impl Graph {
+    /// Replace every connection's `weights_ref` by the `weight_matrix` it points to, reading
+    /// files relative to `base_dir`. The array must be 2D `pre.size x post.size` and finite.
+    /// Returns the number of references loaded.
+    pub fn load_weight_refs(&mut self, base_dir: &Path) -> Result<usize, ValidationError> {
+        let sizes: std::collections::HashMap<&str, u64> =
+            self.populations.iter().map(|p| (p.name.as_str(), p.size as u64)).collect();
+        let mut loaded = Vec::new();
+        for (k, c) in self.connections.iter().enumerate() {
+            let Some(r) = &c.weights_ref else { continue };
+            let err = |e: String| ValidationError(format!("connection {}->{} weights_ref '{}': {e}", c.pre, c.post, r.file));
+            let expected = match (sizes.get(c.pre.as_str()), sizes.get(c.post.as_str())) {
+                (Some(&rows), Some(&cols)) => vec![rows, cols],
+                _ => return Err(err("unknown population".to_string())),
+            };
+            let (shape, values) = r.read(base_dir).map_err(|e| err(e.to_string()))?;
+            if shape != expected {
+                return Err(err(format!("shape {shape:?} must be {expected:?} (pre.size x post.size)")));
+            }
+            if values.iter().any(|w| !w.is_finite()) {
+                return Err(err("non-finite entry".to_string()));
+            }
+            let cols = expected[1].max(1) as usize;
+            loaded.push((k, values.chunks(cols).map(<[f32]>::to_vec).collect::<Vec<_>>()));
+        }
+        let n = loaded.len();
+        for (k, matrix) in loaded {
+            self.connections[k].weight_matrix = Some(matrix);
+            self.connections[k].weights_ref = None;
+        }
+        Ok(n)
+    }
+
+    /// Error on the first connection whose `weights_ref` has not been loaded. Callers that
+    /// only have the document text, and so no directory to resolve paths against, use this
+    /// instead of compiling or simulating with missing weights.
+    pub fn check_weight_refs_loaded(&self) -> Result<(), ValidationError> {
+        match self.connections.iter().find_map(|c| Some((c, c.weights_ref.as_ref()?))) {
+            Some((c, r)) => Err(ValidationError(format!(
+                "connection {}->{} weights_ref '{}' is not loaded (resolve it against the document's directory)",
+                c.pre, c.post, r.file
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Move every `weight_matrix` with at least `min_entries` entries to `dir` as
+    /// `w<index>_<pre>_<post>.npy` and point `weights_ref` at it. Save the document in `dir`
+    /// so the relative paths resolve. Returns the number of files written.
+    pub fn save_weight_refs(&mut self, dir: &Path, min_entries: usize) -> io::Result<usize> {
+        let mut written = 0;
+        for (k, c) in self.connections.iter_mut().enumerate() {
+            let Some(m) = &c.weight_matrix else { continue };
+            let cols = m.first().map_or(0, Vec::len);
+            if m.len() * cols < min_entries {
+                continue;
+            }
+            let clean = |s: &str| s.chars().map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' }).collect::<String>();
+            let file = format!("w{k}_{}_{}.npy", clean(&c.pre), clean(&c.post));
+            let values: Vec<f32> = m.iter().flatten().copied().collect();
+            c.weights_ref = Some(TensorRef::write_npy(dir, &file, &[m.len() as u64, cols as u64], &values)?);
+            c.weight_matrix = None;
+            written += 1;
+        }
+        Ok(written)
+    }
+}
@@ -0,0 +1,107 @@
+//! Graphviz DOT and Mermaid renderings of a graph for debugging partition/placement results.
+//! Populations become nodes (DOT sizes them by neuron count), and every `pre -> post` pair
+//! becomes one edge weighted by the number of connections and projections between them.
+
+use crate::{Graph, PartitionMeta};
+use std::collections::{BTreeMap, HashMap};
+
+/// Options for `Graph::to_dot_with` / `Graph::to_mermaid_with`.
+#[derive(Debug, Clone, Default)]
+pub struct VizOptions {
+    /// Fill nodes by their part in the `partition` attribute (`assignment[].part`), when present.
+    pub color_by_partition: bool,
+}
+
+/// Fill colors for parts, cycled when there are more parts.
+const PALETTE: &[&str] = &[
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462", "#b3de69", "#fccde5", "#d9d9d9", "#bc80bd",
+    "#ccebc5", "#ffed6f",
+];
+
+impl Graph {
+    /// Graphviz DOT source with default `VizOptions`.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&VizOptions::default())
+    }
+
+    /// Graphviz DOT source. Node width grows with log10 of the population size, edge pen width
+    /// with log2 of the connection count (shown as the label when above one).
+    pub fn to_dot_with(&self, opts: &VizOptions) -> String {
+        let parts = if opts.color_by_partition { self.partition_of() } else { HashMap::new() };
+        let mut out = format!("digraph {} {{\n  rankdir=LR;\n  node [shape=ellipse];\n", dot_quote(&self.name));
+        for p in &self.populations {
+            let width = 0.75 + 0.5 * (p.size.max(1) as f64).log10();
+            let mut attrs = format!("label={}, width={width:.2}", dot_quote(&format!("{}\n{}", p.name, p.size)));
+            if let Some(&part) = parts.get(p.name.as_str()) {
+                attrs.push_str(&format!(", style=filled, fillcolor=\"{}\", tooltip=\"part {part}\"", PALETTE[part % PALETTE.len()]));
+            }
+            out.push_str(&format!("  {} [{attrs}];\n", dot_quote(&p.name)));
+        }
+        for ((pre, post), n) in self.edge_counts() {
+            let penwidth = 1.0 + (n as f64).log2();
+            let label = if n > 1 { format!(", label=\"{n}\"") } else { String::new() };
+            out.push_str(&format!("  {} -> {} [penwidth={penwidth:.2}{label}];\n", dot_quote(pre), dot_quote(post)));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid flowchart with default `VizOptions`.
+    pub fn to_mermaid(&self) -> String {
+        self.to_mermaid_with(&VizOptions::default())
+    }
+
+    /// Mermaid flowchart. Mermaid cannot size nodes, so labels carry the population size;
+    /// edges are labelled with the connection count when above one.
+    pub fn to_mermaid_with(&self, opts: &VizOptions) -> String {
+        let parts = if opts.color_by_partition { self.partition_of() } else { HashMap::new() };
+        let ids: HashMap<&str, usize> = self.populations.iter().enumerate().map(|(i, p)| (p.name.as_str(), i)).collect();
+        let mut out = String::from("flowchart LR\n");
+        for (i, p) in self.populations.iter().enumerate() {
+            out.push_str(&format!("  n{i}[\"{}<br/>{}\"]\n", mermaid_escape(&p.name), p.size));
+        }
+        for ((pre, post), n) in self.edge_counts() {
+            let (Some(a), Some(b)) = (ids.get(pre), ids.get(post)) else { continue };
+            if n > 1 {
+                out.push_str(&format!("  n{a} -->|{n}| n{b}\n"));
+            } else {
+                out.push_str(&format!("  n{a} --> n{b}\n"));
+            }
+        }
+        let mut used: Vec<usize> = parts.values().copied().collect();
+        used.sort_unstable();
+        used.dedup();
+        for part in used {
+            out.push_str(&format!("  classDef part{part} fill:{}\n", PALETTE[part % PALETTE.len()]));
+        }
+        for (i, p) in self.populations.iter().enumerate() {
+            if let Some(part) = parts.get(p.name.as_str()) {
+                out.push_str(&format!("  class n{i} part{part}\n"));
+            }
+        }
+        out
+    }
+
+    /// Connections plus projections per `(pre, post)`, in name order.
+    fn edge_counts(&self) -> BTreeMap<(&str, &str), usize> {
+        let mut counts = BTreeMap::new();
+        let pairs = self.connections.iter().map(|c| (c.pre.as_str(), c.post.as_str()));
+        for key in pairs.chain(self.projections.iter().map(|p| (p.pre.as_str(), p.post.as_str()))) {
+            *counts.entry(key).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Population -> part from the `partition` attribute written by the partition pass.
+    fn partition_of(&self) -> HashMap<String, usize> {
+        self.attrs::<PartitionMeta>().map(|m| m.part_of()).unwrap_or_default()
+    }
+}
+
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+fn mermaid_escape(s: &str) -> String {
+    s.replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;")
+}
@@ -10,6 +10,52 @@ use nc_telemetry as telemetry;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PartitionPlan {
     pub parts: usize,
+    /// Part index per population, in `Graph::populations` order.
+    #[serde(default)]
+    pub assignment: Vec<usize>,
+}
+
+impl PartitionPlan {
+    /// One subgraph per part (`Graph::extract` of the populations assigned to it, named
+    /// `<graph>.part<i>`), so each part can go to its own backend. Connections between parts
+    /// are dropped and marked with boundary probes; see `Graph::extract`.
+    pub fn subgraphs(&self, g: &nir::Graph) -> Vec<nir::Graph> {
+        (0..self.parts)
+            .map(|part| {
+                let names: Vec<&str> = g
+                    .populations
+                    .iter()
+                    .zip(&self.assignment)
+                    .filter(|(_, &a)| a == part)
+                    .map(|(p, _)| p.name.as_str())
+                    .collect();
+                let mut sub = g.extract(&names);
+                sub.name = format!("{}.part{part}", g.name);
+                sub
+            })
+            .collect()
+    }
+}
+
+/// Greedy least-loaded assignment of populations to `parts` buckets by neuron count.
+/// Largest populations are placed first; ties go to the lowest bucket, so the result is deterministic.
+fn balance_by_size(g: &nir::Graph, parts: usize) -> Vec<usize> {
+    let parts = parts.max(1);
+    let mut order: Vec<usize> = (0..g.populations.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(g.populations[i].size));
+    let mut loads = vec![0u64; parts];
+    let mut assignment = vec![0usize; g.populations.len()];
+    for i in order {
+        let idx = loads
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, &l)| l)
+            .map(|(b, _)| b)
+            .unwrap_or(0);
+        loads[idx] += g.populations[i].size as u64;
+        assignment[i] = idx;
+    }
+    assignment
 }
 
 pub fn partition(_g: &nir::Graph, _targets: &[&str]) -> Result<PartitionPlan> {
@@ -37,8 +83,11 @@ pub fn partition(_g: &nir::Graph, _targets: &[&str]) -> Result<PartitionPlan> {
     #[cfg(not(feature = "orchestrator_partition"))]
     let plan = {
         let n = _g.populations.len();
-        let parts = if n == 0 { 1 } else { std::cmp::min(n, 4) };
-        PartitionPlan { parts }
+        let target_parts = if n == 0 { 1 } else { std::cmp::min(n, 4) };
+        let assignment = balance_by_size(_g, target_parts);
+        // Parts actually used by the assignment (every bucket is filled while n >= target_parts)
+        let parts = assignment.iter().max().map_or(1, |m| m + 1);
+        PartitionPlan { parts, assignment }
     };
 
     #[cfg(feature = "telemetry")]
@@ -101,7 +150,8 @@ impl PartitionBuilder for GreedyRefineBuilder {
             parts = max_allowed;
         }
 
-        PartitionPlan { parts }
+        let assignment = balance_by_size(g, parts);
+        PartitionPlan { parts, assignment }
     }
 }
 
@@ -136,10 +186,44 @@ mod tests {
         let s = std::fs::read_to_string(&p).expect("read fixture chain_small");
         let g = nir::Graph::from_json_str(&s).expect("parse NIR from json");
         let plan = partition(&g, &["riscv64gcv_linux"]).expect("partition ok");
-        let upper = std::cmp::min(std::cmp::max(g.populations.len(), 1), 4);
+        let upper = g.populations.len().clamp(1, 4);
         assert!(plan.parts >= 1 && plan.parts <= upper);
     }
 
+    #[cfg(not(feature = "orchestrator_partition"))]
+    #[test]
+    fn fallback_balances_skewed_sizes() {
+        let g = nir::fixtures::chain(&[100, 60, 50, 40, 30, 20, 10, 10]);
+        let plan = partition(&g, &[]).expect("partition ok");
+        assert_eq!(plan.parts, 4);
+        assert_eq!(plan.assignment.len(), g.populations.len());
+        let mut loads = vec![0u32; plan.parts];
+        for (p, &part) in g.populations.iter().zip(&plan.assignment) {
+            loads[part] += p.size;
+        }
+        let (min, max) = (*loads.iter().min().unwrap(), *loads.iter().max().unwrap());
+        assert!(min > 0, "every part should be used: {loads:?}");
+        assert!(max <= 2 * min, "imbalance above 2x: {loads:?}");
+    }
+
+    #[test]
+    fn plan_subgraphs_cover_every_population_once() {
+        let g = nir::fixtures::chain(&[4, 3, 2, 1]);
+        let plan = PartitionPlan { parts: 2, assignment: vec![0, 0, 1, 1] };
+        let subs = plan.subgraphs(&g);
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[1].name, "chain.part1");
+        let names: Vec<Vec<&str>> = subs.iter().map(|s| s.populations.iter().map(|p| p.name.as_str()).collect()).collect();
+        assert_eq!(names, [vec!["p0", "p1"], vec!["p2", "p3"]]);
+        // p1 -> p2 crosses the cut
+        assert_eq!(subs[0].connections.len(), 1);
+        assert!(subs[0].probes.iter().any(|p| p.target == "p1" && p.kind == nir::BOUNDARY_OUT));
+        assert!(subs[1].probes.iter().any(|p| p.target == "p2" && p.kind == nir::BOUNDARY_IN));
+        for s in &subs {
+            s.validate().expect("subgraph validates");
+        }
+    }
+
     // Heuristic tests behind feature gate.
     #[cfg(feature = "orchestrator_partition")]
     #[test]
@@ -2,7 +2,7 @@
 //! Computes simple structural metrics used by partition planning and tests.
 
 use crate::nir;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Structural metrics over a NIR graph (population-level).
 #[derive(Debug, Clone, PartialEq)]
@@ -19,6 +19,8 @@ pub struct GraphMetrics {
     pub max_fanin: usize,
     /// Maximum fan-out across populations.
     pub max_fanout: usize,
+    /// Average local clustering coefficient over the undirected population graph.
+    pub clustering: f64,
 }
 
 /// Compute structural metrics from a NIR graph.
@@ -74,9 +76,69 @@ pub fn compute_metrics(g: &nir::Graph) -> GraphMetrics {
         avg_fanout: (sum_out as f64) / denom,
         max_fanin: max_in,
         max_fanout: max_out,
+        clustering: clustering_coefficient(g),
     }
 }
 
+/// Average local clustering coefficient over the undirected population graph.
+///
+/// Edge direction, duplicate connections and self-loops are ignored. Populations with
+/// fewer than two neighbours contribute 0; an empty graph yields 0.0.
+pub fn clustering_coefficient(g: &nir::Graph) -> f64 {
+    if g.populations.is_empty() {
+        return 0.0;
+    }
+    let mut adj: HashMap<&str, HashSet<&str>> = HashMap::with_capacity(g.populations.len());
+    for p in &g.populations {
+        adj.insert(p.name.as_str(), HashSet::new());
+    }
+    for c in &g.connections {
+        if c.pre == c.post || !adj.contains_key(c.pre.as_str()) || !adj.contains_key(c.post.as_str()) {
+            continue;
+        }
+        if let Some(s) = adj.get_mut(c.pre.as_str()) {
+            s.insert(c.post.as_str());
+        }
+        if let Some(s) = adj.get_mut(c.post.as_str()) {
+            s.insert(c.pre.as_str());
+        }
+    }
+
+    let mut sum = 0.0;
+    for p in &g.populations {
+        let nbrs: Vec<&str> = adj[p.name.as_str()].iter().copied().collect();
+        let k = nbrs.len();
+        if k < 2 {
+            continue;
+        }
+        let mut links = 0usize;
+        for (i, a) in nbrs.iter().enumerate() {
+            for b in &nbrs[i + 1..] {
+                if adj[a].contains(b) {
+                    links += 1;
+                }
+            }
+        }
+        sum += (2 * links) as f64 / (k * (k - 1)) as f64;
+    }
+    sum / g.populations.len() as f64
+}
+
+/// Fan-in weighted by source size: for each population, the sum of `pre` population sizes
+/// over its incoming connections. A better proxy for incoming spike traffic than the
+/// plain connection count. Every population is present (0 when it has no inputs);
+/// connections naming unknown populations are ignored.
+pub fn weighted_fanin(g: &nir::Graph) -> HashMap<String, u64> {
+    let sizes: HashMap<&str, u64> = g.populations.iter().map(|p| (p.name.as_str(), p.size as u64)).collect();
+    let mut out: HashMap<String, u64> = g.populations.iter().map(|p| (p.name.clone(), 0)).collect();
+    for c in &g.connections {
+        if let (Some(sz), Some(x)) = (sizes.get(c.pre.as_str()), out.get_mut(&c.post)) {
+            *x += sz;
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,6 +176,36 @@ mod tests {
         assert_eq!(m.max_fanin, 1);
     }
 
+    #[test]
+    fn clustering_triangle_is_one() {
+        // ring of 3 is a triangle once direction is ignored
+        let g = nir::fixtures::ring(3, 4, 0.5, 1.0);
+        assert!(approx_eq(clustering_coefficient(&g), 1.0));
+        assert!(approx_eq(compute_metrics(&g).clustering, 1.0));
+    }
+
+    #[test]
+    fn clustering_chain_is_zero() {
+        let g = nir::fixtures::chain(&[4, 4, 4, 4]);
+        assert!(approx_eq(clustering_coefficient(&g), 0.0));
+    }
+
+    #[test]
+    fn weighted_fanin_sums_source_sizes_on_star() {
+        // Reverse the star so the hub receives from every spoke
+        let mut g = nir::fixtures::star(32, 8, 5, 0.5, 1.0);
+        for c in &mut g.connections {
+            std::mem::swap(&mut c.pre, &mut c.post);
+        }
+        let w = weighted_fanin(&g);
+        assert_eq!(w["center"], 5 * 8);
+        assert_eq!(w["s0"], 0);
+
+        let fwd = weighted_fanin(&nir::fixtures::star(32, 8, 5, 0.5, 1.0));
+        assert_eq!(fwd["s3"], 32);
+        assert_eq!(fwd["center"], 0);
+    }
+
     #[test]
     fn metrics_on_empty_graph_is_safe() {
         let g = nir::Graph::new("empty");
@@ -124,5 +216,6 @@ mod tests {
         assert!(approx_eq(m.avg_fanout, 0.0));
         assert_eq!(m.max_fanin, 0);
         assert_eq!(m.max_fanout, 0);
+        assert!(approx_eq(m.clustering, 0.0));
     }
 }
\ No newline at end of file
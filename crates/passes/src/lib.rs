@@ -4,12 +4,15 @@ use nc_hal as hal;
 use std::fs;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
-#[cfg(feature = "telemetry")]
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 #[cfg(feature = "telemetry")]
 use nc_telemetry as telemetry;
 use nc_orchestrator as orchestrator;
 
+mod partition;
+mod placement;
+mod routing;
+
 #[derive(Debug, Error)]
 pub enum PassError {
     #[error("mapping violation: {0}")]
@@ -19,6 +22,11 @@ pub enum PassError {
 pub trait Pass {
     fn name(&self) -> &str;
     fn run(&self, g: nir::Graph) -> Result<nir::Graph>;
+    /// `run` under `PassManager::run_with_config`. Passes with settings in `PipelineConfig`
+    /// (`placement`, `routing`) read them from `cfg` here; `run` uses their defaults.
+    fn run_with_config(&self, g: nir::Graph, _cfg: &PipelineConfig) -> Result<nir::Graph> {
+        self.run(g)
+    }
 }
 
 pub struct NoOpPass;
@@ -36,28 +44,113 @@ impl Pass for ValidatePass {
     }
 }
 
+/// Rounding used by `QuantizeWeightsPass`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuantMode {
+    /// Round to the nearest level.
+    #[default]
+    Nearest,
+    /// Round up with probability equal to the fractional distance to the lower level, using a
+    /// deterministic RNG seeded from `seed` and the connection index (reproducible per seed).
+    Stochastic { seed: u64 },
+}
+
 pub struct QuantizeWeightsPass {
     pub bits: u32,
+    pub mode: QuantMode,
 }
 
 impl QuantizeWeightsPass {
-    fn quantize(w: f32, bits: u32) -> f32 {
+    /// Position of `w` on the level grid: `(index, step)` with `index` in `[0, levels-1]`.
+    fn grid(w: f32, bits: u32) -> (f32, f32) {
         // Uniform symmetric quantization onto [-1,1] with 2^bits levels
         let levels: u32 = if bits >= 31 { u32::MAX } else { 1u32 << bits };
         let l_minus_1 = (levels.saturating_sub(1)) as f32;
         let l_minus_1 = if l_minus_1 <= 0.0 { 1.0 } else { l_minus_1 };
         let w_clamped = w.clamp(-1.0, 1.0);
         let step = 2.0 / l_minus_1;
-        ((w_clamped + 1.0) / step).round() * step - 1.0
+        ((w_clamped + 1.0) / step, step)
+    }
+
+    fn quantize(w: f32, bits: u32) -> f32 {
+        let (x, step) = Self::grid(w, bits);
+        x.round() * step - 1.0
+    }
+
+    /// Stochastic rounding with uniform sample `u` in `[0, 1)`.
+    fn quantize_stochastic(w: f32, bits: u32, u: f64) -> f32 {
+        let (x, step) = Self::grid(w, bits);
+        let lo = x.floor();
+        let q = if u < (x - lo) as f64 { lo + 1.0 } else { lo };
+        (q * step - 1.0).clamp(-1.0, 1.0)
     }
 }
 
+/// Uniform `[0, 1)` sample number `idx` of stream `seed` (splitmix64 over seed and index).
+fn unit_sample(seed: u64, idx: usize) -> f64 {
+    let mut z = seed ^ (idx as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
 impl Pass for QuantizeWeightsPass {
     fn name(&self) -> &str { "quantize" }
     fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
-        for c in &mut g.connections {
-            c.weight = Self::quantize(c.weight, self.bits);
+        for (i, w) in g.weights_mut().enumerate() {
+            *w = match self.mode {
+                QuantMode::Nearest => Self::quantize(*w, self.bits),
+                QuantMode::Stochastic { seed } => Self::quantize_stochastic(*w, self.bits, unit_sample(seed, i)),
+            };
+        }
+        Ok(g)
+    }
+}
+
+/// Clamp every weight (connections, `weight_matrix` entries and projections; see
+/// `Graph::weights_mut`) into `[min, max]`, e.g. a device range ahead of
+/// `QuantizeWeightsPass`. Records `clamp = {min, max, clamped, total}`, counting weights.
+pub struct ClampWeightsPass {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ClampWeightsPass {
+    /// Parse the `MIN:MAX` part of a `clamp:MIN:MAX` pipeline name.
+    fn parse(spec: &str) -> Result<Self> {
+        let bounds = spec
+            .split_once(':')
+            .and_then(|(lo, hi)| Some((lo.trim().parse::<f32>().ok()?, hi.trim().parse::<f32>().ok()?)));
+        match bounds {
+            Some((min, max)) if min.is_finite() && max.is_finite() && min <= max => Ok(Self { min, max }),
+            _ => bail!("invalid clamp pass 'clamp:{spec}' (expected clamp:MIN:MAX with finite MIN <= MAX)"),
+        }
+    }
+}
+
+impl Pass for ClampWeightsPass {
+    fn name(&self) -> &str { "clamp" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let (mut clamped, mut total) = (0usize, 0usize);
+        for w in g.weights_mut() {
+            total += 1;
+            let c = w.clamp(self.min, self.max);
+            if c != *w {
+                *w = c;
+                clamped += 1;
+            }
         }
+        g.attributes.insert(
+            "clamp".to_string(),
+            serde_json::json!({
+                "min": self.min,
+                "max": self.max,
+                "clamped": clamped,
+                "total": total
+            }),
+        );
         Ok(g)
     }
 }
@@ -71,6 +164,11 @@ fn extract_caps_from_graph(g: &nir::Graph) -> Option<hal::Capabilities> {
     None
 }
 
+/// Assign populations to parts. With HAL caps, runs the multilevel partitioner (see
+/// `partition.rs`) to minimize synapses between parts while keeping each part within
+/// `max_neurons_per_core` / `max_synapses_per_core`, adding parts when the lower bound from the
+/// totals does not fit; without caps everything goes to part 0. Records `partition`
+/// (`PartitionMeta`, including `cut_synapses`).
 pub struct PartitionPass;
 impl Pass for PartitionPass {
     fn name(&self) -> &str { "partition" }
@@ -79,6 +177,7 @@ impl Pass for PartitionPass {
         let mut parts: usize = 1;
         let mut assignment: Vec<(String, usize)> = Vec::new();
         let mut violations: Vec<serde_json::Value> = Vec::new();
+        let mut cut_synapses = 0u64;
 
         // Orchestrator plan (serialized for handoff)
         let targets_vec: Vec<String> = g.attributes
@@ -86,239 +185,397 @@ impl Pass for PartitionPass {
             .and_then(|v| v.as_str())
             .and_then(|p| Path::new(p).file_stem().and_then(|s| s.to_str()))
             .map(|s| vec![s.to_string()])
-            .unwrap_or_else(|| Vec::new());
+            .unwrap_or_default();
         let target_slices: Vec<&str> = targets_vec.iter().map(|s| s.as_str()).collect();
         if let Ok(plan) = orchestrator::partition(&g, &target_slices) {
             g.attributes.insert("orchestrator_plan".to_string(), serde_json::json!({
                 "parts": plan.parts,
+                "assignment": plan.assignment,
                 "targets": target_slices,
             }));
         }
 
         if let Some(caps) = extract_caps_from_graph(&g) {
-            strategy = "cap-aware";
+            strategy = "multilevel";
             let max_neurons = caps.max_neurons_per_core.unwrap_or(0) as usize;
             let max_syn = caps.max_synapses_per_core.unwrap_or(0) as usize;
 
-            let total_neurons: usize = g.populations.iter().map(|p| p.size as usize).sum();
-            let total_synapses: usize = g.connections.len();
+            // Vertices are populations (neurons, incoming synapses); edges count the synapses
+            // between two populations, one per connection and one per projection entry
+            let index: std::collections::HashMap<&str, usize> = g.populations.iter().enumerate().map(|(i, p)| (p.name.as_str(), i)).collect();
+            let mut load: Vec<(u64, u64)> = g.populations.iter().map(|p| (p.size as u64, 0)).collect();
+            let mut edges: Vec<(usize, usize, u64)> = Vec::new();
+            let synapses = g.connections.iter().map(|c| (&c.pre, &c.post, 1u64))
+                .chain(g.projections.iter().map(|pj| (&pj.pre, &pj.post, pj.nnz() as u64)));
+            for (pre, post, n) in synapses {
+                if let (Some(&a), Some(&b)) = (index.get(pre.as_str()), index.get(post.as_str())) {
+                    load[b].1 += n;
+                    edges.push((a, b, n));
+                }
+            }
 
+            let total_neurons: usize = load.iter().map(|l| l.0 as usize).sum();
+            let total_synapses: usize = load.iter().map(|l| l.1 as usize).sum();
             let parts_by_neurons = if max_neurons > 0 { total_neurons.div_ceil(max_neurons) } else { 1 };
             let parts_by_syn = if max_syn > 0 { total_synapses.div_ceil(max_syn) } else { 1 };
-            parts = parts_by_neurons.max(parts_by_syn).max(1);
-
-            // Greedy size-balanced assignment
-            let mut buckets: Vec<usize> = vec![0; parts];
-            let mut pops: Vec<(String, usize)> = g.populations.iter().map(|p| (p.name.clone(), p.size as usize)).collect();
-            pops.sort_by_key(|(_, s)| std::cmp::Reverse(*s));
-            for (name, sz) in pops {
-                let idx = buckets
-                    .iter()
-                    .enumerate()
-                    .min_by_key(|&(_, &v)| v)
-                    .map(|(i, _)| i)
-                    .unwrap_or(0);
-                if max_neurons > 0 && sz > max_neurons {
+
+            let limits = partition::Caps { max_neurons: max_neurons as u64, max_synapses: max_syn as u64 };
+            let (k, part) = partition::multilevel(&load, &edges, limits, parts_by_neurons.max(parts_by_syn).max(1));
+            parts = k;
+            cut_synapses = partition::cut(&edges, &part);
+            for (p, (&(sz, syn), part)) in g.populations.iter().zip(load.iter().zip(part)) {
+                if max_neurons > 0 && sz as usize > max_neurons {
                     violations.push(serde_json::json!({
                         "code": "POP_EXCEEDS_MAX_NEURONS_PER_CORE",
-                        "population": name,
+                        "population": p.name,
                         "size": sz,
                         "max": max_neurons
                     }));
                 }
-                buckets[idx] += sz;
-                assignment.push((name, idx));
+                if max_syn > 0 && syn as usize > max_syn {
+                    violations.push(serde_json::json!({
+                        "code": "POP_EXCEEDS_MAX_SYNAPSES_PER_CORE",
+                        "population": p.name,
+                        "synapses": syn,
+                        "max": max_syn
+                    }));
+                }
+                assignment.push((p.name.clone(), part));
             }
         } else {
             // Naive: single part, trivial assignment (use initial default of 1)
             assignment = g.populations.iter().map(|p| (p.name.clone(), 0usize)).collect();
         }
 
-        let assignment_json: Vec<serde_json::Value> = assignment
-            .iter()
-            .map(|(pop, part)| serde_json::json!({ "population": pop, "part": part }))
-            .collect();
-
-        let meta = serde_json::json!({
-            "parts": parts as u32,
-            "strategy": strategy,
-            "assignment": assignment_json,
-            "violations": violations,
+        g.set_attr(&nir::PartitionMeta {
+            parts,
+            strategy: strategy.to_string(),
+            assignment: assignment.into_iter().map(|(population, part)| nir::PartAssignment { population, part }).collect(),
+            violations,
+            cut_synapses,
         });
-        g.attributes.insert("partition".to_string(), meta);
         Ok(g)
     }
 }
 
+/// Simulated-annealing settings for the `placement` pass (`PipelineConfig::placement`).
+#[derive(Debug, Clone)]
+pub struct PlacementOptions {
+    /// Annealing steps; `0` keeps part `i` on core `i`.
+    pub iterations: usize,
+    /// Starting temperature in synapse-hops; `None` uses the mean cost of one communicating
+    /// pair of parts in the initial placement.
+    pub initial_temperature: Option<f64>,
+    /// Factor applied to the temperature after every step.
+    pub cooling: f64,
+    pub seed: u64,
+}
+
+impl Default for PlacementOptions {
+    fn default() -> Self {
+        Self { iterations: 20_000, initial_temperature: None, cooling: 0.999, seed: 0 }
+    }
+}
+
+/// Per-part resource estimate and capability violations. When the HAL gives a core mesh
+/// (`mesh_width` x `mesh_height`), parts are also placed on cores by simulated annealing to
+/// minimize synapses times hops between parts; `placement.coords` holds each part's `[x, y]`
+/// and `hop_cost` / `initial_hop_cost` the cost after and before annealing.
 pub struct PlacementPass;
+
 impl Pass for PlacementPass {
     fn name(&self) -> &str { "placement" }
-    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
-        // Derive partition assignment
-        let parts = g.attributes.get("partition").and_then(|v| v.get("parts")).and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-        let mut pop_to_part: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-        if let Some(assign) = g.attributes.get("partition").and_then(|v| v.get("assignment")).and_then(|v| v.as_array()) {
-            for a in assign {
-                if let (Some(pop), Some(part)) = (a.get("population").and_then(|x| x.as_str()), a.get("part").and_then(|x| x.as_u64())) {
-                    pop_to_part.insert(pop.to_string(), part as usize);
-                }
-            }
-        } else {
-            for p in &g.populations {
-                pop_to_part.insert(p.name.clone(), 0usize);
-            }
-        }
+    fn run(&self, g: nir::Graph) -> Result<nir::Graph> { place(g, &PlacementOptions::default()) }
+    fn run_with_config(&self, g: nir::Graph, cfg: &PipelineConfig) -> Result<nir::Graph> { place(g, &cfg.placement) }
+}
 
-        // Count resources per part
-        let mut neurons_per_part = vec![0usize; parts];
-        for p in &g.populations {
-            let part = *pop_to_part.get(&p.name).unwrap_or(&0usize);
-            neurons_per_part[part] += p.size as usize;
+fn place(mut g: nir::Graph, options: &PlacementOptions) -> Result<nir::Graph> {
+    // Derive partition assignment (unassigned populations go to part 0)
+    let partition = g.attrs::<nir::PartitionMeta>().unwrap_or_default();
+    let parts = partition.parts;
+    let pop_to_part = partition.part_of();
+
+    // Count resources per part
+    let mut neurons_per_part = vec![0usize; parts];
+    for p in &g.populations {
+        let part = *pop_to_part.get(&p.name).unwrap_or(&0usize);
+        neurons_per_part[part] += p.size as usize;
+    }
+    let mut syn_per_part = vec![0usize; parts];
+    for c in &g.connections {
+        let pre_part = *pop_to_part.get(&c.pre).unwrap_or(&0usize);
+        let post_part = *pop_to_part.get(&c.post).unwrap_or(&0usize);
+        if pre_part == post_part {
+            syn_per_part[pre_part] += 1;
         }
-        let mut syn_per_part = vec![0usize; parts];
-        for c in &g.connections {
-            let pre_part = *pop_to_part.get(&c.pre).unwrap_or(&0usize);
-            let post_part = *pop_to_part.get(&c.post).unwrap_or(&0usize);
-            if pre_part == post_part {
-                syn_per_part[pre_part] += 1;
+    }
+
+    // Target-aware memory model (fallback to coarse defaults if unspecified)
+    let caps = extract_caps_from_graph(&g);
+    let neuron_mem_kib: f64 = caps.as_ref().and_then(|c| c.neuron_mem_kib_per).unwrap_or(0.01); // ~10B/neuron
+    let syn_mem_kib: f64 = caps.as_ref().and_then(|c| c.syn_mem_kib_per).unwrap_or(0.001);      // ~1B/synapse
+    let core_mem_cap: Option<f64> = caps.as_ref().and_then(|c| c.core_memory_kib).map(|v| v as f64);
+    let max_fan_in = caps.as_ref().and_then(|c| c.max_fan_in).map(|v| v as usize);
+    let max_fan_out = caps.as_ref().and_then(|c| c.max_fan_out).map(|v| v as usize);
+
+    let mut violations: Vec<serde_json::Value> = Vec::new();
+    for part in 0..parts {
+        let mem: f64 = (neurons_per_part[part] as f64) * neuron_mem_kib
+            + (syn_per_part[part] as f64) * syn_mem_kib;
+        if let Some(cap) = core_mem_cap {
+            if mem > cap {
+                violations.push(serde_json::json!({
+                    "code": "CORE_MEMORY_EXCEEDED",
+                    "part": part,
+                    "estimate_kib": mem,
+                    "cap_kib": cap
+                }));
             }
         }
+    }
 
-        // Target-aware memory model (fallback to coarse defaults if unspecified)
-        let caps = extract_caps_from_graph(&g);
-        let neuron_mem_kib: f64 = caps.as_ref().and_then(|c| c.neuron_mem_kib_per).unwrap_or(0.01); // ~10B/neuron
-        let syn_mem_kib: f64 = caps.as_ref().and_then(|c| c.syn_mem_kib_per).unwrap_or(0.001);      // ~1B/synapse
-        let core_mem_cap: Option<f64> = caps.as_ref().and_then(|c| c.core_memory_kib).map(|v| v as f64);
-        let max_fan_in = caps.as_ref().and_then(|c| c.max_fan_in).map(|v| v as usize);
-        let max_fan_out = caps.as_ref().and_then(|c| c.max_fan_out).map(|v| v as usize);
-
-        let mut violations: Vec<serde_json::Value> = Vec::new();
-        for part in 0..parts {
-            let mem: f64 = (neurons_per_part[part] as f64) * neuron_mem_kib
-                + (syn_per_part[part] as f64) * syn_mem_kib;
-            if let Some(cap) = core_mem_cap {
-                if mem > cap {
+    // Fan-in/out checks per population
+    let mut fan_in: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    let mut fan_out: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for c in &g.connections {
+        *fan_out.entry(c.pre.clone()).or_insert(0) += 1;
+        *fan_in.entry(c.post.clone()).or_insert(0) += 1;
+    }
+    for p in &g.populations {
+        if let Some(cap) = max_fan_in {
+            if let Some(v) = fan_in.get(&p.name) {
+                if *v > cap {
                     violations.push(serde_json::json!({
-                        "code": "CORE_MEMORY_EXCEEDED",
-                        "part": part,
-                        "estimate_kib": mem,
-                        "cap_kib": cap
+                        "code": "MAX_FAN_IN_EXCEEDED",
+                        "population": p.name,
+                        "fan_in": v,
+                        "cap": cap
                     }));
                 }
             }
         }
-
-        // Fan-in/out checks per population
-        let mut fan_in: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-        let mut fan_out: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-        for c in &g.connections {
-            *fan_out.entry(c.pre.clone()).or_insert(0) += 1;
-            *fan_in.entry(c.post.clone()).or_insert(0) += 1;
-        }
-        for p in &g.populations {
-            if let Some(cap) = max_fan_in {
-                if let Some(v) = fan_in.get(&p.name) {
-                    if *v > cap {
-                        violations.push(serde_json::json!({
-                            "code": "MAX_FAN_IN_EXCEEDED",
-                            "population": p.name,
-                            "fan_in": v,
-                            "cap": cap
-                        }));
-                    }
+        if let Some(cap) = max_fan_out {
+            if let Some(v) = fan_out.get(&p.name) {
+                if *v > cap {
+                    violations.push(serde_json::json!({
+                        "code": "MAX_FAN_OUT_EXCEEDED",
+                        "population": p.name,
+                        "fan_out": v,
+                        "cap": cap
+                    }));
                 }
             }
-            if let Some(cap) = max_fan_out {
-                if let Some(v) = fan_out.get(&p.name) {
-                    if *v > cap {
-                        violations.push(serde_json::json!({
-                            "code": "MAX_FAN_OUT_EXCEEDED",
-                            "population": p.name,
-                            "fan_out": v,
-                            "cap": cap
-                        }));
-                    }
+        }
+    }
+
+    // Mesh placement: part i starts on core i, then annealing trades cores
+    let (mut coords, mut hop_cost, mut initial_hop_cost) = (Vec::new(), 0u64, 0u64);
+    let mesh = caps.as_ref().and_then(|c| Some(placement::Mesh { width: c.mesh_width?, height: c.mesh_height? }));
+    if let Some(mesh) = mesh {
+        if parts > mesh.cores() {
+            violations.push(serde_json::json!({
+                "code": "MESH_TOO_SMALL",
+                "parts": parts,
+                "cores": mesh.cores()
+            }));
+        } else {
+            let mut traffic = vec![vec![0u64; parts]; parts];
+            let synapses = g.connections.iter().map(|c| (&c.pre, &c.post, 1u64))
+                .chain(g.projections.iter().map(|pj| (&pj.pre, &pj.post, pj.nnz() as u64)));
+            for (pre, post, n) in synapses {
+                let i = *pop_to_part.get(pre).unwrap_or(&0usize);
+                let j = *pop_to_part.get(post).unwrap_or(&0usize);
+                if i != j {
+                    traffic[i][j] += n;
+                    traffic[j][i] += n;
                 }
             }
+            initial_hop_cost = placement::hop_cost(mesh, &traffic, &(0..parts).collect::<Vec<_>>());
+            let core = placement::anneal(mesh, &traffic, options);
+            hop_cost = placement::hop_cost(mesh, &traffic, &core);
+            coords = core.iter().map(|&c| mesh.coord(c)).collect();
         }
+    }
 
-        let status = if violations.is_empty() { "ok" } else { "violations" };
-        let meta = serde_json::json!({
-            "status": status,
-            "parts": parts,
-            "neurons_per_part": neurons_per_part,
-            "synapses_per_part": syn_per_part,
-            "violations": violations
-        });
-        g.attributes.insert("placement".to_string(), meta);
-        Ok(g)
+    let status = if violations.is_empty() { "ok" } else { "violations" };
+    g.set_attr(&nir::PlacementMeta {
+        status: status.to_string(),
+        parts,
+        neurons_per_part,
+        synapses_per_part: syn_per_part,
+        violations,
+        coords,
+        hop_cost,
+        initial_hop_cost,
+    });
+    Ok(g)
+}
+
+/// How `routing` picks among minimal paths on a mesh or torus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RouteAlgorithm {
+    /// Dimension-ordered: all x hops, then all y hops.
+    #[default]
+    Xy,
+    /// Flows are routed busiest first, each hop taking whichever minimal direction has the
+    /// lighter link so far.
+    Adaptive,
+}
+
+impl RouteAlgorithm {
+    /// Parse `xy` or `adaptive`.
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "xy" => Ok(RouteAlgorithm::Xy),
+            "adaptive" => Ok(RouteAlgorithm::Adaptive),
+            _ => bail!("unknown routing algorithm '{s}' (expected xy or adaptive)"),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RouteAlgorithm::Xy => "xy",
+            RouteAlgorithm::Adaptive => "adaptive",
+        }
     }
 }
 
+/// Inter-part traffic and its bandwidth estimate. When the HAL describes the interconnect
+/// (`interconnect_topology`, or mesh dimensions alone for a mesh), each part-to-part flow is
+/// also routed from core to core (placement `coords`, else part `i` on core `i`), giving per
+/// link synapse counts, bandwidth and utilization against `link_bandwidth_mbps` (default
+/// `interconnect_bandwidth_mbps`). Links over capacity make the status `congested`.
 pub struct RoutingPass;
+
 impl Pass for RoutingPass {
     fn name(&self) -> &str { "routing" }
-    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
-        // Load partition assignment
-        let parts = g.attributes
-            .get("partition")
-            .and_then(|v| v.get("parts"))
-            .and_then(|v| v.as_u64())
-            .unwrap_or(1) as usize;
-
-        let mut pop_to_part: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-        if let Some(assign) = g.attributes
-            .get("partition")
-            .and_then(|v| v.get("assignment"))
-            .and_then(|v| v.as_array())
-        {
-            for a in assign {
-                if let (Some(pop), Some(part)) = (
-                    a.get("population").and_then(|x| x.as_str()),
-                    a.get("part").and_then(|x| x.as_u64()),
-                ) {
-                    pop_to_part.insert(pop.to_string(), part as usize);
-                }
-            }
-        } else {
-            for p in &g.populations {
-                pop_to_part.insert(p.name.clone(), 0usize);
-            }
+    fn run(&self, g: nir::Graph) -> Result<nir::Graph> { route(g, RouteAlgorithm::default()) }
+    fn run_with_config(&self, g: nir::Graph, cfg: &PipelineConfig) -> Result<nir::Graph> { route(g, cfg.routing) }
+}
+
+fn route(mut g: nir::Graph, algorithm: RouteAlgorithm) -> Result<nir::Graph> {
+    // Load partition assignment (unassigned populations go to part 0)
+    let partition = g.attrs::<nir::PartitionMeta>().unwrap_or_default();
+    let parts = partition.parts;
+    let pop_to_part = partition.part_of();
+
+    // Count inter-part edges
+    let mut matrix = vec![vec![0usize; parts]; parts];
+    let mut cross_edges = 0usize;
+    for c in &g.connections {
+        let i = *pop_to_part.get(&c.pre).unwrap_or(&0usize);
+        let j = *pop_to_part.get(&c.post).unwrap_or(&0usize);
+        if i != j {
+            matrix[i][j] += 1;
+            cross_edges += 1;
         }
+    }
 
-        // Count inter-part edges
-        let mut matrix = vec![vec![0usize; parts]; parts];
-        let mut cross_edges = 0usize;
-        for c in &g.connections {
-            let i = *pop_to_part.get(&c.pre).unwrap_or(&0usize);
-            let j = *pop_to_part.get(&c.post).unwrap_or(&0usize);
+    // Bandwidth estimate against HAL cap using per-event size and default rate
+    let caps = extract_caps_from_graph(&g);
+    let cap_bw = caps.as_ref().and_then(|c| c.interconnect_bandwidth_mbps).map(|v| v as f64);
+    let bytes_per_event = caps.as_ref().and_then(|c| c.bytes_per_event).unwrap_or(4) as f64;
+    let event_rate_hz = caps.as_ref().and_then(|c| c.default_spike_rate_hz).unwrap_or(100.0);
+    // Estimate: each cross-part edge contributes event_rate_hz spikes/s of size bytes_per_event
+    let est_bw_mbps = (cross_edges as f64) * event_rate_hz * bytes_per_event * 8.0 / 1_000_000.0;
+    let mut meta = nir::RoutingMeta {
+        cross_edges,
+        estimated_bandwidth_mbps: est_bw_mbps,
+        matrix,
+        ..Default::default()
+    };
+    let mbps = |synapses: u64| synapses as f64 * event_rate_hz * bytes_per_event * 8.0 / 1_000_000.0;
+
+    // Route every part-to-part flow over the interconnect
+    let topology = caps.as_ref().and_then(|c| {
+        let mesh = c.mesh_width.zip(c.mesh_height);
+        match (c.interconnect_topology.as_deref(), mesh) {
+            (Some("tree"), _) => Some(routing::Topology::Tree { arity: c.tree_arity.unwrap_or(4) }),
+            (Some("torus"), Some((width, height))) => Some(routing::Topology::Torus { width, height }),
+            (None | Some("mesh"), Some((width, height))) => Some(routing::Topology::Mesh { width, height }),
+            _ => None,
+        }
+    });
+    let cores = caps.as_ref().and_then(|c| Some(c.mesh_width? as usize * c.mesh_height? as usize));
+    let coords = g.attrs::<nir::PlacementMeta>().map(|p| p.coords).unwrap_or_default();
+    let fits = match cores {
+        Some(n) => parts <= n,
+        None => true,
+    };
+    if let (Some(n), false) = (cores, fits) {
+        meta.violations.push(serde_json::json!({
+            "code": "MESH_TOO_SMALL",
+            "parts": parts,
+            "cores": n
+        }));
+    }
+    if let Some(topology) = topology.filter(|_| fits) {
+        let width = caps.as_ref().and_then(|c| c.mesh_width).unwrap_or(1) as usize;
+        let core: Vec<usize> = if coords.len() == parts {
+            coords.iter().map(|&[x, y]| y as usize * width + x as usize).collect()
+        } else {
+            (0..parts).collect()
+        };
+        let mut flows = vec![vec![0u64; parts]; parts];
+        let synapses = g.connections.iter().map(|c| (&c.pre, &c.post, 1u64))
+            .chain(g.projections.iter().map(|pj| (&pj.pre, &pj.post, pj.nnz() as u64)));
+        for (pre, post, n) in synapses {
+            let i = *pop_to_part.get(pre).unwrap_or(&0usize);
+            let j = *pop_to_part.get(post).unwrap_or(&0usize);
             if i != j {
-                matrix[i][j] += 1;
-                cross_edges += 1;
+                flows[i][j] += n;
+            }
+        }
+        let mut order: Vec<(usize, usize, u64)> = (0..parts)
+            .flat_map(|i| (0..parts).map(move |j| (i, j)))
+            .filter_map(|(i, j)| (flows[i][j] > 0).then_some((i, j, flows[i][j])))
+            .collect();
+        if algorithm == RouteAlgorithm::Adaptive {
+            order.sort_by_key(|&(_, _, n)| std::cmp::Reverse(n));
+        }
+        let mut load: BTreeMap<(String, String), u64> = BTreeMap::new();
+        for (i, j, n) in order {
+            let path = topology.route(core[i], core[j], algorithm, &load);
+            for hop in path.windows(2) {
+                *load.entry((hop[0].clone(), hop[1].clone())).or_insert(0) += n;
             }
+            meta.routes.push(nir::PartRoute { from_part: i, to_part: j, synapses: n, path });
         }
+        meta.routes.sort_by_key(|r| (r.from_part, r.to_part));
 
-        // Bandwidth estimate against HAL cap using per-event size and default rate
-        let caps = extract_caps_from_graph(&g);
-        let cap_bw = caps.as_ref().and_then(|c| c.interconnect_bandwidth_mbps).map(|v| v as f64);
-        let bytes_per_event = caps.as_ref().and_then(|c| c.bytes_per_event).unwrap_or(4) as f64;
-        let event_rate_hz = caps.as_ref().and_then(|c| c.default_spike_rate_hz).unwrap_or(100.0);
-        // Estimate: each cross-part edge contributes event_rate_hz spikes/s of size bytes_per_event
-        let est_bw_mbps = (cross_edges as f64) * event_rate_hz * bytes_per_event * 8.0 / 1_000_000.0;
-        let status = match cap_bw {
-            Some(cap) if est_bw_mbps > cap => "congested",
-            _ => "ok",
-        };
+        let link_cap = caps.as_ref().and_then(|c| c.link_bandwidth_mbps).map(|v| v as f64).or(cap_bw);
+        meta.links = load
+            .into_iter()
+            .map(|((from, to), synapses)| nir::LinkLoad {
+                from,
+                to,
+                synapses,
+                mbps: mbps(synapses),
+                utilization: link_cap.map_or(0.0, |cap| mbps(synapses) / cap),
+            })
+            .collect();
+        meta.max_link_utilization = meta.links.iter().map(|l| l.utilization).fold(0.0, f64::max);
+        meta.hotspots = meta.links.iter().filter(|l| l.utilization >= nir::HOTSPOT_UTILIZATION).cloned().collect();
+        meta.hotspots.sort_by(|a, b| b.utilization.total_cmp(&a.utilization));
+        meta.topology = Some(topology.name().to_string());
+        meta.algorithm = Some(algorithm.as_str().to_string());
+        meta.link_capacity_mbps = link_cap;
+    }
 
-        let meta = serde_json::json!({
-            "status": status,
-            "cross_edges": cross_edges,
-            "estimated_bandwidth_mbps": est_bw_mbps,
-            "matrix": matrix,
-        });
-        g.attributes.insert("routing".to_string(), meta);
-        Ok(g)
+    let congested = match cap_bw {
+        Some(cap) => est_bw_mbps > cap,
+        None => false,
+    };
+    meta.status = if !meta.violations.is_empty() {
+        "violations"
+    } else if congested || meta.max_link_utilization > 1.0 {
+        "congested"
+    } else {
+        "ok"
     }
+    .to_string();
+    g.set_attr(&meta);
+    Ok(g)
 }
 
 pub struct TimingPass;
@@ -328,44 +585,103 @@ impl Pass for TimingPass {
         // Use HAL time resolution to translate per-edge delay to discrete ticks
         let caps = extract_caps_from_graph(&g);
         let time_res_ns: u64 = caps.as_ref().and_then(|c| c.time_resolution_ns).unwrap_or(1_000_000); // default 1ms
-        let mut ticks: Vec<u64> = Vec::new();
-        for c in &g.connections {
-            let ns = (c.delay_ms.max(0.0) as f64) * 1_000_000.0;
-            let t = (ns / (time_res_ns as f64)).ceil() as u64;
-            ticks.push(t);
-        }
+        // One delay per connection, one per projection synapse
+        let sizes: std::collections::HashMap<&str, u32> = g.populations.iter().map(|p| (p.name.as_str(), p.size)).collect();
+        let delays = g.connections.iter().map(|c| c.delay_ms).chain(g.projections.iter().flat_map(|pj| {
+            pj.iter_synapses(sizes.get(pj.post.as_str()).copied().unwrap_or(0)).map(|(_, _, _, d)| d)
+        }));
+        let ticks: Vec<u64> = delays
+            .map(|d| ((d.max(0.0) as f64) * 1_000_000.0 / (time_res_ns as f64)).ceil() as u64)
+            .collect();
         let max_ticks = ticks.iter().copied().max().unwrap_or(0);
         let min_ticks = ticks.iter().copied().min().unwrap_or(0);
         let avg_ticks = if ticks.is_empty() { 0.0 } else { (ticks.iter().copied().sum::<u64>() as f64) / (ticks.len() as f64) };
+        // tick count -> number of connections and projection synapses, for sizing delay buffers
+        let mut tick_histogram: BTreeMap<u64, usize> = BTreeMap::new();
+        for t in &ticks {
+            *tick_histogram.entry(*t).or_insert(0) += 1;
+        }
         let meta = serde_json::json!({
             "time_resolution_ns": time_res_ns,
             "max_delay_ticks": max_ticks,
             "min_delay_ticks": min_ticks,
-            "avg_delay_ticks": avg_ticks
+            "avg_delay_ticks": avg_ticks,
+            "tick_histogram": tick_histogram
         });
         g.attributes.insert("timing".to_string(), meta);
         Ok(g)
     }
 }
 
+/// Check that the target's `time_resolution_ns` can represent each connection delay.
+/// Delays that round to 0 ticks are under-resolved; delays whose nearest tick count is off
+/// by more than 50% are flagged as imprecise. Findings go to the `timing_feasibility` attribute.
+pub struct TimingFeasibilityPass;
+impl Pass for TimingFeasibilityPass {
+    fn name(&self) -> &str { "timing-feasibility" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let caps = extract_caps_from_graph(&g);
+        let time_res_ns: u64 = caps.as_ref().and_then(|c| c.time_resolution_ns).unwrap_or(1_000_000).max(1);
+        let mut issues: Vec<serde_json::Value> = Vec::new();
+        let mut warnings: Vec<String> = Vec::new();
+        let (mut under_resolved, mut imprecise) = (0usize, 0usize);
+        for c in &g.connections {
+            // Zero delay is exactly representable
+            if c.delay_ms <= 0.0 {
+                continue;
+            }
+            let ns = (c.delay_ms as f64) * 1_000_000.0;
+            let ticks = (ns / (time_res_ns as f64)).round() as u64;
+            let rel_err = ((ticks as f64) * (time_res_ns as f64) - ns).abs() / ns;
+            let kind = if ticks == 0 {
+                under_resolved += 1;
+                warnings.push(format!(
+                    "{}->{}: delay {} ms rounds to 0 ticks at {} ns resolution (under-resolved)",
+                    c.pre, c.post, c.delay_ms, time_res_ns
+                ));
+                "under_resolved"
+            } else if rel_err > 0.5 {
+                imprecise += 1;
+                warnings.push(format!(
+                    "{}->{}: delay {} ms rounds to {} ticks at {} ns resolution ({:.0}% error)",
+                    c.pre, c.post, c.delay_ms, ticks, time_res_ns, rel_err * 100.0
+                ));
+                "rounding_error"
+            } else {
+                continue;
+            };
+            issues.push(serde_json::json!({
+                "pre": c.pre,
+                "post": c.post,
+                "delay_ms": c.delay_ms,
+                "ticks": ticks,
+                "relative_error": rel_err,
+                "kind": kind
+            }));
+        }
+        let meta = serde_json::json!({
+            "time_resolution_ns": time_res_ns,
+            "feasible": issues.is_empty(),
+            "under_resolved": under_resolved,
+            "rounding_error": imprecise,
+            "issues": issues,
+            "warnings": warnings
+        });
+        g.attributes.insert("timing_feasibility".to_string(), meta);
+        Ok(g)
+    }
+}
+
 pub struct ResourceCheckPass;
 impl Pass for ResourceCheckPass {
     fn name(&self) -> &str { "resource-check" }
     fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
         let caps = extract_caps_from_graph(&g);
 
-        // Partition context
-        let parts = g.attributes.get("partition").and_then(|v| v.get("parts")).and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-        let mut pop_to_part: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-        if let Some(assign) = g.attributes.get("partition").and_then(|v| v.get("assignment")).and_then(|v| v.as_array()) {
-            for a in assign {
-                if let (Some(pop), Some(part)) = (a.get("population").and_then(|x| x.as_str()), a.get("part").and_then(|x| x.as_u64())) {
-                    pop_to_part.insert(pop.to_string(), part as usize);
-                }
-            }
-        } else {
-            for p in &g.populations { pop_to_part.insert(p.name.clone(), 0usize); }
-        }
+        // Partition context (unassigned populations go to part 0)
+        let partition = g.attrs::<nir::PartitionMeta>().unwrap_or_default();
+        let parts = partition.parts;
+        let pop_to_part = partition.part_of();
 
         // Per-part resources
         let mut neurons_per_part = vec![0usize; parts];
@@ -455,6 +771,418 @@ impl Pass for ResourceCheckPass {
     }
 }
 
+/// Check each plastic connection's rule against the target's `on_chip_plasticity_rules`.
+/// Unsupported rules are listed in the `plasticity_check` attribute; without a manifest
+/// every rule is accepted.
+pub struct PlasticityCheckPass;
+impl Pass for PlasticityCheckPass {
+    fn name(&self) -> &str { "plasticity-check" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let caps = extract_caps_from_graph(&g);
+        let mut plastic = 0usize;
+        let mut violations: Vec<serde_json::Value> = Vec::new();
+        for c in &g.connections {
+            let Some(rule) = c.plasticity.as_ref() else { continue };
+            plastic += 1;
+            let kind = format!("{:?}", rule.kind);
+            if caps.as_ref().is_some_and(|cap| !cap.supports_plasticity(&kind)) {
+                violations.push(serde_json::json!({
+                    "code": "PLASTICITY_RULE_UNSUPPORTED",
+                    "pre": c.pre,
+                    "post": c.post,
+                    "rule": kind
+                }));
+            }
+        }
+        let meta = serde_json::json!({
+            "supported": violations.is_empty(),
+            "plastic_connections": plastic,
+            "violations": violations
+        });
+        g.attributes.insert("plasticity_check".to_string(), meta);
+        Ok(g)
+    }
+}
+
+/// Materialize `Graph.connectivity` rules into CSR `Projection`s (one per rule, same weight,
+/// delay and plasticity for every synapse) and clear the rule list. Random rules draw from
+/// `unit_sample` seeded by the rule's `seed`, so expansion is reproducible. Records
+/// `connectivity = {rules, synapses, expanded: [{pre, post, kind, synapses}]}`.
+pub struct ExpandConnectivityPass;
+impl Pass for ExpandConnectivityPass {
+    fn name(&self) -> &str { "expand-connectivity" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let specs = std::mem::take(&mut g.connectivity);
+        let mut expanded: Vec<serde_json::Value> = Vec::new();
+        let mut total = 0usize;
+        for spec in &specs {
+            let find = |name: &str| g.populations.iter().find(|p| p.name == name);
+            let (Some(pre), Some(post)) = (find(&spec.pre), find(&spec.post)) else {
+                bail!("connectivity {}->{} references unknown population", spec.pre, spec.post);
+            };
+            let pairs = expand_rule(spec, pre, post);
+            total += pairs.len();
+            expanded.push(serde_json::json!({
+                "pre": spec.pre,
+                "post": spec.post,
+                "kind": spec.rule.kind(),
+                "synapses": pairs.len(),
+            }));
+            let mut indptr = vec![0u32; pre.size as usize + 1];
+            for &(i, _) in &pairs {
+                indptr[i as usize + 1] += 1;
+            }
+            for i in 0..pre.size as usize {
+                indptr[i + 1] += indptr[i];
+            }
+            let projection = nir::Projection {
+                pre: spec.pre.clone(),
+                post: spec.post.clone(),
+                synapses: nir::SynapseMatrix::Csr {
+                    indptr,
+                    indices: pairs.iter().map(|&(_, j)| j).collect(),
+                    weights: vec![spec.weight; pairs.len()],
+                    delays_ms: None,
+                },
+                delay_ms: spec.delay_ms,
+                plasticity: spec.plasticity.clone(),
+                delay_distribution: spec.delay_distribution,
+                delay_seed: spec.seed,
+            };
+            g.projections.push(projection);
+        }
+        g.attributes.insert("connectivity".to_string(), serde_json::json!({
+            "rules": specs.len(),
+            "synapses": total,
+            "expanded": expanded,
+        }));
+        Ok(g)
+    }
+}
+
+/// `(pre, post)` index pairs selected by `spec.rule`, sorted by pre then post.
+fn expand_rule(spec: &nir::ConnectivitySpec, pre: &nir::Population, post: &nir::Population) -> Vec<(u32, u32)> {
+    let skip_self = spec.pre == spec.post && !spec.autapses;
+    let mut draws = 0usize;
+    let mut next = || {
+        draws += 1;
+        unit_sample(spec.seed, draws - 1)
+    };
+    let mut pairs: Vec<(u32, u32)> = Vec::new();
+    if let nir::ConnectivityRule::FixedInDegree { k } = spec.rule {
+        // Partial Fisher-Yates over the eligible pre neurons of each post neuron
+        for j in 0..post.size {
+            let mut pool: Vec<u32> = (0..pre.size).filter(|&i| !(skip_self && i == j)).collect();
+            for n in 0..(k as usize).min(pool.len()) {
+                let pick = n + (next() * (pool.len() - n) as f64) as usize;
+                pool.swap(n, pick);
+                pairs.push((pool[n], j));
+            }
+        }
+        pairs.sort_unstable();
+        return pairs;
+    }
+    for i in 0..pre.size {
+        for j in 0..post.size {
+            if skip_self && i == j {
+                continue;
+            }
+            let keep = match spec.rule {
+                nir::ConnectivityRule::FixedProbability { p } => next() < p,
+                nir::ConnectivityRule::GaussianDistance { sigma, p_max } => {
+                    let (a, b) = (pre.neuron_position(i), post.neuron_position(j));
+                    let d2: f64 = a.iter().zip(&b).map(|(x, y)| (x - y) * (x - y)).sum();
+                    next() < p_max * (-d2 / (2.0 * sigma * sigma)).exp()
+                }
+                nir::ConnectivityRule::AllToAll | nir::ConnectivityRule::FixedInDegree { .. } => true,
+            };
+            if keep {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+/// Draw every projection's `delay_distribution` into per-synapse `delays_ms`
+/// (`Graph::materialize_delays`), rounded to the target's `time_resolution_ns` when the HAL
+/// declares one; fails when a distribution's spread is below one tick. Records
+/// `delays = {time_resolution_ns, materialized}`. Run it after `expand-connectivity`.
+pub struct MaterializeDelaysPass;
+impl Pass for MaterializeDelaysPass {
+    fn name(&self) -> &str { "materialize-delays" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let time_res_ns = extract_caps_from_graph(&g).and_then(|c| c.time_resolution_ns);
+        let materialized = g.materialize_delays(time_res_ns).map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        g.attributes.insert("delays".to_string(), serde_json::json!({
+            "time_resolution_ns": time_res_ns,
+            "materialized": materialized,
+        }));
+        Ok(g)
+    }
+}
+
+/// Longest accumulated `delay_ms` path through the connection DAG (populations as nodes).
+/// Records `critical_path = {status: "ok", total_delay_ms, path: [population, ...]}`; a graph
+/// with a cycle records `{status: "cyclic"}` instead of failing the pipeline.
+pub struct CriticalPathPass;
+impl Pass for CriticalPathPass {
+    fn name(&self) -> &str { "critical-path" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let n = g.populations.len();
+        let index: std::collections::HashMap<&str, usize> =
+            g.populations.iter().enumerate().map(|(i, p)| (p.name.as_str(), i)).collect();
+        let mut out: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+        let mut indeg = vec![0usize; n];
+        for c in &g.connections {
+            if let (Some(&a), Some(&b)) = (index.get(c.pre.as_str()), index.get(c.post.as_str())) {
+                out[a].push((b, c.delay_ms as f64));
+                indeg[b] += 1;
+            }
+        }
+        // Kahn's algorithm; lowest index first keeps tie-breaking deterministic
+        let mut ready: std::collections::BTreeSet<usize> = (0..n).filter(|&i| indeg[i] == 0).collect();
+        let mut order = Vec::with_capacity(n);
+        while let Some(u) = ready.pop_first() {
+            order.push(u);
+            for &(v, _) in &out[u] {
+                indeg[v] -= 1;
+                if indeg[v] == 0 {
+                    ready.insert(v);
+                }
+            }
+        }
+        let meta = if order.len() < n {
+            serde_json::json!({ "status": "cyclic" })
+        } else {
+            let mut dist = vec![0.0f64; n];
+            let mut prev: Vec<Option<usize>> = vec![None; n];
+            for &u in &order {
+                for &(v, d) in &out[u] {
+                    if dist[u] + d > dist[v] {
+                        dist[v] = dist[u] + d;
+                        prev[v] = Some(u);
+                    }
+                }
+            }
+            let end = (0..n).fold(None, |best: Option<usize>, i| match best {
+                Some(b) if dist[b] >= dist[i] => Some(b),
+                _ => Some(i),
+            });
+            let mut path = Vec::new();
+            let mut cur = end;
+            while let Some(u) = cur {
+                path.push(g.populations[u].name.clone());
+                cur = prev[u];
+            }
+            path.reverse();
+            serde_json::json!({
+                "status": "ok",
+                "total_delay_ms": end.map_or(0.0, |e| dist[e]),
+                "path": path
+            })
+        };
+        g.attributes.insert("critical_path".to_string(), meta);
+        Ok(g)
+    }
+}
+
+/// Built-in canonical neuron model names, keyed by lowercase alias.
+const MODEL_CANONICAL: &[(&str, &str)] = &[
+    ("lif", "LIF"),
+    ("leaky_integrate_and_fire", "LIF"),
+    ("adaptivelif", "AdaptiveLIF"),
+    ("adaptive_lif", "AdaptiveLIF"),
+    ("alif", "AdaptiveLIF"),
+    ("izhikevich", "Izhikevich"),
+    ("izh", "Izhikevich"),
+    ("spikerate", "SpikeRate"),
+    ("spike_rate", "SpikeRate"),
+    ("hh", "HH"),
+    ("hodgkin_huxley", "HH"),
+];
+
+/// Rewrite `Population.model` to canonical names (`lif`/`Lif` -> `LIF`) via `MODEL_CANONICAL`,
+/// matched case-insensitively. Unknown models are unified to the spelling of their first
+/// occurrence in the graph. Every rewrite is recorded in the `model_remap` attribute as
+/// `{original: canonical}`.
+pub struct NormalizeModelNamesPass;
+impl Pass for NormalizeModelNamesPass {
+    fn name(&self) -> &str { "normalize-models" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let mut first_seen: BTreeMap<String, String> = BTreeMap::new();
+        let mut remap = serde_json::Map::new();
+        for p in &mut g.populations {
+            let lower = p.model.to_ascii_lowercase();
+            let canonical = match MODEL_CANONICAL.iter().find(|(alias, _)| *alias == lower) {
+                Some((_, c)) => c.to_string(),
+                None => first_seen.entry(lower).or_insert_with(|| p.model.clone()).clone(),
+            };
+            if canonical != p.model {
+                remap.insert(p.model.clone(), serde_json::json!(canonical));
+                p.model = canonical;
+            }
+        }
+        g.attributes.insert("model_remap".to_string(), serde_json::Value::Object(remap));
+        Ok(g)
+    }
+}
+
+/// Replace multi-compartment populations by point neurons for digital backends: params
+/// become `Population::point_params()` and the compartments are dropped. Records
+/// `compartments = {collapsed: [{population, compartments, dropped_params}]}`, where
+/// `dropped_params` lists params only non-soma compartments set.
+pub struct CollapseCompartmentsPass;
+impl Pass for CollapseCompartmentsPass {
+    fn name(&self) -> &str { "collapse-compartments" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let mut collapsed: Vec<serde_json::Value> = Vec::new();
+        for p in g.populations.iter_mut().filter(|p| !p.compartments.is_empty()) {
+            let params = p.point_params();
+            let mut dropped: BTreeSet<&str> = BTreeSet::new();
+            for c in &p.compartments {
+                let keys = c.params.as_object().into_iter().flat_map(|m| m.keys());
+                dropped.extend(keys.map(String::as_str).filter(|k| params.get(*k).is_none()));
+            }
+            collapsed.push(serde_json::json!({
+                "population": p.name,
+                "compartments": p.compartments.len(),
+                "dropped_params": dropped,
+            }));
+            p.params = params;
+            p.compartments.clear();
+        }
+        g.attributes.insert("compartments".to_string(), serde_json::json!({ "collapsed": collapsed }));
+        Ok(g)
+    }
+}
+
+/// How `DedupConnectionsPass` treats duplicates whose delays differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupMode {
+    /// One connection per `(pre, post)`: weights summed, the smallest delay kept.
+    MinDelay,
+    /// One connection per `(pre, post, delay_ms)`: weights summed across equal delays only.
+    PerDelay,
+}
+
+/// Merge repeated scalar connections between the same pre/post pair (and the same
+/// plasticity rule) into the first of them, summing their weights. Connections with a
+/// `weight_matrix` or `weights_ref` are kept as they are. Records `dedup = {mode, before,
+/// after, merged: [{pre, post, delay_ms, count}]}`.
+pub struct DedupConnectionsPass {
+    pub mode: DedupMode,
+}
+
+impl Pass for DedupConnectionsPass {
+    fn name(&self) -> &str { "dedup" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let before = g.connections.len();
+        let mut first: std::collections::HashMap<(String, String, u32, String), usize> = std::collections::HashMap::new();
+        let mut kept: Vec<(nir::Connection, usize)> = Vec::with_capacity(before);
+        for c in std::mem::take(&mut g.connections) {
+            if c.weight_matrix.is_some() || c.weights_ref.is_some() {
+                kept.push((c, 1));
+                continue;
+            }
+            // `+ 0.0` folds -0.0 into 0.0 so both delays share a key
+            let delay = match self.mode {
+                DedupMode::MinDelay => 0,
+                DedupMode::PerDelay => (c.delay_ms + 0.0).to_bits(),
+            };
+            let rule = c.plasticity.as_ref().map_or(String::new(), |p| serde_json::to_string(p).unwrap_or_default());
+            match first.entry((c.pre.clone(), c.post.clone(), delay, rule)) {
+                std::collections::hash_map::Entry::Occupied(e) => {
+                    let (k, n) = &mut kept[*e.get()];
+                    k.weight += c.weight;
+                    k.delay_ms = k.delay_ms.min(c.delay_ms);
+                    *n += 1;
+                }
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    e.insert(kept.len());
+                    kept.push((c, 1));
+                }
+            }
+        }
+        let merged: Vec<serde_json::Value> = kept
+            .iter()
+            .filter(|(_, n)| *n > 1)
+            .map(|(c, n)| serde_json::json!({ "pre": c.pre, "post": c.post, "delay_ms": c.delay_ms, "count": n }))
+            .collect();
+        g.connections = kept.into_iter().map(|(c, _)| c).collect();
+        g.attributes.insert("dedup".to_string(), serde_json::json!({
+            "mode": match self.mode { DedupMode::MinDelay => "min-delay", DedupMode::PerDelay => "per-delay" },
+            "before": before,
+            "after": g.connections.len(),
+            "merged": merged,
+        }));
+        Ok(g)
+    }
+}
+
+/// Dead population elimination: keep probed populations and every population with a path of
+/// connections, projections or connectivity rules into one; drop the others together with the
+/// connections, projections, rules and inputs touching them, plus orphan entries naming
+/// unknown populations. A graph without probes declares no outputs, so only orphans go.
+/// Records `dce = {removed_populations, removed_connections, removed_projections,
+/// removed_connectivity, removed_inputs}`.
+pub struct DcePass;
+impl Pass for DcePass {
+    fn name(&self) -> &str { "dce" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        let names: BTreeSet<String> = g.populations.iter().map(|p| p.name.clone()).collect();
+        let live: BTreeSet<String> = if g.probes.is_empty() {
+            names
+        } else {
+            // Walk edges backwards from the probed populations
+            let mut pres_of: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+            let edges = g.connections.iter().map(|c| (&c.pre, &c.post));
+            let edges = edges.chain(g.projections.iter().map(|p| (&p.pre, &p.post)));
+            for (pre, post) in edges.chain(g.connectivity.iter().map(|c| (&c.pre, &c.post))) {
+                pres_of.entry(post.as_str()).or_default().push(pre.as_str());
+            }
+            let mut reached: BTreeSet<&str> =
+                g.probes.iter().map(|p| p.target.as_str()).filter(|t| names.contains(*t)).collect();
+            let mut stack: Vec<&str> = reached.iter().copied().collect();
+            while let Some(post) = stack.pop() {
+                for &pre in pres_of.get(post).into_iter().flatten() {
+                    if names.contains(pre) && reached.insert(pre) {
+                        stack.push(pre);
+                    }
+                }
+            }
+            reached.into_iter().map(str::to_string).collect()
+        };
+        let removed: Vec<String> = g.populations.iter().filter(|p| !live.contains(&p.name)).map(|p| p.name.clone()).collect();
+        g.populations.retain(|p| live.contains(&p.name));
+        let keep = |pre: &String, post: &String| live.contains(pre) && live.contains(post);
+        let before = (g.connections.len(), g.projections.len(), g.connectivity.len(), g.inputs.len());
+        g.connections.retain(|c| keep(&c.pre, &c.post));
+        g.projections.retain(|p| keep(&p.pre, &p.post));
+        g.connectivity.retain(|c| keep(&c.pre, &c.post));
+        g.inputs.retain(|i| live.contains(&i.target));
+        g.attributes.insert("dce".to_string(), serde_json::json!({
+            "removed_populations": removed,
+            "removed_connections": before.0 - g.connections.len(),
+            "removed_projections": before.1 - g.projections.len(),
+            "removed_connectivity": before.2 - g.connectivity.len(),
+            "removed_inputs": before.3 - g.inputs.len(),
+        }));
+        Ok(g)
+    }
+}
+
+/// `Graph::canonicalize`: stable ordering, normalized numbers, no transient attributes. It
+/// drops `hal_manifest_path`, which capability-aware passes read, so run it last.
+pub struct CanonicalizePass;
+impl Pass for CanonicalizePass {
+    fn name(&self) -> &str { "canonicalize" }
+    fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+        g.canonicalize();
+        Ok(g)
+    }
+}
+
 /* RISC-V specific pass stubs: LowerToKernels, MemoryLayoutAndQuant, KernelFusionAndScheduling,
    VectorizeKernels, BareMetalTuning, ControlPlaneDriverGen. These are backend-agnostic stubs that
    annotate the graph for downstream RISC-V codegen without requiring hardware routing. */
@@ -501,15 +1229,14 @@ impl Pass for RvMemoryLayoutAndQuantPass {
 
         let vector_bytes = if vector_available { 64 } else { 16 };
         let align_bytes = if vector_available { 64 } else { 16 };
-        let meta = serde_json::json!({
-            "status": "ok",
-            "vector_available": vector_available,
-            "vector_bytes": vector_bytes,
-            "align_bytes": align_bytes,
-            "quant_bits_default": default_bits,
-            "profile": if is_rv32_bare { "rv32-bare" } else { "rv64-linux" },
+        g.set_attr(&nir::RvLayoutMeta {
+            align_bytes,
+            vector_available,
+            vector_bytes,
+            quant_bits_default: default_bits as u64,
+            status: Some("ok".to_string()),
+            profile: Some(if is_rv32_bare { "rv32-bare" } else { "rv64-linux" }.to_string()),
         });
-        g.attributes.insert("rv_layout".to_string(), meta);
         Ok(g)
     }
 }
@@ -518,15 +1245,12 @@ pub struct RvKernelFusionAndSchedulingPass;
 impl Pass for RvKernelFusionAndSchedulingPass {
     fn name(&self) -> &str { "rv-schedule" }
     fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
-        let fused = vec!["integrate", "threshold"];
-        let threads: u32 = 1; // M1: single-threaded baseline
-        let meta = serde_json::json!({
-            "status": "ok",
-            "fused_stages": fused,
-            "threads": threads,
-            "notes": "baseline single-thread schedule"
+        g.set_attr(&nir::RvScheduleMeta {
+            threads: 1, // M1: single-threaded baseline
+            fused_stages: vec!["integrate".to_string(), "threshold".to_string()],
+            status: Some("ok".to_string()),
+            notes: Some("baseline single-thread schedule".to_string()),
         });
-        g.attributes.insert("rv_schedule".to_string(), meta);
         Ok(g)
     }
 }
@@ -535,16 +1259,13 @@ pub struct RvVectorizeKernelsPass;
 impl Pass for RvVectorizeKernelsPass {
     fn name(&self) -> &str { "rv-vectorize" }
     fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
-        let layout = g.attributes.get("rv_layout").cloned().unwrap_or(serde_json::json!({}));
-        let vector_available = layout.get("vector_available").and_then(|v| v.as_bool()).unwrap_or(false);
-        let vlen = layout.get("vector_bytes").and_then(|v| v.as_u64()).unwrap_or(0);
-        let meta = serde_json::json!({
-            "status": "ok",
-            "enabled": vector_available,
-            "vlen_bytes": vlen,
-            "notes": "RVV intrinsic mapping deferred to backend"
+        let layout = g.attrs::<nir::RvLayoutMeta>().unwrap_or_default();
+        g.set_attr(&nir::RvVectorizeMeta {
+            enabled: layout.vector_available,
+            vlen_bytes: layout.vector_bytes,
+            status: Some("ok".to_string()),
+            notes: Some("RVV intrinsic mapping deferred to backend".to_string()),
         });
-        g.attributes.insert("rv_vectorize".to_string(), meta);
         Ok(g)
     }
 }
@@ -559,13 +1280,12 @@ impl Pass for RvBareMetalTuningPass {
             .unwrap_or_default()
             .to_string();
         let size_optimized = manifest_path.ends_with("riscv32imac_bare.toml");
-        let meta = serde_json::json!({
-            "status": "ok",
-            "size_optimized": size_optimized,
-            "use_compressed": true,
-            "notes": "optimize for code size on RV32 bare metal"
+        g.set_attr(&nir::RvBareTuningMeta {
+            size_optimized,
+            use_compressed: true,
+            status: Some("ok".to_string()),
+            notes: Some("optimize for code size on RV32 bare metal".to_string()),
         });
-        g.attributes.insert("rv_bare_tuning".to_string(), meta);
         Ok(g)
     }
 }
@@ -580,17 +1300,19 @@ impl Pass for RvControlPlaneDriverGenPass {
             .unwrap_or_default()
             .to_string();
         let targeted = manifest_path.ends_with("riscv64gc_ctrl.toml");
-        let meta = serde_json::json!({
-            "status": if targeted { "ok" } else { "skipped" },
-            "mmio": { "requires_fence_io": true, "aligned_access": true },
-            "dma": { "supported": targeted, "alignment": 64 },
-            "notes": "generate control-plane configuration for accelerator"
+        g.set_attr(&nir::RvCtrlPlaneMeta {
+            mmio_supported: targeted,
+            mmio_width_bits: None,
+            dma_supported: targeted,
+            dma_alignment: Some(64),
+            status: Some(if targeted { "ok" } else { "skipped" }.to_string()),
+            notes: Some("generate control-plane configuration for accelerator".to_string()),
         });
-        g.attributes.insert("rv_ctrl_plane".to_string(), meta);
         Ok(g)
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DumpFormat {
     Json,
     Yaml,
@@ -602,6 +1324,12 @@ pub struct PipelineConfig {
     pub passes: Vec<String>,
     pub dump_dir: Option<PathBuf>,
     pub dump_formats: Vec<DumpFormat>,
+    /// Write JSON dumps with attribute keys sorted (`Graph::to_json_string_sorted`) for golden-file tests.
+    pub sorted_dumps: bool,
+    /// Annealing settings `placement` uses under `PassManager::run_with_config`.
+    pub placement: PlacementOptions,
+    /// Route selection `routing` uses under `PassManager::run_with_config`.
+    pub routing: RouteAlgorithm,
 }
 
 impl Default for PipelineConfig {
@@ -610,6 +1338,9 @@ impl Default for PipelineConfig {
             passes: vec!["noop".into()],
             dump_dir: None,
             dump_formats: vec![DumpFormat::Json],
+            sorted_dumps: false,
+            placement: PlacementOptions::default(),
+            routing: RouteAlgorithm::default(),
         }
     }
 }
@@ -621,15 +1352,19 @@ pub struct PassManager {
 impl PassManager {
     pub fn new() -> Self { Self { passes: Vec::new() } }
     pub fn add_pass<P: Pass + 'static>(&mut self, p: P) { self.passes.push(Box::new(p)); }
+    /// Names of the registered passes, in run order.
+    pub fn pass_names(&self) -> Vec<String> { self.passes.iter().map(|p| p.name().to_string()).collect() }
 
-    pub fn run(&self, mut g: nir::Graph) -> Result<nir::Graph> {
+    pub fn run(&self, g: nir::Graph) -> Result<nir::Graph> {
+        let mut g = flatten_modules(g)?;
         for p in &self.passes {
             g = p.run(g)?;
         }
         Ok(g)
     }
 
-    pub fn run_with_config(&self, mut g: nir::Graph, cfg: &PipelineConfig) -> Result<nir::Graph> {
+    pub fn run_with_config(&self, g: nir::Graph, cfg: &PipelineConfig) -> Result<nir::Graph> {
+        let mut g = flatten_modules(g)?;
         #[cfg(feature = "telemetry")]
         let app = std::env::var("NC_PROFILE_JSONL")
             .ok()
@@ -646,9 +1381,9 @@ impl PassManager {
                 }
             };
 
-            g = p.run(g)?;
+            g = p.run_with_config(g, cfg)?;
             if let Some(dir) = &cfg.dump_dir {
-                dump_graph(&g, dir, idx, p.name(), &cfg.dump_formats)?;
+                dump_graph(&g, dir, idx, p.name(), &cfg.dump_formats, cfg.sorted_dumps)?;
             }
 
             #[cfg(feature = "telemetry")]
@@ -657,56 +1392,139 @@ impl PassManager {
                 let _ = a.counter("graph.populations", g.populations.len() as f64, l.clone());
                 let _ = a.counter("graph.connections", g.connections.len() as f64, l.clone());
                 let _ = a.counter("graph.probes", g.probes.len() as f64, l);
+                let _ = emit_part_counters(a, &g, p.name());
             }
         }
         Ok(g)
     }
 }
 
+/// Per-part `passes.part_neurons` / `passes.part_synapses` counters from the attribute written
+/// by `placement` or `resource-check`, labelled with `part` on top of graph/pass. Other passes
+/// emit nothing.
+#[cfg(feature = "telemetry")]
+fn emit_part_counters(a: &telemetry::profiling::Appender, g: &nir::Graph, pass: &str) -> Result<()> {
+    let attr = match pass {
+        "placement" => "placement",
+        "resource-check" => "resource_check",
+        _ => return Ok(()),
+    };
+    let Some(meta) = g.attributes.get(attr) else { return Ok(()) };
+    for (metric, key) in [("passes.part_neurons", "neurons_per_part"), ("passes.part_synapses", "synapses_per_part")] {
+        let Some(per_part) = meta.get(key).and_then(|v| v.as_array()) else { continue };
+        for (part, v) in per_part.iter().enumerate() {
+            let l = telemetry::labels::with(telemetry::labels::pass(&g.name, pass), "part", &part.to_string());
+            a.counter(metric, v.as_f64().unwrap_or(0.0), l)?;
+        }
+    }
+    Ok(())
+}
+
+/// Passes operate on the flat representation; expand module instances up front.
+fn flatten_modules(g: nir::Graph) -> Result<nir::Graph> {
+    if g.is_flat() {
+        return Ok(g);
+    }
+    g.flatten().map_err(|e| anyhow::anyhow!(e.to_string()))
+}
+
 impl Default for PassManager {
     fn default() -> Self { Self::new() }
 }
 
-fn dump_graph(g: &nir::Graph, dir: &Path, idx: usize, pass: &str, fmts: &[DumpFormat]) -> Result<()> {
+fn dump_graph(g: &nir::Graph, dir: &Path, idx: usize, pass: &str, fmts: &[DumpFormat], sorted: bool) -> Result<()> {
     if !dir.exists() {
         fs::create_dir_all(dir)?;
     }
     let base = format!("{:02}_{}", idx, pass.replace('/', "_"));
+    // Files already holding the exact bytes are left alone (mtime preserved); anything else,
+    // including a dump edited by hand, is rewritten
     for f in fmts {
-        match f {
+        let (ext, bytes) = match f {
             DumpFormat::Json => {
-                let s = g.to_json_string().map_err(|e| anyhow::anyhow!(e))?;
-                fs::write(dir.join(format!("{base}.json")), s)?;
-            }
-            DumpFormat::Yaml => {
-                let s = g.to_yaml_string().map_err(|e| anyhow::anyhow!(e))?;
-                fs::write(dir.join(format!("{base}.yaml")), s)?;
+                let s = if sorted { g.to_json_string_sorted() } else { g.to_json_string() }
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                ("json", s.into_bytes())
             }
+            DumpFormat::Yaml => ("yaml", g.to_yaml_string().map_err(|e| anyhow::anyhow!(e))?.into_bytes()),
             #[cfg(feature = "bin")]
-            DumpFormat::Bin => {
-                let b = g.to_bytes().map_err(|e| anyhow::anyhow!(e))?;
-                fs::write(dir.join(format!("{base}.bin")), b)?;
-            }
+            DumpFormat::Bin => ("bin", g.to_bytes().map_err(|e| anyhow::anyhow!(e))?),
+        };
+        let path = dir.join(format!("{base}.{ext}"));
+        if fs::read(&path).ok().as_deref() != Some(bytes.as_slice()) {
+            fs::write(path, bytes)?;
         }
     }
     Ok(())
 }
 
+/// Every pass name accepted by `build_pipeline` (aliases included).
+const PASS_NAMES: &[&str] = &[
+    "noop",
+    "no-op",
+    "validate",
+    "quantize4",
+    "quantize8",
+    "quantize16",
+    "partition",
+    "placement",
+    "routing",
+    "timing",
+    "timing-feasibility",
+    "plasticity-check",
+    "normalize-models",
+    "critical-path",
+    "expand-connectivity",
+    "materialize-delays",
+    "resource-check",
+    "resource_check",
+    "collapse-compartments",
+    "dce",
+    "dedup",
+    "dedup-per-delay",
+    "canonicalize",
+    // Parameterized: `clamp:-1:1` etc., listed as its template
+    "clamp:MIN:MAX",
+];
+
+/// Names accepted by `build_pipeline`, in pipeline-authoring order. Parameterized passes
+/// appear as templates (`clamp:MIN:MAX`) whose placeholders are replaced by values.
+pub fn available_passes() -> &'static [&'static str] {
+    PASS_NAMES
+}
+
 /// Build a pipeline by pass names (string identifiers)
 pub fn build_pipeline(pm: &mut PassManager, names: &[String]) -> Result<()> {
     for n in names {
+        // Parameterized passes carry their arguments in the name
+        if let Some(spec) = n.strip_prefix("clamp:") {
+            pm.add_pass(ClampWeightsPass::parse(spec)?);
+            continue;
+        }
         match n.as_str() {
             "noop" | "no-op" => pm.add_pass(NoOpPass),
             "validate" => pm.add_pass(ValidatePass),
-            "quantize4" => pm.add_pass(QuantizeWeightsPass { bits: 4 }),
-            "quantize8" => pm.add_pass(QuantizeWeightsPass { bits: 8 }),
-            "quantize16" => pm.add_pass(QuantizeWeightsPass { bits: 16 }),
+            "quantize4" => pm.add_pass(QuantizeWeightsPass { bits: 4, mode: QuantMode::Nearest }),
+            "quantize8" => pm.add_pass(QuantizeWeightsPass { bits: 8, mode: QuantMode::Nearest }),
+            "quantize16" => pm.add_pass(QuantizeWeightsPass { bits: 16, mode: QuantMode::Nearest }),
             "partition" => pm.add_pass(PartitionPass),
             "placement" => pm.add_pass(PlacementPass),
             "routing" => pm.add_pass(RoutingPass),
             "timing" => pm.add_pass(TimingPass),
+            "timing-feasibility" => pm.add_pass(TimingFeasibilityPass),
+            "plasticity-check" => pm.add_pass(PlasticityCheckPass),
+            "normalize-models" => pm.add_pass(NormalizeModelNamesPass),
+            "critical-path" => pm.add_pass(CriticalPathPass),
+            "expand-connectivity" => pm.add_pass(ExpandConnectivityPass),
+            "materialize-delays" => pm.add_pass(MaterializeDelaysPass),
             "resource-check" | "resource_check" => pm.add_pass(ResourceCheckPass),
-            other => bail!("unknown pass '{other}'"),
+            "collapse-compartments" => pm.add_pass(CollapseCompartmentsPass),
+            "dce" => pm.add_pass(DcePass),
+            "dedup" => pm.add_pass(DedupConnectionsPass { mode: DedupMode::MinDelay }),
+            "dedup-per-delay" => pm.add_pass(DedupConnectionsPass { mode: DedupMode::PerDelay }),
+            "canonicalize" => pm.add_pass(CanonicalizePass),
+            // `available_passes_all_buildable` keeps PASS_NAMES in step with these arms
+            other => bail!("unknown pass '{other}' (available: {})", PASS_NAMES.join(", ")),
         }
     }
     Ok(())
@@ -724,6 +1542,387 @@ mod tests {
         assert_eq!(out.name, "t");
     }
 
+    #[test]
+    fn pipeline_flattens_module_instances() {
+        let mut g = nir::Graph::new("mod");
+        g.modules.push(nir::Module {
+            name: "pair".into(),
+            populations: nir::fixtures::chain(&[2, 2]).populations,
+            connections: nir::fixtures::chain(&[2, 2]).connections,
+            instances: Vec::new(),
+            ports: vec!["p0".into()],
+        });
+        g.instances.push(nir::ModuleInstance { name: "a".into(), module: "pair".into() });
+        let mut pm = PassManager::new();
+        pm.add_pass(ValidatePass);
+        let out = pm.run(g).unwrap();
+        assert!(out.is_flat());
+        assert_eq!(out.populations.len(), 2);
+        assert_eq!(out.connections[0].pre, "a.p0");
+    }
+
+    #[test]
+    fn available_passes_all_buildable() {
+        let names = available_passes();
+        assert!(names.contains(&"partition"));
+        assert!(names.contains(&"quantize8"));
+        assert!(names.contains(&"clamp:MIN:MAX"), "parameterized passes are discoverable");
+        for n in names {
+            let n = n.replace("MIN:MAX", "-1:1");
+            let mut pm = PassManager::new();
+            build_pipeline(&mut pm, std::slice::from_ref(&n)).unwrap_or_else(|e| panic!("{n}: {e}"));
+        }
+        let mut pm = PassManager::new();
+        assert!(build_pipeline(&mut pm, &["bogus".to_string()]).is_err());
+    }
+
+    #[test]
+    fn sorted_dumps_write_sorted_attribute_keys() {
+        let dir = std::env::temp_dir().join(format!("nc_sorted_dumps_{}", std::process::id()));
+        let mut g = nir::Graph::new("ts");
+        g.attributes.insert("zeta".into(), serde_json::json!(1));
+        g.attributes.insert("alpha".into(), serde_json::json!(2));
+        let mut pm = PassManager::new();
+        pm.add_pass(NoOpPass);
+        let cfg = PipelineConfig { dump_dir: Some(dir.clone()), sorted_dumps: true, ..Default::default() };
+        let out = pm.run_with_config(g, &cfg).unwrap();
+        let dumped = fs::read_to_string(dir.join("00_no-op.json")).unwrap();
+        assert_eq!(dumped, out.to_json_string_sorted().unwrap());
+        assert!(dumped.find("\"alpha\"").unwrap() < dumped.find("\"zeta\"").unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn collapse_compartments_yields_point_neurons() {
+        let json = r#"{"name": "m", "populations": [
+            {"name": "pyr", "size": 4, "model": "HH", "compartments": [
+                {"name": "soma", "kind": "soma", "params": {"cm": 1.0}},
+                {"name": "d1", "kind": "dendrite", "parent": "soma", "params": {"cm": 3.0, "nmda": 0.2}}]},
+            {"name": "pt", "size": 1, "model": "LIF", "params": {"tau_m": 0.02}}]}"#;
+        let g = nir::Graph::from_json_str(json).unwrap();
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["collapse-compartments".to_string(), "validate".to_string()]).unwrap();
+        let out = pm.run(g).unwrap();
+        assert!(out.populations.iter().all(|p| p.compartments.is_empty()));
+        assert_eq!(out.populations[0].params, serde_json::json!({ "cm": 2.0 }));
+        assert_eq!(out.populations[1].params, serde_json::json!({ "tau_m": 0.02 }));
+        assert_eq!(
+            out.attributes["compartments"],
+            serde_json::json!({ "collapsed": [{ "population": "pyr", "compartments": 2, "dropped_params": ["nmda"] }] })
+        );
+    }
+
+    #[test]
+    fn canonicalize_pass_makes_dumps_independent_of_input_order() {
+        let mut a = nir::Graph::new("c");
+        for name in ["y", "x"] {
+            a.populations.push(nir::Population {
+                name: name.into(),
+                size: 2,
+                model: "LIF".into(),
+                params: serde_json::json!({}),
+                group: None,
+                geometry: None,
+                compartments: Vec::new(),
+            });
+        }
+        a.probes.push(nir::Probe { target: "y".into(), kind: "spikes".into(), ..Default::default() });
+        a.probes.push(nir::Probe { target: "x".into(), kind: "spikes".into(), ..Default::default() });
+        let mut b = a.clone();
+        b.populations.reverse();
+        b.probes.reverse();
+        b.attributes.insert("hal_manifest_path".into(), serde_json::json!("/somewhere/target.toml"));
+
+        let run = |g: nir::Graph| {
+            let mut pm = PassManager::new();
+            build_pipeline(&mut pm, &["canonicalize".to_string()]).unwrap();
+            pm.run(g).unwrap().to_json_string().unwrap()
+        };
+        assert_eq!(run(a), run(b));
+    }
+
+    #[test]
+    fn dumps_are_rewritten_only_when_their_bytes_differ() {
+        let dir = std::env::temp_dir().join(format!("nc_dump_cache_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut pm = PassManager::new();
+        pm.add_pass(NoOpPass);
+        let cfg = PipelineConfig { dump_dir: Some(dir.clone()), ..Default::default() };
+        let dump = dir.join("00_no-op.json");
+        pm.run_with_config(nir::Graph::new("cache"), &cfg).unwrap();
+        let original = fs::read_to_string(&dump).unwrap();
+        let mtime = fs::metadata(&dump).unwrap().modified().unwrap();
+
+        // Same content: the dump is not rewritten
+        pm.run_with_config(nir::Graph::new("cache"), &cfg).unwrap();
+        assert_eq!(fs::metadata(&dump).unwrap().modified().unwrap(), mtime);
+        // A hand-edited dump is restored on the next run
+        fs::write(&dump, "edited").unwrap();
+        pm.run_with_config(nir::Graph::new("cache"), &cfg).unwrap();
+        assert_eq!(fs::read_to_string(&dump).unwrap(), original);
+        // A new format is written alongside
+        let yaml_too = PipelineConfig { dump_formats: vec![DumpFormat::Json, DumpFormat::Yaml], ..cfg };
+        pm.run_with_config(nir::Graph::new("cache"), &yaml_too).unwrap();
+        assert!(dir.join("00_no-op.yaml").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn normalize_models_canonicalizes_case() {
+        let mut g = nir::Graph::new("tn");
+        for (name, model) in [("a", "lif"), ("b", "Lif"), ("c", "LIF"), ("d", "CustomCell"), ("e", "customcell")] {
+            g.populations.push(nir::Population { name: name.into(), size: 1, model: model.into(), params: serde_json::json!({}), group: None, geometry: None, compartments: Vec::new() });
+        }
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["normalize-models".to_string()]).unwrap();
+        let out = pm.run(g).unwrap();
+        let models: Vec<&str> = out.populations.iter().map(|p| p.model.as_str()).collect();
+        assert_eq!(models, ["LIF", "LIF", "LIF", "CustomCell", "CustomCell"]);
+        let remap = &out.attributes["model_remap"];
+        assert_eq!(remap["lif"], "LIF");
+        assert_eq!(remap["Lif"], "LIF");
+        assert_eq!(remap["customcell"], "CustomCell");
+        assert!(remap.get("LIF").is_none());
+    }
+
+    #[test]
+    fn critical_path_sums_chain_delays() {
+        let mut g = nir::fixtures::chain(&[2, 2, 2, 2]);
+        for (c, d) in g.connections.iter_mut().zip([1.0f32, 2.5, 0.5]) {
+            c.delay_ms = d;
+        }
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["critical-path".to_string()]).unwrap();
+        let out = pm.run(g).unwrap();
+        let cp = &out.attributes["critical_path"];
+        assert_eq!(cp["status"], "ok");
+        assert!((cp["total_delay_ms"].as_f64().unwrap() - 4.0).abs() < 1e-9);
+        assert_eq!(cp["path"], serde_json::json!(["p0", "p1", "p2", "p3"]));
+
+        let ring = nir::fixtures::ring(3, 2, 0.5, 1.0);
+        let out = pm.run(ring).expect("cycles do not fail the pipeline");
+        assert_eq!(out.attributes["critical_path"]["status"], "cyclic");
+    }
+
+    #[test]
+    fn timing_histogram_counts_every_connection() {
+        let mut g = nir::fixtures::chain(&[1, 1, 1, 1, 1]);
+        for (c, d) in g.connections.iter_mut().zip([1.0f32, 3.0, 1.0, 0.0]) {
+            c.delay_ms = d;
+        }
+        let out = TimingPass.run(g).unwrap();
+        let hist = out.attributes["timing"]["tick_histogram"].as_object().unwrap();
+        assert_eq!(hist["1"], 2);
+        assert_eq!(hist["3"], 1);
+        assert_eq!(hist["0"], 1);
+        assert_eq!(hist.values().map(|v| v.as_u64().unwrap()).sum::<u64>(), 4);
+
+        let empty = TimingPass.run(nir::Graph::new("empty")).unwrap();
+        assert_eq!(empty.attributes["timing"]["tick_histogram"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn expand_connectivity_materializes_rules() {
+        let mut g = nir::fixtures::chain(&[20, 30]);
+        g.connections.clear();
+        let spec = |rule| nir::ConnectivitySpec {
+            pre: "p0".into(),
+            post: "p1".into(),
+            rule,
+            weight: 0.5,
+            delay_ms: 1.0,
+            seed: 42,
+            autapses: false,
+            plasticity: None,
+            delay_distribution: None,
+        };
+        g.connectivity = vec![
+            spec(nir::ConnectivityRule::AllToAll),
+            spec(nir::ConnectivityRule::FixedInDegree { k: 3 }),
+            spec(nir::ConnectivityRule::FixedProbability { p: 0.5 }),
+            spec(nir::ConnectivityRule::GaussianDistance { sigma: 2.0, p_max: 1.0 }),
+        ];
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["expand-connectivity".to_string(), "validate".to_string()]).unwrap();
+        let out = pm.run(g.clone()).unwrap();
+        assert!(out.connectivity.is_empty());
+        assert_eq!(out.projections.len(), 4);
+        assert_eq!(out.projections[0].nnz(), 600);
+        assert_eq!(out.projections[1].nnz(), 90);
+        let mut in_degree = [0u32; 30];
+        for (_, j, w, d) in out.projections[1].iter_synapses(30) {
+            in_degree[j as usize] += 1;
+            assert_eq!((w, d), (0.5, 1.0));
+        }
+        assert!(in_degree.iter().all(|&n| n == 3));
+        let p = out.projections[2].nnz();
+        assert!((200..400).contains(&p), "p=0.5 over 600 pairs gave {p}");
+        // Gaussian kernel keeps only nearby pairs
+        assert!(out.projections[3].iter_synapses(30).all(|(i, j, _, _)| (i as i64 - j as i64).abs() < 12));
+        assert_eq!(out.attributes["connectivity"]["rules"], 4);
+
+        let again = pm.run(g).unwrap();
+        assert_eq!(again.projections[2].synapses, out.projections[2].synapses, "same seed, same synapses");
+    }
+
+    #[test]
+    fn dce_removes_populations_that_cannot_reach_a_probe() {
+        // p0 -> p1 -> p2 with p1 probed; p3 only receives input; a connection from a missing population
+        let mut g = nir::fixtures::chain(&[2, 2, 2, 2]);
+        g.connections.truncate(2);
+        g.connections.push(nir::Connection { pre: "ghost".into(), post: "p1".into(), ..g.connections[0].clone() });
+        g.probes.push(nir::Probe { target: "p1".into(), kind: "spikes".into(), ..Default::default() });
+        g.inputs.push(nir::Input {
+            name: "bg".into(),
+            target: "p3".into(),
+            source: nir::InputSource::Poisson { rate_hz: 10.0 },
+            seed: 0,
+        });
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["dce".to_string(), "validate".to_string()]).unwrap();
+        let out = pm.run(g.clone()).unwrap();
+        let names: Vec<&str> = out.populations.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["p0", "p1"]);
+        assert_eq!(out.connections.len(), 1);
+        assert!(out.inputs.is_empty());
+        let dce = &out.attributes["dce"];
+        assert_eq!(dce["removed_populations"], serde_json::json!(["p2", "p3"]));
+        assert_eq!((dce["removed_connections"].as_u64(), dce["removed_inputs"].as_u64()), (Some(2), Some(1)));
+
+        // Without probes only the orphan connection goes
+        g.probes.clear();
+        let out = pm.run(g).unwrap();
+        assert_eq!((out.populations.len(), out.connections.len()), (4, 2));
+    }
+
+    #[test]
+    fn dedup_merges_repeated_connections() {
+        let mut g = nir::fixtures::chain(&[2, 2]);
+        let base = g.connections[0].clone();
+        for (w, d) in [(0.25f32, 2.0f32), (0.5, 1.0), (-0.1, 2.0)] {
+            g.connections.push(nir::Connection { weight: w, delay_ms: d, ..base.clone() });
+        }
+        g.connections.push(nir::Connection { weight_matrix: Some(vec![vec![1.0; 2]; 2]), weight: 0.0, ..base.clone() });
+        let before = g.connections.len();
+        let run = |name: &str, g: nir::Graph| {
+            let mut pm = PassManager::new();
+            build_pipeline(&mut pm, &[name.to_string(), "validate".to_string()]).unwrap();
+            pm.run(g).unwrap()
+        };
+
+        let out = run("dedup", g.clone());
+        assert_eq!(out.connections.len(), 2, "scalar duplicates merge, the matrix connection stays");
+        let merged = &out.connections[0];
+        assert!((merged.weight - (base.weight + 0.65)).abs() < 1e-6);
+        assert_eq!(merged.delay_ms, base.delay_ms.min(1.0));
+        let dedup = &out.attributes["dedup"];
+        assert_eq!((dedup["before"].as_u64(), dedup["after"].as_u64()), (Some(before as u64), Some(2)));
+        assert_eq!(dedup["merged"][0]["count"], 4);
+
+        let out = run("dedup-per-delay", g);
+        let scalar: Vec<(f32, f32)> =
+            out.connections.iter().filter(|c| c.weight_matrix.is_none()).map(|c| (c.delay_ms, c.weight)).collect();
+        assert_eq!(scalar.len(), 2, "one connection per distinct delay");
+        assert!((scalar[0].1 - (base.weight + 0.5)).abs() < 1e-6 && (scalar[1].1 - 0.15).abs() < 1e-6, "{scalar:?}");
+        assert_eq!(out.attributes["dedup"]["mode"], "per-delay");
+    }
+
+    #[test]
+    fn clamp_pins_and_counts_out_of_range_weights() {
+        let mut g = nir::fixtures::chain(&[1, 1, 1, 1]);
+        for (c, w) in g.connections.iter_mut().zip([0.9f32, -0.2, -3.0]) {
+            c.weight = w;
+        }
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["clamp:-0.5:0.5".to_string(), "quantize8".to_string()]).unwrap();
+        assert_eq!(pm.pass_names(), ["clamp", "quantize"]);
+        let out = pm.run(g.clone()).unwrap();
+        assert_eq!(out.attributes["clamp"]["clamped"], 2);
+        assert_eq!(out.attributes["clamp"]["total"], 3);
+
+        let clamped = ClampWeightsPass { min: -0.5, max: 0.5 }.run(g).unwrap();
+        let ws: Vec<f32> = clamped.connections.iter().map(|c| c.weight).collect();
+        assert_eq!(ws, [0.5, -0.2, -0.5]);
+
+        for bad in ["clamp:1:0", "clamp:x:1", "clamp:0.5"] {
+            assert!(build_pipeline(&mut PassManager::new(), &[bad.to_string()]).is_err(), "{bad}");
+        }
+    }
+
+    /// `p0 -> p1` (sizes 2 and 3) as a dense `weight_matrix` connection plus a CSR projection
+    /// expanded from a fixed in-degree rule, every synapse carrying weight `w` and delay `d`.
+    fn matrix_and_csr_graph(w: f32, d: f32) -> nir::Graph {
+        let mut g = nir::fixtures::chain(&[2, 3]);
+        g.connections[0].weight = 0.0;
+        g.connections[0].delay_ms = d;
+        g.connections[0].weight_matrix = Some(vec![vec![w; 3]; 2]);
+        g.connectivity.push(nir::ConnectivitySpec {
+            pre: "p0".into(),
+            post: "p1".into(),
+            rule: nir::ConnectivityRule::FixedInDegree { k: 2 },
+            weight: w,
+            delay_ms: d,
+            seed: 1,
+            autapses: false,
+            plasticity: None,
+            delay_distribution: None,
+        });
+        let g = ExpandConnectivityPass.run(g).unwrap();
+        assert!(matches!(g.projections[0].synapses, nir::SynapseMatrix::Csr { .. }));
+        g
+    }
+
+    #[test]
+    fn quantize_rewrites_matrix_and_projection_weights() {
+        let out = QuantizeWeightsPass { bits: 4, mode: QuantMode::Nearest }.run(matrix_and_csr_graph(0.3, 0.0)).unwrap();
+        let q = QuantizeWeightsPass::quantize(0.3, 4);
+        assert_ne!(q, 0.3);
+        assert!(out.connections[0].weight_matrix.iter().flatten().flatten().all(|&w| w == q));
+        assert!(out.projections[0].iter_synapses(3).all(|(_, _, w, _)| w == q));
+        assert_eq!(out.connections[0].weight, 0.0, "the scalar weight stays unused");
+    }
+
+    #[test]
+    fn clamp_covers_matrix_and_projection_weights() {
+        let out = ClampWeightsPass { min: -0.5, max: 0.5 }.run(matrix_and_csr_graph(2.0, 0.0)).unwrap();
+        assert!(out.connections[0].weight_matrix.iter().flatten().flatten().all(|&w| w == 0.5));
+        assert!(out.projections[0].iter_synapses(3).all(|(_, _, w, _)| w == 0.5));
+        // 6 matrix entries plus 3 posts x in-degree 2
+        assert_eq!(out.attributes["clamp"]["clamped"], 12);
+        assert_eq!(out.attributes["clamp"]["total"], 12);
+    }
+
+    #[test]
+    fn timing_histogram_counts_projection_synapses() {
+        let out = TimingPass.run(matrix_and_csr_graph(0.1, 2.0)).unwrap();
+        let hist = out.attributes["timing"]["tick_histogram"].as_object().unwrap();
+        assert_eq!(hist["2"], 7, "one connection and six projection synapses");
+        assert_eq!(out.attributes["timing"]["max_delay_ticks"], 2);
+    }
+
+    #[test]
+    fn stochastic_quantization_is_reproducible_and_bounded() {
+        let mut g = nir::fixtures::star(1, 1, 64, 0.0, 0.0);
+        for (i, c) in g.connections.iter_mut().enumerate() {
+            c.weight = -1.5 + 3.0 * i as f32 / 63.0;
+        }
+        let pass = |seed| QuantizeWeightsPass { bits: 4, mode: QuantMode::Stochastic { seed } };
+        let a = pass(7).run(g.clone()).unwrap();
+        let b = pass(7).run(g.clone()).unwrap();
+        let wa: Vec<f32> = a.connections.iter().map(|c| c.weight).collect();
+        let wb: Vec<f32> = b.connections.iter().map(|c| c.weight).collect();
+        assert_eq!(wa, wb);
+        assert!(wa.iter().all(|w| (-1.0..=1.0).contains(w)));
+        // Every output sits on a 4-bit level adjacent to the input
+        let step = 2.0 / 15.0;
+        for (c, w) in g.connections.iter().zip(&wa) {
+            let x = c.weight.clamp(-1.0, 1.0);
+            assert!((w - x).abs() <= step + 1e-6, "{x} -> {w}");
+        }
+        let other: Vec<f32> = pass(8).run(g).unwrap().connections.iter().map(|c| c.weight).collect();
+        assert_ne!(wa, other, "a different seed should change some roundings");
+    }
+
     #[test]
     fn run_validate_pipeline() {
         let g = nir::Graph::new("t2");
@@ -736,15 +1935,239 @@ mod tests {
     #[test]
     fn run_quantize_pipeline() {
         let mut g = nir::Graph::new("tq");
-        g.populations.push(nir::Population { name: "a".into(), size: 1, model: "LIF".into(), params: serde_json::json!({}) });
-        g.populations.push(nir::Population { name: "b".into(), size: 1, model: "LIF".into(), params: serde_json::json!({}) });
-        g.connections.push(nir::Connection { pre: "a".into(), post: "b".into(), weight: 0.1234, delay_ms: 0.0, plasticity: None });
+        g.populations.push(nir::Population { name: "a".into(), size: 1, model: "LIF".into(), params: serde_json::json!({}), group: None, geometry: None, compartments: Vec::new() });
+        g.populations.push(nir::Population { name: "b".into(), size: 1, model: "LIF".into(), params: serde_json::json!({}), group: None, geometry: None, compartments: Vec::new() });
+        g.connections.push(nir::Connection { pre: "a".into(), post: "b".into(), weight: 0.1234, delay_ms: 0.0, plasticity: None, weight_matrix: None, weights_ref: None });
         let mut pm = PassManager::new();
         pm.add_pass(ValidatePass);
-        pm.add_pass(QuantizeWeightsPass { bits: 8 });
+        pm.add_pass(QuantizeWeightsPass { bits: 8, mode: QuantMode::Nearest });
         let out = pm.run(g).unwrap();
         assert_eq!(out.name, "tq");
         assert!(out.connections[0].weight.is_finite());
         assert!(out.connections[0].weight >= -1.0 && out.connections[0].weight <= 1.0);
     }
+
+    #[test]
+    fn partition_keeps_heavily_connected_populations_together() {
+        // spinnaker2: 1024 neurons per core, so four 500-neuron populations need two parts
+        let manifest = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../targets/spinnaker2.toml");
+        let mut g = nir::fixtures::chain(&[500; 4]);
+        let base = g.connections[0].clone();
+        for (pre, post) in [("p0", "p2"), ("p2", "p0"), ("p1", "p3"), ("p3", "p1")] {
+            for _ in 0..2 {
+                g.connections.push(nir::Connection { pre: pre.into(), post: post.into(), ..base.clone() });
+            }
+        }
+        g.attributes.insert("hal_manifest_path".into(), serde_json::json!(manifest.to_string_lossy()));
+        let mut pm = PassManager::new();
+        pm.add_pass(PartitionPass);
+        let meta = pm.run(g).unwrap().attrs::<nir::PartitionMeta>().unwrap();
+        assert_eq!((meta.parts, meta.strategy.as_str()), (2, "multilevel"));
+        let part = meta.part_of();
+        assert_eq!(part["p0"], part["p2"]);
+        assert_eq!(part["p1"], part["p3"]);
+        assert_ne!(part["p0"], part["p1"]);
+        assert_eq!(meta.cut_synapses, 3, "only the chain edges cross parts");
+        assert!(meta.violations.is_empty());
+    }
+
+    #[test]
+    fn placement_anneals_parts_onto_the_core_mesh() {
+        // spinnaker2 has an 8x19 core mesh; parts start on cores 0..3 along the first row
+        let manifest = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../targets/spinnaker2.toml");
+        let mut g = nir::fixtures::chain(&[10; 4]);
+        g.attributes.insert("hal_manifest_path".into(), serde_json::json!(manifest.to_string_lossy()));
+        // p0 -> p1 -> p2 -> p3 on parts 0, 2, 1, 3: five hops from the identity placement
+        let parts = [0, 2, 1, 3];
+        g.set_attr(&nir::PartitionMeta {
+            parts: 4,
+            assignment: parts.iter().enumerate().map(|(i, &part)| nir::PartAssignment { population: format!("p{i}"), part }).collect(),
+            ..Default::default()
+        });
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["placement".to_string()]).unwrap();
+        let meta = pm.run(g.clone()).unwrap().attrs::<nir::PlacementMeta>().unwrap();
+        assert_eq!((meta.initial_hop_cost, meta.hop_cost), (5, 3), "{:?}", meta.coords);
+        let hops = |a: [u32; 2], b: [u32; 2]| a[0].abs_diff(b[0]) + a[1].abs_diff(b[1]);
+        let c = &meta.coords;
+        assert_eq!(hops(c[0], c[2]) + hops(c[2], c[1]) + hops(c[1], c[3]), 3);
+        assert_eq!(pm.run(g.clone()).unwrap().attrs::<nir::PlacementMeta>().unwrap().coords, meta.coords, "same seed, same placement");
+
+        // Settings come from the config at run time: without annealing steps the parts stay
+        // on their starting cores
+        let cfg = PipelineConfig { placement: PlacementOptions { iterations: 0, ..Default::default() }, ..Default::default() };
+        let meta = pm.run_with_config(g, &cfg).unwrap().attrs::<nir::PlacementMeta>().unwrap();
+        assert_eq!(meta.coords, [[0, 0], [1, 0], [2, 0], [3, 0]]);
+        assert_eq!(meta.hop_cost, 5);
+    }
+
+    #[test]
+    fn routing_maps_flows_onto_interconnect_links() {
+        let dir = std::env::temp_dir().join(format!("nc_passes_routing_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manifest = |topology: &str| {
+            let path = dir.join(format!("{topology}.toml"));
+            fs::write(&path, format!(
+                "name = \"t\"\nvendor = \"v\"\nfamily = \"F\"\nversion = \"1\"\n[capabilities]\n\
+                 mesh_width = 3\nmesh_height = 2\ninterconnect_topology = \"{topology}\"\ntree_arity = 2\n\
+                 link_bandwidth_mbps = 1\nbytes_per_event = 4\ndefault_spike_rate_hz = 100.0\n"
+            )).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        // p0..p3 on parts 0..3 at cores (0,0), (2,1), (0,1), (1,0); 400 extra synapses p0 -> p1
+        let mut g = nir::fixtures::chain(&[10; 4]);
+        let base = g.connections[0].clone();
+        g.connections.extend((0..400).map(|_| base.clone()));
+        g.connections.push(nir::Connection { pre: "p3".into(), post: "p1".into(), ..base });
+        g.set_attr(&nir::PartitionMeta {
+            parts: 4,
+            assignment: (0..4).map(|i| nir::PartAssignment { population: format!("p{i}"), part: i }).collect(),
+            ..Default::default()
+        });
+        g.set_attr(&nir::PlacementMeta { parts: 4, coords: vec![[0, 0], [2, 1], [0, 1], [1, 0]], ..Default::default() });
+        let run = |topology: &str, algorithm: RouteAlgorithm| {
+            let mut g = g.clone();
+            g.attributes.insert("hal_manifest_path".into(), serde_json::json!(manifest(topology)));
+            let cfg = PipelineConfig { routing: algorithm, ..Default::default() };
+            RoutingPass.run_with_config(g, &cfg).unwrap().attrs::<nir::RoutingMeta>().unwrap()
+        };
+        let path = |meta: &nir::RoutingMeta, from: usize, to: usize| {
+            meta.routes.iter().find(|r| (r.from_part, r.to_part) == (from, to)).unwrap().path.join(" ")
+        };
+
+        let xy = run("mesh", RouteAlgorithm::Xy);
+        assert_eq!((xy.topology.as_deref(), xy.algorithm.as_deref()), (Some("mesh"), Some("xy")));
+        assert_eq!(path(&xy, 0, 1), "x0y0 x1y0 x2y0 x2y1");
+        assert_eq!(path(&xy, 3, 1), "x1y0 x2y0 x2y1");
+        // 402 synapses * 100 Hz * 4 B exceed the 1 Mbps link
+        assert_eq!(xy.status, "congested");
+        assert_eq!((xy.hotspots[0].synapses, xy.hotspots[0].from.as_str()), (402, "x1y0"));
+        assert!(xy.max_link_utilization > 1.0 && xy.hotspots.iter().all(|l| l.synapses >= 401));
+
+        let adaptive = run("mesh", RouteAlgorithm::Adaptive);
+        assert_eq!(path(&adaptive, 3, 1), "x1y0 x1y1 x2y1", "steers around the busy x link");
+        assert_eq!(adaptive.hotspots.iter().map(|l| l.synapses).max(), Some(401));
+
+        let torus = run("torus", RouteAlgorithm::Xy);
+        assert_eq!(path(&torus, 1, 2), "x2y1 x0y1", "wraps around");
+
+        let tree = run("tree", RouteAlgorithm::Xy);
+        assert_eq!(path(&tree, 0, 1), "c0 r1.0 r2.0 r3.0 r2.1 r1.2 c5");
+
+        // Seven parts do not fit the six cores: reported instead of silently left unrouted
+        let mut g = g.clone();
+        let mut partition = g.attrs::<nir::PartitionMeta>().unwrap();
+        partition.parts = 7;
+        g.set_attr(&partition);
+        g.attributes.insert("hal_manifest_path".into(), serde_json::json!(manifest("mesh")));
+        let unplaced = RoutingPass.run(g).unwrap().attrs::<nir::RoutingMeta>().unwrap();
+        assert_eq!(unplaced.status, "violations");
+        assert_eq!(unplaced.violations, [serde_json::json!({"code": "MESH_TOO_SMALL", "parts": 7, "cores": 6})]);
+        assert!(unplaced.routes.is_empty() && unplaced.topology.is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn materialize_delays_draws_seeded_delays_on_the_tick_grid() {
+        // spinnaker2 ticks at 1 ms
+        let manifest = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../targets/spinnaker2.toml");
+        let mut g = nir::fixtures::chain(&[10, 10]);
+        g.connections.clear();
+        g.connectivity.push(nir::ConnectivitySpec {
+            pre: "p0".into(),
+            post: "p1".into(),
+            rule: nir::ConnectivityRule::AllToAll,
+            weight: 0.5,
+            delay_ms: 0.0,
+            seed: 7,
+            autapses: false,
+            plasticity: None,
+            delay_distribution: Some(nir::DelayDistribution::Uniform { low_ms: 1.0, high_ms: 5.0 }),
+        });
+        g.attributes.insert("hal_manifest_path".into(), serde_json::json!(manifest.to_string_lossy()));
+        let mut pm = PassManager::new();
+        let names = ["expand-connectivity", "materialize-delays", "validate"].map(String::from);
+        build_pipeline(&mut pm, &names).unwrap();
+        let out = pm.run(g.clone()).unwrap();
+        let pj = &out.projections[0];
+        assert!(pj.delay_distribution.is_none());
+        let delays: Vec<f32> = pj.iter_synapses(10).map(|(_, _, _, d)| d).collect();
+        assert!(delays.iter().all(|&d| (1.0..=5.0).contains(&d) && d.fract() == 0.0), "{delays:?}");
+        assert!(delays.iter().any(|&d| d != delays[0]), "delays must differ across synapses");
+        assert_eq!(out.attributes["delays"]["materialized"], 1);
+        assert_eq!(pm.run(g.clone()).unwrap().projections[0].synapses, pj.synapses, "same seed, same delays");
+
+        // A spread below one 1 ms tick cannot be represented
+        g.connectivity[0].delay_distribution = Some(nir::DelayDistribution::Uniform { low_ms: 1.0, high_ms: 1.5 });
+        let err = pm.run(g).unwrap_err().to_string();
+        assert!(err.contains("below one tick"), "{err}");
+    }
+
+    #[test]
+    fn timing_feasibility_flags_under_resolved_delay() {
+        // spinnaker2 ticks at 1 ms
+        let manifest = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../targets/spinnaker2.toml");
+        let mut g = nir::fixtures::chain(&[4, 4, 4]);
+        g.connections[0].delay_ms = 0.0005;
+        g.connections[1].delay_ms = 2.0;
+        g.attributes.insert("hal_manifest_path".into(), serde_json::json!(manifest.to_string_lossy()));
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["timing-feasibility".to_string()]).unwrap();
+        let out = pm.run(g).unwrap();
+        let tf = &out.attributes["timing_feasibility"];
+        assert_eq!(tf["time_resolution_ns"], 1_000_000);
+        assert_eq!(tf["feasible"], false);
+        assert_eq!(tf["under_resolved"], 1);
+        assert_eq!(tf["issues"][0]["kind"], "under_resolved");
+        assert!(tf["warnings"][0].as_str().unwrap().contains("under-resolved"));
+    }
+
+    #[cfg(feature = "telemetry")]
+    #[test]
+    fn placement_emits_per_part_counters() {
+        let mut g = nir::fixtures::chain(&[3, 5, 7]);
+        g.attributes.insert("partition".into(), serde_json::json!({
+            "parts": 2,
+            "assignment": [
+                {"population": "p0", "part": 0},
+                {"population": "p1", "part": 0},
+                {"population": "p2", "part": 1}
+            ]
+        }));
+        let out = PlacementPass::default().run(g).unwrap();
+        let path = std::env::temp_dir().join(format!("nc_passes_part_counters_{}.jsonl", std::process::id()));
+        let app = telemetry::profiling::Appender::open(&path).unwrap();
+        emit_part_counters(&app, &out, "placement").unwrap();
+        drop(app);
+
+        let recs: Vec<serde_json::Value> = fs::read_to_string(&path).unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let _ = fs::remove_file(&path);
+        let neurons: Vec<_> = recs.iter().filter(|r| r["metric"] == "passes.part_neurons").collect();
+        assert_eq!(neurons.len(), 2);
+        assert_eq!(neurons[1]["labels"]["part"], "1");
+        assert_eq!(neurons[1]["labels"]["pass"], "placement");
+        assert_eq!(neurons[1]["value"], 7.0);
+        assert!(recs.iter().any(|r| r["metric"] == "passes.part_synapses" && r["labels"]["part"] == "0"));
+    }
+
+    #[test]
+    fn plasticity_check_flags_unsupported_rule() {
+        // akida only lists STDP
+        let manifest = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../targets/akida.toml");
+        let mut g = nir::fixtures::chain(&[4, 4, 4]);
+        g.connections[0].plasticity = Some(nir::PlasticityRule { kind: nir::PlasticityKind::STDP, params: serde_json::json!({}) });
+        g.connections[1].plasticity = Some(nir::PlasticityRule { kind: nir::PlasticityKind::Hebbian, params: serde_json::json!({}) });
+        g.attributes.insert("hal_manifest_path".into(), serde_json::json!(manifest.to_string_lossy()));
+        let mut pm = PassManager::new();
+        build_pipeline(&mut pm, &["plasticity-check".to_string()]).unwrap();
+        let out = pm.run(g).unwrap();
+        let pc = &out.attributes["plasticity_check"];
+        assert_eq!(pc["supported"], false);
+        assert_eq!(pc["plastic_connections"], 2);
+        assert_eq!(pc["violations"].as_array().map(Vec::len), Some(1));
+        assert_eq!(pc["violations"][0]["rule"], "Hebbian");
+    }
 }
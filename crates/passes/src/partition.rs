@@ -0,0 +1,252 @@
+//! Multilevel k-way partitioning for `PartitionPass`. Populations are vertices, weighted by
+//! their neurons and incoming synapses; edges carry the synapses between two populations. The
+//! graph is coarsened by heavy-edge matching, the coarsest graph is partitioned greedily under
+//! the per-core caps, and the assignment is projected back level by level with
+//! Fiduccia-Mattheyses refinement to cut fewer synapses. Everything iterates in index order, so
+//! the result is deterministic.
+
+use std::collections::BTreeMap;
+
+/// Coarsening stops once a level has at most this many vertices per requested part.
+const COARSEST_PER_PART: usize = 4;
+/// Refinement passes per level; a pass that gains nothing ends refinement early.
+const FM_PASSES: usize = 8;
+
+/// Per-part limits; `0` means unbounded.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Caps {
+    pub max_neurons: u64,
+    pub max_synapses: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Load {
+    neurons: u64,
+    synapses: u64,
+}
+
+impl Load {
+    fn plus(self, o: Load) -> Load {
+        Load { neurons: self.neurons + o.neurons, synapses: self.synapses + o.synapses }
+    }
+
+    fn minus(self, o: Load) -> Load {
+        Load { neurons: self.neurons - o.neurons, synapses: self.synapses - o.synapses }
+    }
+
+    fn within(self, caps: Caps) -> bool {
+        (caps.max_neurons == 0 || self.neurons <= caps.max_neurons)
+            && (caps.max_synapses == 0 || self.synapses <= caps.max_synapses)
+    }
+
+    /// Whether `w` can join a part holding `self`. An empty part takes anything, so a vertex
+    /// that exceeds the caps on its own still gets a part (and a violation upstream).
+    fn accepts(self, w: Load, caps: Caps) -> bool {
+        self == Load::default() || self.plus(w).within(caps)
+    }
+}
+
+/// One level of the hierarchy: vertex loads and symmetric adjacency without self loops.
+struct Level {
+    load: Vec<Load>,
+    adj: Vec<Vec<(usize, u64)>>,
+}
+
+impl Level {
+    fn new(load: Vec<Load>, edges: &[(usize, usize, u64)]) -> Level {
+        let mut merged: Vec<BTreeMap<usize, u64>> = vec![BTreeMap::new(); load.len()];
+        for &(a, b, w) in edges {
+            if a != b && w > 0 {
+                *merged[a].entry(b).or_insert(0) += w;
+                *merged[b].entry(a).or_insert(0) += w;
+            }
+        }
+        Level { load, adj: merged.into_iter().map(|m| m.into_iter().collect()).collect() }
+    }
+
+    fn len(&self) -> usize {
+        self.load.len()
+    }
+
+    /// Heavy-edge matching: each unmatched vertex (fewest neighbours first) pairs with the
+    /// unmatched neighbour it shares the most synapses with, if the pair fits the caps.
+    /// Returns the coarser level and the fine -> coarse vertex map.
+    fn coarsen(&self, caps: Caps) -> (Level, Vec<usize>) {
+        let n = self.len();
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by_key(|&v| self.adj[v].len());
+        let mut map = vec![usize::MAX; n];
+        let mut next = 0;
+        for v in order {
+            if map[v] != usize::MAX {
+                continue;
+            }
+            let mate = self.adj[v]
+                .iter()
+                .filter(|&&(u, _)| map[u] == usize::MAX && self.load[v].plus(self.load[u]).within(caps))
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+                .map(|&(u, _)| u);
+            map[v] = next;
+            if let Some(u) = mate {
+                map[u] = next;
+            }
+            next += 1;
+        }
+        let mut load = vec![Load::default(); next];
+        for v in 0..n {
+            load[map[v]] = load[map[v]].plus(self.load[v]);
+        }
+        let edges: Vec<(usize, usize, u64)> = (0..n)
+            .flat_map(|v| self.adj[v].iter().filter(move |&&(u, _)| u > v).map(move |&(u, w)| (v, u, w)))
+            .map(|(v, u, w)| (map[v], map[u], w))
+            .collect();
+        (Level::new(load, &edges), map)
+    }
+
+    /// Synapses from `v` into each part.
+    fn links(&self, v: usize, part: &[usize], k: usize) -> Vec<u64> {
+        let mut to = vec![0u64; k];
+        for &(u, w) in &self.adj[v] {
+            to[part[u]] += w;
+        }
+        to
+    }
+
+    /// Greedy initial partition into `k` parts: heaviest vertices first, each into the part it
+    /// is most connected to among those with room (least loaded on ties). `None` when some
+    /// vertex fits nowhere.
+    fn initial(&self, k: usize, caps: Caps) -> Option<Vec<usize>> {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by_key(|&v| std::cmp::Reverse((self.load[v].neurons, self.load[v].synapses)));
+        let mut part = vec![usize::MAX; self.len()];
+        let mut loads = vec![Load::default(); k];
+        for v in order {
+            let mut to = vec![0u64; k];
+            for &(u, w) in &self.adj[v] {
+                if part[u] != usize::MAX {
+                    to[part[u]] += w;
+                }
+            }
+            let p = (0..k)
+                .filter(|&p| loads[p].accepts(self.load[v], caps))
+                .max_by(|&a, &b| to[a].cmp(&to[b]).then(loads[b].neurons.cmp(&loads[a].neurons)).then(b.cmp(&a)))?;
+            part[v] = p;
+            loads[p] = loads[p].plus(self.load[v]);
+        }
+        Some(part)
+    }
+
+    /// Fiduccia-Mattheyses refinement: each pass moves every vertex at most once, always
+    /// taking the best-gain move (even a negative one, to climb out of local minima), then
+    /// rolls back to the best prefix that leaves no more load over the caps than the pass
+    /// started with. A move may overfill its target; the next move must then come out of an
+    /// overfull part, so full parts can still trade vertices.
+    fn refine(&self, part: &mut [usize], k: usize, caps: Caps) {
+        let mut loads = vec![Load::default(); k];
+        for (v, &p) in part.iter().enumerate() {
+            loads[p] = loads[p].plus(self.load[v]);
+        }
+        for _ in 0..FM_PASSES {
+            let base = excess(&loads, caps);
+            let mut locked = vec![false; self.len()];
+            let mut moves: Vec<(usize, usize)> = Vec::new();
+            let (mut gain, mut best, mut best_len) = (0i64, 0i64, 0usize);
+            loop {
+                let overfull = excess(&loads, caps) > base;
+                let mut pick: Option<(i64, usize, usize)> = None;
+                for v in (0..self.len()).filter(|&v| !locked[v]) {
+                    if overfull && loads[part[v]].within(caps) {
+                        continue;
+                    }
+                    let to = self.links(v, part, k);
+                    for p in (0..k).filter(|&p| p != part[v] && to[p] > 0) {
+                        let g = to[p] as i64 - to[part[v]] as i64;
+                        let better = match pick {
+                            Some((bg, _, _)) => g > bg,
+                            None => true,
+                        };
+                        if better && (!overfull || loads[p].accepts(self.load[v], caps)) {
+                            pick = Some((g, v, p));
+                        }
+                    }
+                }
+                let Some((g, v, p)) = pick else { break };
+                moves.push((v, part[v]));
+                loads[part[v]] = loads[part[v]].minus(self.load[v]);
+                loads[p] = loads[p].plus(self.load[v]);
+                part[v] = p;
+                locked[v] = true;
+                gain += g;
+                if gain > best && excess(&loads, caps) <= base {
+                    (best, best_len) = (gain, moves.len());
+                }
+            }
+            for &(v, from) in moves[best_len..].iter().rev() {
+                loads[part[v]] = loads[part[v]].minus(self.load[v]);
+                loads[from] = loads[from].plus(self.load[v]);
+                part[v] = from;
+            }
+            if best <= 0 {
+                break;
+            }
+        }
+    }
+}
+
+/// Total load above the caps over all parts.
+fn excess(loads: &[Load], caps: Caps) -> u64 {
+    let over = |x: u64, cap: u64| if cap == 0 { 0 } else { x.saturating_sub(cap) };
+    loads.iter().map(|l| over(l.neurons, caps.max_neurons) + over(l.synapses, caps.max_synapses)).sum()
+}
+
+/// Partition vertices with `(neurons, synapses)` loads and `(a, b, synapses)` edges into at
+/// least `min_parts` parts within `caps`, adding parts until the greedy initial partition fits.
+/// Returns the number of non-empty parts and the part of every vertex.
+pub(crate) fn multilevel(load: &[(u64, u64)], edges: &[(usize, usize, u64)], caps: Caps, min_parts: usize) -> (usize, Vec<usize>) {
+    let load: Vec<Load> = load.iter().map(|&(neurons, synapses)| Load { neurons, synapses }).collect();
+    if load.is_empty() {
+        return (min_parts.max(1), Vec::new());
+    }
+    let mut levels = vec![Level::new(load, edges)];
+    let mut maps: Vec<Vec<usize>> = Vec::new();
+    while levels[levels.len() - 1].len() > COARSEST_PER_PART * min_parts.max(2) {
+        let last = &levels[levels.len() - 1];
+        let (coarse, map) = last.coarsen(caps);
+        if coarse.len() == last.len() {
+            break;
+        }
+        levels.push(coarse);
+        maps.push(map);
+    }
+
+    let coarsest = &levels[levels.len() - 1];
+    let mut k = min_parts.max(1);
+    let mut part = loop {
+        match coarsest.initial(k, caps) {
+            Some(p) => break p,
+            None => k += 1,
+        }
+    };
+    coarsest.refine(&mut part, k, caps);
+    for (level, map) in levels[..levels.len() - 1].iter().zip(&maps).rev() {
+        part = map.iter().map(|&c| part[c]).collect();
+        level.refine(&mut part, k, caps);
+    }
+
+    // Renumber by first use so empty parts do not leave gaps
+    let mut renumber = vec![usize::MAX; k];
+    let mut used = 0;
+    for p in part.iter_mut() {
+        if renumber[*p] == usize::MAX {
+            renumber[*p] = used;
+            used += 1;
+        }
+        *p = renumber[*p];
+    }
+    (used, part)
+}
+
+/// Synapses on edges whose endpoints are in different parts.
+pub(crate) fn cut(edges: &[(usize, usize, u64)], part: &[usize]) -> u64 {
+    edges.iter().filter(|&&(a, b, _)| part[a] != part[b]).map(|&(_, _, w)| w).sum()
+}
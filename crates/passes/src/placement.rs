@@ -0,0 +1,105 @@
+//! Part-to-core placement on the target's 2D core mesh for `PlacementPass`, by simulated
+//! annealing. The cost is synapses times Manhattan hops summed over every pair of parts; a step
+//! moves one part to a random core, swapping with the part already there. Draws come from
+//! `unit_sample`, so a seed always yields the same placement.
+
+use crate::{unit_sample, PlacementOptions};
+
+/// Core mesh of `width x height` cores, numbered row-major.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Mesh {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Mesh {
+    pub fn cores(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// `[x, y]` of `core`.
+    pub fn coord(&self, core: usize) -> [u32; 2] {
+        [(core % self.width as usize) as u32, (core / self.width as usize) as u32]
+    }
+
+    fn hops(&self, a: usize, b: usize) -> u64 {
+        let ([ax, ay], [bx, by]) = (self.coord(a), self.coord(b));
+        (ax.abs_diff(bx) + ay.abs_diff(by)) as u64
+    }
+}
+
+/// Weighted hop cost of placing part `i` on `core[i]`, with `traffic[i][j]` the synapses
+/// between parts `i` and `j` (either direction).
+pub(crate) fn hop_cost(mesh: Mesh, traffic: &[Vec<u64>], core: &[usize]) -> u64 {
+    let mut cost = 0;
+    for i in 0..core.len() {
+        for j in i + 1..core.len() {
+            cost += traffic[i][j] * mesh.hops(core[i], core[j]);
+        }
+    }
+    cost
+}
+
+/// Change in cost when part `p` moves from its core to `to`, other parts staying put
+/// (`skip` is excluded, for swaps where it moves too).
+fn delta(mesh: Mesh, traffic: &[Vec<u64>], core: &[usize], p: usize, to: usize, skip: usize) -> i64 {
+    (0..core.len())
+        .filter(|&q| q != p && q != skip)
+        .map(|q| traffic[p][q] as i64 * (mesh.hops(to, core[q]) as i64 - mesh.hops(core[p], core[q]) as i64))
+        .sum()
+}
+
+/// Anneal the placement of `parts = traffic.len()` parts, starting from part `i` on core `i`.
+/// The temperature starts at `initial_temperature` (default: the mean cost of one
+/// communicating pair) and is multiplied by `cooling` every step; a worse placement is accepted
+/// with probability `exp(-delta / T)`. Returns the best core per part seen. Requires
+/// `parts <= mesh.cores()`.
+pub(crate) fn anneal(mesh: Mesh, traffic: &[Vec<u64>], opts: &PlacementOptions) -> Vec<usize> {
+    let parts = traffic.len();
+    let cores = mesh.cores();
+    let mut core: Vec<usize> = (0..parts).collect();
+    // part on each core, if any
+    let mut owner: Vec<Option<usize>> = (0..cores).map(|c| (c < parts).then_some(c)).collect();
+    let pairs = (0..parts).flat_map(|i| (i + 1..parts).map(move |j| (i, j))).filter(|&(i, j)| traffic[i][j] > 0).count();
+    if pairs == 0 || cores < 2 {
+        return core;
+    }
+
+    let mut cost = hop_cost(mesh, traffic, &core) as i64;
+    let (mut best, mut best_cost) = (core.clone(), cost);
+    let mut t = opts.initial_temperature.unwrap_or(cost as f64 / pairs as f64).max(f64::MIN_POSITIVE);
+    let mut draw = 0usize;
+    let mut sample = || {
+        draw += 1;
+        unit_sample(opts.seed, draw)
+    };
+    for _ in 0..opts.iterations {
+        let p = ((sample() * parts as f64) as usize).min(parts - 1);
+        let to = ((sample() * cores as f64) as usize).min(cores - 1);
+        let accept_draw = sample();
+        if to == core[p] {
+            continue;
+        }
+        let from = core[p];
+        let other = owner[to];
+        let mut d = delta(mesh, traffic, &core, p, to, other.unwrap_or(usize::MAX));
+        if let Some(q) = other {
+            d += delta(mesh, traffic, &core, q, from, p);
+            // p and q keep their distance when they trade cores
+        }
+        if d <= 0 || accept_draw < (-(d as f64) / t).exp() {
+            core[p] = to;
+            owner[to] = Some(p);
+            owner[from] = other;
+            if let Some(q) = other {
+                core[q] = from;
+            }
+            cost += d;
+            if cost < best_cost {
+                (best, best_cost) = (core.clone(), cost);
+            }
+        }
+        t *= opts.cooling;
+    }
+    best
+}
@@ -0,0 +1,101 @@
+//! Route computation for `RoutingPass` over the target's interconnect. Cores sit on a 2D mesh
+//! or torus (dimension-ordered XY or minimal adaptive routing) or are the leaves of a k-ary
+//! tree (up to the common ancestor and back down). Each inter-part flow is routed hop by hop,
+//! adding its synapses to every directed link it crosses.
+
+use crate::RouteAlgorithm;
+use std::collections::BTreeMap;
+
+/// Interconnect between cores.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Topology {
+    Mesh { width: u32, height: u32 },
+    Torus { width: u32, height: u32 },
+    Tree { arity: u32 },
+}
+
+impl Topology {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topology::Mesh { .. } => "mesh",
+            Topology::Torus { .. } => "torus",
+            Topology::Tree { .. } => "tree",
+        }
+    }
+
+    /// Node name of `core`: `x{X}y{Y}` on a mesh or torus, `c{core}` in a tree.
+    fn core_node(&self, core: usize) -> String {
+        match *self {
+            Topology::Mesh { width, .. } | Topology::Torus { width, .. } => {
+                format!("x{}y{}", core % width as usize, core / width as usize)
+            }
+            Topology::Tree { .. } => format!("c{core}"),
+        }
+    }
+
+    /// Nodes from core `a` to core `b`, both included. `load` holds the synapses already routed
+    /// over each directed link; adaptive routing picks the lighter of the minimal next hops.
+    pub fn route(&self, a: usize, b: usize, algorithm: RouteAlgorithm, load: &BTreeMap<(String, String), u64>) -> Vec<String> {
+        match *self {
+            Topology::Mesh { width, height } => grid_route(a, b, width, height, false, algorithm, load),
+            Topology::Torus { width, height } => grid_route(a, b, width, height, true, algorithm, load),
+            Topology::Tree { arity } => {
+                let arity = arity.max(2) as usize;
+                let (mut up, mut down) = (vec![self.core_node(a)], vec![self.core_node(b)]);
+                let (mut x, mut y, mut level) = (a, b, 0);
+                while x != y {
+                    (x, y, level) = (x / arity, y / arity, level + 1);
+                    up.push(format!("r{level}.{x}"));
+                    down.push(format!("r{level}.{y}"));
+                }
+                down.pop();
+                up.extend(down.into_iter().rev());
+                up
+            }
+        }
+    }
+}
+
+/// Minimal route on a `width x height` grid (`wrap` for a torus): XY goes along x first,
+/// adaptive takes the y step instead when its link carries fewer synapses so far.
+fn grid_route(
+    a: usize,
+    b: usize,
+    width: u32,
+    height: u32,
+    wrap: bool,
+    algorithm: RouteAlgorithm,
+    load: &BTreeMap<(String, String), u64>,
+) -> Vec<String> {
+    let (w, h) = (width as i64, height as i64);
+    // Signed steps left in each dimension; on a torus the shorter way round (ties go direct)
+    let step = |from: i64, to: i64, size: i64| {
+        let d = to - from;
+        if wrap && d.abs() * 2 > size {
+            d - d.signum() * size
+        } else {
+            d
+        }
+    };
+    let (mut x, mut y) = ((a as i64) % w, (a as i64) / w);
+    let (tx, ty) = ((b as i64) % w, (b as i64) / w);
+    let node = |x: i64, y: i64| format!("x{x}y{y}");
+    let mut path = vec![node(x, y)];
+    loop {
+        let (dx, dy) = (step(x, tx, w), step(y, ty, h));
+        if dx == 0 && dy == 0 {
+            break;
+        }
+        let by_x = ((x + dx.signum()).rem_euclid(w), y);
+        let by_y = (x, (y + dy.signum()).rem_euclid(h));
+        let here = node(x, y);
+        let weight = |(nx, ny): (i64, i64)| load.get(&(here.clone(), node(nx, ny))).copied().unwrap_or(0);
+        (x, y) = match (dx != 0, dy != 0, algorithm) {
+            (true, true, RouteAlgorithm::Adaptive) if weight(by_y) < weight(by_x) => by_y,
+            (true, _, _) => by_x,
+            _ => by_y,
+        };
+        path.push(node(x, y));
+    }
+    path
+}
@@ -1,6 +1,4 @@
 use anyhow::Result;
-use std::collections::HashMap;
-#[cfg(feature = "telemetry")]
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
@@ -8,6 +6,7 @@ use std::io::{BufRead, BufReader};
 // Rust API always available
 pub fn version() -> &'static str { "0.0.1" }
 pub fn list_targets() -> Vec<&'static str> { nc_hal::builtin_targets().to_vec() }
+pub fn list_passes() -> Vec<&'static str> { nc_passes::available_passes().to_vec() }
 
 pub fn import_nir_json_str(s: &str) -> Result<nc_nir::Graph> {
     let g = nc_nir::Graph::from_json_str(s)?;
@@ -28,6 +27,8 @@ pub fn compile_stub(target: &str) -> Result<String> {
 pub fn compile_nir_json_str(target: &str, json: &str) -> Result<String> {
     let mut g = nc_nir::Graph::from_json_str(json)?;
     g.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    // Documents passed as strings have no directory to resolve weights_ref files against
+    g.check_weight_refs_loaded().map_err(|e| anyhow::anyhow!(e.to_string()))?;
     g.ensure_version_tag();
     let manifest_path = std::path::PathBuf::from(format!("targets/{target}.toml"));
     let manifest = nc_hal::parse_target_manifest_path(&manifest_path)?;
@@ -64,6 +65,7 @@ pub fn compile_nir_json_str(target: &str, json: &str) -> Result<String> {
 pub fn compile_nir_yaml_str(target: &str, yaml: &str) -> Result<String> {
     let mut g = nc_nir::Graph::from_yaml_str(yaml)?;
     g.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    g.check_weight_refs_loaded().map_err(|e| anyhow::anyhow!(e.to_string()))?;
     g.ensure_version_tag();
     let manifest_path = std::path::PathBuf::from(format!("targets/{target}.toml"));
     let manifest = nc_hal::parse_target_manifest_path(&manifest_path)?;
@@ -108,6 +110,41 @@ pub fn compile_nir_str(target: &str, s: &str) -> Result<String> {
 }
 
 
+/// Structural stats for NIR given as a string (auto-detect JSON vs YAML): `Graph::stats()`
+/// merged with the orchestrator metrics (fan-in/out, clustering) into one JSON object.
+pub fn graph_stats_str(s: &str) -> Result<serde_json::Value> {
+    let t = s.trim_start();
+    let g = if t.starts_with('{') || t.starts_with('[') {
+        import_nir_json_str(s)?
+    } else {
+        import_nir_yaml_str(s).or_else(|_| import_nir_json_str(s))?
+    };
+    let m = nc_orchestrator::metrics::compute_metrics(&g);
+    let mut out = serde_json::to_value(g.stats())?;
+    if let Some(o) = out.as_object_mut() {
+        o.insert("node_count".into(), m.node_count.into());
+        o.insert("edge_count".into(), m.edge_count.into());
+        o.insert("avg_fanin".into(), m.avg_fanin.into());
+        o.insert("avg_fanout".into(), m.avg_fanout.into());
+        o.insert("max_fanin".into(), m.max_fanin.into());
+        o.insert("max_fanout".into(), m.max_fanout.into());
+        o.insert("clustering".into(), m.clustering.into());
+    }
+    Ok(out)
+}
+
+/// Every validation problem in NIR given as a string (auto-detect JSON vs YAML), see
+/// `Graph::validate_all`. Parse failures are errors; an invalid graph is not.
+pub fn validate_nir_str(s: &str) -> Result<Vec<nc_nir::Diagnostic>> {
+    let t = s.trim_start();
+    let g = if t.starts_with('{') || t.starts_with('[') {
+        import_nir_json_str(s)?
+    } else {
+        import_nir_yaml_str(s).or_else(|_| import_nir_json_str(s))?
+    };
+    Ok(g.validate_all())
+}
+
 pub fn simulate_stub(sim: &str) -> Result<String> {
     Ok(format!("simulate: simulator={sim}"))
 }
@@ -116,6 +153,7 @@ pub fn simulate_stub(sim: &str) -> Result<String> {
 pub fn simulate_nir_json_str(simulator: &str, json: &str, out_dir: Option<&str>) -> Result<String> {
     let mut g = nc_nir::Graph::from_json_str(json)?;
     g.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    g.check_weight_refs_loaded().map_err(|e| anyhow::anyhow!(e.to_string()))?;
     g.ensure_version_tag();
 
     let out_path = match out_dir {
@@ -198,6 +236,7 @@ pub fn simulate_nir_json_str(simulator: &str, json: &str, out_dir: Option<&str>)
 pub fn simulate_nir_yaml_str(simulator: &str, yaml: &str, out_dir: Option<&str>) -> Result<String> {
     let mut g = nc_nir::Graph::from_yaml_str(yaml)?;
     g.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    g.check_weight_refs_loaded().map_err(|e| anyhow::anyhow!(e.to_string()))?;
     g.ensure_version_tag();
 
     let out_path = match out_dir {
@@ -288,11 +327,41 @@ pub fn simulate_nir_str(simulator: &str, s: &str, out_dir: Option<&str>) -> Resu
     }
 }
 
-/// Summarize a JSONL profiling file into CSV metrics: metric,count,avg,min,max
+/// Simulate NIR from a string (auto-detect JSON vs YAML) and return the files present under the
+/// out_dir afterwards, as sorted '/'-separated paths relative to it. Use a fresh out_dir to see
+/// only the files this run wrote.
+pub fn simulate_result_str(simulator: &str, s: &str, out_dir: Option<&str>) -> Result<Vec<String>> {
+    let root = std::path::PathBuf::from(simulate_nir_str(simulator, s, out_dir)?);
+    let mut files = Vec::new();
+    let mut stack = vec![root.clone()];
+    while let Some(dir) = stack.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let p = entry?.path();
+            if p.is_dir() {
+                stack.push(p);
+            } else if let Ok(rel) = p.strip_prefix(&root) {
+                let parts: Vec<String> = rel.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+                files.push(parts.join("/"));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Zip a backend out_dir (pass dumps, README, profile.jsonl when present) into `zip_path`
+/// with a `MANIFEST.json` index. See `nc_runtime::bundle_artifacts`.
+pub fn bundle_artifacts(out_dir: &str, zip_path: &str) -> Result<()> {
+    nc_runtime::bundle_artifacts(out_dir, zip_path)
+}
+
+/// Summarize a JSONL profiling file into CSV metrics: metric,count,avg,min,max.
+/// Rows are sorted by metric name and numbers use fixed `.`-decimal formatting, so the
+/// same input always yields byte-identical CSV.
 pub fn profile_summary_jsonl(path: &str) -> Result<String> {
     let file = File::open(path)?;
     let rdr = BufReader::new(file);
-    let mut stats: HashMap<String, (usize, f64, f64, f64)> = HashMap::new(); // count,sum,min,max
+    let mut stats: BTreeMap<String, (usize, f64, f64, f64)> = BTreeMap::new(); // count,sum,min,max
     for l in rdr.lines().map_while(Result::ok) {
         if l.trim().is_empty() { continue; }
         if let Ok(v) = serde_json::from_str::<serde_json::Value>(&l) {
@@ -309,11 +378,34 @@ pub fn profile_summary_jsonl(path: &str) -> Result<String> {
     let mut out = String::from("metric,count,avg,min,max\n");
     for (m, (c, sum, min, max)) in stats {
         let avg = if c > 0 { sum / c as f64 } else { 0.0 };
+        let m = if m.contains([',', '"', '\n']) { format!("\"{}\"", m.replace('"', "\"\"")) } else { m };
         out.push_str(&format!("{m},{c},{avg:.4},{min:.4},{max:.4}\n"));
     }
     Ok(out)
 }
 
+/// Lower NIR from a string (auto-detect JSON vs YAML) to MLIR text. Requires the `mlir` feature.
+pub fn export_mlir_str(s: &str) -> Result<String> {
+    #[cfg(feature = "mlir")]
+    {
+        let t = s.trim_start();
+        let mut g = if t.starts_with('{') || t.starts_with('[') {
+            nc_nir::Graph::from_json_str(s)?
+        } else {
+            nc_nir::Graph::from_yaml_str(s).or_else(|_| nc_nir::Graph::from_json_str(s))?
+        };
+        g.validate().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        g.check_weight_refs_loaded().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        g.ensure_version_tag();
+        nc_mlir_bridge::lower_to_mlir(&g)
+    }
+    #[cfg(not(feature = "mlir"))]
+    {
+        let _ = s;
+        Err(anyhow::anyhow!("mlir export not enabled; rebuild neuro-compiler-py with feature 'mlir'"))
+    }
+}
+
 /// Deploy stub (placeholder for runtime-backed deployment)
 pub fn deploy_stub(target: &str) -> Result<String> {
     Ok(format!("deploy: target={target}"))
@@ -322,6 +414,71 @@ pub fn deploy_stub(target: &str) -> Result<String> {
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 
+/// Context manager that points `NC_PROFILE_JSONL` at `path` for the duration of a `with`
+/// block. `__exit__` restores the previous value (or unsets it) and returns the summary of
+/// the captured JSONL as `{metric: {"count", "avg", "min", "max"}}`, also kept on `.summary`.
+#[cfg(all(feature = "python", feature = "telemetry"))]
+#[pyclass]
+pub struct Telemetry {
+    path: String,
+    previous: Option<String>,
+    summary: Option<PyObject>,
+}
+
+#[cfg(all(feature = "python", feature = "telemetry"))]
+#[pymethods]
+impl Telemetry {
+    #[new]
+    fn new(path: String) -> Self {
+        Self { path, previous: None, summary: None }
+    }
+
+    fn __enter__(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        slf.previous = std::env::var("NC_PROFILE_JSONL").ok();
+        std::env::set_var("NC_PROFILE_JSONL", &slf.path);
+        slf
+    }
+
+    /// Returns the summary dict, or None while an exception propagates (a non-empty dict
+    /// is truthy and would otherwise suppress it).
+    #[pyo3(signature = (exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        exc_type: Option<PyObject>,
+        _exc_value: Option<PyObject>,
+        _traceback: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        match self.previous.take() {
+            Some(p) => std::env::set_var("NC_PROFILE_JSONL", p),
+            None => std::env::remove_var("NC_PROFILE_JSONL"),
+        }
+        let dict = pyo3::types::PyDict::new_bound(py);
+        // A block that recorded nothing leaves no file: report an empty summary
+        if let Ok(stats) = nc_telemetry::profiling::summarize_jsonl(&self.path) {
+            for (metric, (count, sum, min, max)) in stats {
+                let entry = pyo3::types::PyDict::new_bound(py);
+                entry.set_item("count", count)?;
+                entry.set_item("avg", if count > 0 { sum / count as f64 } else { 0.0 })?;
+                entry.set_item("min", min)?;
+                entry.set_item("max", max)?;
+                dict.set_item(metric, entry)?;
+            }
+        }
+        let summary: PyObject = dict.into_any().unbind();
+        self.summary = Some(summary.clone_ref(py));
+        if exc_type.is_some_and(|t| !t.is_none(py)) {
+            return Ok(py.None());
+        }
+        Ok(summary)
+    }
+
+    #[getter]
+    fn summary(&self, py: Python<'_>) -> PyObject {
+        self.summary.as_ref().map_or_else(|| py.None(), |s| s.clone_ref(py))
+    }
+}
+
 #[cfg(feature = "python")]
 #[pymodule]
 fn neuro_compiler(_py: Python, m: &PyModule) -> PyResult<()> {
@@ -330,6 +487,8 @@ fn neuro_compiler(_py: Python, m: &PyModule) -> PyResult<()> {
     #[pyfn(m)]
     fn list_targets_py() -> Vec<&'static str> { list_targets() }
     #[pyfn(m)]
+    fn list_passes_py() -> Vec<&'static str> { list_passes() }
+    #[pyfn(m)]
     fn import_json_py(s: &str) -> PyResult<String> {
         let g = import_nir_json_str(s).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
         Ok(g.name)
@@ -381,12 +540,45 @@ fn neuro_compiler(_py: Python, m: &PyModule) -> PyResult<()> {
     fn simulate_nir_str_py(simulator: &str, s: &str, out_dir: Option<&str>) -> PyResult<String> {
         simulate_nir_str(simulator, s, out_dir).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
     }
+    #[pyfn(m)]
+    fn simulate_result_py(simulator: &str, s: &str, out_dir: Option<&str>) -> PyResult<Vec<String>> {
+        simulate_result_str(simulator, s, out_dir).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    #[pyfn(m)]
+    fn export_mlir_py(s: &str) -> PyResult<String> {
+        export_mlir_str(s).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+    #[pyfn(m)]
+    fn graph_stats_py(py: Python, s: &str) -> PyResult<PyObject> {
+        let v = graph_stats_str(s).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let dict = py.import_bound("json")?.call_method1("loads", (v.to_string(),))?;
+        Ok(dict.unbind())
+    }
+    #[pyfn(m)]
+    fn validate_py(py: Python, s: &str) -> PyResult<PyObject> {
+        let diags = validate_nir_str(s).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        let json = serde_json::to_string(&diags).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        let list = py.import_bound("json")?.call_method1("loads", (json,))?;
+        Ok(list.unbind())
+    }
+    #[pyfn(m)]
+    fn validate_table_py(s: &str) -> PyResult<String> {
+        let diags = validate_nir_str(s).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        Ok(nc_nir::render_table(&diags))
+    }
+    #[cfg(feature = "telemetry")]
+    m.add_class::<Telemetry>()?;
+    #[pyfn(m)]
+    fn bundle_artifacts_py(out_dir: &str, zip_path: &str) -> PyResult<()> {
+        bundle_artifacts(out_dir, zip_path).map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(all(feature = "backend-riscv", feature = "python"))]
     use std::path::PathBuf;
 
     // Feature-gated Python API test: compile-only for RISC-V (no external tools)
@@ -414,6 +606,145 @@ mod tests {
         });
     }
 
+    #[cfg(feature = "python")]
+    #[test]
+    fn py_graph_stats_dict() {
+        let nir = std::fs::read_to_string("examples/nir/simple.json").expect("read NIR");
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let m = pyo3::types::PyModule::new(py, "neuro_compiler").expect("module new");
+            crate::neuro_compiler(py, m).expect("init module");
+            let f = m.getattr("graph_stats_py").expect("get graph_stats_py");
+            let d = f.call1((nir.as_str(),)).expect("call ok");
+            let d: &pyo3::types::PyDict = d.downcast().expect("dict");
+            assert!(d.contains("total_neurons").expect("contains"));
+            assert!(d.contains("max_fanout").expect("contains"));
+            assert!(f.call1(("{not nir",)).is_err());
+        });
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn py_validate_lists_diagnostics() {
+        let nir = r#"{"name": "g", "populations": [{"name": "a", "size": 0, "model": "LIF"}],
+                      "probes": [{"target": "b", "kind": "spikes"}]}"#;
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let m = pyo3::types::PyModule::new(py, "neuro_compiler").expect("module new");
+            crate::neuro_compiler(py, m).expect("init module");
+            let f = m.getattr("validate_py").expect("get validate_py");
+            let l = f.call1((nir,)).expect("call ok");
+            let l: &pyo3::types::PyList = l.downcast().expect("list");
+            assert_eq!(l.len(), 2);
+            let d: &pyo3::types::PyDict = l.get_item(0).expect("item").downcast().expect("dict");
+            let code: String = d.get_item("code").expect("get").expect("code").extract().expect("str");
+            assert_eq!(code, "E103");
+            let table: String = m.getattr("validate_table_py").expect("get").call1((nir,)).expect("call").extract().expect("str");
+            assert!(table.contains("E602  /probes/0"), "{table}");
+        });
+    }
+
+    #[cfg(all(feature = "python", feature = "telemetry", feature = "backend-riscv"))]
+    #[test]
+    fn py_telemetry_context_captures_compile() {
+        std::env::set_var("NC_RISCV_QEMU_RUN", "0");
+        let nir = std::fs::read_to_string("examples/nir/simple.json").expect("read NIR");
+        let path = "target/py-telemetry-ctx.jsonl";
+        let _ = std::fs::remove_file(path);
+        pyo3::prepare_freethreaded_python();
+        pyo3::Python::with_gil(|py| {
+            let m = pyo3::types::PyModule::new(py, "neuro_compiler").expect("module new");
+            crate::neuro_compiler(py, m).expect("init module");
+            let ctx = m.getattr("Telemetry").expect("Telemetry").call1((path,)).expect("new");
+            ctx.call_method0("__enter__").expect("enter");
+            assert_eq!(std::env::var("NC_PROFILE_JSONL").ok().as_deref(), Some(path));
+            m.getattr("compile_nir_str_py").expect("compile fn").call1(("riscv64gcv_linux", nir.as_str())).expect("compile");
+            let summary = ctx.call_method1("__exit__", (py.None(), py.None(), py.None())).expect("exit");
+            let summary: &pyo3::types::PyDict = summary.downcast().expect("dict");
+            assert!(!summary.is_empty(), "telemetry summary should not be empty");
+            assert!(std::env::var("NC_PROFILE_JSONL").is_err());
+        });
+    }
+
+    #[cfg(feature = "sim-arbor")]
+    #[test]
+    fn simulate_result_lists_arbor_files() {
+        let nir = std::fs::read_to_string("examples/nir/simple.json").expect("read NIR");
+        let out = "target/py-sim-result-arbor";
+        let _ = std::fs::remove_dir_all(out);
+        let files = simulate_result_str("arbor", &nir, Some(out)).expect("simulate arbor");
+        assert!(files.iter().any(|f| f == "model_summary.json"), "files: {files:?}");
+        assert!(files.iter().any(|f| f == "RUN.txt"), "files: {files:?}");
+    }
+
+    #[test]
+    fn profile_summary_csv_is_sorted_and_stable() {
+        let path = "target/py-profile-summary-sorted.jsonl";
+        std::fs::create_dir_all("target").unwrap();
+        std::fs::write(
+            path,
+            "{\"metric\":\"zeta.ms\",\"value\":2.0}\n{\"metric\":\"alpha.ms\",\"value\":1.5}\n\
+             {\"metric\":\"mid,x\",\"value\":3}\n{\"metric\":\"alpha.ms\",\"value\":0.5}\n",
+        )
+        .unwrap();
+        let a = profile_summary_jsonl(path).expect("summary");
+        let b = profile_summary_jsonl(path).expect("summary");
+        assert_eq!(a, b);
+        let rows: Vec<&str> = a.lines().collect();
+        assert_eq!(rows[0], "metric,count,avg,min,max");
+        assert_eq!(rows[1], "alpha.ms,2,1.0000,0.5000,1.5000");
+        assert!(rows[2].starts_with("\"mid,x\",1,"));
+        assert!(rows[3].starts_with("zeta.ms,1,"));
+    }
+
+    #[test]
+    fn bundle_artifacts_zips_out_dir() {
+        let out = std::path::Path::new("target/py-bundle-src");
+        let _ = std::fs::remove_dir_all(out);
+        std::fs::create_dir_all(out.join("passes")).unwrap();
+        std::fs::write(out.join("README.txt"), "readme").unwrap();
+        std::fs::write(out.join("passes/00_noop.json"), "{}").unwrap();
+        let zip_path = "target/py-bundle.zip";
+        bundle_artifacts("target/py-bundle-src", zip_path).expect("bundle");
+
+        let mut ar = zip::ZipArchive::new(std::fs::File::open(zip_path).unwrap()).unwrap();
+        let names: Vec<String> = ar.file_names().map(String::from).collect();
+        for want in ["README.txt", "passes/00_noop.json", "MANIFEST.json"] {
+            assert!(names.iter().any(|n| n == want), "missing {want}: {names:?}");
+        }
+        let mut manifest = String::new();
+        std::io::Read::read_to_string(&mut ar.by_name("MANIFEST.json").unwrap(), &mut manifest).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(v["has_passes"], true);
+        assert_eq!(v["has_profile"], false);
+    }
+
+    #[test]
+    fn string_documents_reject_unloaded_weight_refs() {
+        let nir = r#"{"name":"refs",
+            "populations":[{"name":"a","size":2,"model":"LIF"},{"name":"b","size":1,"model":"LIF"}],
+            "connections":[{"pre":"a","post":"b","weights_ref":{"file":"w.npy","dtype":"f32"}}]}"#;
+        let err = compile_nir_json_str("riscv64gcv_linux", nir).unwrap_err();
+        assert!(err.to_string().contains("weights_ref 'w.npy' is not loaded"), "error: {err}");
+        let err = simulate_nir_json_str("neuron", nir, None).unwrap_err();
+        assert!(err.to_string().contains("is not loaded"), "error: {err}");
+    }
+
+    #[cfg(feature = "mlir")]
+    #[test]
+    fn export_mlir_emits_module() {
+        let nir = std::fs::read_to_string("examples/nir/simple.json").expect("read NIR");
+        let mlir = export_mlir_str(&nir).expect("export mlir");
+        assert!(mlir.contains("module @"), "mlir: {mlir}");
+    }
+
+    #[cfg(not(feature = "mlir"))]
+    #[test]
+    fn export_mlir_disabled_has_clear_error() {
+        let err = export_mlir_str("{}").unwrap_err();
+        assert!(err.to_string().contains("feature 'mlir'"), "error: {err}");
+    }
+
     // Negative test when RISC-V backend feature is NOT enabled
     #[cfg(not(feature = "backend-riscv"))]
     #[test]
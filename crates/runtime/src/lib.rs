@@ -1,4 +1,9 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 #[cfg(feature = "telemetry")]
 use nc_telemetry as telemetry;
 #[cfg(feature = "telemetry")]
@@ -14,6 +19,12 @@ pub struct RuntimeStatus {
     pub running: bool,
 }
 
+/// Process-wide running flag, toggled by `start()` / `stop()`.
+fn running_flag() -> &'static AtomicBool {
+    static RUNNING: OnceLock<AtomicBool> = OnceLock::new();
+    RUNNING.get_or_init(|| AtomicBool::new(false))
+}
+
 pub fn deploy(_spec: &DeploySpec) -> Result<()> {
     #[cfg(feature = "telemetry")]
     let app = std::env::var("NC_PROFILE_JSONL")
@@ -49,6 +60,7 @@ pub fn start() -> Result<()> {
         let labels = BTreeMap::new();
         a.start_timer("runtime.start_ms", labels)
     });
+    running_flag().store(true, Ordering::SeqCst);
     Ok(())
 }
 
@@ -62,20 +74,95 @@ pub fn stop() -> Result<()> {
         let labels = BTreeMap::new();
         a.start_timer("runtime.stop_ms", labels)
     });
+    running_flag().store(false, Ordering::SeqCst);
     Ok(())
 }
 
 pub fn status() -> RuntimeStatus {
-    RuntimeStatus { running: false }
+    RuntimeStatus { running: running_flag().load(Ordering::SeqCst) }
 }
 
 pub fn version() -> &'static str { "0.0.1" }
 
+/// Zip a backend out_dir (README, `passes/` dumps, profile.jsonl, ...) into `zip_path`.
+///
+/// Every regular file under `out_dir` is stored under its relative path, plus a
+/// `MANIFEST.json` index listing `{path, bytes}` per entry and which of the
+/// well-known optional artifacts were present. Missing optional files are skipped.
+pub fn bundle_artifacts<P: AsRef<Path>, Q: AsRef<Path>>(out_dir: P, zip_path: Q) -> Result<()> {
+    let out_dir = out_dir.as_ref();
+    let zip_path = zip_path.as_ref();
+    if !out_dir.is_dir() {
+        bail!("bundle: out_dir {:?} is not a directory", out_dir);
+    }
+    // Never pull the bundle into itself when it is written inside out_dir
+    let zip_abs = zip_path.canonicalize().ok();
+
+    let mut files: Vec<(String, PathBuf)> = Vec::new();
+    let mut stack = vec![out_dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir).with_context(|| format!("bundle: reading {:?}", dir))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.is_file() {
+                if zip_abs.is_some() && path.canonicalize().ok() == zip_abs {
+                    continue;
+                }
+                let rel = path
+                    .strip_prefix(out_dir)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((rel, path));
+            }
+        }
+    }
+    files.sort();
+
+    if let Some(parent) = zip_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+    let file = fs::File::create(zip_path).with_context(|| format!("bundle: creating {:?}", zip_path))?;
+    let mut zw = zip::ZipWriter::new(file);
+    let opts = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut entries = Vec::new();
+    for (rel, path) in &files {
+        let data = fs::read(path).with_context(|| format!("bundle: reading {:?}", path))?;
+        zw.start_file(rel.as_str(), opts)?;
+        zw.write_all(&data)?;
+        entries.push(serde_json::json!({ "path": rel, "bytes": data.len() }));
+    }
+    let has = |name: &str| files.iter().any(|(r, _)| r == name);
+    let manifest = serde_json::json!({
+        "source": out_dir.display().to_string(),
+        "has_readme": has("README.txt") || has("README.md"),
+        "has_profile": has("profile.jsonl"),
+        "has_passes": files.iter().any(|(r, _)| r.starts_with("passes/")),
+        "files": entries,
+    });
+    zw.start_file("MANIFEST.json", opts)?;
+    zw.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    zw.finish()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    // start/stop share process-wide state; serialize tests that touch it
+    static LIFECYCLE_LOCK: Mutex<()> = Mutex::new(());
+
     #[test]
     fn lifecycle_stubs_succeed() {
+        let _g = LIFECYCLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let spec = DeploySpec { target: "riscv64gcv_linux".to_string() };
         deploy(&spec).expect("deploy ok");
         start().expect("start ok");
@@ -83,6 +170,17 @@ mod tests {
         let s = status();
         assert!(!s.running);
     }
+
+    #[test]
+    fn status_tracks_start_and_stop() {
+        let _g = LIFECYCLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        start().expect("start ok");
+        assert!(status().running);
+        deploy(&DeploySpec { target: "riscv64gcv_linux".to_string() }).expect("deploy ok");
+        assert!(status().running, "deploy does not touch running state");
+        stop().expect("stop ok");
+        assert!(!status().running);
+    }
 }
 
 pub mod adaptive {
@@ -90,7 +188,8 @@ pub mod adaptive {
 
     #[cfg(feature = "telemetry")]
     use nc_telemetry as telemetry;
-    use std::collections::HashSet;
+    use nc_orchestrator::PartitionPlan;
+    use std::collections::{HashMap, HashSet};
     use std::sync::{Mutex, OnceLock};
 
     #[derive(Debug, Clone)]
@@ -127,6 +226,43 @@ pub mod adaptive {
         }
     }
 
+    /// Threshold policy: throttle when utilization reaches `utilization_high_pct`, otherwise
+    /// reschedule when buffer occupancy reaches `buffer_high_pct`.
+    #[derive(Debug, Clone)]
+    pub struct ThresholdPolicy {
+        pub utilization_high_pct: f32,
+        pub buffer_high_pct: f32,
+    }
+
+    impl Default for ThresholdPolicy {
+        fn default() -> Self {
+            Self { utilization_high_pct: 90.0, buffer_high_pct: 80.0 }
+        }
+    }
+
+    impl Policy for ThresholdPolicy {
+        fn name(&self) -> &str { "threshold-policy" }
+        fn decide(&self, snapshot: &ResourceSnapshot) -> Decision {
+            if snapshot.utilization_pct >= self.utilization_high_pct {
+                Decision::Throttle
+            } else if snapshot.buffer_occupancy_pct >= self.buffer_high_pct {
+                Decision::Reschedule
+            } else {
+                Decision::NoChange
+            }
+        }
+    }
+
+    /// Built-in policy by config name: `"noop"` or `"threshold"` (default thresholds).
+    /// Unknown names return `None`.
+    pub fn policy_by_name(name: &str) -> Option<Box<dyn Policy>> {
+        match name {
+            "noop" => Some(Box::new(NoOpPolicy)),
+            "threshold" => Some(Box::new(ThresholdPolicy::default())),
+            _ => None,
+        }
+    }
+
     /// Apply options for runtime decisions.
     #[derive(Debug, Clone, Default)]
     pub struct ApplyOptions {
@@ -175,6 +311,19 @@ pub mod adaptive {
         }
     }
 
+    /// Forget every recorded idempotency key (and the repartition plans kept for them), so
+    /// previously applied keys are treated as new. For tests and for long-running processes
+    /// that prune the registry periodically.
+    pub fn clear_idempotency() {
+        idem().lock().expect("idempotency mutex poisoned").clear();
+        plans().lock().expect("plan mutex poisoned").clear();
+    }
+
+    /// Number of idempotency keys currently recorded.
+    pub fn idempotency_len() -> usize {
+        idem().lock().expect("idempotency mutex poisoned").len()
+    }
+
     /// Apply a decision to the running system with options.
     pub fn apply_with_options(decision: &Decision, opts: &ApplyOptions) -> Result<()> {
         // Optional telemetry: count decisions with labels
@@ -208,7 +357,7 @@ pub mod adaptive {
         match decision {
             Decision::NoChange => Ok(()),
             Decision::Repartition => {
-                // TODO(H2-5): invoke orchestrator handoff and update partition context
+                // No graph is held here; callers with a graph use `apply_repartition`
                 Ok(())
             }
             Decision::Reschedule => {
@@ -227,9 +376,61 @@ pub mod adaptive {
         apply_with_options(decision, &ApplyOptions::default())
     }
 
+    // Plans applied by `apply_repartition`, by idempotency key, returned again on replays.
+    static PLANS: OnceLock<Mutex<HashMap<String, PartitionPlan>>> = OnceLock::new();
+
+    fn plans() -> &'static Mutex<HashMap<String, PartitionPlan>> {
+        PLANS.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    /// Apply `Decision::Repartition` for graph `g`: re-run the orchestrator partitioner over
+    /// `targets` and return the new plan.
+    ///
+    /// Dry-run computes the plan without recording it or the idempotency key. A replayed
+    /// idempotency key returns the plan first applied under that key instead of partitioning
+    /// again.
+    pub fn apply_repartition(g: &nc_nir::Graph, targets: &[&str], opts: &ApplyOptions) -> Result<PartitionPlan> {
+        #[cfg(feature = "telemetry")]
+        let app = std::env::var("NC_PROFILE_JSONL")
+            .ok()
+            .and_then(|p| telemetry::profiling::Appender::open(p).ok());
+
+        #[cfg(feature = "telemetry")]
+        if let Some(a) = app.as_ref() {
+            let mut labels = std::collections::BTreeMap::new();
+            labels.insert("decision".to_string(), format!("{:?}", Decision::Repartition));
+            if let Some(k) = &opts.idempotency_key {
+                labels.insert("idem".to_string(), k.clone());
+            }
+            let _ = a.counter("runtime.decisions", 1.0, labels);
+        }
+
+        if opts.dry_run {
+            return nc_orchestrator::partition(g, targets);
+        }
+
+        if let Some(k) = &opts.idempotency_key {
+            if register_idem_if_new(k) {
+                if let Some(prev) = plans().lock().expect("plan mutex poisoned").get(k) {
+                    return Ok(prev.clone());
+                }
+            }
+        }
+
+        let plan = nc_orchestrator::partition(g, targets)?;
+        if let Some(k) = &opts.idempotency_key {
+            plans().lock().expect("plan mutex poisoned").insert(k.clone(), plan.clone());
+        }
+        Ok(plan)
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
+
+        // Serializes tests that record keys and assert on the shared idempotency registry
+        static IDEM_LOCK: Mutex<()> = Mutex::new(());
+
         #[test]
         fn noop_policy_decides_no_change() {
             let p = NoOpPolicy;
@@ -237,12 +438,78 @@ pub mod adaptive {
             assert_eq!(p.decide(&s), Decision::NoChange);
         }
 
+        #[test]
+        fn policy_registry_resolves_threshold() {
+            let p = policy_by_name("threshold").expect("threshold policy");
+            assert_eq!(p.decide(&ResourceSnapshot::new(97.0, 10.0)), Decision::Throttle);
+            assert_eq!(p.decide(&ResourceSnapshot::new(20.0, 85.0)), Decision::Reschedule);
+            assert_eq!(p.decide(&ResourceSnapshot::new(20.0, 10.0)), Decision::NoChange);
+            assert_eq!(policy_by_name("noop").expect("noop").decide(&ResourceSnapshot::new(99.0, 99.0)), Decision::NoChange);
+            assert!(policy_by_name("bogus").is_none());
+        }
+
         #[test]
         fn apply_with_options_dry_run_is_ok() {
             let opts = ApplyOptions { idempotency_key: Some("key".into()), dry_run: true };
             apply_with_options(&Decision::Repartition, &opts).expect("dry run ok");
         }
 
+        #[test]
+        fn apply_repartition_returns_plan() {
+            let _guard = IDEM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let g = nc_nir::Graph::from_json_str(
+                r#"{"name":"g","populations":[{"name":"a","size":4,"model":"LIF","params":{}},{"name":"b","size":2,"model":"LIF","params":{}}],"connections":[],"probes":[]}"#,
+            )
+            .expect("graph");
+            let targets = ["riscv64gcv_linux", "loihi2"];
+
+            let dry = ApplyOptions { idempotency_key: Some("repart-dry".into()), dry_run: true };
+            let plan = apply_repartition(&g, &targets, &dry).expect("dry run plan");
+            assert_eq!(plan.assignment.len(), 2);
+            assert!(!register_idem_if_new("repart-dry"), "dry run must not record the key");
+
+            let real = ApplyOptions { idempotency_key: Some("repart-real".into()), dry_run: false };
+            let plan = apply_repartition(&g, &targets, &real).expect("plan");
+            assert_eq!(plan.assignment.len(), 2);
+            assert!(register_idem_if_new("repart-real"));
+        }
+
+        #[test]
+        fn apply_repartition_replays_the_plan_of_each_key() {
+            let _guard = IDEM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let graph = |sizes: &str| {
+                nc_nir::Graph::from_json_str(&format!(r#"{{"name":"g","populations":[{sizes}],"connections":[],"probes":[]}}"#))
+                    .expect("graph")
+            };
+            let one = graph(r#"{"name":"a","size":4,"model":"LIF"}"#);
+            let two = graph(r#"{"name":"a","size":4,"model":"LIF"},{"name":"b","size":2,"model":"LIF"}"#);
+            let targets = ["riscv64gcv_linux", "loihi2"];
+            let key = |k: &str| ApplyOptions { idempotency_key: Some(k.into()), dry_run: false };
+
+            let first = apply_repartition(&one, &targets, &key("repart-one")).expect("plan one");
+            let second = apply_repartition(&two, &targets, &key("repart-two")).expect("plan two");
+            assert_eq!((first.assignment.len(), second.assignment.len()), (1, 2));
+            // Replays return the plan of their own key, not the most recent one
+            let replay_one = apply_repartition(&two, &targets, &key("repart-one")).expect("replay one");
+            assert_eq!(replay_one, first);
+            let replay_two = apply_repartition(&one, &targets, &key("repart-two")).expect("replay two");
+            assert_eq!(replay_two, second);
+        }
+
+        #[test]
+        fn clear_idempotency_forgets_keys() {
+            let _guard = IDEM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let before = idempotency_len();
+            let opts = ApplyOptions { idempotency_key: Some("idem-clear".into()), dry_run: false };
+            apply_with_options(&Decision::Throttle, &opts).expect("apply");
+            assert_eq!(idempotency_len(), before + 1);
+            assert!(register_idem_if_new("idem-clear"), "key recorded by apply");
+
+            clear_idempotency();
+            assert_eq!(idempotency_len(), 0);
+            assert!(!register_idem_if_new("idem-clear"), "cleared key is new again");
+        }
+
         #[test]
         fn apply_handles_all_decisions_ok() {
             apply(&Decision::NoChange).expect("no change ok");
@@ -5,7 +5,19 @@ use nc_nir as nir;
 #[cfg(feature = "telemetry")]
 use nc_telemetry as telemetry;
 
+/// Neuron models the Arbor emitter maps natively; other models (spike sources aside) get a warning.
+const SUPPORTED_MODELS: &[&str] = &["LIF", "AdaptiveLIF", "Izhikevich", "HH"];
+
 pub fn emit_artifacts(g: &nir::Graph, out_dir: &Path) -> Result<PathBuf> {
+    emit_artifacts_with_diagnostics(g, out_dir).map(|(dir, _)| dir)
+}
+
+/// Like `emit_artifacts`, but also returns non-fatal diagnostics (e.g. populations whose
+/// model is not supported). The same lines are written to `WARN.txt` when any are reported.
+/// Probe settings, with defaults filled in, go to `probes.json` for the driver script to
+/// set up recording; the emitter itself records nothing.
+/// Populations with compartments are listed with their trees in `morphology.json`.
+pub fn emit_artifacts_with_diagnostics(g: &nir::Graph, out_dir: &Path) -> Result<(PathBuf, Vec<String>)> {
     if !out_dir.exists() {
         fs::create_dir_all(out_dir)?;
     }
@@ -25,15 +37,24 @@ pub fn emit_artifacts(g: &nir::Graph, out_dir: &Path) -> Result<PathBuf> {
         }
     };
 
+    let warnings = g.unsupported_model_warnings("arbor", SUPPORTED_MODELS);
+
     let summary = serde_json::json!({
         "simulator": "arbor",
         "name": g.name,
         "populations": g.populations.len(),
         "connections": g.connections.len(),
-        "probes": g.probes.len()
+        "probes": g.probes.len(),
+        "compartments": g.populations.iter().map(|p| p.compartments.len()).sum::<usize>(),
+        "warnings": warnings
     });
     fs::write(out_dir.join("model_summary.json"), serde_json::to_string_pretty(&summary)?)?;
     fs::write(out_dir.join("RUN.txt"), format!("simulator: arbor\nname: {}\n", g.name))?;
+    g.write_morphology(out_dir)?;
+    g.write_probe_settings(out_dir)?;
+    if !warnings.is_empty() {
+        fs::write(out_dir.join("WARN.txt"), warnings.join("\n"))?;
+    }
 
     #[cfg(feature = "telemetry")]
     if let Some(a) = &app {
@@ -43,7 +64,58 @@ pub fn emit_artifacts(g: &nir::Graph, out_dir: &Path) -> Result<PathBuf> {
         let _ = a.counter("graph.probes", g.probes.len() as f64, l);
     }
 
-    Ok(out_dir.to_path_buf())
+    Ok((out_dir.to_path_buf(), warnings))
 }
 
 pub fn stub() -> &'static str { "ok" }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_model_yields_diagnostic() {
+        let mut g = nir::Graph::new("arbor_diag");
+        for (name, model) in [("src", "SpikeSourceArray"), ("a", "LIF"), ("b", "Sigma-Delta")] {
+            g.populations.push(nir::Population { name: name.into(), size: 2, model: model.into(), params: serde_json::json!({}), group: None, geometry: None, compartments: Vec::new() });
+        }
+        let out_dir = std::env::temp_dir().join(format!("nc_sim_arbor_diag_{}", std::process::id()));
+        let (dir, warnings) = emit_artifacts_with_diagnostics(&g, &out_dir).expect("emit");
+        assert_eq!(dir, out_dir);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0], "population 'b': model 'Sigma-Delta' is not supported by arbor");
+        assert_eq!(fs::read_to_string(out_dir.join("WARN.txt")).unwrap(), warnings[0]);
+        let _ = fs::remove_dir_all(&out_dir);
+    }
+
+    #[test]
+    fn compartments_are_written_to_morphology_json() {
+        let json = r#"{"name": "arbor_morph", "populations": [{"name": "pyr", "size": 3, "model": "HH",
+            "compartments": [{"name": "soma", "kind": "soma", "params": {"cm": 1.0}},
+                             {"name": "apical", "kind": "dendrite", "parent": "soma", "length_um": 200.0, "diameter_um": 2.0}]}]}"#;
+        let g = nir::Graph::from_json_str(json).unwrap();
+        let out_dir = std::env::temp_dir().join(format!("nc_sim_arbor_morph_{}", std::process::id()));
+        emit_artifacts(&g, &out_dir).expect("emit");
+        let summary: serde_json::Value = serde_json::from_str(&fs::read_to_string(out_dir.join("model_summary.json")).unwrap()).unwrap();
+        assert_eq!(summary["compartments"], 2);
+        let morph: serde_json::Value = serde_json::from_str(&fs::read_to_string(out_dir.join("morphology.json")).unwrap()).unwrap();
+        assert_eq!(morph[0]["population"], "pyr");
+        assert_eq!(morph[0]["compartments"][1]["parent"], "soma");
+        let _ = fs::remove_dir_all(&out_dir);
+    }
+
+    #[test]
+    fn probe_settings_are_written_to_probes_json() {
+        let json = r#"{"name": "arbor_probes", "populations": [{"name": "a", "size": 2, "model": "LIF"}],
+            "probes": [{"target": "a", "kind": "v", "interval_ms": 1.0, "stop_ms": 50.0, "format": "jsonl"}]}"#;
+        let g = nir::Graph::from_json_str(json).unwrap();
+        let out_dir = std::env::temp_dir().join(format!("nc_sim_arbor_probes_{}", std::process::id()));
+        emit_artifacts(&g, &out_dir).expect("emit");
+        let probes: serde_json::Value = serde_json::from_str(&fs::read_to_string(out_dir.join("probes.json")).unwrap()).unwrap();
+        assert_eq!(probes[0]["variables"], serde_json::json!(["v"]));
+        assert_eq!(probes[0]["interval_ms"], 1.0);
+        assert_eq!(probes[0]["start_ms"], 0.0);
+        assert_eq!(probes[0]["format"], "jsonl");
+        let _ = fs::remove_dir_all(&out_dir);
+    }
+}
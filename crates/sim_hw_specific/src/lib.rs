@@ -4,7 +4,10 @@ use std::collections::BTreeMap;
 use nc_telemetry as telemetry;
 use anyhow::Result;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Neuron models the hardware-specific simulator models natively; other models (spike sources aside) get a warning.
+const SUPPORTED_MODELS: &[&str] = &["LIF", "AdaptiveLIF", "Izhikevich", "SpikeRate"];
 
 pub fn stub() -> &'static str {
     #[cfg(feature = "telemetry")]
@@ -23,6 +26,13 @@ pub fn stub() -> &'static str {
 /// Emit minimal artifacts for a hardware-specific simulation run.
 /// Writes RUN.txt and a simple model_summary.txt under out_dir.
 pub fn emit_artifacts(g: &nc_nir::Graph, out_dir: &Path) -> Result<()> {
+    emit_artifacts_with_diagnostics(g, out_dir).map(|_| ())
+}
+
+/// Like `emit_artifacts`, but also returns out_dir and non-fatal diagnostics (e.g.
+/// populations whose model is not supported). The same lines are written to `WARN.txt` when any are reported.
+/// Probe settings, with defaults filled in, go to `probes.json`.
+pub fn emit_artifacts_with_diagnostics(g: &nc_nir::Graph, out_dir: &Path) -> Result<(PathBuf, Vec<String>)> {
     fs::create_dir_all(out_dir)?;
     #[cfg(feature = "telemetry")]
     {
@@ -41,5 +51,10 @@ pub fn emit_artifacts(g: &nc_nir::Graph, out_dir: &Path) -> Result<()> {
         g.name, g.populations.len(), g.connections.len()
     );
     fs::write(out_dir.join("model_summary.txt"), summary)?;
-    Ok(())
+    g.write_probe_settings(out_dir)?;
+    let warnings = g.unsupported_model_warnings("hw", SUPPORTED_MODELS);
+    if !warnings.is_empty() {
+        fs::write(out_dir.join("WARN.txt"), warnings.join("\n"))?;
+    }
+    Ok((out_dir.to_path_buf(), warnings))
 }
@@ -5,7 +5,19 @@ use nc_nir as nir;
 #[cfg(feature = "telemetry")]
 use nc_telemetry as telemetry;
 
+/// Neuron models the NEURON emitter maps natively; other models (spike sources aside) get a warning.
+const SUPPORTED_MODELS: &[&str] = &["LIF", "AdaptiveLIF", "Izhikevich", "HH"];
+
 pub fn emit_artifacts(g: &nir::Graph, out_dir: &Path) -> Result<PathBuf> {
+    emit_artifacts_with_diagnostics(g, out_dir).map(|(dir, _)| dir)
+}
+
+/// Like `emit_artifacts`, but also returns non-fatal diagnostics (e.g. populations whose
+/// model is not supported). The same lines are written to `WARN.txt` when any are reported.
+/// Probe settings, with defaults filled in, go to `probes.json` for the driver script to
+/// set up recording; the emitter itself records nothing.
+/// Populations with compartments are listed with their trees in `morphology.json`.
+pub fn emit_artifacts_with_diagnostics(g: &nir::Graph, out_dir: &Path) -> Result<(PathBuf, Vec<String>)> {
     if !out_dir.exists() {
         fs::create_dir_all(out_dir)?;
     }
@@ -25,15 +37,24 @@ pub fn emit_artifacts(g: &nir::Graph, out_dir: &Path) -> Result<PathBuf> {
         }
     };
 
+    let warnings = g.unsupported_model_warnings("neuron", SUPPORTED_MODELS);
+
     let summary = serde_json::json!({
         "simulator": "neuron",
         "name": g.name,
         "populations": g.populations.len(),
         "connections": g.connections.len(),
-        "probes": g.probes.len()
+        "probes": g.probes.len(),
+        "compartments": g.populations.iter().map(|p| p.compartments.len()).sum::<usize>(),
+        "warnings": warnings
     });
     fs::write(out_dir.join("model_summary.json"), serde_json::to_string_pretty(&summary)?)?;
     fs::write(out_dir.join("RUN.txt"), format!("simulator: neuron\nname: {}\n", g.name))?;
+    g.write_morphology(out_dir)?;
+    g.write_probe_settings(out_dir)?;
+    if !warnings.is_empty() {
+        fs::write(out_dir.join("WARN.txt"), warnings.join("\n"))?;
+    }
 
     #[cfg(feature = "telemetry")]
     if let Some(a) = &app {
@@ -43,7 +64,7 @@ pub fn emit_artifacts(g: &nir::Graph, out_dir: &Path) -> Result<PathBuf> {
         let _ = a.counter("graph.probes", g.probes.len() as f64, l);
     }
 
-    Ok(out_dir.to_path_buf())
+    Ok((out_dir.to_path_buf(), warnings))
 }
 
 pub fn stub() -> &'static str { "ok" }